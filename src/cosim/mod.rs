@@ -0,0 +1,454 @@
+#[cfg(feature = "F")]
+use crate::privilege::FFLAGS;
+use crate::{
+    hart::Hart,
+    privilege::MSTATUS,
+    xlen::{Cast, XlenT},
+};
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, Read},
+};
+
+mod trace;
+
+pub use trace::{BIN_CSR, BIN_FPR, BIN_GPR};
+
+/// csrs also written as side effect, eg. fflags accrual and mstatus.FS
+const IMPLICIT_CSRS: &[u16] = &[
+    MSTATUS,
+    #[cfg(feature = "F")]
+    FFLAGS,
+];
+
+/// architectural effects of one retired instruction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Commit<Xlen: XlenT> {
+    pub pc: Xlen,
+    /// raw instruction bits, if known
+    pub ins: Option<u32>,
+    /// (gp-rd, value)
+    pub gprs: Vec<(u8, Xlen)>,
+    /// (fp-rd, value), value is the whole register with Nan boxing
+    pub fprs: Vec<(u8, u128)>,
+    /// (csr_addr, value)
+    pub csrs: Vec<(u16, Xlen)>,
+}
+
+fn wide<Xlen: XlenT>(val: Xlen) -> u128 {
+    <Xlen as Cast<u128>>::into(val)
+}
+
+impl<Xlen: XlenT> Commit<Xlen> {
+    fn sort(&mut self) {
+        self.gprs.sort_by_key(|&(reg, _)| reg);
+        self.fprs.sort_by_key(|&(reg, _)| reg);
+        self.csrs.sort_by_key(|&(addr, _)| addr);
+    }
+}
+
+impl<Xlen: XlenT> Display for Commit<Xlen> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pc {:#x}", wide(self.pc))?;
+        if let Some(ins) = self.ins {
+            write!(f, " ({:#010x})", ins)?;
+        }
+        for &(reg, val) in &self.gprs {
+            write!(f, " x{} {:#x}", reg, wide(val))?;
+        }
+        for &(reg, val) in &self.fprs {
+            write!(f, " f{} {:#x}", reg, val)?;
+        }
+        for &(addr, val) in &self.csrs {
+            write!(f, " csr[{:#05x}] {:#x}", addr, wide(val))?;
+        }
+        Ok(())
+    }
+}
+
+/// first mismatch between the hart and the reference trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence<Xlen: XlenT> {
+    /// number of instructions retired before the mismatch
+    pub retired: usize,
+    pub expected: Commit<Xlen>,
+    pub actual: Commit<Xlen>,
+}
+
+impl<Xlen: XlenT> Display for Divergence<Xlen> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (exp, act) = (&self.expected, &self.actual);
+        writeln!(f, "diverged after {} retired instructions", self.retired)?;
+        writeln!(f, " expected: {}", exp)?;
+        writeln!(f, " actual:   {}", act)?;
+        if exp.pc != act.pc {
+            writeln!(
+                f,
+                " pc: expected {:#x}, actual {:#x}",
+                wide(exp.pc),
+                wide(act.pc)
+            )?;
+        }
+        fn diff<K: Ord + Copy, V: PartialEq + Copy>(
+            f: &mut fmt::Formatter<'_>,
+            exp: &[(K, V)],
+            act: &[(K, V)],
+            show: impl Fn(&mut fmt::Formatter<'_>, K, Option<V>, Option<V>) -> fmt::Result,
+        ) -> fmt::Result {
+            let mut keys: Vec<K> = exp.iter().chain(act).map(|&(k, _)| k).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let get = |wrs: &[(K, V)]| wrs.iter().find(|&&(k, _)| k == key).map(|&(_, v)| v);
+                let (e, a) = (get(exp), get(act));
+                if e != a {
+                    show(f, key, e, a)?;
+                }
+            }
+            Ok(())
+        }
+        fn hex<T: Into<u128>>(val: Option<T>) -> String {
+            val.map_or("<none>".into(), |v| format!("{:#x}", v.into()))
+        }
+        diff(f, &exp.gprs, &act.gprs, |f, reg, e, a| {
+            let e = e.map(|v| wide(v));
+            let a = a.map(|v| wide(v));
+            writeln!(f, " x{}: expected {}, actual {}", reg, hex(e), hex(a))
+        })?;
+        diff(f, &exp.fprs, &act.fprs, |f, reg, e, a| {
+            writeln!(f, " f{}: expected {}, actual {}", reg, hex(e), hex(a))
+        })?;
+        diff(f, &exp.csrs, &act.csrs, |f, addr, e, a| {
+            let e = e.map(|v| wide(v));
+            let a = a.map(|v| wide(v));
+            writeln!(
+                f,
+                " csr[{:#05x}]: expected {}, actual {}",
+                addr,
+                hex(e),
+                hex(a)
+            )
+        })
+    }
+}
+
+/// lock-step co-simulation against a reference commit log\
+/// every retired instruction is checked against the next trace record,
+/// the hart stops at the first divergence or at the end of trace
+#[derive(Debug, Clone, Default)]
+pub struct Cosim<Xlen: XlenT> {
+    trace: Vec<Commit<Xlen>>,
+    /// number of retired instructions
+    retired: usize,
+    /// effects of the in-flight instruction
+    cur: Commit<Xlen>,
+    /// in-flight instruction trapped and will not retire
+    squashed: bool,
+    /// fp registers before the in-flight instruction
+    #[cfg(feature = "F")]
+    fprs: [u128; 32],
+    /// `IMPLICIT_CSRS` before the in-flight instruction, none if unreadable
+    csrs: Vec<Option<Xlen>>,
+    divergence: Option<Divergence<Xlen>>,
+}
+
+impl<Xlen: XlenT> Cosim<Xlen> {
+    pub fn new(trace: Vec<Commit<Xlen>>) -> Self {
+        Self {
+            trace,
+            ..Default::default()
+        }
+    }
+
+    /// load spike `--log-commits` output
+    pub fn from_spike<R: BufRead>(reader: R) -> io::Result<Self> {
+        Ok(Self::new(trace::parse_spike(reader)?))
+    }
+
+    /// load binary commit log, see `trace::parse_binary` for layout
+    pub fn from_binary<R: Read>(reader: R) -> io::Result<Self> {
+        Ok(Self::new(trace::parse_binary(reader)?))
+    }
+
+    pub fn retired(&self) -> usize {
+        self.retired
+    }
+
+    pub fn divergence(&self) -> Option<&Divergence<Xlen>> {
+        self.divergence.as_ref()
+    }
+
+    /// all trace records retired without divergence
+    pub fn passed(&self) -> bool {
+        self.divergence.is_none() && self.retired == self.trace.len()
+    }
+
+    pub fn wr_gpr(&mut self, reg: u8, val: Xlen) {
+        if reg != 0 {
+            self.cur.gprs.retain(|&(r, _)| r != reg);
+            self.cur.gprs.push((reg, val));
+        }
+    }
+
+    pub fn wr_csr(&mut self, addr: u16, val: Xlen) {
+        self.cur.csrs.retain(|&(a, _)| a != addr);
+        self.cur.csrs.push((addr, val));
+    }
+
+    pub fn squash(&mut self) {
+        self.squashed = true;
+    }
+}
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    pub fn attach_cosim(&mut self, cosim: Cosim<Xlen>) {
+        self.cosim = Some(Box::new(cosim));
+    }
+
+    pub fn detach_cosim(&mut self) -> Option<Cosim<Xlen>> {
+        self.cosim.take().map(|cosim| *cosim)
+    }
+
+    fn implicit_csrs(&mut self) -> Vec<Option<Xlen>> {
+        IMPLICIT_CSRS
+            .iter()
+            .map(|&addr| self.csr_rd(addr).ok())
+            .collect()
+    }
+
+    /// start tracking effects of the instruction at pc
+    pub fn cosim_begin(&mut self) {
+        let pc = self.get_pc();
+        let csrs = self.implicit_csrs();
+        #[cfg(feature = "F")]
+        let fprs: [u128; 32] = std::array::from_fn(|reg| self.fpu.fpr_bits(reg as u8));
+        if let Some(cosim) = self.cosim.as_mut() {
+            cosim.cur = Commit {
+                pc,
+                ..Default::default()
+            };
+            cosim.squashed = false;
+            cosim.csrs = csrs;
+            #[cfg(feature = "F")]
+            {
+                cosim.fprs = fprs;
+            }
+        }
+    }
+
+    /// compare effects of the in-flight instruction with the reference
+    pub fn cosim_retire(&mut self) {
+        #[cfg(feature = "F")]
+        let fprs: [u128; 32] = std::array::from_fn(|reg| self.fpu.fpr_bits(reg as u8));
+        let csrs = self.implicit_csrs();
+        let cosim = match self.cosim.as_mut() {
+            Some(cosim) if !cosim.squashed && cosim.divergence.is_none() => cosim,
+            _ => return,
+        };
+        let mut actual = std::mem::take(&mut cosim.cur);
+        let mut expected = match cosim.trace.get(cosim.retired) {
+            Some(expected) => expected.clone(),
+            None => {
+                // ran past the end of trace
+                self.stop_tok = true;
+                return;
+            }
+        };
+        actual.ins = expected.ins;
+        #[cfg(feature = "F")]
+        {
+            // fp writes are not tracked, a register counts as written
+            // if it changed or the reference claims to write it
+            for (reg, &val) in fprs.iter().enumerate() {
+                let reg = reg as u8;
+                let claimed = expected.fprs.iter().any(|&(r, _)| r == reg);
+                if claimed || val != cosim.fprs[reg as usize] {
+                    actual.fprs.push((reg, val));
+                }
+            }
+        }
+        // same for csrs written as side effect, explicit writes take precedence
+        for ((&addr, &before), &after) in IMPLICIT_CSRS.iter().zip(&cosim.csrs).zip(&csrs) {
+            let Some(after) = after else {
+                continue;
+            };
+            let claimed = expected.csrs.iter().any(|&(a, _)| a == addr);
+            let written = actual.csrs.iter().any(|&(a, _)| a == addr);
+            if !written && (claimed || before != Some(after)) {
+                actual.csrs.push((addr, after));
+            }
+        }
+        actual.sort();
+        expected.sort();
+        if actual != expected {
+            cosim.divergence = Some(Divergence {
+                retired: cosim.retired,
+                expected,
+                actual,
+            });
+            self.stop_tok = true;
+            return;
+        }
+        cosim.retired += 1;
+        if cosim.retired == cosim.trace.len() {
+            self.stop_tok = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "RV64")]
+    #[test]
+    fn parse() {
+        let log = "\
+core   0: 0x0000000000001000 (0x00000297) auipc   t0, 0x0
+core   0: 3 0x0000000080000000 (0x00000297) x5  0x0000000080000000
+core   0: 3 0x000000008000000c (0x0182b283) x5  0x0000000080000000 mem 0x0000000080000018
+core   0: 3 0x0000000080000010 (0x0052a023) mem 0x0000000080000020 0x0000000080000000
+core   0: exception trap_illegal_instruction, epc 0x0000000080000014
+core   0: 3 0x0000000080000018 (0x30529073) c773_mtvec 0x0000000080000000
+core   0: 0x000000008000001c (0x00a5f553) f10 0xffffffff3f800000 c1_fflags 0x0000000000000001
+";
+        let trace = trace::parse_spike::<u64, _>(log.as_bytes()).unwrap();
+        assert_eq!(trace.len(), 5);
+        assert_eq!(trace[0].pc, 0x80000000);
+        assert_eq!(trace[0].ins, Some(0x00000297));
+        assert_eq!(trace[0].gprs, vec![(5, 0x80000000)]);
+        assert_eq!(trace[1].gprs, vec![(5, 0x80000000)]);
+        assert!(trace[2].gprs.is_empty());
+        assert_eq!(trace[3].csrs, vec![(0x305, 0x80000000)]);
+        assert_eq!(trace[4].fprs, vec![(10, 0xffffffff3f800000)]);
+        assert_eq!(trace[4].csrs, vec![(0x001, 1)]);
+
+        let mut bin = Vec::new();
+        bin.extend_from_slice(&0x80000000u64.to_le_bytes());
+        bin.extend_from_slice(&0x00000297u32.to_le_bytes());
+        bin.push(2);
+        bin.push(BIN_GPR);
+        bin.extend_from_slice(&5u16.to_le_bytes());
+        bin.extend_from_slice(&0x80000000u64.to_le_bytes());
+        bin.push(BIN_CSR);
+        bin.extend_from_slice(&0x305u16.to_le_bytes());
+        bin.extend_from_slice(&0x80000000u64.to_le_bytes());
+        let trace = trace::parse_binary::<u64, _>(bin.as_slice()).unwrap();
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0].gprs, vec![(5, 0x80000000)]);
+        assert_eq!(trace[0].csrs, vec![(0x305, 0x80000000)]);
+        assert!(trace::parse_binary::<u64, _>(&bin[..bin.len() - 1]).is_err());
+    }
+
+    fn run_prog(hart: &mut Hart<u32>, prog: &[u32], log: &str) -> Cosim<u32> {
+        hart.priv_ctrl.hooked = true;
+        let mut mem_region = Box::new([0u8; 64]);
+        for (idx, ins) in prog.iter().enumerate() {
+            mem_region[idx * 4..idx * 4 + 4].copy_from_slice(&ins.to_le_bytes());
+        }
+        hart.mem.hook_mem = Some(mem_region);
+        hart.attach_cosim(Cosim::from_spike(log.as_bytes()).unwrap());
+        hart.run();
+        hart.detach_cosim().unwrap()
+    }
+
+    fn run(log: &str) -> Cosim<u32> {
+        let prog = [
+            0x00500513, // addi a0, zero, 5
+            0x00150593, // addi a1, a0, 1
+            0x00100073, // ebreak
+        ];
+        run_prog(&mut Hart::default(), &prog, log)
+    }
+
+    #[test]
+    fn lock_step() {
+        let cosim = run("\
+core   0: 3 0x00000000 (0x00500513) x10 0x00000005
+core   0: 3 0x00000004 (0x00150593) x11 0x00000006
+");
+        assert!(cosim.passed());
+        assert_eq!(cosim.retired(), 2);
+
+        let cosim = run("\
+core   0: 3 0x00000000 (0x00500513) x10 0x00000005
+core   0: 3 0x00000004 (0x00150593) x11 0x00000007
+core   0: 3 0x00000008 (0x00100073)
+");
+        assert!(!cosim.passed());
+        let div = cosim.divergence().unwrap();
+        assert_eq!(div.retired, 1);
+        assert_eq!(div.actual.gprs, vec![(11, 6)]);
+        assert!(div.to_string().contains("x11: expected 0x7, actual 0x6"));
+
+        let cosim = run("\
+core   0: 3 0x00000000 (0x00500513) x10 0x00000005
+core   0: 3 0x00000008 (0x00150593) x11 0x00000006
+");
+        let div = cosim.divergence().unwrap();
+        assert!(div.to_string().contains("pc: expected 0x8, actual 0x4"));
+    }
+
+    #[cfg(feature = "Zicsr")]
+    #[test]
+    fn csr_effects() {
+        // logged after WARL legalization, FS or VS dirty sets SD
+        let mut mstatus = if cfg!(feature = "F") {
+            0x80207888u32
+        } else {
+            0x00201888
+        };
+        if cfg!(feature = "S") {
+            mstatus |= 0x005e0122;
+        }
+        if cfg!(feature = "V") {
+            mstatus |= 0x80000600;
+        }
+        let prog = [
+            0xfff00513, // addi a0, zero, -1
+            0x30051073, // csrw mstatus, a0
+            0x00100073, // ebreak
+        ];
+        let log = format!(
+            "\
+core   0: 3 0x00000000 (0xfff00513) x10 0xffffffff
+core   0: 3 0x00000004 (0x30051073) c768_mstatus {:#010x}
+",
+            mstatus
+        );
+        assert!(run_prog(&mut Hart::default(), &prog, &log).passed());
+
+        // fflags accrual and FS dirtying are written too
+        #[cfg(feature = "F")]
+        {
+            let prog = [
+                0xc0007553, // fcvt.w.s a0, ft0
+                0x00100073, // ebreak
+            ];
+            // VS sits at initial alongside
+            let mstatus = if cfg!(feature = "V") {
+                0x80006200u32
+            } else {
+                0x80006000
+            };
+            let log = format!(
+                "\
+core   0: 3 0x00000000 (0xc0007553) x10 0x00000000 c1_fflags 0x00000001 c768_mstatus {:#010x}
+",
+                mstatus
+            );
+            let mut hart = Hart::default();
+            hart.fpu.u32_mv_f32(0, 0x3f000000);
+            assert!(run_prog(&mut hart, &prog, &log).passed());
+
+            let log = "\
+core   0: 3 0x00000000 (0xc0007553) x10 0x00000000
+";
+            let mut hart = Hart::default();
+            hart.fpu.u32_mv_f32(0, 0x3f000000);
+            let cosim = run_prog(&mut hart, &prog, log);
+            let div = cosim.divergence().unwrap();
+            assert_eq!(div.actual.csrs, vec![(0x001, 1), (0x300, mstatus)]);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// reference commit log parsers
+
+use crate::{cosim::Commit, xlen::XlenT};
+use std::io::{self, BufRead, Read};
+
+fn bad_data(line: usize, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("trace line {}: {}", line, msg),
+    )
+}
+
+fn parse_hex(tok: &str) -> Option<u128> {
+    let tok = tok.strip_prefix("0x")?;
+    u128::from_str_radix(tok, 16).ok()
+}
+
+/// parse `x5` / `f12` style register names
+fn parse_reg(tok: &str, prefix: char) -> Option<u8> {
+    let idx: u8 = tok.strip_prefix(prefix)?.parse().ok()?;
+    if idx < 32 {
+        Some(idx)
+    } else {
+        None
+    }
+}
+
+/// parse `c768_mstatus` style csr names
+fn parse_csr(tok: &str) -> Option<u16> {
+    let (addr, _name) = tok.strip_prefix('c')?.split_once('_')?;
+    addr.parse().ok()
+}
+
+/// commit fields are `mem`, `c<addr>_<name>` or a letter followed by digits
+/// (`x5`, `f1`, vector `e8`, `m1`, `v2`, ...), anything else is disassembly
+fn is_field(tok: &str) -> bool {
+    let mut chars = tok.chars();
+    tok == "mem"
+        || parse_csr(tok).is_some()
+        || (chars.next().is_some_and(|c| c.is_ascii_lowercase())
+            && !chars.as_str().is_empty()
+            && chars.all(|c| c.is_ascii_digit()))
+}
+
+/// parse one line of spike `--log-commits` output\
+/// `core   0: 3 0x80000000 (0x00000297) x5  0x80000000 mem 0x80000018`\
+/// returns `None` for lines that are not commits (exceptions, interrupts, ...)
+fn parse_spike_line<Xlen: XlenT>(line: &str, lineno: usize) -> io::Result<Option<Commit<Xlen>>> {
+    let line = line.trim();
+    let rest = match line.strip_prefix("core") {
+        Some(rest) => rest,
+        None => return Ok(None),
+    };
+    let rest = match rest.split_once(':') {
+        Some((_, rest)) => rest,
+        None => return Ok(None),
+    };
+    let mut toks = rest.split_whitespace().peekable();
+    // newer spike prints privilege level before pc
+    if let Some(&tok) = toks.peek() {
+        if tok.len() == 1 && tok.chars().all(|c| c.is_ascii_digit()) {
+            toks.next();
+        }
+    }
+    let pc = match toks.next().and_then(parse_hex) {
+        Some(pc) => pc,
+        None => return Ok(None),
+    };
+    let ins = match toks.next() {
+        Some(tok) if tok.starts_with("(0x") && tok.ends_with(')') => {
+            parse_hex(&tok[1..tok.len() - 1]).ok_or_else(|| bad_data(lineno, "bad instruction"))?
+        }
+        // exception / interrupt line
+        _ => return Ok(None),
+    };
+    if toks.peek().is_some_and(|tok| !is_field(tok)) {
+        // disassembly line of `--log`
+        return Ok(None);
+    }
+    let mut commit = Commit {
+        pc: Xlen::from(pc),
+        ins: Some(ins as u32),
+        ..Default::default()
+    };
+    while let Some(tok) = toks.next() {
+        let mut value = || {
+            toks.next()
+                .and_then(parse_hex)
+                .ok_or_else(|| bad_data(lineno, "missing value"))
+        };
+        if tok == "mem" {
+            // address, followed by data for stores
+            value()?;
+            if toks.peek().is_some_and(|tok| tok.starts_with("0x")) {
+                toks.next();
+            }
+        } else if let Some(reg) = parse_reg(tok, 'x') {
+            let val = value()?;
+            commit.gprs.push((reg, Xlen::from(val)));
+        } else if let Some(reg) = parse_reg(tok, 'f') {
+            let val = value()?;
+            commit.fprs.push((reg, val));
+        } else if let Some(addr) = parse_csr(tok) {
+            let val = value()?;
+            commit.csrs.push((addr, Xlen::from(val)));
+        }
+        // unsupported fields (eg. vector registers) and their values are skipped
+    }
+    Ok(Some(commit))
+}
+
+/// parse spike commit log
+pub fn parse_spike<Xlen: XlenT, R: BufRead>(reader: R) -> io::Result<Vec<Commit<Xlen>>> {
+    let mut trace = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        if let Some(commit) = parse_spike_line(&line?, idx + 1)? {
+            trace.push(commit);
+        }
+    }
+    Ok(trace)
+}
+
+pub const BIN_GPR: u8 = 0;
+pub const BIN_FPR: u8 = 1;
+pub const BIN_CSR: u8 = 2;
+
+/// parse binary commit log, records are little endian
+///
+/// | field | size | note |
+/// |-------|------|------|
+/// | pc    | 8    |      |
+/// | ins   | 4    | raw instruction bits |
+/// | n     | 1    | number of writes |
+/// | kind  | 1    | per write, `BIN_GPR` / `BIN_FPR` / `BIN_CSR` |
+/// | idx   | 2    | per write, register index or csr address |
+/// | value | 8    | per write |
+pub fn parse_binary<Xlen: XlenT, R: Read>(mut reader: R) -> io::Result<Vec<Commit<Xlen>>> {
+    fn take(buf: &mut &[u8], len: usize, rec: usize) -> io::Result<u64> {
+        if buf.len() < len {
+            return Err(bad_data(rec, "truncated record"));
+        }
+        let mut val = [0u8; 8];
+        val[..len].copy_from_slice(&buf[..len]);
+        *buf = &buf[len..];
+        Ok(u64::from_le_bytes(val))
+    }
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let mut buf = data.as_slice();
+    let mut trace = Vec::new();
+    while !buf.is_empty() {
+        let rec = trace.len() + 1;
+        let mut commit = Commit {
+            pc: Xlen::from(take(&mut buf, 8, rec)?),
+            ins: Some(take(&mut buf, 4, rec)? as u32),
+            ..Default::default()
+        };
+        for _ in 0..take(&mut buf, 1, rec)? {
+            let kind = take(&mut buf, 1, rec)? as u8;
+            let idx = take(&mut buf, 2, rec)? as u16;
+            let val = take(&mut buf, 8, rec)?;
+            match kind {
+                BIN_GPR if idx < 32 => commit.gprs.push((idx as u8, Xlen::from(val))),
+                BIN_FPR if idx < 32 => commit.fprs.push((idx as u8, val as u128)),
+                BIN_CSR if idx < 4096 => commit.csrs.push((idx, Xlen::from(val))),
+                _ => return Err(bad_data(rec, "bad write record")),
+            }
+        }
+        trace.push(commit);
+    }
+    Ok(trace)
+}
@@ -25,18 +25,127 @@ pub struct Isa<Xlen: XlenT> {
     /// Double-precision floating-point
     #[cfg(feature = "D")]
     pub D: bool,
+    /// Embedded, x16 - x31 are reserved
+    #[cfg(feature = "E")]
+    pub E: bool,
     /// Single-precision floating-point
     #[cfg(feature = "F")]
     pub F: bool,
+    /// Hypervisor, VS / VU modes and two-stage translation
+    #[cfg(feature = "H")]
+    pub H: bool,
     /// Integer Multiply/Divide
     #[cfg(feature = "M")]
     pub M: bool,
+    /// Quad-precision floating-point
+    #[cfg(feature = "Q")]
+    pub Q: bool,
+    /// Supervisor-mode
+    #[cfg(feature = "S")]
+    pub S: bool,
+    /// Vector
+    #[cfg(feature = "V")]
+    pub V: bool,
     /// CSR instructions
     #[cfg(feature = "Zicsr")]
     pub Zicsr: bool,
     /// Instruction-Fetch Fence
     #[cfg(feature = "Zifencei")]
     pub Zifencei: bool,
+    /// Cache-Block Management
+    #[cfg(feature = "Zicbom")]
+    pub Zicbom: bool,
+    /// Cache-Block Prefetch
+    #[cfg(feature = "Zicbop")]
+    pub Zicbop: bool,
+    /// Cache-Block Zero
+    #[cfg(feature = "Zicboz")]
+    pub Zicboz: bool,
+    /// Integer Conditional Operations
+    #[cfg(feature = "Zicond")]
+    pub Zicond: bool,
+    /// Non-Temporal Locality Hints
+    #[cfg(feature = "Zihintntl")]
+    pub Zihintntl: bool,
+    /// Pause Hint
+    #[cfg(feature = "Zihintpause")]
+    pub Zihintpause: bool,
+    /// May-Be-Operations
+    #[cfg(feature = "Zimop")]
+    pub Zimop: bool,
+    /// Byte and Halfword Atomic Memory Operations
+    #[cfg(feature = "Zabha")]
+    pub Zabha: bool,
+    /// Atomic Compare-and-Swap
+    #[cfg(feature = "Zacas")]
+    pub Zacas: bool,
+    /// Wait-on-Reservation-Set
+    #[cfg(feature = "Zawrs")]
+    pub Zawrs: bool,
+    /// Address Generation
+    #[cfg(feature = "Zba")]
+    pub Zba: bool,
+    /// Basic Bit-Manipulation
+    #[cfg(feature = "Zbb")]
+    pub Zbb: bool,
+    /// Carry-less Multiplication
+    #[cfg(feature = "Zbc")]
+    pub Zbc: bool,
+    /// Single-bit Instructions
+    #[cfg(feature = "Zbs")]
+    pub Zbs: bool,
+    /// Additional Floating-Point Instructions
+    #[cfg(feature = "Zfa")]
+    pub Zfa: bool,
+    /// Half-Precision Floating-Point
+    #[cfg(feature = "Zfh")]
+    pub Zfh: bool,
+    /// Minimal Half-Precision Floating-Point
+    #[cfg(feature = "Zfhmin")]
+    pub Zfhmin: bool,
+    /// Floating-Point in Integer Registers, replaces the fp register file,
+    /// D becomes Zdinx with register pairs on rv32
+    #[cfg(feature = "Zfinx")]
+    pub Zfinx: bool,
+    /// Compressed Basic Bit-Manipulation
+    #[cfg(feature = "Zcb")]
+    pub Zcb: bool,
+    /// Compressed Push / Pop and Double Move, reuses c.fsdsp encodings
+    #[cfg(feature = "Zcmp")]
+    pub Zcmp: bool,
+    /// Compressed Table Jump, reuses c.fsdsp encodings
+    #[cfg(feature = "Zcmt")]
+    pub Zcmt: bool,
+    /// Compressed May-Be-Operations
+    #[cfg(feature = "Zcmop")]
+    pub Zcmop: bool,
+    /// Bit-manipulation for Cryptography
+    #[cfg(feature = "Zbkb")]
+    pub Zbkb: bool,
+    /// Carry-less multiplication for Cryptography
+    #[cfg(feature = "Zbkc")]
+    pub Zbkc: bool,
+    /// Crossbar permutations
+    #[cfg(feature = "Zbkx")]
+    pub Zbkx: bool,
+    /// NIST Suite: AES Decryption
+    #[cfg(feature = "Zknd")]
+    pub Zknd: bool,
+    /// NIST Suite: AES Encryption
+    #[cfg(feature = "Zkne")]
+    pub Zkne: bool,
+    /// NIST Suite: Hash Function Instructions
+    #[cfg(feature = "Zknh")]
+    pub Zknh: bool,
+    /// ShangMi Suite: SM4 Block Cipher Instructions
+    #[cfg(feature = "Zksed")]
+    pub Zksed: bool,
+    /// ShangMi Suite: SM3 Hash Function Instructions
+    #[cfg(feature = "Zksh")]
+    pub Zksh: bool,
+    /// Total Store Ordering, plain loads and stores are acquire / release
+    #[cfg(feature = "Ztso")]
+    pub Ztso: bool,
 
     xlen: std::marker::PhantomData<Xlen>,
 }
@@ -50,19 +159,250 @@ impl<Xlen: XlenT> Default for Isa<Xlen> {
             C: true,
             #[cfg(feature = "D")]
             D: true,
+            // off by default, I has 32 registers
+            #[cfg(feature = "E")]
+            E: false,
             #[cfg(feature = "F")]
             F: true,
+            #[cfg(feature = "H")]
+            H: true,
             #[cfg(feature = "M")]
             M: true,
+            #[cfg(feature = "Q")]
+            Q: true,
+            #[cfg(feature = "S")]
+            S: true,
+            #[cfg(feature = "V")]
+            V: true,
             #[cfg(feature = "Zicsr")]
             Zicsr: true,
             #[cfg(feature = "Zifencei")]
             Zifencei: true,
+            #[cfg(feature = "Zicbom")]
+            Zicbom: true,
+            #[cfg(feature = "Zicbop")]
+            Zicbop: true,
+            #[cfg(feature = "Zicboz")]
+            Zicboz: true,
+            #[cfg(feature = "Zicond")]
+            Zicond: true,
+            #[cfg(feature = "Zihintntl")]
+            Zihintntl: true,
+            #[cfg(feature = "Zihintpause")]
+            Zihintpause: true,
+            #[cfg(feature = "Zimop")]
+            Zimop: true,
+            #[cfg(feature = "Zabha")]
+            Zabha: true,
+            #[cfg(feature = "Zacas")]
+            Zacas: true,
+            #[cfg(feature = "Zawrs")]
+            Zawrs: true,
+            #[cfg(feature = "Zba")]
+            Zba: true,
+            #[cfg(feature = "Zbb")]
+            Zbb: true,
+            #[cfg(feature = "Zbc")]
+            Zbc: true,
+            #[cfg(feature = "Zbs")]
+            Zbs: true,
+            #[cfg(feature = "Zfa")]
+            Zfa: true,
+            #[cfg(feature = "Zfh")]
+            Zfh: true,
+            #[cfg(feature = "Zfhmin")]
+            Zfhmin: true,
+            // off by default, F and D use fp registers
+            #[cfg(feature = "Zfinx")]
+            Zfinx: false,
+            #[cfg(feature = "Zcb")]
+            Zcb: true,
+            // off by default, D uses c.fsdsp
+            #[cfg(feature = "Zcmp")]
+            Zcmp: false,
+            #[cfg(feature = "Zcmt")]
+            Zcmt: false,
+            #[cfg(feature = "Zcmop")]
+            Zcmop: true,
+            #[cfg(feature = "Zbkb")]
+            Zbkb: true,
+            #[cfg(feature = "Zbkc")]
+            Zbkc: true,
+            #[cfg(feature = "Zbkx")]
+            Zbkx: true,
+            #[cfg(feature = "Zknd")]
+            Zknd: true,
+            #[cfg(feature = "Zkne")]
+            Zkne: true,
+            #[cfg(feature = "Zknh")]
+            Zknh: true,
+            #[cfg(feature = "Zksed")]
+            Zksed: true,
+            #[cfg(feature = "Zksh")]
+            Zksh: true,
+            // off by default, RVWMO is cheaper on weakly ordered hosts
+            #[cfg(feature = "Ztso")]
+            Ztso: false,
             xlen: Default::default(),
         }
     }
 }
 
+impl<Xlen: XlenT> Isa<Xlen> {
+    /// same extensions decoding for another xlen
+    pub fn with_xlen<T: XlenT>(&self) -> Isa<T> {
+        Isa {
+            #[cfg(feature = "A")]
+            A: self.A,
+            #[cfg(feature = "C")]
+            C: self.C,
+            #[cfg(feature = "D")]
+            D: self.D,
+            #[cfg(feature = "E")]
+            E: self.E,
+            #[cfg(feature = "F")]
+            F: self.F,
+            #[cfg(feature = "H")]
+            H: self.H,
+            #[cfg(feature = "M")]
+            M: self.M,
+            #[cfg(feature = "Q")]
+            Q: self.Q,
+            #[cfg(feature = "S")]
+            S: self.S,
+            #[cfg(feature = "V")]
+            V: self.V,
+            #[cfg(feature = "Zicsr")]
+            Zicsr: self.Zicsr,
+            #[cfg(feature = "Zifencei")]
+            Zifencei: self.Zifencei,
+            #[cfg(feature = "Zicbom")]
+            Zicbom: self.Zicbom,
+            #[cfg(feature = "Zicbop")]
+            Zicbop: self.Zicbop,
+            #[cfg(feature = "Zicboz")]
+            Zicboz: self.Zicboz,
+            #[cfg(feature = "Zicond")]
+            Zicond: self.Zicond,
+            #[cfg(feature = "Zihintntl")]
+            Zihintntl: self.Zihintntl,
+            #[cfg(feature = "Zihintpause")]
+            Zihintpause: self.Zihintpause,
+            #[cfg(feature = "Zimop")]
+            Zimop: self.Zimop,
+            #[cfg(feature = "Zabha")]
+            Zabha: self.Zabha,
+            #[cfg(feature = "Zacas")]
+            Zacas: self.Zacas,
+            #[cfg(feature = "Zawrs")]
+            Zawrs: self.Zawrs,
+            #[cfg(feature = "Zba")]
+            Zba: self.Zba,
+            #[cfg(feature = "Zbb")]
+            Zbb: self.Zbb,
+            #[cfg(feature = "Zbc")]
+            Zbc: self.Zbc,
+            #[cfg(feature = "Zbs")]
+            Zbs: self.Zbs,
+            #[cfg(feature = "Zfa")]
+            Zfa: self.Zfa,
+            #[cfg(feature = "Zfh")]
+            Zfh: self.Zfh,
+            #[cfg(feature = "Zfhmin")]
+            Zfhmin: self.Zfhmin,
+            #[cfg(feature = "Zfinx")]
+            Zfinx: self.Zfinx,
+            #[cfg(feature = "Zcb")]
+            Zcb: self.Zcb,
+            #[cfg(feature = "Zcmp")]
+            Zcmp: self.Zcmp,
+            #[cfg(feature = "Zcmt")]
+            Zcmt: self.Zcmt,
+            #[cfg(feature = "Zcmop")]
+            Zcmop: self.Zcmop,
+            #[cfg(feature = "Zbkb")]
+            Zbkb: self.Zbkb,
+            #[cfg(feature = "Zbkc")]
+            Zbkc: self.Zbkc,
+            #[cfg(feature = "Zbkx")]
+            Zbkx: self.Zbkx,
+            #[cfg(feature = "Zknd")]
+            Zknd: self.Zknd,
+            #[cfg(feature = "Zkne")]
+            Zkne: self.Zkne,
+            #[cfg(feature = "Zknh")]
+            Zknh: self.Zknh,
+            #[cfg(feature = "Zksed")]
+            Zksed: self.Zksed,
+            #[cfg(feature = "Zksh")]
+            Zksh: self.Zksh,
+            #[cfg(feature = "Ztso")]
+            Ztso: self.Ztso,
+            xlen: Default::default(),
+        }
+    }
+
+    /// extension bits of misa
+    pub fn misa_ext(&self) -> u32 {
+        fn bit(ext: char) -> u32 {
+            1 << (ext as u8 - b'A')
+        }
+        // base integer isa
+        #[allow(unused_mut)]
+        let mut ext = bit('I');
+        #[cfg(feature = "E")]
+        if self.E {
+            ext = bit('E');
+        }
+        #[cfg(feature = "A")]
+        if self.A {
+            ext |= bit('A');
+        }
+        #[cfg(all(feature = "Zba", feature = "Zbb", feature = "Zbs"))]
+        if self.Zba && self.Zbb && self.Zbs {
+            ext |= bit('B');
+        }
+        #[cfg(feature = "C")]
+        if self.C {
+            ext |= bit('C');
+        }
+        #[cfg(feature = "D")]
+        if self.D {
+            ext |= bit('D');
+        }
+        #[cfg(feature = "F")]
+        if self.F {
+            ext |= bit('F');
+        }
+        #[cfg(feature = "H")]
+        if self.hyp() {
+            ext |= bit('H');
+        }
+        #[cfg(feature = "M")]
+        if self.M {
+            ext |= bit('M');
+        }
+        #[cfg(feature = "Q")]
+        if self.Q {
+            ext |= bit('Q');
+        }
+        #[cfg(feature = "S")]
+        if self.S {
+            ext |= bit('S');
+        }
+        #[cfg(feature = "V")]
+        if self.V {
+            ext |= bit('V');
+        }
+        // fp in integer registers is not reported as F or D
+        #[cfg(feature = "Zfinx")]
+        if self.Zfinx {
+            ext &= !(bit('F') | bit('D') | bit('Q'));
+        }
+        ext | bit('U')
+    }
+}
+
 /// sign-extend any length imm to i32
 pub fn sext(imm: u32, sign_bit: u32) -> i32 {
     let len = 31 - sign_bit;
@@ -142,6 +482,12 @@ macro_rules! if_ext_d {
     };
 }
 
+macro_rules! if_ext_e {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(E, "E", $isa, $($e), *)
+    };
+}
+
 macro_rules! if_ext_f {
     ($isa:expr, $($e:expr), *) => {
         if_ext!(F, "F", $isa, $($e), *)
@@ -154,6 +500,30 @@ macro_rules! if_ext_m {
     };
 }
 
+macro_rules! if_ext_q {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Q, "Q", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_h {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(H, "H", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_s {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(S, "S", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_v {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(V, "V", $isa, $($e), *)
+    };
+}
+
 macro_rules! if_ext_zicsr {
     ($isa:expr, $($e:expr), *) => {
         if_ext!(Zicsr, "Zicsr", $isa, $($e), *)
@@ -166,6 +536,199 @@ macro_rules! if_ext_zifencei {
     };
 }
 
+macro_rules! if_ext_zicbom {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zicbom, "Zicbom", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zicbop {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zicbop, "Zicbop", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zicboz {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zicboz, "Zicboz", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zicond {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zicond, "Zicond", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zihintntl {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zihintntl, "Zihintntl", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zihintpause {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zihintpause, "Zihintpause", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zimop {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zimop, "Zimop", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zabha {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zabha, "Zabha", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zacas {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zacas, "Zacas", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zawrs {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zawrs, "Zawrs", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zba {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zba, "Zba", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zbb {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zbb, "Zbb", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zbc {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zbc, "Zbc", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zbs {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zbs, "Zbs", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zfa {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zfa, "Zfa", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zfh {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zfh, "Zfh", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zfhmin {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zfhmin, "Zfhmin", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zfinx {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zfinx, "Zfinx", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zcb {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zcb, "Zcb", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zcmp {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zcmp, "Zcmp", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zcmt {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zcmt, "Zcmt", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zcmop {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zcmop, "Zcmop", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zbkb {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zbkb, "Zbkb", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zbkc {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zbkc, "Zbkc", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zbkx {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zbkx, "Zbkx", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zknd {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zknd, "Zknd", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zkne {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zkne, "Zkne", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zknh {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zknh, "Zknh", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zksed {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zksed, "Zksed", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_zksh {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Zksh, "Zksh", $isa, $($e), *)
+    };
+}
+
+macro_rules! if_ext_ztso {
+    ($isa:expr, $($e:expr), *) => {
+        if_ext!(Ztso, "Ztso", $isa, $($e), *)
+    };
+}
+
+/// instructions shared by two extensions, eg. rotates of Zbb and Zbkb
+macro_rules! if_ext_either {
+    ($id1:ident, $lit1:literal, $id2:ident, $lit2:literal, $isa:expr, $e:expr) => {
+        if_ext!($id1, $lit1, $isa, Ok($e), if_ext!($id2, $lit2, $isa, $e))
+    };
+}
+
 /// `result` = `val`\[`high`:`low`\]
 pub fn select_bits<T>(val: T, high: u8, low: u8) -> T
 where
@@ -47,6 +47,11 @@ fn lsp4b_uimm(ins: u16) -> i32 {
     shuffle_bits!(ins, 2, 6, 4, 12, 12, 3, 2) as i32
 }
 
+#[cfg(feature = "RV128")]
+fn ls16b_uimm(ins: u16) -> i32 {
+    shuffle_bits!(ins, 4, 12, 11, 6, 5, 10, 10) as i32
+}
+
 fn lsp8b_uimm(ins: u16) -> i32 {
     shuffle_bits!(ins, 3, 6, 5, 12, 12, 4, 2) as i32
 }
@@ -59,6 +64,24 @@ fn ssp8b_uimm(ins: u16) -> i32 {
     shuffle_bits!(ins, 3, 12, 10, 9, 7) as i32
 }
 
+#[cfg(feature = "RV128")]
+fn lsp16b_uimm(ins: u16) -> i32 {
+    shuffle_bits!(ins, 4, 6, 6, 12, 12, 5, 2) as i32
+}
+
+#[cfg(feature = "RV128")]
+fn ssp16b_uimm(ins: u16) -> i32 {
+    shuffle_bits!(ins, 4, 12, 11, 10, 7) as i32
+}
+
+/// Zcmp s-register of rs1' / rs2' encoding
+fn sreg(ins: u16, high: u8, low: u8) -> u8 {
+    match select_bits(ins, high, low) as u8 {
+        r @ 0..=1 => r + 8,
+        r => r + 16,
+    }
+}
+
 fn dec16_addi4spn(ins: u16) -> Maybe<Instr> {
     let rd = crlow(ins);
     let imm = shuffle_bits!(ins, 2, 6, 6, 5, 5, 12, 11, 10, 7) as i32;
@@ -137,17 +160,13 @@ fn dec16_lwsp(ins: u16) -> Maybe<Instr> {
     Ok(Instr::CLoad(rd, GP_SP, lsp4b_uimm(ins), MemWidth::W))
 }
 
-fn dec16_misc(ins: u16) -> Maybe<Instr> {
-    let rs1 = rhigh(ins);
-    let rs2 = rlow(ins);
-    Ok(match (test_bit(ins, 12), rs1, rs2) {
-        (false, 0, 0) => return Err(()),
-        (false, rs1, 0) => Instr::CJalr(GP_ZERO, rs1),
-        (false, rd, rs1) => Instr::COpImm(rd, rs1, 0, BinaryOp::Add),
-        (true, 0, 0) => Instr::Trap(Exception::Ebreak),
-        (true, rs1, 0) => Instr::CJalr(GP_RA, rs1),
-        (true, rd_rs1, rs2) => Instr::COp(rd_rs1, rs2, BinaryOp::Add),
-    })
+/// c.mop.n is c.lui x(n), 0 for odd n up to 15,
+/// no register is written until redefined
+fn dec16_mop(ins: u16) -> Maybe<Instr> {
+    if ins & 0xf8ff != 0x6081 {
+        return Err(());
+    }
+    Ok(Instr::COpImm(GP_ZERO, GP_ZERO, 0, BinaryOp::Add))
 }
 
 fn dec16_swsp(ins: u16) -> Maybe<Instr> {
@@ -164,9 +183,12 @@ impl<Xlen: XlenT> Isa<Xlen> {
         let (rd, rs1) = cregs(ins);
         if_rv128!(
             // lq
-            unimplemented!("rv128 not implemented yet"),
+            Ok(Instr::CLoad(rd, rs1, ls16b_uimm(ins), MemWidth::Q)),
             // fld
-            if_ext_d!(self, Instr::CLoadFp(rd, rs1, ls8b_uimm(ins), Precision::D))
+            if_ext_d!(
+                self,
+                self.check_fp_inx(Instr::CLoadFp(rd, rs1, ls8b_uimm(ins), Precision::D))?
+            )
         )
     }
 
@@ -176,7 +198,10 @@ impl<Xlen: XlenT> Isa<Xlen> {
             // ld
             Ok(Instr::CLoad(rd, rs1, ls8b_uimm(ins), MemWidth::D)),
             // flw
-            if_ext_f!(self, Instr::CLoadFp(rd, rs1, ls4b_uimm(ins), Precision::S))
+            if_ext_f!(
+                self,
+                self.check_fp_inx(Instr::CLoadFp(rd, rs1, ls4b_uimm(ins), Precision::S))?
+            )
         )
     }
 
@@ -184,11 +209,11 @@ impl<Xlen: XlenT> Isa<Xlen> {
         let (rs2, rs1) = cregs(ins);
         if_rv128!(
             // sq
-            unimplemented!("rv128 not implemented yet"),
+            Ok(Instr::CStore(rs1, rs2, ls16b_uimm(ins), MemWidth::Q)),
             // fsd
             if_ext_d!(
                 self,
-                Instr::CStoreFp(rs1, rs2, ls8b_uimm(ins), Precision::D)
+                self.check_fp_inx(Instr::CStoreFp(rs1, rs2, ls8b_uimm(ins), Precision::D))?
             )
         )
     }
@@ -201,17 +226,33 @@ impl<Xlen: XlenT> Isa<Xlen> {
             // fsw
             if_ext_f!(
                 self,
-                Instr::CStoreFp(rs1, rs2, ls4b_uimm(ins), Precision::S)
+                self.check_fp_inx(Instr::CStoreFp(rs1, rs2, ls4b_uimm(ins), Precision::S))?
             )
         )
     }
 
+    fn dec16_zcb_ls(&self, ins: u16) -> Maybe<Instr> {
+        let (rd_rs2, rs1) = cregs(ins);
+        let b_uimm = shuffle_bits!(ins, 0, 6, 6, 5, 5) as i32;
+        let h_uimm = shuffle_bits!(ins, 1, 5, 5) as i32;
+        let ins = match (select_bits(ins, 12, 10), test_bit(ins, 6)) {
+            (0b000, _) => Instr::CLoad(rd_rs2, rs1, b_uimm, MemWidth::BU),
+            (0b001, false) => Instr::CLoad(rd_rs2, rs1, h_uimm, MemWidth::HU),
+            (0b001, true) => Instr::CLoad(rd_rs2, rs1, h_uimm, MemWidth::H),
+            (0b010, _) => Instr::CStore(rs1, rd_rs2, b_uimm, MemWidth::B),
+            (0b011, false) => Instr::CStore(rs1, rd_rs2, h_uimm, MemWidth::H),
+            _ => return Err(()),
+        };
+        if_ext_zcb!(self, ins)
+    }
+
     fn dec16_c0(&self, ins: u16) -> Maybe<Instr> {
         match fn3(ins) {
             0b000 => dec16_addi4spn(ins),
             0b001 => self.dec16_lq_fld(ins),
             0b010 => dec16_lw(ins),
             0b011 => self.dec16_ld_flw(ins),
+            0b100 => self.dec16_zcb_ls(ins),
             0b101 => self.dec16_sq_fsd(ins),
             0b110 => dec_sw(ins),
             0b111 => self.dec16_sd_fsw(ins),
@@ -265,7 +306,28 @@ impl<Xlen: XlenT> Isa<Xlen> {
         Ok(Instr::COpImm(rd_rs1, rd_rs1, imm, BinaryOp::Sra))
     }
 
-    fn dec16_op(ins: u16, rd_rs1: u8) -> Maybe<Instr> {
+    /// c.zext.*, c.sext.* and c.not
+    fn dec16_zcb_unary(&self, rd_rs1: u8, fn3: u8) -> Maybe<Instr> {
+        let ins = match fn3 {
+            0b000 => Instr::COpImm(rd_rs1, rd_rs1, 0xff, BinaryOp::And),
+            #[cfg(all(feature = "Zcb", feature = "Zbb"))]
+            0b001 => if_ext_zbb!(self, Instr::COpUnary(rd_rs1, UnaryOp::SextB))?,
+            #[cfg(all(feature = "Zcb", feature = "Zbb"))]
+            0b010 => if_ext_zbb!(self, Instr::COpUnary(rd_rs1, UnaryOp::ZextH))?,
+            #[cfg(all(feature = "Zcb", feature = "Zbb"))]
+            0b011 => if_ext_zbb!(self, Instr::COpUnary(rd_rs1, UnaryOp::SextH))?,
+            #[cfg(all(feature = "Zba", feature = "RV64"))]
+            0b100 => {
+                let zext_w = if_ext_zba!(self, Instr::COp(rd_rs1, GP_ZERO, BinaryOp::AddUW))?;
+                if_ge_rv64!(zext_w)?
+            }
+            0b101 => Instr::COpImm(rd_rs1, rd_rs1, -1, BinaryOp::Xor),
+            _ => return Err(()),
+        };
+        if_ext_zcb!(self, ins)
+    }
+
+    fn dec16_op(&self, ins: u16, rd_rs1: u8) -> Maybe<Instr> {
         let rs2 = crlow(ins);
         let op = match (test_bit(ins, 12), select_bits(ins, 6, 5)) {
             (false, 0b00) => BinaryOp::Sub,
@@ -274,29 +336,36 @@ impl<Xlen: XlenT> Isa<Xlen> {
             (false, 0b11) => BinaryOp::And,
             (true, 0b00) => if_ge_rv64!(BinaryOp::SubW)?,
             (true, 0b01) => if_ge_rv64!(BinaryOp::AddW)?,
+            #[cfg(feature = "M")]
+            (true, 0b10) => {
+                // c.mul
+                let mul = if_ext_m!(self, BinaryOp::Mul)?;
+                if_ext_zcb!(self, mul)?
+            }
+            (true, 0b11) => return self.dec16_zcb_unary(rd_rs1, select_bits(ins, 4, 2) as u8),
             _ => return Err(()),
         };
         Ok(Instr::COp(rd_rs1, rs2, op))
     }
 
-    fn dec16_misc_alu(ins: u16) -> Maybe<Instr> {
+    fn dec16_misc_alu(&self, ins: u16) -> Maybe<Instr> {
         let rd_rs1 = crhigh(ins);
         match select_bits(ins, 11, 10) {
             0b00 => Self::dec16_srli(ins, rd_rs1),
             0b01 => Self::dec16_srai(ins, rd_rs1),
             0b10 => dec16_andi(ins, rd_rs1),
-            0b11 => Self::dec16_op(ins, rd_rs1),
+            0b11 => self.dec16_op(ins, rd_rs1),
             _ => unreachable!(),
         }
     }
 
-    fn dec16_c1(ins: u16) -> Maybe<Instr> {
+    fn dec16_c1(&self, ins: u16) -> Maybe<Instr> {
         match fn3(ins) {
             0b000 => dec16_addi(ins),
             0b001 => Self::dec16_jal_addiw(ins),
             0b010 => dec16_li(ins),
-            0b011 => dec16_addi16sp_lui(ins),
-            0b100 => Self::dec16_misc_alu(ins),
+            0b011 => dec16_addi16sp_lui(ins).or_else(|_| if_ext_zcmop!(self, dec16_mop(ins)?)),
+            0b100 => self.dec16_misc_alu(ins),
             0b101 => dec_j(ins),
             0b110 => dec_branch(ins, CmpCond::Eq),
             0b111 => dec_branch(ins, CmpCond::Ne),
@@ -313,12 +382,17 @@ impl<Xlen: XlenT> Isa<Xlen> {
     fn dec16_lq_fld_sp(&self, ins: u16) -> Maybe<Instr> {
         let rd = rhigh(ins);
         if_rv128!(
-            // lqsp
-            unimplemented!("rv128 not implemented yet"),
+            {
+                // lqsp
+                if rd == 0 {
+                    return Err(());
+                }
+                Ok(Instr::CLoad(rd, GP_SP, lsp16b_uimm(ins), MemWidth::Q))
+            },
             // fldsp
             if_ext_d!(
                 self,
-                Instr::CLoadFp(rd, GP_SP, lsp8b_uimm(ins), Precision::D)
+                self.check_fp_inx(Instr::CLoadFp(rd, GP_SP, lsp8b_uimm(ins), Precision::D))?
             )
         )
     }
@@ -336,20 +410,72 @@ impl<Xlen: XlenT> Isa<Xlen> {
             // flwsp
             if_ext_f!(
                 self,
-                Instr::CLoadFp(rd, GP_SP, lsp4b_uimm(ins), Precision::S)
+                self.check_fp_inx(Instr::CLoadFp(rd, GP_SP, lsp4b_uimm(ins), Precision::S))?
             )
         )
     }
 
+    /// cm.push / cm.pop*, registers beyond s1 are reserved with E
+    #[cfg(feature = "Zcmp")]
+    fn dec16_push_pop(&self, ins: u16) -> Maybe<Instr> {
+        let rlist = select_bits(ins, 7, 4) as u8;
+        if rlist < 4 || if_ext_e!(self, rlist > 6, false) {
+            return Err(());
+        }
+        // ra, s0 - s(rlist - 5), s10 and s11 always go together
+        let count = if rlist == 15 { 13 } else { rlist - 3 };
+        let bytes = count as u32 * Xlen::XLEN / 8;
+        let base = (bytes + 15) & !15;
+        let stack_adj = base + select_bits(ins, 3, 2) as u32 * 16;
+        Ok(match select_bits(ins, 10, 8) {
+            0b000 => Instr::CPush(count, stack_adj),
+            0b010 => Instr::CPop(count, stack_adj, PopOp::Pop),
+            0b100 => Instr::CPop(count, stack_adj, PopOp::PopRetZ),
+            0b110 => Instr::CPop(count, stack_adj, PopOp::PopRet),
+            _ => return Err(()),
+        })
+    }
+
+    /// cm.mvsa01 / cm.mva01s
+    #[cfg(feature = "Zcmp")]
+    fn dec16_mv_pair(ins: u16) -> Maybe<Instr> {
+        let r1 = sreg(ins, 9, 7);
+        let r2 = sreg(ins, 4, 2);
+        match select_bits(ins, 6, 5) {
+            0b01 if r1 != r2 => Ok(Instr::CMvSA01(r1, r2)),
+            0b11 => Ok(Instr::CMvA01S(r1, r2)),
+            _ => Err(()),
+        }
+    }
+
+    /// Zcmp and Zcmt replace c.fsdsp, no rv128 encodings
+    fn dec16_zcm(&self, ins: u16) -> Maybe<Instr> {
+        if Xlen::XLEN == 128 {
+            return Err(());
+        }
+        match select_bits(ins, 12, 10) {
+            // cm.jt / cm.jalt
+            0b000 => if_ext_zcmt!(self, Instr::CTableJump(select_bits(ins, 9, 2) as u8)),
+            #[cfg(feature = "Zcmp")]
+            0b011 => Self::dec16_mv_pair(ins),
+            #[cfg(feature = "Zcmp")]
+            0b110 | 0b111 => self.dec16_push_pop(ins),
+            _ => Err(()),
+        }
+    }
+
     fn dec16_sq_fsd_sp(&self, ins: u16) -> Maybe<Instr> {
+        if if_ext_zcmp!(self, true, false) || if_ext_zcmt!(self, true, false) {
+            return self.dec16_zcm(ins);
+        }
         let rs2 = rlow(ins);
         if_rv128!(
             // sqsp
-            unimplemented!("rv128 not implemented yet"),
+            Ok(Instr::CStore(GP_SP, rs2, ssp16b_uimm(ins), MemWidth::Q)),
             // fsdsp
             if_ext_d!(
                 self,
-                Instr::CStoreFp(GP_SP, rs2, ssp8b_uimm(ins), Precision::D)
+                self.check_fp_inx(Instr::CStoreFp(GP_SP, rs2, ssp8b_uimm(ins), Precision::D))?
             )
         )
     }
@@ -362,18 +488,35 @@ impl<Xlen: XlenT> Isa<Xlen> {
             // fswsp
             if_ext_f!(
                 self,
-                Instr::CStoreFp(GP_SP, rs2, ssp4b_uimm(ins), Precision::S)
+                self.check_fp_inx(Instr::CStoreFp(GP_SP, rs2, ssp4b_uimm(ins), Precision::S))?
             )
         )
     }
 
+    fn dec16_misc(&self, ins: u16) -> Maybe<Instr> {
+        let rs1 = rhigh(ins);
+        let rs2 = rlow(ins);
+        #[cfg(feature = "Zihintntl")]
+        if let (true, 0, Some(domain)) = (test_bit(ins, 12), rs1, self.ntl_domain(rs2)) {
+            return Ok(Instr::CNtl(domain));
+        }
+        Ok(match (test_bit(ins, 12), rs1, rs2) {
+            (false, 0, 0) => return Err(()),
+            (false, rs1, 0) => Instr::CJalr(GP_ZERO, rs1),
+            (false, rd, rs1) => Instr::COpImm(rd, rs1, 0, BinaryOp::Add),
+            (true, 0, 0) => Instr::Trap(Exception::Ebreak),
+            (true, rs1, 0) => Instr::CJalr(GP_RA, rs1),
+            (true, rd_rs1, rs2) => Instr::COp(rd_rs1, rs2, BinaryOp::Add),
+        })
+    }
+
     fn dec16_c2(&self, ins: u16) -> Maybe<Instr> {
         match fn3(ins) {
             0b000 => Self::dec16_slli(ins),
             0b001 => self.dec16_lq_fld_sp(ins),
             0b010 => dec16_lwsp(ins),
             0b011 => self.dec16_ld_flw_sp(ins),
-            0b100 => dec16_misc(ins),
+            0b100 => self.dec16_misc(ins),
             0b101 => self.dec16_sq_fsd_sp(ins),
             0b110 => dec16_swsp(ins),
             0b111 => self.dec16_sd_fsw_sp(ins),
@@ -384,10 +527,11 @@ impl<Xlen: XlenT> Isa<Xlen> {
     pub fn dec16(&self, ins: u16) -> Instr {
         match select_bits(ins, 1, 0) {
             0b00 => self.dec16_c0(ins),
-            0b01 => Self::dec16_c1(ins),
+            0b01 => self.dec16_c1(ins),
             0b10 => self.dec16_c2(ins),
             _ => Err(()),
         }
+        .and_then(|ins| self.check_e(ins))
         .unwrap_or(Instr::Trap(Exception::IllegalInstr))
     }
 }
@@ -399,6 +543,8 @@ mod tests {
     type RV32 = Isa<u32>;
     #[cfg(feature = "RV64")]
     type RV64 = Isa<u64>;
+    #[cfg(feature = "RV128")]
+    type RV128 = Isa<u128>;
 
     fn all_pass<Xlen: XlenT>(hart: &Isa<Xlen>, ins_raw: &[u16], ins_expect: &[Instr]) -> bool {
         for (&raw, &expect) in ins_raw.iter().zip(ins_expect.iter()) {
@@ -499,5 +645,157 @@ mod tests {
             ];
             assert!(all_pass(&RV32::default(), &ins_raw[1..6], &ins_expect32));
         }
+
+        #[cfg(feature = "RV128")]
+        {
+            let ins_raw = [
+                0x3ee8u16, 0xbee8u16, 0x3afeu16, 0xbfd6u16, 0x8101u16, 0x1516u16, 0x9d15u16,
+            ];
+            let ins_expect = [
+                Instr::CLoad(10, 13, 496, MemWidth::Q),
+                Instr::CStore(13, 10, 496, MemWidth::Q),
+                Instr::CLoad(21, GP_SP, 1008, MemWidth::Q),
+                Instr::CStore(GP_SP, 21, 1008, MemWidth::Q),
+                Instr::COpImm(10, 10, 64, BinaryOp::Srl),
+                Instr::COpImm(10, 10, 101, BinaryOp::Sll),
+                Instr::COp(10, 13, BinaryOp::SubW),
+            ];
+            assert!(all_pass(&RV128::default(), &ins_raw, &ins_expect));
+            // lqsp with rd = 0 is reserved
+            let ins_expect = [Instr::Trap(Exception::IllegalInstr)];
+            assert!(all_pass(&RV128::default(), &[0x307eu16], &ins_expect));
+        }
+
+        #[cfg(all(feature = "Zcb", feature = "Zbb", feature = "M"))]
+        {
+            let ins_raw = [
+                0x81e8u16, 0x85a8u16, 0x85e8u16, 0x89c8u16, 0x8da8u16, 0x9d61u16, 0x9d65u16,
+                0x9d69u16, 0x9d6du16, 0x9d75u16, 0x9d4du16, 0x9d71u16,
+            ];
+            let mut ins_expect = [
+                Instr::CLoad(10, 11, 3, MemWidth::BU),
+                Instr::CLoad(10, 11, 2, MemWidth::HU),
+                Instr::CLoad(10, 11, 2, MemWidth::H),
+                Instr::CStore(11, 10, 1, MemWidth::B),
+                Instr::CStore(11, 10, 2, MemWidth::H),
+                Instr::COpImm(10, 10, 0xff, BinaryOp::And),
+                Instr::COpUnary(10, UnaryOp::SextB),
+                Instr::COpUnary(10, UnaryOp::ZextH),
+                Instr::COpUnary(10, UnaryOp::SextH),
+                Instr::COpImm(10, 10, -1, BinaryOp::Xor),
+                Instr::COp(10, 11, BinaryOp::Mul),
+                // c.zext.w is rv64 only
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_expect));
+            #[cfg(feature = "Zba")]
+            {
+                ins_expect[11] = Instr::COp(10, GP_ZERO, BinaryOp::AddUW);
+                assert!(all_pass(&RV64::default(), &ins_raw, &ins_expect));
+            }
+            let mut isa = RV32::default();
+            isa.Zbb = false;
+            let ins_expect = [Instr::Trap(Exception::IllegalInstr)];
+            assert!(all_pass(&isa, &ins_raw[6..7], &ins_expect));
+        }
+
+        #[cfg(all(feature = "Zcmp", feature = "Zcmt"))]
+        {
+            let ins_raw = [
+                0xb842u16, 0xb876u16, 0xb8feu16, 0xba62u16, 0xbe56u16, 0xbc42u16, 0xac2au16,
+                0xacfeu16, 0xa016u16, 0xa0a2u16, 0xb832u16, 0xac22u16,
+            ];
+            let ins_expect = [
+                Instr::CPush(1, 16),
+                Instr::CPush(4, 32),
+                Instr::CPush(13, 112),
+                Instr::CPop(3, 16, PopOp::Pop),
+                Instr::CPop(2, 32, PopOp::PopRet),
+                Instr::CPop(1, 16, PopOp::PopRetZ),
+                Instr::CMvSA01(8, 18),
+                Instr::CMvA01S(9, 23),
+                Instr::CTableJump(5),
+                Instr::CTableJump(40),
+                // reserved rlist
+                Instr::Trap(Exception::IllegalInstr),
+                // same s register twice
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            let mut isa = RV32::default();
+            isa.Zcmp = true;
+            isa.Zcmt = true;
+            assert!(all_pass(&isa, &ins_raw, &ins_expect));
+
+            // stack_adj grows with xlen
+            #[cfg(feature = "RV64")]
+            {
+                let mut isa = RV64::default();
+                isa.Zcmp = true;
+                let ins_expect = [Instr::CPush(13, 160), Instr::CPush(4, 48)];
+                assert!(all_pass(&isa, &[0xb8feu16, 0xb876u16], &ins_expect));
+            }
+
+            // only s0 - s1 with E
+            #[cfg(feature = "E")]
+            {
+                isa.E = true;
+                let ins_expect = [
+                    Instr::Trap(Exception::IllegalInstr),
+                    Instr::CPop(3, 16, PopOp::Pop),
+                    Instr::Trap(Exception::IllegalInstr),
+                ];
+                assert!(all_pass(
+                    &isa,
+                    &[0xb876u16, 0xba62u16, 0xac2au16],
+                    &ins_expect
+                ));
+            }
+
+            // c.fsdsp when disabled
+            #[cfg(feature = "D")]
+            {
+                let ins_expect = [Instr::CStoreFp(GP_SP, 16, 48, Precision::D)];
+                assert!(all_pass(&RV32::default(), &[0xb842u16], &ins_expect));
+            }
+        }
+
+        #[cfg(all(feature = "Zihintntl", feature = "Zcmop"))]
+        {
+            let ins_raw = [0x900au16, 0x9016u16, 0x901au16, 0x6081u16, 0x6781u16];
+            let ins_expect = [
+                Instr::CNtl(NtlDomain::P1),
+                Instr::CNtl(NtlDomain::All),
+                Instr::COp(0, 6, BinaryOp::Add),
+                Instr::COpImm(GP_ZERO, GP_ZERO, 0, BinaryOp::Add),
+                Instr::COpImm(GP_ZERO, GP_ZERO, 0, BinaryOp::Add),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_expect));
+
+            let mut isa = RV32::default();
+            isa.Zihintntl = false;
+            isa.Zcmop = false;
+            let ins_expect = [
+                Instr::COp(0, 2, BinaryOp::Add),
+                Instr::COp(0, 5, BinaryOp::Add),
+                Instr::COp(0, 6, BinaryOp::Add),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&isa, &ins_raw, &ins_expect));
+        }
+
+        #[cfg(feature = "E")]
+        {
+            let ins_raw = [0x952eu16, 0x892eu16, 0x4502u16, 0x4e02u16];
+            let ins_expect = [
+                Instr::COp(10, 11, BinaryOp::Add),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::CLoad(10, GP_SP, 0, MemWidth::W),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            let mut isa = RV32::default();
+            isa.E = true;
+            assert!(all_pass(&isa, &ins_raw, &ins_expect));
+        }
     }
 }
@@ -103,6 +103,24 @@ fn sr_imm(imm: &mut i32, xlen: u32) -> Maybe<BinaryOp> {
     }
 }
 
+/// shift immediate -> (funct6, shamt)\
+/// funct6 of rv128 keeps its low bit clear since shamt takes 7 bits
+fn shift_imm(imm: i32, xlen: u32) -> (u32, u32) {
+    let imm = imm as u32 & 0xfff;
+    let shamt_len = if xlen == 128 { 7 } else { 6 };
+    let fn6 = (imm >> shamt_len) << (shamt_len - 6);
+    (fn6, imm & ((1 << shamt_len) - 1))
+}
+
+/// shamt of bit-manipulation immediates, funct6 stripped
+fn bm_shamt(shamt: u32, xlen: u32) -> Maybe<i32> {
+    if shamt >= xlen {
+        Err(())
+    } else {
+        Ok(shamt as i32)
+    }
+}
+
 #[cfg(feature = "F")]
 fn round_mode(fn3: u8) -> Maybe<RoundMode> {
     Ok(match fn3 {
@@ -145,17 +163,6 @@ fn dec32_fp_sgnj(ins: u32, pr: Precision) -> Maybe<Instr> {
     Ok(Instr::FpOp2(rd, rs1, rs2, RoundMode::None, pr, op))
 }
 
-#[cfg(feature = "F")]
-fn dec32_fp_minmax(ins: u32, pr: Precision) -> Maybe<Instr> {
-    let (rd, fn3, rs1, rs2, _) = r_type(ins);
-    let op = match fn3 {
-        0b000 => FpBinaryOp::Min,
-        0b001 => FpBinaryOp::Max,
-        _ => return Err(()),
-    };
-    Ok(Instr::FpOp2(rd, rs1, rs2, RoundMode::None, pr, op))
-}
-
 #[cfg(feature = "F")]
 fn dec32_fp_op(ins: u32, pr: Precision, op: FpUnaryOp) -> Maybe<Instr> {
     let (rd, fn3, rs1, rs2, _) = r_type(ins);
@@ -166,18 +173,6 @@ fn dec32_fp_op(ins: u32, pr: Precision, op: FpUnaryOp) -> Maybe<Instr> {
     Ok(Instr::FpOp(rd, rs1, rm, pr, op))
 }
 
-#[cfg(feature = "F")]
-fn dec32_fp_cmp(ins: u32, pr: Precision) -> Maybe<Instr> {
-    let (rd, fn3, rs1, rs2, _) = r_type(ins);
-    let cond = match fn3 {
-        0b000 => FpCmpCond::Le,
-        0b001 => FpCmpCond::Lt,
-        0b010 => FpCmpCond::Eq,
-        _ => return Err(()),
-    };
-    Ok(Instr::FpCmp(rd, rs1, rs2, pr, cond))
-}
-
 fn dec32_branch(ins: u32) -> Maybe<Instr> {
     let (fn3, rs1, rs2, imm) = b_type(ins);
     let cond = match fn3 {
@@ -205,6 +200,165 @@ fn dec32_jal(ins: u32) -> Maybe<Instr> {
     Ok(Instr::Jal(rd, imm))
 }
 
+/// x0 fills unused slots
+impl Instr {
+    /// gp register operands
+    fn gprs(self) -> [u8; 3] {
+        match self {
+            Instr::Undecoded | Instr::Trap(_) | Instr::MiscMem(_) => [0; 3],
+            Instr::OpImm(rd, rs1, ..) => [rd, rs1, 0],
+            Instr::Op(rd, rs1, rs2, _) => [rd, rs1, rs2],
+            #[cfg(any(
+                feature = "Zbb",
+                feature = "Zbkb",
+                feature = "Zknd",
+                feature = "Zknh",
+                feature = "Zksh"
+            ))]
+            Instr::OpUnary(rd, rs1, _) => [rd, rs1, 0],
+            Instr::Auipc(rd, _) | Instr::Jal(rd, _) => [rd, 0, 0],
+            Instr::Load(rd, rs1, ..) | Instr::Jalr(rd, rs1, _) => [rd, rs1, 0],
+            Instr::Store(rs1, rs2, ..) | Instr::Branch(rs1, rs2, ..) => [rs1, rs2, 0],
+            #[cfg(feature = "S")]
+            Instr::System(SystemOp::SfenceVma(rs1, rs2)) => [rs1, rs2, 0],
+            #[cfg(feature = "H")]
+            Instr::System(SystemOp::HfenceVvma(rs1, rs2) | SystemOp::HfenceGvma(rs1, rs2)) => {
+                [rs1, rs2, 0]
+            }
+            #[cfg(feature = "H")]
+            Instr::HLoad(rd, rs1, ..) => [rd, rs1, 0],
+            #[cfg(feature = "H")]
+            Instr::HStore(rs1, rs2, _) => [rs1, rs2, 0],
+            Instr::System(SystemOp::Mret | SystemOp::Wfi) => [0; 3],
+            #[cfg(feature = "S")]
+            Instr::System(SystemOp::Sret) => [0; 3],
+            #[cfg(feature = "Zawrs")]
+            Instr::System(SystemOp::WrsNto | SystemOp::WrsSto) => [0; 3],
+            #[cfg(feature = "Zihintntl")]
+            Instr::Ntl(_) => [0; 3],
+            #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+            Instr::Cbo(rs1, _) => [rs1, 0, 0],
+            #[cfg(feature = "Zicbop")]
+            Instr::Prefetch(rs1, ..) => [rs1, 0, 0],
+            #[cfg(feature = "Zicsr")]
+            Instr::Csr(rd, rs1, _, CsrOp::Rw | CsrOp::Rs | CsrOp::Rc) => [rd, rs1, 0],
+            #[cfg(feature = "Zicsr")]
+            Instr::Csr(rd, _, _, CsrOp::Rwi | CsrOp::Rsi | CsrOp::Rci) => [rd, 0, 0],
+            #[cfg(feature = "A")]
+            Instr::LoadReserved(rd, rs1, ..) => [rd, rs1, 0],
+            #[cfg(feature = "A")]
+            Instr::StoreConditional(rd, rs1, rs2, ..) | Instr::Amo(rd, rs1, rs2, ..) => {
+                [rd, rs1, rs2]
+            }
+            #[cfg(feature = "Zacas")]
+            Instr::AmoCas(rd, rs1, rs2, ..) => [rd, rs1, rs2],
+            #[cfg(feature = "F")]
+            Instr::LoadFp(_, rs1, ..) | Instr::StoreFp(rs1, ..) => [rs1, 0, 0],
+            #[cfg(feature = "F")]
+            Instr::FpCvtGp(rd, ..) | Instr::FpCmp(rd, ..) => [rd, 0, 0],
+            #[cfg(feature = "F")]
+            Instr::GpCvtFp(_, rs1, ..) => [rs1, 0, 0],
+            // fp registers, see fprs
+            #[cfg(feature = "F")]
+            Instr::FpOp3(..) | Instr::FpOp2(..) | Instr::FpOp(..) | Instr::FpCvtFp(..) => [0; 3],
+            #[cfg(feature = "Zfa")]
+            Instr::FpLi(..) => [0; 3],
+            #[cfg(feature = "Zfa")]
+            Instr::FpMvP(_, rs1, rs2, _) => [rs1, rs2, 0],
+            #[cfg(feature = "V")]
+            Instr::VSetVl(rd, VCfg::Vli(rs1, _)) => [rd, rs1, 0],
+            #[cfg(feature = "V")]
+            Instr::VSetVl(rd, VCfg::Vl(rs1, rs2)) => [rd, rs1, rs2],
+            #[cfg(feature = "V")]
+            Instr::VSetVl(rd, VCfg::Ivli(..)) => [rd, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VLoad(op) | Instr::VStore(op) => match op.mode {
+                VAddrMode::Strided(rs2) => [op.rs1, rs2, 0],
+                VAddrMode::Unit
+                | VAddrMode::UnitFF
+                | VAddrMode::Indexed(..)
+                | VAddrMode::Mask
+                | VAddrMode::Whole => [op.rs1, 0, 0],
+            },
+            #[cfg(feature = "V")]
+            Instr::VOp(_, _, VSrc::X(rs1), ..)
+            | Instr::VOpW(_, _, VSrc::X(rs1), ..)
+            | Instr::VCmp(_, _, VSrc::X(rs1), ..)
+            | Instr::VPerm(_, _, VSrc::X(rs1), ..) => [rs1, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VOp(..) | Instr::VOpW(..) | Instr::VCmp(..) | Instr::VPerm(..) => [0; 3],
+            #[cfg(feature = "V")]
+            Instr::VUnary(rd, _, _, VUnaryOp::Cpop | VUnaryOp::First) => [rd, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VUnary(..) | Instr::VRed(..) | Instr::VMask(..) => [0; 3],
+            #[cfg(feature = "V")]
+            Instr::VMv(rd, _, VMvOp::XS) => [rd, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VMv(_, rs1, VMvOp::SX) => [rs1, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VMv(_, _, VMvOp::FS | VMvOp::SF | VMvOp::Whole(_)) => [0; 3],
+            #[cfg(feature = "V")]
+            Instr::VFOp(..)
+            | Instr::VFOpW(..)
+            | Instr::VFCmp(..)
+            | Instr::VFRed(..)
+            | Instr::VFUnary(..) => [0; 3],
+            #[cfg(feature = "C")]
+            Instr::COpImm(rd, rs1, ..) | Instr::CLoad(rd, rs1, ..) | Instr::CJalr(rd, rs1) => {
+                [rd, rs1, 0]
+            }
+            #[cfg(feature = "C")]
+            Instr::COp(rd, rs2, _) | Instr::CStore(rd, rs2, ..) => [rd, rs2, 0],
+            #[cfg(feature = "C")]
+            Instr::CBranch(rs1, ..) | Instr::CJal(rs1, _) => [rs1, 0, 0],
+            #[cfg(all(feature = "C", feature = "Zihintntl"))]
+            Instr::CNtl(_) => [0; 3],
+            #[cfg(all(feature = "C", feature = "F"))]
+            Instr::CLoadFp(_, rs1, ..) | Instr::CStoreFp(rs1, ..) => [rs1, 0, 0],
+            #[cfg(all(feature = "Zcb", feature = "Zbb"))]
+            Instr::COpUnary(rd_rs1, _) => [rd_rs1, 0, 0],
+            // push / pop register lists are checked when decoding
+            #[cfg(feature = "Zcmp")]
+            Instr::CPush(..) | Instr::CPop(..) => [0; 3],
+            #[cfg(feature = "Zcmp")]
+            Instr::CMvSA01(r1, r2) | Instr::CMvA01S(r1, r2) => [r1, r2, 0],
+            #[cfg(feature = "Zcmt")]
+            Instr::CTableJump(_) => [0; 3],
+        }
+    }
+
+    /// fp register operands
+    #[cfg(feature = "F")]
+    fn fprs(self) -> [u8; 4] {
+        match self {
+            Instr::LoadFp(rd, ..) | Instr::GpCvtFp(rd, ..) => [rd, 0, 0, 0],
+            Instr::StoreFp(_, rs2, ..) => [rs2, 0, 0, 0],
+            Instr::FpOp3(rd, rs1, rs2, rs3, ..) => [rd, rs1, rs2, rs3],
+            Instr::FpOp2(rd, rs1, rs2, ..) => [rd, rs1, rs2, 0],
+            Instr::FpOp(rd, rs1, ..) | Instr::FpCvtFp(rd, rs1, ..) => [rd, rs1, 0, 0],
+            Instr::FpCvtGp(_, rs1, ..) => [rs1, 0, 0, 0],
+            Instr::FpCmp(_, rs1, rs2, ..) => [rs1, rs2, 0, 0],
+            #[cfg(feature = "Zfa")]
+            Instr::FpLi(rd, ..) | Instr::FpMvP(rd, ..) => [rd, 0, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VOp(_, _, VSrc::F(rs1), ..)
+            | Instr::VFOp(_, _, VSrc::F(rs1), ..)
+            | Instr::VFOpW(_, _, VSrc::F(rs1), ..)
+            | Instr::VFCmp(_, _, VSrc::F(rs1), ..)
+            | Instr::VPerm(_, _, VSrc::F(rs1), ..) => [rs1, 0, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VMv(rd, _, VMvOp::FS) => [rd, 0, 0, 0],
+            #[cfg(feature = "V")]
+            Instr::VMv(_, rs1, VMvOp::SF) => [rs1, 0, 0, 0],
+            #[cfg(feature = "C")]
+            Instr::CLoadFp(rd, ..) => [rd, 0, 0, 0],
+            #[cfg(feature = "C")]
+            Instr::CStoreFp(_, rs2, ..) => [rs2, 0, 0, 0],
+            _ => [0; 4],
+        }
+    }
+}
+
 impl<Xlen: XlenT> Isa<Xlen> {
     fn dec32_load(ins: u32) -> Maybe<Instr> {
         let (rd, fn3, rs1, imm) = i_type(ins);
@@ -216,6 +370,7 @@ impl<Xlen: XlenT> Isa<Xlen> {
             0b100 => MemWidth::BU,
             0b101 => MemWidth::HU,
             0b110 => if_ge_rv64!(MemWidth::WU)?,
+            0b111 => if_rv128!(MemWidth::DU)?,
             _ => return Err(()),
         };
         Ok(Instr::Load(rd, rs1, imm, mem_width))
@@ -225,17 +380,22 @@ impl<Xlen: XlenT> Isa<Xlen> {
     #[cfg(feature = "F")]
     fn dec32_ls_pr(&self, fmt: u8) -> Maybe<Precision> {
         match fmt {
+            0b001 => if_ext_zfhmin!(self, Precision::H),
             0b010 => Ok(Precision::S),
             0b011 => if_ext_d!(self, Precision::D),
+            0b100 => if_ext_q!(self, Precision::Q),
             _ => Err(()),
         }
     }
 
     fn dec32_load_fp(&self, ins: u32) -> Maybe<Instr> {
+        if matches!(fn3(ins), 0b000 | 0b101 | 0b110 | 0b111) {
+            return if_ext_v!(self, self.dec32_vmem(ins, false)?);
+        }
         if_ext_f!(self, {
             let (rd, fn3, rs1, imm) = i_type(ins);
             let pr = self.dec32_ls_pr(fn3)?;
-            Instr::LoadFp(rd, rs1, imm, pr)
+            self.check_fp_inx(Instr::LoadFp(rd, rs1, imm, pr))?
         })
     }
 
@@ -246,6 +406,9 @@ impl<Xlen: XlenT> Isa<Xlen> {
                 let succ = select_bits(ins, 23, 20) as u8;
                 let fm = select_bits(ins, 31, 28);
                 let fence = match (fm, pred, succ) {
+                    // fence w, 0 with x0 operands
+                    #[cfg(feature = "Zihintpause")]
+                    _ if ins == 0x0100_000f && self.Zihintpause => MiscMemOp::Pause,
                     (0b0000, _, _) => MiscMemOp::Fence(pred, succ),
                     (0b1000, 3, 3) => MiscMemOp::FenceTso,
                     // Base implementations shall treat all such reserved
@@ -255,19 +418,135 @@ impl<Xlen: XlenT> Isa<Xlen> {
                 Ok(Instr::MiscMem(fence))
             }
             0b001 => if_ext_zifencei!(self, Instr::MiscMem(MiscMemOp::FenceI)),
+            // lq, rv128 takes the encoding of cbo.*
+            0b010 if Xlen::XLEN == 128 => if_rv128!({
+                let (rd, _, rs1, imm) = i_type(ins);
+                Instr::Load(rd, rs1, imm, MemWidth::Q)
+            }),
+            #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+            0b010 => self.dec32_cbo(ins),
             _ => Err(()),
         }
     }
 
-    fn dec32_op_imm(ins: u32) -> Maybe<Instr> {
+    /// cbo.* with rd = x0, operation in imm
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+    fn dec32_cbo(&self, ins: u32) -> Maybe<Instr> {
+        let (rd, _, rs1, imm) = i_type(ins);
+        if rd != 0 {
+            return Err(());
+        }
+        let op = match imm {
+            0b000 => if_ext_zicbom!(self, CboOp::Inval)?,
+            0b001 => if_ext_zicbom!(self, CboOp::Clean)?,
+            0b010 => if_ext_zicbom!(self, CboOp::Flush)?,
+            0b100 => if_ext_zicboz!(self, CboOp::Zero)?,
+            _ => return Err(()),
+        };
+        Ok(Instr::Cbo(rs1, op))
+    }
+
+    fn dec32_op_imm(&self, ins: u32) -> Maybe<Instr> {
         let (rd, fn3, rs1, mut imm) = i_type(ins);
+        // prefetch.* are ori x0 with the kind in imm[4:0]
+        #[cfg(feature = "Zicbop")]
+        if (fn3, rd) == (0b110, 0) && self.Zicbop {
+            let prot = match imm & 0x1f {
+                0b00000 => Some(MemProtect::X),
+                0b00001 => Some(MemProtect::R),
+                0b00011 => Some(MemProtect::W),
+                _ => None,
+            };
+            if let Some(prot) = prot {
+                return Ok(Instr::Prefetch(rs1, imm & !0x1f, prot));
+            }
+        }
+        let (fn6, shamt) = shift_imm(imm, Xlen::XLEN);
         let op = match fn3 {
             0b000 => BinaryOp::Add,
-            0b001 => sl_imm(imm, Xlen::XLEN)?,
+            0b001 => match fn6 {
+                0b000000 => sl_imm(imm, Xlen::XLEN)?,
+                0b011000 => {
+                    return if_ext_zbb!(self, {
+                        let op = match shamt {
+                            0b00000 => UnaryOp::Clz,
+                            0b00001 => UnaryOp::Ctz,
+                            0b00010 => UnaryOp::Cpop,
+                            0b00100 => UnaryOp::SextB,
+                            0b00101 => UnaryOp::SextH,
+                            _ => return Err(()),
+                        };
+                        Instr::OpUnary(rd, rs1, op)
+                    })
+                }
+                0b001010 => {
+                    imm = bm_shamt(shamt, Xlen::XLEN)?;
+                    if_ext_zbs!(self, BinaryOp::Bset)?
+                }
+                0b010010 => {
+                    imm = bm_shamt(shamt, Xlen::XLEN)?;
+                    if_ext_zbs!(self, BinaryOp::Bclr)?
+                }
+                0b011010 => {
+                    imm = bm_shamt(shamt, Xlen::XLEN)?;
+                    if_ext_zbs!(self, BinaryOp::Binv)?
+                }
+                0b000100 => {
+                    return match shamt {
+                        0b0000 => if_ext_zknh!(self, Instr::OpUnary(rd, rs1, UnaryOp::Sha256Sum0)),
+                        0b0001 => if_ext_zknh!(self, Instr::OpUnary(rd, rs1, UnaryOp::Sha256Sum1)),
+                        0b0010 => if_ext_zknh!(self, Instr::OpUnary(rd, rs1, UnaryOp::Sha256Sig0)),
+                        0b0011 => if_ext_zknh!(self, Instr::OpUnary(rd, rs1, UnaryOp::Sha256Sig1)),
+                        0b0100..=0b0111 if Xlen::XLEN == 64 => {
+                            if_ge_rv64!(self.dec32_sha512(rd, rs1, shamt), Err(()))
+                        }
+                        0b1000 => if_ext_zksh!(self, Instr::OpUnary(rd, rs1, UnaryOp::Sm3P0)),
+                        0b1001 => if_ext_zksh!(self, Instr::OpUnary(rd, rs1, UnaryOp::Sm3P1)),
+                        _ => Err(()),
+                    };
+                }
+                0b001100 if Xlen::XLEN == 64 => {
+                    return if_ge_rv64!(self.dec32_aes64_imm(rd, rs1, shamt), Err(()));
+                }
+                0b000010 if shamt == 0b01111 && Xlen::XLEN == 32 => {
+                    return if_ext_zbkb!(self, Instr::OpUnary(rd, rs1, UnaryOp::Zip));
+                }
+                _ => return Err(()),
+            },
             0b010 => BinaryOp::Slt,
             0b011 => BinaryOp::SltU,
             0b100 => BinaryOp::Xor,
-            0b101 => sr_imm(&mut imm, Xlen::XLEN)?,
+            0b101 => match fn6 {
+                0b000000 | 0b010000 => sr_imm(&mut imm, Xlen::XLEN)?,
+                0b011000 => {
+                    imm = bm_shamt(shamt, Xlen::XLEN)?;
+                    if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::Ror)?
+                }
+                0b010010 => {
+                    imm = bm_shamt(shamt, Xlen::XLEN)?;
+                    if_ext_zbs!(self, BinaryOp::Bext)?
+                }
+                0b001010 if shamt == 0b111 => {
+                    return if_ext_zbb!(self, Instr::OpUnary(rd, rs1, UnaryOp::OrcB));
+                }
+                0b011010 if shamt == Xlen::XLEN - 8 => {
+                    return if_ext_either!(
+                        Zbb,
+                        "Zbb",
+                        Zbkb,
+                        "Zbkb",
+                        self,
+                        Instr::OpUnary(rd, rs1, UnaryOp::Rev8)
+                    );
+                }
+                0b011010 if shamt == 0b111 => {
+                    return if_ext_zbkb!(self, Instr::OpUnary(rd, rs1, UnaryOp::Brev8));
+                }
+                0b000010 if shamt == 0b01111 && Xlen::XLEN == 32 => {
+                    return if_ext_zbkb!(self, Instr::OpUnary(rd, rs1, UnaryOp::Unzip));
+                }
+                _ => return Err(()),
+            },
             0b110 => BinaryOp::Or,
             0b111 => BinaryOp::And,
             _ => unreachable!(),
@@ -275,18 +554,64 @@ impl<Xlen: XlenT> Isa<Xlen> {
         Ok(Instr::OpImm(rd, rs1, imm, op))
     }
 
-    fn dec32_op_imm_32(ins: u32) -> Maybe<Instr> {
+    fn dec32_op_imm_32(&self, ins: u32) -> Maybe<Instr> {
         if_ge_rv64!({
             let (rd, fn3, rs1, mut imm) = i_type(ins);
+            let fn7 = (imm as u32 >> 5) & 0x7f;
+            let shamt = imm & 0x1f;
             let op = match fn3 {
                 0b000 => BinaryOp::AddW,
+                0b001 => match fn7 {
+                    0b0000000 => BinaryOp::SllW,
+                    // slli.uw takes a 6 bit shamt
+                    0b0000100 | 0b0000101 => {
+                        return if_ext_zba!(
+                            self,
+                            Instr::OpImm(rd, rs1, imm & 0x3f, BinaryOp::SllUW)
+                        );
+                    }
+                    0b0110000 => {
+                        return if_ext_zbb!(self, {
+                            let op = match shamt {
+                                0b00000 => UnaryOp::ClzW,
+                                0b00001 => UnaryOp::CtzW,
+                                0b00010 => UnaryOp::CpopW,
+                                _ => return Err(()),
+                            };
+                            Instr::OpUnary(rd, rs1, op)
+                        });
+                    }
+                    _ => return Err(()),
+                },
+                0b101 => match fn7 {
+                    0b0110000 => {
+                        imm = shamt;
+                        if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::RorW)?
+                    }
+                    _ => match sr_imm(&mut imm, 32)? {
+                        BinaryOp::Srl => BinaryOp::SrlW,
+                        BinaryOp::Sra => BinaryOp::SraW,
+                        _ => unreachable!(),
+                    },
+                },
+                _ => return Err(()),
+            };
+            Instr::OpImm(rd, rs1, imm, op)
+        })
+    }
+
+    fn dec32_op_imm_64(&self, ins: u32) -> Maybe<Instr> {
+        if_rv128!({
+            let (rd, fn3, rs1, mut imm) = i_type(ins);
+            let op = match fn3 {
+                0b000 => BinaryOp::AddD,
                 0b001 => {
-                    sl_imm(imm, 32)?;
-                    BinaryOp::SllW
+                    sl_imm(imm, 64)?;
+                    BinaryOp::SllD
                 }
-                0b101 => match sr_imm(&mut imm, 32)? {
-                    BinaryOp::Srl => BinaryOp::SrlW,
-                    BinaryOp::Sra => BinaryOp::SraW,
+                0b101 => match sr_imm(&mut imm, 64)? {
+                    BinaryOp::Srl => BinaryOp::SrlD,
+                    BinaryOp::Sra => BinaryOp::SraD,
                     _ => unreachable!(),
                 },
                 _ => return Err(()),
@@ -302,27 +627,26 @@ impl<Xlen: XlenT> Isa<Xlen> {
             0b001 => MemWidth::H,
             0b010 => MemWidth::W,
             0b011 => if_ge_rv64!(MemWidth::D)?,
+            0b100 => if_rv128!(MemWidth::Q)?,
             _ => return Err(()),
         };
         Ok(Instr::Store(rs1, rs2, imm, mem_width))
     }
 
     fn dec32_store_fp(&self, ins: u32) -> Maybe<Instr> {
+        if matches!(fn3(ins), 0b000 | 0b101 | 0b110 | 0b111) {
+            return if_ext_v!(self, self.dec32_vmem(ins, true)?);
+        }
         if_ext_f!(self, {
             let (fn3, rs1, rs2, imm) = s_type(ins);
             let pr = self.dec32_ls_pr(fn3)?;
-            Instr::StoreFp(rs1, rs2, imm, pr)
+            self.check_fp_inx(Instr::StoreFp(rs1, rs2, imm, pr))?
         })
     }
 
     fn dec32_amo(&self, ins: u32) -> Maybe<Instr> {
         if_ext_a!(self, {
             let (rd, fn3, rs1, rs2, fn2, fn5) = r4_type(ins);
-            let mem_width = match fn3 {
-                0b010 => MemWidth::W,
-                0b011 => if_ge_rv64!(MemWidth::D)?,
-                _ => return Err(()),
-            };
             let mem_order = match fn2 {
                 0b00 => MemOrder::Relaxed,
                 0b01 => MemOrder::Release,
@@ -330,17 +654,35 @@ impl<Xlen: XlenT> Isa<Xlen> {
                 0b11 => MemOrder::AcqRel,
                 _ => unreachable!(),
             };
+            if fn5 == 0b00101 {
+                return self.dec32_amocas(rd, fn3, rs1, rs2, mem_order);
+            }
+            let mem_width = match fn3 {
+                0b000 => if_ext_zabha!(self, MemWidth::B)?,
+                0b001 => if_ext_zabha!(self, MemWidth::H)?,
+                0b010 => MemWidth::W,
+                0b011 => if_ge_rv64!(MemWidth::D)?,
+                _ => return Err(()),
+            };
+            // no byte / halfword lr / sc
+            let word = fn3 >= 0b010;
             let op = match fn5 {
                 0b00000 => BinaryOp::Add,
                 0b00001 => BinaryOp::Second,
                 0b00010 => {
-                    if rs2 != 0 {
+                    if rs2 != 0 || !word {
                         return Err(());
                     } else {
                         return Ok(Instr::LoadReserved(rd, rs1, mem_order, mem_width));
                     }
                 }
-                0b00011 => return Ok(Instr::StoreConditional(rd, rs1, rs2, mem_order, mem_width)),
+                0b00011 => {
+                    if !word {
+                        return Err(());
+                    } else {
+                        return Ok(Instr::StoreConditional(rd, rs1, rs2, mem_order, mem_width));
+                    }
+                }
                 0b00100 => BinaryOp::Xor,
                 0b01000 => BinaryOp::Or,
                 0b01100 => BinaryOp::And,
@@ -354,8 +696,38 @@ impl<Xlen: XlenT> Isa<Xlen> {
         })
     }
 
+    #[cfg(feature = "A")]
+    fn dec32_amocas(&self, rd: u8, fn3: u8, rs1: u8, rs2: u8, mem_order: MemOrder) -> Maybe<Instr> {
+        if_ext_zacas!(self, {
+            let mem_width = match fn3 {
+                0b000 => if_ext_zabha!(self, MemWidth::B)?,
+                0b001 => if_ext_zabha!(self, MemWidth::H)?,
+                0b010 => MemWidth::W,
+                0b011 => MemWidth::D,
+                // amocas.q is only defined for rv64
+                0b100 if Xlen::XLEN == 64 => MemWidth::Q,
+                _ => return Err(()),
+            };
+            let pair = match mem_width {
+                MemWidth::D => Xlen::XLEN == 32,
+                MemWidth::Q => true,
+                _ => false,
+            };
+            if pair && !(rd.is_multiple_of(2) && rs2.is_multiple_of(2)) {
+                return Err(());
+            }
+            Instr::AmoCas(rd, rs1, rs2, mem_order, mem_width)
+        })
+    }
+
     fn dec32_op(&self, ins: u32) -> Maybe<Instr> {
         let (rd, fn3, rs1, rs2, fn7) = r_type(ins);
+        #[cfg(feature = "Zihintntl")]
+        if (rd, fn3, rs1, fn7) == (0, 0b000, 0, 0) {
+            if let Some(domain) = self.ntl_domain(rs2) {
+                return Ok(Instr::Ntl(domain));
+            }
+        }
         let op = match fn7 {
             0b0000000 => match fn3 {
                 0b000 => BinaryOp::Add,
@@ -370,7 +742,10 @@ impl<Xlen: XlenT> Isa<Xlen> {
             },
             0b0100000 => match fn3 {
                 0b000 => BinaryOp::Sub,
+                0b100 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::Xnor)?,
                 0b101 => BinaryOp::Sra,
+                0b110 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::Orn)?,
+                0b111 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::Andn)?,
                 _ => return Err(()),
             },
             0b0000001 => if_ext_m!(self, {
@@ -386,11 +761,127 @@ impl<Xlen: XlenT> Isa<Xlen> {
                     _ => unreachable!(),
                 }
             })?,
+            0b0010000 => if_ext_zba!(self, {
+                match fn3 {
+                    0b010 => BinaryOp::Sh1Add,
+                    0b100 => BinaryOp::Sh2Add,
+                    0b110 => BinaryOp::Sh3Add,
+                    _ => return Err(()),
+                }
+            })?,
+            0b0000101 => match fn3 {
+                0b001 => if_ext_either!(Zbc, "Zbc", Zbkc, "Zbkc", self, BinaryOp::Clmul)?,
+                0b010 => if_ext_zbc!(self, BinaryOp::Clmulr)?,
+                0b011 => if_ext_either!(Zbc, "Zbc", Zbkc, "Zbkc", self, BinaryOp::Clmulh)?,
+                0b100 => if_ext_zbb!(self, BinaryOp::Min)?,
+                0b101 => if_ext_zbb!(self, BinaryOp::MinU)?,
+                0b110 => if_ext_zbb!(self, BinaryOp::Max)?,
+                0b111 => if_ext_zbb!(self, BinaryOp::MaxU)?,
+                _ => return Err(()),
+            },
+            0b0000100 => match fn3 {
+                // zext.h of rv32 is pack with x0, rv64 encodes it in op-32
+                0b100 if rs2 == 0 && Xlen::XLEN == 32 => {
+                    let pack = if_ext_zbkb!(self, Instr::Op(rd, rs1, rs2, BinaryOp::Pack));
+                    return if_ext_zbb!(self, Ok(Instr::OpUnary(rd, rs1, UnaryOp::ZextH)), pack);
+                }
+                0b100 => if_ext_zbkb!(self, BinaryOp::Pack)?,
+                0b111 => if_ext_zbkb!(self, BinaryOp::PackH)?,
+                _ => return Err(()),
+            },
+            0b0110000 => match fn3 {
+                0b001 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::Rol)?,
+                0b101 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::Ror)?,
+                _ => return Err(()),
+            },
+            0b0100100 => match fn3 {
+                0b001 => if_ext_zbs!(self, BinaryOp::Bclr)?,
+                0b101 => if_ext_zbs!(self, BinaryOp::Bext)?,
+                _ => return Err(()),
+            },
+            0b0110100 if fn3 == 0b001 => if_ext_zbs!(self, BinaryOp::Binv)?,
+            0b0000111 => match fn3 {
+                0b101 => if_ext_zicond!(self, BinaryOp::CzeroEqz)?,
+                0b111 => if_ext_zicond!(self, BinaryOp::CzeroNez)?,
+                _ => return Err(()),
+            },
+            0b0010100 => match fn3 {
+                0b001 => if_ext_zbs!(self, BinaryOp::Bset)?,
+                0b010 => if_ext_zbkx!(self, BinaryOp::Xperm4)?,
+                0b100 => if_ext_zbkx!(self, BinaryOp::Xperm8)?,
+                _ => return Err(()),
+            },
+            _ if fn3 == 0b000 => self.dec32_op_crypto(fn7)?,
             _ => return Err(()),
         };
         Ok(Instr::Op(rd, rs1, rs2, op))
     }
 
+    /// scalar cryptography in op with funct3 = 0, byte select in funct7\[6:5\]
+    fn dec32_op_crypto(&self, fn7: u8) -> Maybe<BinaryOp> {
+        let bs = fn7 >> 5;
+        let rv32 = Xlen::XLEN == 32;
+        match fn7 & 0x1f {
+            0b10001 if rv32 => if_ext_zkne!(self, BinaryOp::Aes32Esi(bs)),
+            0b10011 if rv32 => if_ext_zkne!(self, BinaryOp::Aes32Esmi(bs)),
+            0b10101 if rv32 => if_ext_zknd!(self, BinaryOp::Aes32Dsi(bs)),
+            0b10111 if rv32 => if_ext_zknd!(self, BinaryOp::Aes32Dsmi(bs)),
+            0b11000 => if_ext_zksed!(self, BinaryOp::Sm4Ed(bs)),
+            0b11010 => if_ext_zksed!(self, BinaryOp::Sm4Ks(bs)),
+            _ => match fn7 {
+                0b0101000 if rv32 => if_ext_zknh!(self, BinaryOp::Sha512Sum0R),
+                0b0101001 if rv32 => if_ext_zknh!(self, BinaryOp::Sha512Sum1R),
+                0b0101010 if rv32 => if_ext_zknh!(self, BinaryOp::Sha512Sig0L),
+                0b0101011 if rv32 => if_ext_zknh!(self, BinaryOp::Sha512Sig1L),
+                0b0101110 if rv32 => if_ext_zknh!(self, BinaryOp::Sha512Sig0H),
+                0b0101111 if rv32 => if_ext_zknh!(self, BinaryOp::Sha512Sig1H),
+                _ if Xlen::XLEN == 64 => if_ge_rv64!(self.dec32_aes64(fn7), Err(())),
+                _ => Err(()),
+            },
+        }
+    }
+
+    #[cfg(feature = "RV64")]
+    fn dec32_aes64(&self, fn7: u8) -> Maybe<BinaryOp> {
+        match fn7 {
+            0b0011001 => if_ext_zkne!(self, BinaryOp::Aes64Es),
+            0b0011011 => if_ext_zkne!(self, BinaryOp::Aes64Esm),
+            0b0011101 => if_ext_zknd!(self, BinaryOp::Aes64Ds),
+            0b0011111 => if_ext_zknd!(self, BinaryOp::Aes64Dsm),
+            0b0111111 => if_ext_either!(Zkne, "Zkne", Zknd, "Zknd", self, BinaryOp::Aes64Ks2),
+            _ => Err(()),
+        }
+    }
+
+    /// aes64im and aes64ks1i
+    #[cfg(feature = "RV64")]
+    fn dec32_aes64_imm(&self, rd: u8, rs1: u8, shamt: u32) -> Maybe<Instr> {
+        match shamt {
+            0b000000 => if_ext_zknd!(self, Instr::OpUnary(rd, rs1, UnaryOp::Aes64Im)),
+            // round number 0xa is the last valid one
+            0b010000..=0b011010 => {
+                let rnum = (shamt & 0xf) as i32;
+                let ks1i = if_ext_either!(Zkne, "Zkne", Zknd, "Zknd", self, BinaryOp::Aes64Ks1i)?;
+                Ok(Instr::OpImm(rd, rs1, rnum, ks1i))
+            }
+            _ => Err(()),
+        }
+    }
+
+    #[cfg(feature = "RV64")]
+    fn dec32_sha512(&self, rd: u8, rs1: u8, shamt: u32) -> Maybe<Instr> {
+        if_ext_zknh!(self, {
+            let op = match shamt {
+                0b0100 => UnaryOp::Sha512Sum0,
+                0b0101 => UnaryOp::Sha512Sum1,
+                0b0110 => UnaryOp::Sha512Sig0,
+                0b0111 => UnaryOp::Sha512Sig1,
+                _ => unreachable!(),
+            };
+            Instr::OpUnary(rd, rs1, op)
+        })
+    }
+
     fn dec32_op_32(&self, ins: u32) -> Maybe<Instr> {
         if_ge_rv64!({
             let (rd, fn3, rs1, rs2, fn7) = r_type(ins);
@@ -416,6 +907,64 @@ impl<Xlen: XlenT> Isa<Xlen> {
                         _ => return Err(()),
                     }
                 })?,
+                0b0000100 => match fn3 {
+                    0b000 => if_ext_zba!(self, BinaryOp::AddUW)?,
+                    // zext.h of rv64 is packw with x0
+                    0b100 if rs2 == 0 => {
+                        let packw = if_ext_zbkb!(self, Instr::Op(rd, rs1, rs2, BinaryOp::PackW));
+                        return if_ext_zbb!(
+                            self,
+                            Ok(Instr::OpUnary(rd, rs1, UnaryOp::ZextH)),
+                            packw
+                        );
+                    }
+                    0b100 => if_ext_zbkb!(self, BinaryOp::PackW)?,
+                    _ => return Err(()),
+                },
+                0b0010000 => if_ext_zba!(self, {
+                    match fn3 {
+                        0b010 => BinaryOp::Sh1AddUW,
+                        0b100 => BinaryOp::Sh2AddUW,
+                        0b110 => BinaryOp::Sh3AddUW,
+                        _ => return Err(()),
+                    }
+                })?,
+                0b0110000 => match fn3 {
+                    0b001 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::RolW)?,
+                    0b101 => if_ext_either!(Zbb, "Zbb", Zbkb, "Zbkb", self, BinaryOp::RorW)?,
+                    _ => return Err(()),
+                },
+                _ => return Err(()),
+            };
+            Instr::Op(rd, rs1, rs2, op)
+        })
+    }
+
+    fn dec32_op_64(&self, ins: u32) -> Maybe<Instr> {
+        if_rv128!({
+            let (rd, fn3, rs1, rs2, fn7) = r_type(ins);
+            let op = match fn7 {
+                0b0000000 => match fn3 {
+                    0b000 => BinaryOp::AddD,
+                    0b001 => BinaryOp::SllD,
+                    0b101 => BinaryOp::SrlD,
+                    _ => return Err(()),
+                },
+                0b0100000 => match fn3 {
+                    0b000 => BinaryOp::SubD,
+                    0b101 => BinaryOp::SraD,
+                    _ => return Err(()),
+                },
+                0b0000001 => if_ext_m!(self, {
+                    match fn3 {
+                        0b000 => BinaryOp::MulD,
+                        0b100 => BinaryOp::DivD,
+                        0b101 => BinaryOp::DivUD,
+                        0b110 => BinaryOp::RemD,
+                        0b111 => BinaryOp::RemUD,
+                        _ => return Err(()),
+                    }
+                })?,
                 _ => return Err(()),
             };
             Instr::Op(rd, rs1, rs2, op)
@@ -427,8 +976,120 @@ impl<Xlen: XlenT> Isa<Xlen> {
         match fmt {
             0b00 => Ok(Precision::S),
             0b01 => if_ext_d!(self, Precision::D),
+            0b10 => if_ext_zfhmin!(self, Precision::H),
+            0b11 => if_ext_q!(self, Precision::Q),
+            _ => Err(()),
+        }
+    }
+
+    /// Zfhmin only provides half-precision load, store, move and conversion
+    #[cfg(feature = "F")]
+    fn check_fp_arith(&self, pr: Precision) -> Maybe<()> {
+        match pr {
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => if_ext_zfh!(self, ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Zfinx, fp values are held in gprs
+    #[cfg(feature = "F")]
+    pub fn fp_inx(&self) -> bool {
+        if_ext_zfinx!(self, true, false)
+    }
+
+    /// S-mode is implemented
+    pub fn s_mode(&self) -> bool {
+        if_ext_s!(self, true, false)
+    }
+
+    /// V is implemented
+    pub fn vector(&self) -> bool {
+        if_ext_v!(self, true, false)
+    }
+
+    /// H is implemented, requires S-mode
+    pub fn hyp(&self) -> bool {
+        if_ext_h!(self, self.s_mode(), false)
+    }
+
+    /// Ztso memory model instead of RVWMO
+    pub fn tso(&self) -> bool {
+        if_ext_ztso!(self, true, false)
+    }
+
+    /// ntl.* hint domain of rs2 in add x0, x0, rs2
+    #[cfg(feature = "Zihintntl")]
+    pub fn ntl_domain(&self, rs2: u8) -> Option<NtlDomain> {
+        if !self.Zihintntl {
+            return None;
+        }
+        match rs2 {
+            2 => Some(NtlDomain::P1),
+            3 => Some(NtlDomain::Pall),
+            4 => Some(NtlDomain::S1),
+            5 => Some(NtlDomain::All),
+            _ => None,
+        }
+    }
+
+    /// RV32E / RV64E only have x0 - x15,
+    /// fp operands are gprs as well with Zfinx
+    pub fn check_e(&self, ins: Instr) -> Maybe<Instr> {
+        if_ext_e!(
+            self,
+            {
+                #[cfg(feature = "F")]
+                let fprs = if self.fp_inx() { ins.fprs() } else { [0; 4] };
+                #[cfg(not(feature = "F"))]
+                let fprs = [0u8; 4];
+                if ins.gprs().iter().chain(&fprs).any(|&reg| reg > 15) {
+                    return Err(());
+                }
+                Ok(ins)
+            },
+            Ok(ins)
+        )
+    }
+
+    /// Zfinx has no fp register file to load, store or move,
+    /// rv32 doubles take an even-odd pair, no half or quad
+    #[cfg(feature = "F")]
+    pub fn check_fp_inx(&self, ins: Instr) -> Maybe<Instr> {
+        if !self.fp_inx() {
+            return Ok(ins);
+        }
+        let regs = |pr: Precision, regs: &[u8]| match pr {
+            Precision::S => Ok(()),
+            #[cfg(feature = "D")]
+            Precision::D if Xlen::XLEN > 32 || regs.iter().all(|r| r % 2 == 0) => Ok(()),
+            #[allow(unreachable_patterns)]
             _ => Err(()),
+        };
+        match ins {
+            Instr::LoadFp(..) | Instr::StoreFp(..) => return Err(()),
+            #[cfg(feature = "C")]
+            Instr::CLoadFp(..) | Instr::CStoreFp(..) => return Err(()),
+            Instr::FpCvtGp(_, _, _, _, FpGpOp::MV) | Instr::GpCvtFp(_, _, _, _, GpFpOp::MV) => {
+                return Err(())
+            }
+            #[cfg(feature = "Zfa")]
+            Instr::FpCvtGp(_, _, _, _, FpGpOp::MvH) | Instr::FpLi(..) | Instr::FpMvP(..) => {
+                return Err(())
+            }
+            Instr::FpOp3(rd, rs1, rs2, rs3, _, pr, _) => regs(pr, &[rd, rs1, rs2, rs3])?,
+            Instr::FpOp2(rd, rs1, rs2, _, pr, _) => regs(pr, &[rd, rs1, rs2])?,
+            Instr::FpOp(rd, rs1, _, pr, _) => regs(pr, &[rd, rs1])?,
+            Instr::FpCvtGp(_, rs1, _, pr, _) => regs(pr, &[rs1])?,
+            Instr::GpCvtFp(rd, _, _, pr, _) => regs(pr, &[rd])?,
+            Instr::FpCmp(_, rs1, rs2, pr, _) => regs(pr, &[rs1, rs2])?,
+            Instr::FpCvtFp(rd, rs1, _, from, to) => {
+                regs(from, &[rs1])?;
+                regs(to, &[rd])?;
+            }
+            _ => (),
         }
+        Ok(ins)
     }
 
     #[cfg(feature = "F")]
@@ -436,7 +1097,8 @@ impl<Xlen: XlenT> Isa<Xlen> {
         let (rd, fn3, rs1, rs2, fn2, rs3) = r4_type(ins);
         let rm = round_mode(fn3)?;
         let pr = self.dec32_fp_pr(fn2)?;
-        Ok(Instr::FpOp3(rd, rs1, rs2, rs3, rm, pr, op))
+        self.check_fp_arith(pr)?;
+        self.check_fp_inx(Instr::FpOp3(rd, rs1, rs2, rs3, rm, pr, op))
     }
 
     fn dec32_madd(&self, ins: u32) -> Maybe<Instr> {
@@ -455,16 +1117,58 @@ impl<Xlen: XlenT> Isa<Xlen> {
         if_ext_f!(self, self.dec32_fp_op3(ins, FpTernaryOp::NMAdd)?)
     }
 
-    #[cfg(feature = "D")]
+    #[cfg(feature = "F")]
+    fn dec32_fp_minmax(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
+        let (rd, fn3, rs1, rs2, _) = r_type(ins);
+        let op = match fn3 {
+            0b000 => FpBinaryOp::Min,
+            0b001 => FpBinaryOp::Max,
+            0b010 => if_ext_zfa!(self, FpBinaryOp::MinM)?,
+            0b011 => if_ext_zfa!(self, FpBinaryOp::MaxM)?,
+            _ => return Err(()),
+        };
+        Ok(Instr::FpOp2(rd, rs1, rs2, RoundMode::None, pr, op))
+    }
+
+    #[cfg(feature = "F")]
+    fn dec32_fp_cmp(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
+        let (rd, fn3, rs1, rs2, _) = r_type(ins);
+        let cond = match fn3 {
+            0b000 => FpCmpCond::Le,
+            0b001 => FpCmpCond::Lt,
+            0b010 => FpCmpCond::Eq,
+            0b100 => if_ext_zfa!(self, FpCmpCond::LeQ)?,
+            0b101 => if_ext_zfa!(self, FpCmpCond::LtQ)?,
+            _ => return Err(()),
+        };
+        Ok(Instr::FpCmp(rd, rs1, rs2, pr, cond))
+    }
+
+    #[cfg(feature = "F")]
     fn dec32_fp_cvt_fp(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
         let (rd, fn3, rs1, rs2, _) = r_type(ins);
         let rm = round_mode(fn3)?;
+        // fround / froundnx share the encoding
+        if rs2 == 0b0_0100 || rs2 == 0b0_0101 {
+            return if_ext_zfa!(self, {
+                self.check_fp_arith(pr)?;
+                let op = if rs2 == 0b0_0100 {
+                    FpUnaryOp::Round
+                } else {
+                    FpUnaryOp::RoundNX
+                };
+                Instr::FpOp(rd, rs1, rm, pr, op)
+            });
+        }
         let from_pr = self.dec32_fp_pr(rs2)?;
+        if from_pr == pr {
+            return Err(());
+        }
         Ok(Instr::FpCvtFp(rd, rs1, rm, from_pr, pr))
     }
 
     #[cfg(feature = "F")]
-    fn dec32_fp_cvt_gp(ins: u32, pr: Precision) -> Maybe<Instr> {
+    fn dec32_fp_cvt_gp(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
         let (rd, fn3, rs1, rs2, _) = r_type(ins);
         let rm = round_mode(fn3)?;
         let op = match rs2 {
@@ -472,6 +1176,11 @@ impl<Xlen: XlenT> Isa<Xlen> {
             0b01 => FpGpOp::WU,
             0b10 => if_ge_rv64!(FpGpOp::L)?,
             0b11 => if_ge_rv64!(FpGpOp::LU)?,
+            // fcvtmod.w.d, only with rtz
+            #[cfg(feature = "D")]
+            0b0_1000 if pr == Precision::D && rm == RoundMode::Rtz => {
+                if_ext_zfa!(self, FpGpOp::ModW)?
+            }
             _ => return Err(()),
         };
         Ok(Instr::FpCvtGp(rd, rs1, rm, pr, op))
@@ -497,12 +1206,34 @@ impl<Xlen: XlenT> Isa<Xlen> {
             Precision::S => Ok(()),
             #[cfg(feature = "D")]
             Precision::D => if_ge_rv64!(()),
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => Ok(()),
+            #[cfg(feature = "Q")]
+            Precision::Q => if_rv128!(()),
+        }
+    }
+
+    /// fmvh / fmvp move values twice as wide as xlen
+    #[cfg(feature = "Zfa")]
+    fn check_fp_mvp(pr: Precision) -> Maybe<()> {
+        match pr {
+            #[cfg(feature = "D")]
+            Precision::D if Xlen::XLEN == 32 => Ok(()),
+            #[cfg(feature = "Q")]
+            Precision::Q if Xlen::XLEN == 64 => Ok(()),
+            _ => Err(()),
         }
     }
 
     #[cfg(feature = "F")]
-    fn dec32_fp_mv_gp(ins: u32, pr: Precision) -> Maybe<Instr> {
+    fn dec32_fp_mv_gp(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
         let (rd, fn3, rs1, rs2, _) = r_type(ins);
+        if (rs2, fn3) == (0b1, 0b0) {
+            return if_ext_zfa!(self, {
+                Self::check_fp_mvp(pr)?;
+                Instr::FpCvtGp(rd, rs1, RoundMode::None, pr, FpGpOp::MvH)
+            });
+        }
         if rs2 != 0 {
             return Err(());
         }
@@ -518,8 +1249,12 @@ impl<Xlen: XlenT> Isa<Xlen> {
     }
 
     #[cfg(feature = "F")]
-    fn dec32_gp_mv_fp(ins: u32, pr: Precision) -> Maybe<Instr> {
+    fn dec32_gp_mv_fp(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
         let (rd, fn3, rs1, rs2, _) = r_type(ins);
+        // fli, rs1 is the constant index
+        if (rs2, fn3) == (0b1, 0b0) {
+            return if_ext_zfa!(self, Instr::FpLi(rd, rs1, pr));
+        }
         if fn3 != 0 || rs2 != 0 {
             return Err(());
         }
@@ -527,26 +1262,46 @@ impl<Xlen: XlenT> Isa<Xlen> {
         Ok(Instr::GpCvtFp(rd, rs1, RoundMode::None, pr, GpFpOp::MV))
     }
 
+    #[cfg(feature = "F")]
+    fn dec32_gp_mvp_fp(&self, ins: u32, pr: Precision) -> Maybe<Instr> {
+        if_ext_zfa!(self, {
+            let (rd, fn3, rs1, rs2, _) = r_type(ins);
+            if fn3 != 0 {
+                return Err(());
+            }
+            Self::check_fp_mvp(pr)?;
+            Instr::FpMvP(rd, rs1, rs2, pr)
+        })
+    }
+
     fn dec32_op_fp(&self, ins: u32) -> Maybe<Instr> {
         if_ext_f!(self, {
             let pr = self.dec32_fp_pr(fn2(ins))?;
-            match rs3(ins) {
+            if !matches!(
+                (rs3(ins), fn3(ins)),
+                (0b0_1000, _) | (0b1_1100, 0b000) | (0b1_1110, _)
+            ) {
+                self.check_fp_arith(pr)?;
+            }
+            let ins = match rs3(ins) {
                 0b0_0000 => dec32_fp_op2(ins, pr, FpBinaryOp::Add),
                 0b0_0001 => dec32_fp_op2(ins, pr, FpBinaryOp::Sub),
                 0b0_0010 => dec32_fp_op2(ins, pr, FpBinaryOp::Mul),
                 0b0_0011 => dec32_fp_op2(ins, pr, FpBinaryOp::Div),
                 0b0_0100 => dec32_fp_sgnj(ins, pr),
-                0b0_0101 => dec32_fp_minmax(ins, pr),
-                0b0_1000 => if_ext_d!(self, self.dec32_fp_cvt_fp(ins, pr)?),
+                0b0_0101 => self.dec32_fp_minmax(ins, pr),
+                0b0_1000 => self.dec32_fp_cvt_fp(ins, pr),
                 0b0_1011 => dec32_fp_op(ins, pr, FpUnaryOp::Sqrt),
-                0b1_0100 => dec32_fp_cmp(ins, pr),
-                0b1_1000 => Self::dec32_fp_cvt_gp(ins, pr),
+                0b1_0100 => self.dec32_fp_cmp(ins, pr),
+                0b1_0110 => self.dec32_gp_mvp_fp(ins, pr),
+                0b1_1000 => self.dec32_fp_cvt_gp(ins, pr),
                 0b1_1010 => Self::dec32_gp_cvt_fp(ins, pr),
-                0b1_1100 => Self::dec32_fp_mv_gp(ins, pr),
-                0b1_1110 => Self::dec32_gp_mv_fp(ins, pr),
+                0b1_1100 => self.dec32_fp_mv_gp(ins, pr),
+                0b1_1110 => self.dec32_gp_mv_fp(ins, pr),
                 _ => Err(()),
-            }
-        }?)
+            }?;
+            self.check_fp_inx(ins)?
+        })
     }
 
     #[cfg(feature = "Zicsr")]
@@ -567,11 +1322,87 @@ impl<Xlen: XlenT> Isa<Xlen> {
         Ok(Instr::Csr(rd, rs1, addr, csr_op))
     }
 
+    /// mop.r.n / mop.rr.n write zero to rd until redefined
+    fn dec32_mop(&self, ins: u32) -> Maybe<Instr> {
+        let mop_r = ins & 0xb3c0_0000 == 0x81c0_0000;
+        let mop_rr = ins & 0xb200_0000 == 0x8200_0000;
+        if !mop_r && !mop_rr {
+            return Err(());
+        }
+        if_ext_zimop!(self, Instr::OpImm(rd(ins), GP_ZERO, 0, BinaryOp::Add))
+    }
+
+    /// hlv / hlvx / hsv share funct3 with mop.*, which set bit 31
+    #[cfg(feature = "H")]
+    fn dec32_hlsv(&self, ins: u32) -> Maybe<Instr> {
+        let (rd, _, rs1, rs2, fn7) = r_type(ins);
+        if fn7 & 1 != 0 {
+            let mem_width = match fn7 {
+                0b011_0001 => MemWidth::B,
+                0b011_0011 => MemWidth::H,
+                0b011_0101 => MemWidth::W,
+                0b011_0111 => if_ge_rv64!(MemWidth::D)?,
+                _ => return Err(()),
+            };
+            if rd != 0 {
+                return Err(());
+            }
+            return Ok(Instr::HStore(rs1, rs2, mem_width));
+        }
+        // hlvx.wu on rv32 is a plain word
+        let (mem_width, hlvx) = match (fn7, rs2) {
+            (0b011_0000, 0) => (MemWidth::B, false),
+            (0b011_0000, 1) => (MemWidth::BU, false),
+            (0b011_0010, 0) => (MemWidth::H, false),
+            (0b011_0010, 1) => (MemWidth::HU, false),
+            (0b011_0010, 3) => (MemWidth::HU, true),
+            (0b011_0100, 0) => (MemWidth::W, false),
+            (0b011_0100, 1) => (if_ge_rv64!(MemWidth::WU)?, false),
+            (0b011_0100, 3) => (if_ge_rv64!(Ok(MemWidth::WU), Ok(MemWidth::W))?, true),
+            (0b011_0110, 0) => (if_ge_rv64!(MemWidth::D)?, false),
+            _ => return Err(()),
+        };
+        Ok(Instr::HLoad(rd, rs1, mem_width, hlvx))
+    }
+
     fn dec32_system(&self, ins: u32) -> Maybe<Instr> {
+        if fn3(ins) == 0b100 {
+            #[cfg(feature = "H")]
+            if ins >> 31 == 0 && self.hyp() {
+                return self.dec32_hlsv(ins);
+            }
+            return self.dec32_mop(ins);
+        }
         if fn3(ins) == 0 {
             match ins >> 7 {
-                0b0 => Ok(Instr::Trap(Exception::Ecall)),
-                0b10_0000_0000_0000 => Ok(Instr::Trap(Exception::Ebreak)),
+                0b0 => return Ok(Instr::Trap(Exception::Ecall)),
+                0b10_0000_0000_0000 => return Ok(Instr::Trap(Exception::Ebreak)),
+                0b0010_0000_0100_0000_0000_0000 => {
+                    return if_ext_s!(self, Instr::System(SystemOp::Sret))
+                }
+                0b0110_0000_0100_0000_0000_0000 => return Ok(Instr::System(SystemOp::Mret)),
+                0b0010_0000_1010_0000_0000_0000 => return Ok(Instr::System(SystemOp::Wfi)),
+                0b0000_0001_1010_0000_0000_0000 => {
+                    return if_ext_zawrs!(self, Instr::System(SystemOp::WrsNto))
+                }
+                0b0000_0011_1010_0000_0000_0000 => {
+                    return if_ext_zawrs!(self, Instr::System(SystemOp::WrsSto))
+                }
+                _ => (),
+            }
+            match (fn7(ins), rd(ins)) {
+                #[cfg(feature = "S")]
+                (0b000_1001, 0) if self.s_mode() => {
+                    Ok(Instr::System(SystemOp::SfenceVma(rs1(ins), rs2(ins))))
+                }
+                #[cfg(feature = "H")]
+                (0b001_0001, 0) if self.hyp() => {
+                    Ok(Instr::System(SystemOp::HfenceVvma(rs1(ins), rs2(ins))))
+                }
+                #[cfg(feature = "H")]
+                (0b011_0001, 0) if self.hyp() => {
+                    Ok(Instr::System(SystemOp::HfenceGvma(rs1(ins), rs2(ins))))
+                }
                 _ => Err(()),
             }
         } else {
@@ -584,9 +1415,9 @@ impl<Xlen: XlenT> Isa<Xlen> {
             0b0_0000 => Self::dec32_load(ins),
             0b0_0001 => self.dec32_load_fp(ins),
             0b0_0011 => self.dec32_misc_mem(ins),
-            0b0_0100 => Self::dec32_op_imm(ins),
+            0b0_0100 => self.dec32_op_imm(ins),
             0b0_0101 => dec32_auipc(ins),
-            0b0_0110 => Self::dec32_op_imm_32(ins),
+            0b0_0110 => self.dec32_op_imm_32(ins),
             0b0_1000 => Self::dec32_store(ins),
             0b0_1001 => self.dec32_store_fp(ins),
             0b0_1011 => self.dec32_amo(ins),
@@ -598,12 +1429,16 @@ impl<Xlen: XlenT> Isa<Xlen> {
             0b1_0010 => self.dec32_nmsub(ins),
             0b1_0011 => self.dec32_nmadd(ins),
             0b1_0100 => self.dec32_op_fp(ins),
+            0b1_0101 => if_ext_v!(self, self.dec32_op_v(ins), Err(())),
+            0b1_0110 => self.dec32_op_imm_64(ins),
             0b1_1000 => dec32_branch(ins),
             0b1_1001 => dec32_jalr(ins),
             0b1_1011 => dec32_jal(ins),
             0b1_1100 => self.dec32_system(ins),
+            0b1_1110 => self.dec32_op_64(ins),
             _ => Err(()),
         }
+        .and_then(|ins| self.check_e(ins))
         .unwrap_or(Instr::Trap(Exception::IllegalInstr))
     }
 }
@@ -615,6 +1450,8 @@ mod tests {
     type RV32 = Isa<u32>;
     #[cfg(feature = "RV64")]
     type RV64 = Isa<u64>;
+    #[cfg(feature = "RV128")]
+    type RV128 = Isa<u128>;
 
     fn all_pass<Xlen: XlenT>(hart: &Isa<Xlen>, ins_raw: &[u32], ins_expect: &[Instr]) -> bool {
         for (&raw, &expect) in ins_raw.iter().zip(ins_expect.iter()) {
@@ -674,6 +1511,7 @@ mod tests {
             0x8330000fu32,
             0x00000073u32,
             0x00100073u32,
+            0x30200073u32,
         ];
         let ins_dec = [
             Instr::OpImm(21, 0, -1431658496, BinaryOp::Add),
@@ -717,6 +1555,7 @@ mod tests {
             Instr::MiscMem(MiscMemOp::FenceTso),
             Instr::Trap(Exception::Ecall),
             Instr::Trap(Exception::Ebreak),
+            Instr::System(SystemOp::Mret),
         ];
         assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
 
@@ -759,6 +1598,49 @@ mod tests {
             assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
         }
 
+        #[cfg(feature = "RV128")]
+        {
+            let ins_raw = [
+                0xaaadaa8fu32,
+                0xaaadfa83u32,
+                0xaaddc523u32,
+                0x054d9a93u32,
+                0x054dda93u32,
+                0x454dda93u32,
+                0xaaad8adbu32,
+                0x02ad9adbu32,
+                0x02addadbu32,
+                0x42addadbu32,
+                0x00dd8afbu32,
+                0x40dd8afbu32,
+                0x00dd9afbu32,
+                0x00dddafbu32,
+                0x40dddafbu32,
+            ];
+            let ins_dec = [
+                Instr::Load(21, 27, -1366, MemWidth::Q),
+                Instr::Load(21, 27, -1366, MemWidth::DU),
+                Instr::Store(27, 13, -1366, MemWidth::Q),
+                Instr::OpImm(21, 27, 84, BinaryOp::Sll),
+                Instr::OpImm(21, 27, 84, BinaryOp::Srl),
+                Instr::OpImm(21, 27, 84, BinaryOp::Sra),
+                Instr::OpImm(21, 27, -1366, BinaryOp::AddD),
+                Instr::OpImm(21, 27, 42, BinaryOp::SllD),
+                Instr::OpImm(21, 27, 42, BinaryOp::SrlD),
+                Instr::OpImm(21, 27, 42, BinaryOp::SraD),
+                Instr::Op(21, 27, 13, BinaryOp::AddD),
+                Instr::Op(21, 27, 13, BinaryOp::SubD),
+                Instr::Op(21, 27, 13, BinaryOp::SllD),
+                Instr::Op(21, 27, 13, BinaryOp::SrlD),
+                Instr::Op(21, 27, 13, BinaryOp::SraD),
+            ];
+            assert!(all_pass(&RV128::default(), &ins_raw, &ins_dec));
+            // the 128-bit forms and *d ops are reserved below rv128
+            let ins_dec = [Instr::Trap(Exception::IllegalInstr); 15];
+            assert!(all_pass(&RV64::default(), &ins_raw[..3], &ins_dec));
+            assert!(all_pass(&RV64::default(), &ins_raw[6..], &ins_dec));
+        }
+
         #[cfg(feature = "Zifencei")]
         {
             let ins_raw = [0x0000100fu32];
@@ -831,6 +1713,25 @@ mod tests {
             assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
         }
 
+        #[cfg(all(feature = "M", feature = "RV128"))]
+        {
+            let ins_raw = [
+                0x02dd8afbu32,
+                0x02ddcafbu32,
+                0x02dddafbu32,
+                0x02ddeafbu32,
+                0x02ddfafbu32,
+            ];
+            let ins_dec = [
+                Instr::Op(21, 27, 13, BinaryOp::MulD),
+                Instr::Op(21, 27, 13, BinaryOp::DivD),
+                Instr::Op(21, 27, 13, BinaryOp::DivUD),
+                Instr::Op(21, 27, 13, BinaryOp::RemD),
+                Instr::Op(21, 27, 13, BinaryOp::RemUD),
+            ];
+            assert!(all_pass(&RV128::default(), &ins_raw, &ins_dec));
+        }
+
         #[cfg(feature = "A")]
         {
             let ins_raw = [
@@ -893,6 +1794,283 @@ mod tests {
             assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
         }
 
+        #[cfg(all(feature = "Zba", feature = "Zbb", feature = "Zbc", feature = "Zbs"))]
+        {
+            let ins_raw = [
+                0x20ddaab3u32,
+                0x20ddeab3u32,
+                0x40ddfab3u32,
+                0x40ddeab3u32,
+                0x40ddcab3u32,
+                0x0addcab3u32,
+                0x0adddab3u32,
+                0x0addeab3u32,
+                0x0addfab3u32,
+                0x0add9ab3u32,
+                0x0addaab3u32,
+                0x0addbab3u32,
+                0x60dd9ab3u32,
+                0x60dddab3u32,
+                0x48dd9ab3u32,
+                0x48dddab3u32,
+                0x68dd9ab3u32,
+                0x28dd9ab3u32,
+                0x080dcab3u32,
+                0x600d9a93u32,
+                0x601d9a93u32,
+                0x602d9a93u32,
+                0x604d9a93u32,
+                0x605d9a93u32,
+                0x61fdda93u32,
+                0x285d9a93u32,
+                0x487d9a93u32,
+                0x689d9a93u32,
+                0x483dda93u32,
+                0x287dda93u32,
+                0x698dda93u32,
+                0x6b8dda93u32,
+                0x628dda93u32,
+                0x08dd8abbu32,
+            ];
+            let ins_dec = [
+                Instr::Op(21, 27, 13, BinaryOp::Sh1Add),
+                Instr::Op(21, 27, 13, BinaryOp::Sh3Add),
+                Instr::Op(21, 27, 13, BinaryOp::Andn),
+                Instr::Op(21, 27, 13, BinaryOp::Orn),
+                Instr::Op(21, 27, 13, BinaryOp::Xnor),
+                Instr::Op(21, 27, 13, BinaryOp::Min),
+                Instr::Op(21, 27, 13, BinaryOp::MinU),
+                Instr::Op(21, 27, 13, BinaryOp::Max),
+                Instr::Op(21, 27, 13, BinaryOp::MaxU),
+                Instr::Op(21, 27, 13, BinaryOp::Clmul),
+                Instr::Op(21, 27, 13, BinaryOp::Clmulr),
+                Instr::Op(21, 27, 13, BinaryOp::Clmulh),
+                Instr::Op(21, 27, 13, BinaryOp::Rol),
+                Instr::Op(21, 27, 13, BinaryOp::Ror),
+                Instr::Op(21, 27, 13, BinaryOp::Bclr),
+                Instr::Op(21, 27, 13, BinaryOp::Bext),
+                Instr::Op(21, 27, 13, BinaryOp::Binv),
+                Instr::Op(21, 27, 13, BinaryOp::Bset),
+                Instr::OpUnary(21, 27, UnaryOp::ZextH),
+                Instr::OpUnary(21, 27, UnaryOp::Clz),
+                Instr::OpUnary(21, 27, UnaryOp::Ctz),
+                Instr::OpUnary(21, 27, UnaryOp::Cpop),
+                Instr::OpUnary(21, 27, UnaryOp::SextB),
+                Instr::OpUnary(21, 27, UnaryOp::SextH),
+                Instr::OpImm(21, 27, 31, BinaryOp::Ror),
+                Instr::OpImm(21, 27, 5, BinaryOp::Bset),
+                Instr::OpImm(21, 27, 7, BinaryOp::Bclr),
+                Instr::OpImm(21, 27, 9, BinaryOp::Binv),
+                Instr::OpImm(21, 27, 3, BinaryOp::Bext),
+                Instr::OpUnary(21, 27, UnaryOp::OrcB),
+                Instr::OpUnary(21, 27, UnaryOp::Rev8),
+                // rev8 of rv64, rori with shamt >= 32
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                // op-32 is rv64 only
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zba", feature = "Zbb", feature = "Zbs", feature = "RV64"))]
+        {
+            let ins_raw = [
+                0x6b8dda93u32,
+                0x628dda93u32,
+                0x2a8d9a93u32,
+                0x08dd8abbu32,
+                0x080dcabbu32,
+                0x20ddaabbu32,
+                0x20ddcabbu32,
+                0x20ddeabbu32,
+                0x60dd9abbu32,
+                0x60dddabbu32,
+                0x0a8d9a9bu32,
+                0x600d9a9bu32,
+                0x601d9a9bu32,
+                0x602d9a9bu32,
+                0x611dda9bu32,
+            ];
+            let ins_dec = [
+                Instr::OpUnary(21, 27, UnaryOp::Rev8),
+                Instr::OpImm(21, 27, 40, BinaryOp::Ror),
+                Instr::OpImm(21, 27, 40, BinaryOp::Bset),
+                Instr::Op(21, 27, 13, BinaryOp::AddUW),
+                Instr::OpUnary(21, 27, UnaryOp::ZextH),
+                Instr::Op(21, 27, 13, BinaryOp::Sh1AddUW),
+                Instr::Op(21, 27, 13, BinaryOp::Sh2AddUW),
+                Instr::Op(21, 27, 13, BinaryOp::Sh3AddUW),
+                Instr::Op(21, 27, 13, BinaryOp::RolW),
+                Instr::Op(21, 27, 13, BinaryOp::RorW),
+                Instr::OpImm(21, 27, 40, BinaryOp::SllUW),
+                Instr::OpUnary(21, 27, UnaryOp::ClzW),
+                Instr::OpUnary(21, 27, UnaryOp::CtzW),
+                Instr::OpUnary(21, 27, UnaryOp::CpopW),
+                Instr::OpImm(21, 27, 17, BinaryOp::RorW),
+            ];
+            assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zkn", feature = "Zks"))]
+        {
+            let ins_raw = [
+                0x08ddcab3u32,
+                0x08ddfab3u32,
+                0x687dda93u32,
+                0x08fd9a93u32,
+                0x08fdda93u32,
+                0x28ddaab3u32,
+                0x28ddcab3u32,
+                0xa2dd8ab3u32,
+                0xe6dd8ab3u32,
+                0x2add8ab3u32,
+                0x6edd8ab3u32,
+                0x70dd8ab3u32,
+                0xf4dd8ab3u32,
+                0x100d9a93u32,
+                0x101d9a93u32,
+                0x102d9a93u32,
+                0x103d9a93u32,
+                0x108d9a93u32,
+                0x109d9a93u32,
+                0x50dd8ab3u32,
+                0x52dd8ab3u32,
+                0x54dd8ab3u32,
+                0x56dd8ab3u32,
+                0x5cdd8ab3u32,
+                0x5edd8ab3u32,
+                0x32dd8ab3u32,
+                0x106d9a93u32,
+            ];
+            let ins_dec = [
+                Instr::Op(21, 27, 13, BinaryOp::Pack),
+                Instr::Op(21, 27, 13, BinaryOp::PackH),
+                Instr::OpUnary(21, 27, UnaryOp::Brev8),
+                Instr::OpUnary(21, 27, UnaryOp::Zip),
+                Instr::OpUnary(21, 27, UnaryOp::Unzip),
+                Instr::Op(21, 27, 13, BinaryOp::Xperm4),
+                Instr::Op(21, 27, 13, BinaryOp::Xperm8),
+                Instr::Op(21, 27, 13, BinaryOp::Aes32Esi(2)),
+                Instr::Op(21, 27, 13, BinaryOp::Aes32Esmi(3)),
+                Instr::Op(21, 27, 13, BinaryOp::Aes32Dsi(0)),
+                Instr::Op(21, 27, 13, BinaryOp::Aes32Dsmi(1)),
+                Instr::Op(21, 27, 13, BinaryOp::Sm4Ed(1)),
+                Instr::Op(21, 27, 13, BinaryOp::Sm4Ks(3)),
+                Instr::OpUnary(21, 27, UnaryOp::Sha256Sum0),
+                Instr::OpUnary(21, 27, UnaryOp::Sha256Sum1),
+                Instr::OpUnary(21, 27, UnaryOp::Sha256Sig0),
+                Instr::OpUnary(21, 27, UnaryOp::Sha256Sig1),
+                Instr::OpUnary(21, 27, UnaryOp::Sm3P0),
+                Instr::OpUnary(21, 27, UnaryOp::Sm3P1),
+                Instr::Op(21, 27, 13, BinaryOp::Sha512Sum0R),
+                Instr::Op(21, 27, 13, BinaryOp::Sha512Sum1R),
+                Instr::Op(21, 27, 13, BinaryOp::Sha512Sig0L),
+                Instr::Op(21, 27, 13, BinaryOp::Sha512Sig1L),
+                Instr::Op(21, 27, 13, BinaryOp::Sha512Sig0H),
+                Instr::Op(21, 27, 13, BinaryOp::Sha512Sig1H),
+                // aes64es, sha512sig0 are rv64 only
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zkn", feature = "Zks", feature = "RV64"))]
+        {
+            let ins_raw = [
+                0x32dd8ab3u32,
+                0x36dd8ab3u32,
+                0x3add8ab3u32,
+                0x3edd8ab3u32,
+                0x7edd8ab3u32,
+                0x300d9a93u32,
+                0x315d9a93u32,
+                0x104d9a93u32,
+                0x105d9a93u32,
+                0x106d9a93u32,
+                0x107d9a93u32,
+                0x08ddcabbu32,
+                0x08ddcab3u32,
+                0x080dcab3u32,
+                0x70dd8ab3u32,
+                0x31bd9a93u32,
+                0xa2dd8ab3u32,
+                0x50dd8ab3u32,
+                0x08fd9a93u32,
+            ];
+            let ins_dec = [
+                Instr::Op(21, 27, 13, BinaryOp::Aes64Es),
+                Instr::Op(21, 27, 13, BinaryOp::Aes64Esm),
+                Instr::Op(21, 27, 13, BinaryOp::Aes64Ds),
+                Instr::Op(21, 27, 13, BinaryOp::Aes64Dsm),
+                Instr::Op(21, 27, 13, BinaryOp::Aes64Ks2),
+                Instr::OpUnary(21, 27, UnaryOp::Aes64Im),
+                Instr::OpImm(21, 27, 5, BinaryOp::Aes64Ks1i),
+                Instr::OpUnary(21, 27, UnaryOp::Sha512Sum0),
+                Instr::OpUnary(21, 27, UnaryOp::Sha512Sum1),
+                Instr::OpUnary(21, 27, UnaryOp::Sha512Sig0),
+                Instr::OpUnary(21, 27, UnaryOp::Sha512Sig1),
+                Instr::Op(21, 27, 13, BinaryOp::PackW),
+                Instr::Op(21, 27, 13, BinaryOp::Pack),
+                // zext.h of rv32 is pack with x0 on rv64
+                Instr::Op(21, 27, 0, BinaryOp::Pack),
+                Instr::Op(21, 27, 13, BinaryOp::Sm4Ed(1)),
+                // aes64ks1i round number out of range, rv32 only ones
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(feature = "Zabha")]
+        {
+            let ins_raw = [0x06dd8aafu32, 0xe6dd9aafu32, 0x160d8aafu32];
+            let ins_dec = [
+                Instr::Amo(21, 27, 13, MemOrder::AcqRel, MemWidth::B, BinaryOp::Add),
+                Instr::Amo(21, 27, 13, MemOrder::AcqRel, MemWidth::H, BinaryOp::MaxU),
+                // no lr.b
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zacas", feature = "Zabha"))]
+        {
+            let ins_raw = [
+                0x2eddaaafu32,
+                0x2ecdba2fu32,
+                0x2eddbaafu32,
+                0x2edd8aafu32,
+                0x2edd9aafu32,
+                0x2ecdca2fu32,
+            ];
+            let ins_dec = [
+                Instr::AmoCas(21, 27, 13, MemOrder::AcqRel, MemWidth::W),
+                Instr::AmoCas(20, 27, 12, MemOrder::AcqRel, MemWidth::D),
+                // odd register pair
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::AmoCas(21, 27, 13, MemOrder::AcqRel, MemWidth::B),
+                Instr::AmoCas(21, 27, 13, MemOrder::AcqRel, MemWidth::H),
+                // no amocas.q on rv32
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zacas", feature = "RV64"))]
+        {
+            let ins_raw = [0x2eddbaafu32, 0x2ecdca2fu32, 0x2eddcaafu32];
+            let ins_dec = [
+                Instr::AmoCas(21, 27, 13, MemOrder::AcqRel, MemWidth::D),
+                Instr::AmoCas(20, 27, 12, MemOrder::AcqRel, MemWidth::Q),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
+        }
+
         #[cfg(feature = "F")]
         {
             let ins_raw = [
@@ -1111,5 +2289,454 @@ mod tests {
             ];
             assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
         }
+
+        #[cfg(feature = "Q")]
+        {
+            let ins_raw = [
+                0x010dca87u32,
+                0x015dc827u32,
+                0x07fdfad3u32,
+                0x461d8ad3u32,
+                0x423dfad3u32,
+                0xc60d9ad3u32,
+                0xe60d8ad3u32,
+            ];
+            let ins_dec = [
+                Instr::LoadFp(21, 27, 16, Precision::Q),
+                Instr::StoreFp(27, 21, 16, Precision::Q),
+                Instr::FpOp2(21, 27, 31, RoundMode::Dyn, Precision::Q, FpBinaryOp::Add),
+                Instr::FpCvtFp(21, 27, RoundMode::Rne, Precision::D, Precision::Q),
+                Instr::FpCvtFp(21, 27, RoundMode::Dyn, Precision::Q, Precision::D),
+                Instr::FpCvtGp(21, 27, RoundMode::Rtz, Precision::Q, FpGpOp::W),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+            #[cfg(feature = "RV64")]
+            assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
+        }
+
+        #[cfg(feature = "Zfh")]
+        {
+            let ins_raw = [
+                0x004d9a87u32,
+                0x015d9227u32,
+                0x45fd8ac3u32,
+                0x05fdfad3u32,
+                0x402dfad3u32,
+                0x440dfad3u32,
+                0xe40d8ad3u32,
+                0xe40d9ad3u32,
+                0xf40d8ad3u32,
+                0x442dfad3u32,
+            ];
+            let ins_dec = [
+                Instr::LoadFp(21, 27, 4, Precision::H),
+                Instr::StoreFp(27, 21, 4, Precision::H),
+                Instr::FpOp3(
+                    21,
+                    27,
+                    31,
+                    8,
+                    RoundMode::Rne,
+                    Precision::H,
+                    FpTernaryOp::MAdd,
+                ),
+                Instr::FpOp2(21, 27, 31, RoundMode::Dyn, Precision::H, FpBinaryOp::Add),
+                Instr::FpCvtFp(21, 27, RoundMode::Dyn, Precision::H, Precision::S),
+                Instr::FpCvtFp(21, 27, RoundMode::Dyn, Precision::S, Precision::H),
+                Instr::FpCvtGp(21, 27, RoundMode::None, Precision::H, FpGpOp::MV),
+                Instr::FpCvtGp(21, 27, RoundMode::None, Precision::H, FpGpOp::Class),
+                Instr::GpCvtFp(21, 27, RoundMode::None, Precision::H, GpFpOp::MV),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+
+            // Zfhmin only, arithmetic and class are illegal
+            let mut isa = RV32::default();
+            isa.Zfh = false;
+            let ins_dec = [
+                Instr::LoadFp(21, 27, 4, Precision::H),
+                Instr::StoreFp(27, 21, 4, Precision::H),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::FpCvtFp(21, 27, RoundMode::Dyn, Precision::H, Precision::S),
+                Instr::FpCvtFp(21, 27, RoundMode::Dyn, Precision::S, Precision::H),
+                Instr::FpCvtGp(21, 27, RoundMode::None, Precision::H, FpGpOp::MV),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::GpCvtFp(21, 27, RoundMode::None, Precision::H, GpFpOp::MV),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zfa", feature = "D"))]
+        {
+            let ins_raw = [
+                0xf01f0ad3u32,
+                0x29fdaad3u32,
+                0x2bfdbad3u32,
+                0x404dfad3u32,
+                0x425d9ad3u32,
+                0xc28d9ad3u32,
+                0xc28d8ad3u32,
+                0xa1fdcad3u32,
+                0xa3fddad3u32,
+                0xe21d8ad3u32,
+                0xb3fd8ad3u32,
+            ];
+            let ins_dec = [
+                Instr::FpLi(21, 30, Precision::S),
+                Instr::FpOp2(21, 27, 31, RoundMode::None, Precision::S, FpBinaryOp::MinM),
+                Instr::FpOp2(21, 27, 31, RoundMode::None, Precision::D, FpBinaryOp::MaxM),
+                Instr::FpOp(21, 27, RoundMode::Dyn, Precision::S, FpUnaryOp::Round),
+                Instr::FpOp(21, 27, RoundMode::Rtz, Precision::D, FpUnaryOp::RoundNX),
+                Instr::FpCvtGp(21, 27, RoundMode::Rtz, Precision::D, FpGpOp::ModW),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::FpCmp(21, 27, 31, Precision::S, FpCmpCond::LeQ),
+                Instr::FpCmp(21, 27, 31, Precision::D, FpCmpCond::LtQ),
+                Instr::FpCvtGp(21, 27, RoundMode::None, Precision::D, FpGpOp::MvH),
+                Instr::FpMvP(21, 27, 31, Precision::D),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+
+            // fmvh / fmvp only move doubles on rv32
+            #[cfg(feature = "RV64")]
+            {
+                let mut ins_dec = ins_dec;
+                ins_dec[9] = Instr::Trap(Exception::IllegalInstr);
+                ins_dec[10] = Instr::Trap(Exception::IllegalInstr);
+                assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
+            }
+
+            let mut isa = RV32::default();
+            isa.Zfa = false;
+            let ins_dec = [Instr::Trap(Exception::IllegalInstr); 11];
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zfinx", feature = "D"))]
+        {
+            let ins_raw = [
+                0x0045a507u32,
+                0x00c505d3u32,
+                0x02c68753u32,
+                0xc0059553u32,
+                0xe0058553u32,
+                0xf0058553u32,
+            ];
+            let mut ins_dec = [
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::FpOp2(11, 10, 12, RoundMode::Rne, Precision::S, FpBinaryOp::Add),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::FpCvtGp(10, 11, RoundMode::Rtz, Precision::S, FpGpOp::W),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            let mut isa = RV32::default();
+            isa.Zfinx = true;
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+
+            // no register pairs on rv64
+            #[cfg(feature = "RV64")]
+            {
+                ins_dec[2] =
+                    Instr::FpOp2(14, 13, 12, RoundMode::Rne, Precision::D, FpBinaryOp::Add);
+                let mut isa = RV64::default();
+                isa.Zfinx = true;
+                assert!(all_pass(&isa, &ins_raw, &ins_dec));
+            }
+        }
+
+        #[cfg(all(
+            feature = "Zicond",
+            feature = "Zihintntl",
+            feature = "Zihintpause",
+            feature = "Zimop"
+        ))]
+        {
+            let ins_raw = [
+                0x0ec5d533u32,
+                0x0ec5f533u32,
+                0x0100000fu32,
+                0x0110000fu32,
+                0x00300033u32,
+                0x00600033u32,
+                0x81c5c573u32,
+                0xcec5c573u32,
+            ];
+            let ins_dec = [
+                Instr::Op(10, 11, 12, BinaryOp::CzeroEqz),
+                Instr::Op(10, 11, 12, BinaryOp::CzeroNez),
+                Instr::MiscMem(MiscMemOp::Pause),
+                Instr::MiscMem(MiscMemOp::Fence(1, 1)),
+                Instr::Ntl(NtlDomain::Pall),
+                Instr::Op(0, 0, 6, BinaryOp::Add),
+                Instr::OpImm(10, GP_ZERO, 0, BinaryOp::Add),
+                Instr::OpImm(10, GP_ZERO, 0, BinaryOp::Add),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+
+            // hints fall back to their base instructions
+            let mut isa = RV32::default();
+            isa.Zicond = false;
+            isa.Zihintntl = false;
+            isa.Zihintpause = false;
+            isa.Zimop = false;
+            let ins_dec = [
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::MiscMem(MiscMemOp::Fence(1, 0)),
+                Instr::MiscMem(MiscMemOp::Fence(1, 1)),
+                Instr::Op(0, 0, 3, BinaryOp::Add),
+                Instr::Op(0, 0, 6, BinaryOp::Add),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+        }
+
+        #[cfg(all(feature = "Zicbom", feature = "Zicboz", feature = "Zicbop"))]
+        {
+            let ins_raw = [
+                0x0005200fu32,
+                0x0015200fu32,
+                0x0025200fu32,
+                0x0045200fu32,
+                0x0035200fu32,
+                0x0045210fu32,
+                0x04056013u32,
+                0xfe356013u32,
+                0x00256013u32,
+            ];
+            let ins_dec = [
+                Instr::Cbo(10, CboOp::Inval),
+                Instr::Cbo(10, CboOp::Clean),
+                Instr::Cbo(10, CboOp::Flush),
+                Instr::Cbo(10, CboOp::Zero),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Prefetch(10, 64, MemProtect::X),
+                Instr::Prefetch(10, -32, MemProtect::W),
+                Instr::OpImm(0, 10, 2, BinaryOp::Or),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+
+            // prefetches fall back to ori
+            let mut isa = RV32::default();
+            isa.Zicbom = false;
+            isa.Zicboz = false;
+            isa.Zicbop = false;
+            let ins_dec = [
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::OpImm(0, 10, 64, BinaryOp::Or),
+                Instr::OpImm(0, 10, -29, BinaryOp::Or),
+                Instr::OpImm(0, 10, 2, BinaryOp::Or),
+            ];
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+        }
+
+        #[cfg(feature = "Zawrs")]
+        {
+            let ins_raw = [0x10500073u32, 0x00d00073u32, 0x01d00073u32, 0x00d08073u32];
+            let ins_dec = [
+                Instr::System(SystemOp::Wfi),
+                Instr::System(SystemOp::WrsNto),
+                Instr::System(SystemOp::WrsSto),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+
+            let mut isa = RV32::default();
+            isa.Zawrs = false;
+            let ins_dec = [
+                Instr::System(SystemOp::Wfi),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+        }
+
+        #[cfg(feature = "S")]
+        {
+            let ins_raw = [0x12d00073u32];
+            let ins_dec = [Instr::System(SystemOp::SfenceVma(0, 13))];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+            let mut isa = RV32::default();
+            isa.S = false;
+            assert_eq!(isa.dec32(ins_raw[0]), Instr::Trap(Exception::IllegalInstr));
+        }
+
+        #[cfg(feature = "H")]
+        {
+            let ins_raw = [
+                0x6005c573u32,
+                0x6415c573u32,
+                0x6435c573u32,
+                0x6805c573u32,
+                0x6835c573u32,
+                0x6ac5c073u32,
+                0x22c58073u32,
+                0x62000073u32,
+                0x6815c573u32,
+                0x6025c573u32,
+                0x6ac5c573u32,
+            ];
+            let ins_dec = [
+                Instr::HLoad(10, 11, MemWidth::B, false),
+                Instr::HLoad(10, 11, MemWidth::HU, false),
+                Instr::HLoad(10, 11, MemWidth::HU, true),
+                Instr::HLoad(10, 11, MemWidth::W, false),
+                Instr::HLoad(10, 11, MemWidth::W, true),
+                Instr::HStore(11, 12, MemWidth::W),
+                Instr::System(SystemOp::HfenceVvma(11, 12)),
+                Instr::System(SystemOp::HfenceGvma(0, 0)),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+
+            #[cfg(feature = "RV64")]
+            {
+                let ins_raw = [0x6815c573u32, 0x6835c573u32, 0x6c05c573u32, 0x6ec5c073u32];
+                let ins_dec = [
+                    Instr::HLoad(10, 11, MemWidth::WU, false),
+                    Instr::HLoad(10, 11, MemWidth::WU, true),
+                    Instr::HLoad(10, 11, MemWidth::D, false),
+                    Instr::HStore(11, 12, MemWidth::D),
+                ];
+                assert!(all_pass(&RV64::default(), &ins_raw, &ins_dec));
+            }
+
+            let mut isa = RV32::default();
+            isa.H = false;
+            assert!(ins_raw[..8]
+                .iter()
+                .all(|&raw| isa.dec32(raw) == Instr::Trap(Exception::IllegalInstr)));
+        }
+
+        #[cfg(feature = "E")]
+        {
+            let ins_raw = [
+                0x00c58533u32,
+                0x00c58933u32,
+                0x00082503u32,
+                0x30089573u32,
+                0x00001e37u32,
+            ];
+            let ins_dec = [
+                Instr::Op(10, 11, 12, BinaryOp::Add),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            let mut isa = RV32::default();
+            isa.E = true;
+            assert!(all_pass(&isa, &ins_raw, &ins_dec));
+
+            // fp registers are not limited
+            #[cfg(feature = "F")]
+            assert!(all_pass(
+                &isa,
+                &[0x00052f07u32],
+                &[Instr::LoadFp(30, 10, 0, Precision::S)]
+            ));
+
+            // unless they are gprs
+            #[cfg(feature = "Zfinx")]
+            {
+                let ins_raw = [0x00c5f553u32, 0x0145f553u32];
+                let ins_dec = [
+                    Instr::FpOp2(10, 11, 12, RoundMode::Dyn, Precision::S, FpBinaryOp::Add),
+                    Instr::Trap(Exception::IllegalInstr),
+                ];
+                isa.Zfinx = true;
+                assert!(all_pass(&isa, &ins_raw, &ins_dec));
+            }
+        }
+
+        #[cfg(feature = "V")]
+        {
+            let mem = |mode, eew, nf, vm| VMemOp {
+                vd: 4,
+                rs1: 10,
+                mode,
+                eew,
+                nf,
+                vm,
+            };
+            let ins_raw = [
+                0x0d0572d7u32,
+                0xc0147057u32,
+                0x80c5f557u32,
+                0x022180d7u32,
+                0x002fb0d7u32,
+                0xa28fb257u32,
+                0x5e054257u32,
+                0x6e85c057u32,
+                0xee21a257u32,
+                0x0221a0d7u32,
+                0x6621a0d7u32,
+                0x42282557u32,
+                0x42202557u32,
+                0x4a22a257u32,
+                0x5208a257u32,
+                0x9e20b257u32,
+                0x022550d7u32,
+                0x42201557u32,
+                0x4a261257u32,
+                0x0e2190d7u32,
+                0x02056207u32,
+                0x0ab57207u32,
+                0x03050207u32,
+                0x04855227u32,
+                0x42055207u32,
+                0x22856207u32,
+                0x02850227u32,
+                0x02b50207u32,
+                0x422180d7u32,
+                0x22856227u32,
+            ];
+            let ins_dec = [
+                Instr::VSetVl(5, VCfg::Vli(10, 0xd0)),
+                Instr::VSetVl(0, VCfg::Ivli(8, 0x01)),
+                Instr::VSetVl(10, VCfg::Vl(11, 12)),
+                Instr::VOp(1, 2, VSrc::V(3), true, VIntOp::Add),
+                Instr::VOp(1, 2, VSrc::I(-1), false, VIntOp::Add),
+                Instr::VOp(4, 8, VSrc::I(31), true, VIntOp::Srl),
+                Instr::VOp(4, 0, VSrc::X(10), true, VIntOp::Merge),
+                Instr::VCmp(0, 8, VSrc::X(11), true, VCmpOp::Lt),
+                Instr::VOpW(4, 2, VSrc::V(3), true, VWideOp::WMul),
+                Instr::VRed(1, 2, 3, true, VRedOp::Sum),
+                Instr::VMask(1, 2, 3, VMaskLogic::And),
+                Instr::VUnary(10, 2, true, VUnaryOp::Cpop),
+                Instr::VMv(10, 2, VMvOp::XS),
+                Instr::VUnary(4, 2, true, VUnaryOp::Sext(4)),
+                Instr::VUnary(4, 0, true, VUnaryOp::Id),
+                Instr::VMv(4, 2, VMvOp::Whole(2)),
+                Instr::VFOp(1, 2, VSrc::F(10), true, VFpOp::Add),
+                Instr::VMv(10, 2, VMvOp::FS),
+                Instr::VFUnary(4, 2, true, VFpUnaryOp::Cvt(VFpCvt::FF, VWidth::Widen)),
+                Instr::VFRed(1, 2, 3, true, VFpRedOp::OSum),
+                Instr::VLoad(mem(VAddrMode::Unit, 4, 1, true)),
+                Instr::VLoad(mem(VAddrMode::Strided(11), 8, 1, true)),
+                Instr::VLoad(mem(VAddrMode::UnitFF, 1, 1, true)),
+                Instr::VStore(mem(VAddrMode::Indexed(8, false), 2, 1, false)),
+                Instr::VLoad(mem(VAddrMode::Unit, 2, 3, true)),
+                Instr::VLoad(mem(VAddrMode::Whole, 4, 2, true)),
+                Instr::VStore(mem(VAddrMode::Whole, 1, 1, true)),
+                Instr::VLoad(mem(VAddrMode::Mask, 1, 1, true)),
+                Instr::Trap(Exception::IllegalInstr),
+                Instr::Trap(Exception::IllegalInstr),
+            ];
+            assert!(all_pass(&RV32::default(), &ins_raw, &ins_dec));
+        }
     }
 }
@@ -0,0 +1,380 @@
+use crate::{decode::common::*, uop::*, utils::Maybe, xlen::XlenT};
+
+fn fn3(ins: u32) -> u8 {
+    select_bits(ins, 14, 12) as u8
+}
+
+fn fn6(ins: u32) -> u8 {
+    select_bits(ins, 31, 26) as u8
+}
+
+fn vd(ins: u32) -> u8 {
+    select_bits(ins, 11, 7) as u8
+}
+
+fn vs1(ins: u32) -> u8 {
+    select_bits(ins, 19, 15) as u8
+}
+
+fn vs2(ins: u32) -> u8 {
+    select_bits(ins, 24, 20) as u8
+}
+
+/// unmasked
+fn vm(ins: u32) -> bool {
+    select_bits(ins, 25, 25) != 0
+}
+
+fn simm5(ins: u32) -> i8 {
+    sext(select_bits(ins, 19, 15), 4) as i8
+}
+
+fn uimm5(ins: u32) -> i8 {
+    select_bits(ins, 19, 15) as i8
+}
+
+/// (vd, vs2, vm)
+fn v_type(ins: u32) -> (u8, u8, bool) {
+    (vd(ins), vs2(ins), vm(ins))
+}
+
+/// operand category of OP-V
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum VCat {
+    Ivv,
+    Fvv,
+    Mvv,
+    Ivi,
+    Ivx,
+    Fvf,
+    Mvx,
+}
+
+/// second operand of OP-V by category, `uimm` for unsigned immediate
+fn v_src(ins: u32, cat: VCat, uimm: bool) -> VSrc {
+    match cat {
+        VCat::Ivv | VCat::Fvv | VCat::Mvv => VSrc::V(vs1(ins)),
+        VCat::Ivi if uimm => VSrc::I(uimm5(ins)),
+        VCat::Ivi => VSrc::I(simm5(ins)),
+        VCat::Ivx | VCat::Mvx => VSrc::X(vs1(ins)),
+        VCat::Fvf => VSrc::F(vs1(ins)),
+    }
+}
+
+/// element width in bytes of vector load / store
+fn v_mem_eew(width: u8) -> Maybe<u8> {
+    match width {
+        0b000 => Ok(1),
+        0b101 => Ok(2),
+        0b110 => Ok(4),
+        0b111 => Ok(8),
+        _ => Err(()),
+    }
+}
+
+fn dec32_vsetvl(ins: u32) -> Maybe<Instr> {
+    let rd = vd(ins);
+    let rs1 = vs1(ins);
+    let cfg = match select_bits(ins, 31, 30) {
+        0b00 | 0b01 => VCfg::Vli(rs1, select_bits(ins, 30, 20) as u16),
+        0b11 => VCfg::Ivli(rs1, select_bits(ins, 29, 20) as u16),
+        _ if select_bits(ins, 31, 25) == 0b100_0000 => VCfg::Vl(rs1, vs2(ins)),
+        _ => return Err(()),
+    };
+    Ok(Instr::VSetVl(rd, cfg))
+}
+
+fn dec32_opi(ins: u32, cat: VCat) -> Maybe<Instr> {
+    use VCat::*;
+    let (vd, vs2, vm) = v_type(ins);
+    let op = |op| Ok(Instr::VOp(vd, vs2, v_src(ins, cat, false), vm, op));
+    let op_u = |op| Ok(Instr::VOp(vd, vs2, v_src(ins, cat, true), vm, op));
+    let wide = |op| Ok(Instr::VOpW(vd, vs2, v_src(ins, cat, true), vm, op));
+    let cmp = |op| Ok(Instr::VCmp(vd, vs2, v_src(ins, cat, false), vm, op));
+    let perm = |op| Ok(Instr::VPerm(vd, vs2, v_src(ins, cat, true), vm, op));
+    match (fn6(ins), cat) {
+        (0b00_0000, _) => op(VIntOp::Add),
+        (0b00_0010, Ivv | Ivx) => op(VIntOp::Sub),
+        (0b00_0011, Ivi | Ivx) => op(VIntOp::RSub),
+        (0b00_0100, Ivv | Ivx) => op(VIntOp::MinU),
+        (0b00_0101, Ivv | Ivx) => op(VIntOp::Min),
+        (0b00_0110, Ivv | Ivx) => op(VIntOp::MaxU),
+        (0b00_0111, Ivv | Ivx) => op(VIntOp::Max),
+        (0b00_1001, _) => op(VIntOp::And),
+        (0b00_1010, _) => op(VIntOp::Or),
+        (0b00_1011, _) => op(VIntOp::Xor),
+        (0b00_1100, _) => perm(VPermOp::RGather),
+        (0b00_1110, Ivv) => perm(VPermOp::RGatherEi16),
+        (0b00_1110, _) => perm(VPermOp::SlideUp),
+        (0b00_1111, Ivi | Ivx) => perm(VPermOp::SlideDown),
+        (0b01_0000, _) if !vm => op(VIntOp::Adc),
+        (0b01_0001, _) => cmp(VCmpOp::Madc),
+        (0b01_0010, Ivv | Ivx) if !vm => op(VIntOp::Sbc),
+        (0b01_0011, Ivv | Ivx) => cmp(VCmpOp::Msbc),
+        // vmv.v.* when unmasked
+        (0b01_0111, _) if !vm || vs2 == 0 => op(VIntOp::Merge),
+        (0b01_1000, _) => cmp(VCmpOp::Eq),
+        (0b01_1001, _) => cmp(VCmpOp::Ne),
+        (0b01_1010, Ivv | Ivx) => cmp(VCmpOp::LtU),
+        (0b01_1011, Ivv | Ivx) => cmp(VCmpOp::Lt),
+        (0b01_1100, _) => cmp(VCmpOp::LeU),
+        (0b01_1101, _) => cmp(VCmpOp::Le),
+        (0b01_1110, Ivi | Ivx) => cmp(VCmpOp::GtU),
+        (0b01_1111, Ivi | Ivx) => cmp(VCmpOp::Gt),
+        (0b10_0000, _) => op(VIntOp::SAddU),
+        (0b10_0001, _) => op(VIntOp::SAdd),
+        (0b10_0010, Ivv | Ivx) => op(VIntOp::SSubU),
+        (0b10_0011, Ivv | Ivx) => op(VIntOp::SSub),
+        (0b10_0101, _) => op_u(VIntOp::Sll),
+        (0b10_0111, Ivv | Ivx) => op(VIntOp::SMul),
+        // vmv<nr>r.v
+        (0b10_0111, Ivi) if vm => match uimm5(ins) {
+            nr @ (0 | 1 | 3 | 7) => Ok(Instr::VMv(vd, vs2, VMvOp::Whole(nr as u8 + 1))),
+            _ => Err(()),
+        },
+        (0b10_1000, _) => op_u(VIntOp::Srl),
+        (0b10_1001, _) => op_u(VIntOp::Sra),
+        (0b10_1010, _) => op_u(VIntOp::SSrl),
+        (0b10_1011, _) => op_u(VIntOp::SSra),
+        (0b10_1100, _) => wide(VWideOp::NSrl),
+        (0b10_1101, _) => wide(VWideOp::NSra),
+        (0b10_1110, _) => wide(VWideOp::NClipU),
+        (0b10_1111, _) => wide(VWideOp::NClip),
+        (0b11_0000, Ivv) => Ok(Instr::VRed(vd, vs2, vs1(ins), vm, VRedOp::WSumU)),
+        (0b11_0001, Ivv) => Ok(Instr::VRed(vd, vs2, vs1(ins), vm, VRedOp::WSum)),
+        _ => Err(()),
+    }
+}
+
+fn dec32_opm(ins: u32, cat: VCat) -> Maybe<Instr> {
+    use VCat::*;
+    let (vd, vs2, vm) = v_type(ins);
+    let vs1 = vs1(ins);
+    let src = v_src(ins, cat, false);
+    let op = |op| Ok(Instr::VOp(vd, vs2, src, vm, op));
+    let wide = |op| Ok(Instr::VOpW(vd, vs2, src, vm, op));
+    let red = |op| Ok(Instr::VRed(vd, vs2, vs1, vm, op));
+    let unary = |op| Ok(Instr::VUnary(vd, vs2, vm, op));
+    let mask = |op| match vm {
+        true => Ok(Instr::VMask(vd, vs2, vs1, op)),
+        false => Err(()),
+    };
+    match (fn6(ins), cat) {
+        (0b00_0000, Mvv) => red(VRedOp::Sum),
+        (0b00_0001, Mvv) => red(VRedOp::And),
+        (0b00_0010, Mvv) => red(VRedOp::Or),
+        (0b00_0011, Mvv) => red(VRedOp::Xor),
+        (0b00_0100, Mvv) => red(VRedOp::MinU),
+        (0b00_0101, Mvv) => red(VRedOp::Min),
+        (0b00_0110, Mvv) => red(VRedOp::MaxU),
+        (0b00_0111, Mvv) => red(VRedOp::Max),
+        (0b00_1000, _) => op(VIntOp::AAddU),
+        (0b00_1001, _) => op(VIntOp::AAdd),
+        (0b00_1010, _) => op(VIntOp::ASubU),
+        (0b00_1011, _) => op(VIntOp::ASub),
+        (0b00_1110, Mvx) => Ok(Instr::VPerm(vd, vs2, src, vm, VPermOp::Slide1Up)),
+        (0b00_1111, Mvx) => Ok(Instr::VPerm(vd, vs2, src, vm, VPermOp::Slide1Down)),
+        (0b01_0000, Mvv) if vm => match vs1 {
+            0b0_0000 => Ok(Instr::VMv(vd, vs2, VMvOp::XS)),
+            0b1_0000 => unary(VUnaryOp::Cpop),
+            0b1_0001 => unary(VUnaryOp::First),
+            _ => Err(()),
+        },
+        (0b01_0000, Mvx) if vm && vs2 == 0 => Ok(Instr::VMv(vd, vs1, VMvOp::SX)),
+        (0b01_0010, Mvv) => match vs1 {
+            0b0_0010 => unary(VUnaryOp::Zext(8)),
+            0b0_0011 => unary(VUnaryOp::Sext(8)),
+            0b0_0100 => unary(VUnaryOp::Zext(4)),
+            0b0_0101 => unary(VUnaryOp::Sext(4)),
+            0b0_0110 => unary(VUnaryOp::Zext(2)),
+            0b0_0111 => unary(VUnaryOp::Sext(2)),
+            _ => Err(()),
+        },
+        (0b01_0100, Mvv) => match vs1 {
+            0b0_0001 => unary(VUnaryOp::Msbf),
+            0b0_0010 => unary(VUnaryOp::Msof),
+            0b0_0011 => unary(VUnaryOp::Msif),
+            0b1_0000 => unary(VUnaryOp::Iota),
+            0b1_0001 if vs2 == 0 => unary(VUnaryOp::Id),
+            _ => Err(()),
+        },
+        (0b01_0111, Mvv) if vm => Ok(Instr::VPerm(vd, vs2, src, vm, VPermOp::Compress)),
+        (0b01_1000, Mvv) => mask(VMaskLogic::AndN),
+        (0b01_1001, Mvv) => mask(VMaskLogic::And),
+        (0b01_1010, Mvv) => mask(VMaskLogic::Or),
+        (0b01_1011, Mvv) => mask(VMaskLogic::Xor),
+        (0b01_1100, Mvv) => mask(VMaskLogic::OrN),
+        (0b01_1101, Mvv) => mask(VMaskLogic::Nand),
+        (0b01_1110, Mvv) => mask(VMaskLogic::Nor),
+        (0b01_1111, Mvv) => mask(VMaskLogic::Xnor),
+        (0b10_0000, _) => op(VIntOp::DivU),
+        (0b10_0001, _) => op(VIntOp::Div),
+        (0b10_0010, _) => op(VIntOp::RemU),
+        (0b10_0011, _) => op(VIntOp::Rem),
+        (0b10_0100, _) => op(VIntOp::MulHU),
+        (0b10_0101, _) => op(VIntOp::Mul),
+        (0b10_0110, _) => op(VIntOp::MulHSU),
+        (0b10_0111, _) => op(VIntOp::MulH),
+        (0b10_1001, _) => op(VIntOp::MAdd),
+        (0b10_1011, _) => op(VIntOp::NMSub),
+        (0b10_1101, _) => op(VIntOp::Macc),
+        (0b10_1111, _) => op(VIntOp::NMSac),
+        (0b11_0000, _) => wide(VWideOp::WAddU),
+        (0b11_0001, _) => wide(VWideOp::WAdd),
+        (0b11_0010, _) => wide(VWideOp::WSubU),
+        (0b11_0011, _) => wide(VWideOp::WSub),
+        (0b11_0100, _) => wide(VWideOp::WAddUW),
+        (0b11_0101, _) => wide(VWideOp::WAddW),
+        (0b11_0110, _) => wide(VWideOp::WSubUW),
+        (0b11_0111, _) => wide(VWideOp::WSubW),
+        (0b11_1000, _) => wide(VWideOp::WMulU),
+        (0b11_1010, _) => wide(VWideOp::WMulSU),
+        (0b11_1011, _) => wide(VWideOp::WMul),
+        (0b11_1100, _) => wide(VWideOp::WMaccU),
+        (0b11_1101, _) => wide(VWideOp::WMacc),
+        (0b11_1110, Mvx) => wide(VWideOp::WMaccUS),
+        (0b11_1111, _) => wide(VWideOp::WMaccSU),
+        _ => Err(()),
+    }
+}
+
+/// vfcvt / vfwcvt / vfncvt by vs1 field
+fn dec32_vfcvt(sel: u8) -> Maybe<VFpUnaryOp> {
+    let width = match sel >> 3 {
+        0b00 => VWidth::Single,
+        0b01 => VWidth::Widen,
+        0b10 => VWidth::Narrow,
+        _ => return Err(()),
+    };
+    let cvt = match (sel & 7, width) {
+        (0b000, _) => VFpCvt::XuF,
+        (0b001, _) => VFpCvt::XF,
+        (0b010, _) => VFpCvt::FXu,
+        (0b011, _) => VFpCvt::FX,
+        (0b100, VWidth::Widen | VWidth::Narrow) => VFpCvt::FF,
+        (0b101, VWidth::Narrow) => VFpCvt::RodFF,
+        (0b110, _) => VFpCvt::RtzXuF,
+        (0b111, _) => VFpCvt::RtzXF,
+        _ => return Err(()),
+    };
+    Ok(VFpUnaryOp::Cvt(cvt, width))
+}
+
+fn dec32_opf(ins: u32, cat: VCat) -> Maybe<Instr> {
+    use VCat::*;
+    let (vd, vs2, vm) = v_type(ins);
+    let vs1 = vs1(ins);
+    let src = v_src(ins, cat, false);
+    let op = |op| Ok(Instr::VFOp(vd, vs2, src, vm, op));
+    let wide = |op| Ok(Instr::VFOpW(vd, vs2, src, vm, op));
+    let cmp = |op| Ok(Instr::VFCmp(vd, vs2, src, vm, op));
+    let red = |op| Ok(Instr::VFRed(vd, vs2, vs1, vm, op));
+    let unary = |op| Ok(Instr::VFUnary(vd, vs2, vm, op));
+    match (fn6(ins), cat) {
+        (0b00_0000, _) => op(VFpOp::Add),
+        (0b00_0001, Fvv) => red(VFpRedOp::USum),
+        (0b00_0010, _) => op(VFpOp::Sub),
+        (0b00_0011, Fvv) => red(VFpRedOp::OSum),
+        (0b00_0100, _) => op(VFpOp::Min),
+        (0b00_0101, Fvv) => red(VFpRedOp::Min),
+        (0b00_0110, _) => op(VFpOp::Max),
+        (0b00_0111, Fvv) => red(VFpRedOp::Max),
+        (0b00_1000, _) => op(VFpOp::SgnJ),
+        (0b00_1001, _) => op(VFpOp::SgnJN),
+        (0b00_1010, _) => op(VFpOp::SgnJX),
+        (0b00_1110, Fvf) => Ok(Instr::VPerm(vd, vs2, src, vm, VPermOp::Slide1Up)),
+        (0b00_1111, Fvf) => Ok(Instr::VPerm(vd, vs2, src, vm, VPermOp::Slide1Down)),
+        (0b01_0000, Fvv) if vm && vs1 == 0 => Ok(Instr::VMv(vd, vs2, VMvOp::FS)),
+        (0b01_0000, Fvf) if vm && vs2 == 0 => Ok(Instr::VMv(vd, vs1, VMvOp::SF)),
+        (0b01_0010, Fvv) => unary(dec32_vfcvt(vs1)?),
+        (0b01_0011, Fvv) => match vs1 {
+            0b0_0000 => unary(VFpUnaryOp::Sqrt),
+            0b0_0100 => unary(VFpUnaryOp::Rsqrt7),
+            0b0_0101 => unary(VFpUnaryOp::Rec7),
+            0b1_0000 => unary(VFpUnaryOp::Class),
+            _ => Err(()),
+        },
+        // vfmv.v.f when unmasked
+        (0b01_0111, Fvf) if !vm || vs2 == 0 => op(VFpOp::Merge),
+        (0b01_1000, _) => cmp(VFpCmpOp::Eq),
+        (0b01_1001, _) => cmp(VFpCmpOp::Le),
+        (0b01_1011, _) => cmp(VFpCmpOp::Lt),
+        (0b01_1100, _) => cmp(VFpCmpOp::Ne),
+        (0b01_1101, Fvf) => cmp(VFpCmpOp::Gt),
+        (0b01_1111, Fvf) => cmp(VFpCmpOp::Ge),
+        (0b10_0000, _) => op(VFpOp::Div),
+        (0b10_0001, Fvf) => op(VFpOp::RDiv),
+        (0b10_0100, _) => op(VFpOp::Mul),
+        (0b10_0111, Fvf) => op(VFpOp::RSub),
+        (0b10_1000, _) => op(VFpOp::MAdd),
+        (0b10_1001, _) => op(VFpOp::NMAdd),
+        (0b10_1010, _) => op(VFpOp::MSub),
+        (0b10_1011, _) => op(VFpOp::NMSub),
+        (0b10_1100, _) => op(VFpOp::Macc),
+        (0b10_1101, _) => op(VFpOp::NMacc),
+        (0b10_1110, _) => op(VFpOp::MSac),
+        (0b10_1111, _) => op(VFpOp::NMSac),
+        (0b11_0000, _) => wide(VFpWideOp::WAdd),
+        (0b11_0001, Fvv) => red(VFpRedOp::WUSum),
+        (0b11_0010, _) => wide(VFpWideOp::WSub),
+        (0b11_0011, Fvv) => red(VFpRedOp::WOSum),
+        (0b11_0100, _) => wide(VFpWideOp::WAddW),
+        (0b11_0110, _) => wide(VFpWideOp::WSubW),
+        (0b11_1000, _) => wide(VFpWideOp::WMul),
+        (0b11_1100, _) => wide(VFpWideOp::WMacc),
+        (0b11_1101, _) => wide(VFpWideOp::WNMacc),
+        (0b11_1110, _) => wide(VFpWideOp::WMSac),
+        (0b11_1111, _) => wide(VFpWideOp::WNMSac),
+        _ => Err(()),
+    }
+}
+
+impl<Xlen: XlenT> Isa<Xlen> {
+    pub fn dec32_op_v(&self, ins: u32) -> Maybe<Instr> {
+        match fn3(ins) {
+            0b000 => dec32_opi(ins, VCat::Ivv),
+            0b001 => dec32_opf(ins, VCat::Fvv),
+            0b010 => dec32_opm(ins, VCat::Mvv),
+            0b011 => dec32_opi(ins, VCat::Ivi),
+            0b100 => dec32_opi(ins, VCat::Ivx),
+            0b101 => dec32_opf(ins, VCat::Fvf),
+            0b110 => dec32_opm(ins, VCat::Mvx),
+            _ => dec32_vsetvl(ins),
+        }
+    }
+
+    /// vector load / store under LOAD-FP / STORE-FP
+    pub fn dec32_vmem(&self, ins: u32, store: bool) -> Maybe<Instr> {
+        let (vd, rs2, vm) = v_type(ins);
+        let eew = v_mem_eew(fn3(ins))?;
+        let mew = select_bits(ins, 28, 28);
+        let nf = select_bits(ins, 31, 29) as u8 + 1;
+        if mew != 0 {
+            return Err(());
+        }
+        let mode = match (select_bits(ins, 27, 26), rs2) {
+            (0b00, 0b0_0000) => VAddrMode::Unit,
+            (0b00, 0b1_0000) if !store => VAddrMode::UnitFF,
+            (0b00, 0b0_1000) if vm && nf.is_power_of_two() && (!store || eew == 1) => {
+                VAddrMode::Whole
+            }
+            (0b00, 0b0_1011) if vm && nf == 1 && eew == 1 => VAddrMode::Mask,
+            (0b01, _) => VAddrMode::Indexed(rs2, false),
+            (0b10, _) => VAddrMode::Strided(rs2),
+            (0b11, _) => VAddrMode::Indexed(rs2, true),
+            _ => return Err(()),
+        };
+        let op = VMemOp {
+            vd,
+            rs1: vs1(ins),
+            mode,
+            eew,
+            nf,
+            vm,
+        };
+        Ok(match store {
+            true => Instr::VStore(op),
+            false => Instr::VLoad(op),
+        })
+    }
+}
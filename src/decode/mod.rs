@@ -1,5 +1,6 @@
 use crate::{
     hart::Hart,
+    privilege::xl_of,
     uop::{Exception, Instr, MemProtect},
     utils::{is_aligned, Maybe},
     xlen::XlenT,
@@ -11,11 +12,25 @@ mod cache;
 #[cfg(feature = "C")]
 mod dec16;
 mod dec32;
+#[cfg(feature = "V")]
+mod decv;
 
 #[derive(Debug, Clone, Default)]
 pub struct FrontEnd<Xlen: XlenT> {
     cache: cache::UopCache<Xlen>,
-    isa: common::Isa<Xlen>,
+    pub isa: common::Isa<Xlen>,
+    /// decoder of an effective xlen narrower than `Xlen`,
+    /// eg. rv32 user code on rv64 hart
+    #[cfg(feature = "RV64")]
+    narrow: Option<NarrowIsa>,
+}
+
+#[cfg(feature = "RV64")]
+#[derive(Debug, Clone)]
+enum NarrowIsa {
+    Rv32(common::Isa<u32>),
+    #[cfg(feature = "RV128")]
+    Rv64(common::Isa<u64>),
 }
 
 fn is_c_ins(ins: u16) -> bool {
@@ -25,7 +40,75 @@ fn combine(low: u16, high: u16) -> u32 {
     ((high as u32) << 16) | low as u32
 }
 
+impl<Xlen: XlenT> FrontEnd<Xlen> {
+    /// drop all decoded uops
+    pub fn flush(&mut self) {
+        self.cache.flush();
+    }
+    /// drop decoded uops on the page of `addr`
+    pub fn flush_page(&mut self, addr: Xlen) {
+        self.cache.flush_page(addr);
+    }
+    /// effective xlen
+    pub fn xlen(&self) -> u32 {
+        #[cfg(feature = "RV64")]
+        match self.narrow {
+            Some(NarrowIsa::Rv32(_)) => return 32,
+            #[cfg(feature = "RV128")]
+            Some(NarrowIsa::Rv64(_)) => return 64,
+            None => (),
+        }
+        Xlen::XLEN
+    }
+    /// switch effective xlen, decoded uops are dropped on change
+    pub fn set_xlen(&mut self, xlen: u32) {
+        if xlen == self.xlen() {
+            return;
+        }
+        #[cfg(feature = "RV64")]
+        {
+            self.narrow = match xlen {
+                32 => Some(NarrowIsa::Rv32(self.isa.with_xlen())),
+                #[cfg(feature = "RV128")]
+                64 if Xlen::XLEN > 64 => Some(NarrowIsa::Rv64(self.isa.with_xlen())),
+                _ => None,
+            };
+        }
+        self.flush();
+    }
+    fn dec32(&self, ins: u32) -> Instr {
+        #[cfg(feature = "RV64")]
+        match &self.narrow {
+            Some(NarrowIsa::Rv32(isa)) => return isa.dec32(ins),
+            #[cfg(feature = "RV128")]
+            Some(NarrowIsa::Rv64(isa)) => return isa.dec32(ins),
+            None => (),
+        }
+        self.isa.dec32(ins)
+    }
+    #[cfg(feature = "C")]
+    fn dec16(&self, ins: u16) -> Instr {
+        #[cfg(feature = "RV64")]
+        match &self.narrow {
+            Some(NarrowIsa::Rv32(isa)) => return isa.dec16(ins),
+            #[cfg(feature = "RV128")]
+            Some(NarrowIsa::Rv64(isa)) => return isa.dec16(ins),
+            None => (),
+        }
+        self.isa.dec16(ins)
+    }
+}
+
 impl<Xlen: XlenT> Hart<Xlen> {
+    /// misa with MXL and enabled extensions
+    pub fn misa(&self) -> Xlen {
+        let mxl = xl_of(Xlen::XLEN);
+        (Xlen::from(mxl) << (Xlen::XLEN - 2)) | Xlen::from(self.fe.isa.misa_ext())
+    }
+    /// effective xlen, follows UXL in user mode
+    pub fn xlen(&self) -> u32 {
+        self.fe.xlen()
+    }
     pub fn fetch_uop(&mut self) -> Maybe<Instr> {
         let pc = self.get_pc();
         if let Some(ins) = self.fe.cache.read(pc) {
@@ -33,12 +116,12 @@ impl<Xlen: XlenT> Hart<Xlen> {
         }
         let ins = if is_aligned(pc, 4) {
             let ins = self.fetch_mem32(pc)?;
-            let isa = &self.fe.isa;
+            let fe = &self.fe;
             if is_c_ins(ins as u16) {
-                if_ext_c!(isa, isa.dec16(ins as u16))
+                if_ext_c!(fe.isa, fe.dec16(ins as u16))
                     .unwrap_or_else(|_| Instr::Trap(Exception::IllegalInstr))
             } else {
-                isa.dec32(ins)
+                fe.dec32(ins)
             }
         } else {
             #[cfg(feature = "C")]
@@ -58,13 +141,13 @@ impl<Xlen: XlenT> Hart<Xlen> {
         let pc = self.get_pc();
         let ins = self.fetch_mem16(pc)?;
         Ok(if is_c_ins(ins) {
-            self.fe.isa.dec16(ins)
+            self.fe.dec16(ins)
         } else {
             // spec doesn't mandate atomicity of misaligned access
             // so seperate fetch is fine
             let high = self.fetch_mem16(pc.add(2))?;
             let ins = combine(ins, high);
-            self.fe.isa.dec32(ins)
+            self.fe.dec32(ins)
         })
     }
     fn fetch_uop_misalign(&mut self) -> Maybe<Instr> {
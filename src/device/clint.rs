@@ -0,0 +1,123 @@
+use crate::{memory::Device, utils::Maybe};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+pub const CLINT_SIZE: u64 = 0x10000;
+const MSIP: u64 = 0x0000;
+const MTIMECMP: u64 = 0x4000;
+const MTIME: u64 = 0xbff8;
+
+/// core local interruptor, sifive compatible layout\
+/// provides inter-processor interrupts (msip) and timer (mtime / mtimecmp)
+#[derive(Debug)]
+pub struct Clint {
+    msip: Box<[AtomicU32]>,
+    mtimecmp: Box<[AtomicU64]>,
+    mtime: AtomicU64,
+}
+
+/// read `size` bytes at byte offset `off` of a 64 bit register
+fn rd_part(reg: u64, off: u64, size: u8) -> u64 {
+    let val = reg >> (off * 8);
+    if size == 8 {
+        val
+    } else {
+        val & ((1 << (size * 8)) - 1)
+    }
+}
+
+/// write `size` bytes at byte offset `off` of a 64 bit register
+fn wr_part(reg: u64, off: u64, size: u8, data: u64) -> u64 {
+    let mask = if size == 8 { !0 } else { (1 << (size * 8)) - 1 };
+    let shift = off * 8;
+    (reg & !(mask << shift)) | ((data & mask) << shift)
+}
+
+impl Clint {
+    pub fn new(nharts: usize) -> Self {
+        Self {
+            msip: (0..nharts).map(|_| AtomicU32::new(0)).collect(),
+            mtimecmp: (0..nharts).map(|_| AtomicU64::new(u64::MAX)).collect(),
+            mtime: AtomicU64::new(0),
+        }
+    }
+
+    /// machine software interrupt pending for `hart`
+    pub fn msip(&self, hart: usize) -> bool {
+        self.msip[hart].load(Ordering::Acquire) & 1 != 0
+    }
+
+    /// machine timer interrupt pending for `hart`
+    pub fn mtip(&self, hart: usize) -> bool {
+        self.mtime.load(Ordering::Relaxed) >= self.mtimecmp[hart].load(Ordering::Relaxed)
+    }
+
+    pub fn mtime(&self) -> u64 {
+        self.mtime.load(Ordering::Relaxed)
+    }
+
+    /// advance mtime by `ticks`
+    pub fn tick(&self, ticks: u64) {
+        self.mtime.fetch_add(ticks, Ordering::Relaxed);
+    }
+
+    /// earliest armed mtimecmp still ahead of mtime
+    pub fn next_timer(&self) -> Option<u64> {
+        let now = self.mtime();
+        self.mtimecmp
+            .iter()
+            .map(|cmp| cmp.load(Ordering::Relaxed))
+            .filter(|&cmp| cmp > now && cmp != u64::MAX)
+            .min()
+    }
+
+    /// advance mtime to `time` unless already past
+    pub fn skip_to(&self, time: u64) {
+        self.mtime.fetch_max(time, Ordering::Relaxed);
+    }
+
+    /// offset -> (register, offset in register)
+    fn decode(&self, offset: u64, size: u8) -> Maybe<(&AtomicU64, u64)> {
+        let (reg, base) = match offset {
+            MTIMECMP..=0xbff7 => {
+                let idx = ((offset - MTIMECMP) / 8) as usize;
+                (self.mtimecmp.get(idx).ok_or(())?, MTIMECMP + idx as u64 * 8)
+            }
+            MTIME..=0xbfff => (&self.mtime, MTIME),
+            _ => return Err(()),
+        };
+        if offset - base + size as u64 > 8 {
+            return Err(());
+        }
+        Ok((reg, offset - base))
+    }
+}
+
+impl Device for Clint {
+    fn read(&self, offset: u64, size: u8) -> Maybe<u64> {
+        if offset < MTIMECMP {
+            if !offset.is_multiple_of(4) || size != 4 {
+                return Err(());
+            }
+            let msip = self.msip.get((offset / 4) as usize).ok_or(())?;
+            return Ok(msip.load(Ordering::Acquire) as u64);
+        }
+        let (reg, off) = self.decode(offset, size)?;
+        Ok(rd_part(reg.load(Ordering::Relaxed), off, size))
+    }
+
+    fn write(&self, offset: u64, size: u8, data: u64) -> Maybe<()> {
+        if offset < MTIMECMP {
+            if !offset.is_multiple_of(4) || size != 4 {
+                return Err(());
+            }
+            let msip = self.msip.get((offset / 4) as usize).ok_or(())?;
+            msip.store(data as u32 & 1, Ordering::Release);
+            return Ok(());
+        }
+        let (reg, off) = self.decode(offset, size)?;
+        let _ = reg.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |val| {
+            Some(wr_part(val, off, size, data))
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+// memory mapped devices
+
+mod clint;
+
+pub use clint::{Clint, CLINT_SIZE};
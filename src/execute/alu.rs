@@ -1,3 +1,20 @@
+#[cfg(any(
+    feature = "Zbkb",
+    feature = "Zknd",
+    feature = "Zkne",
+    feature = "Zknh",
+    feature = "Zksed",
+    feature = "Zksh"
+))]
+use super::crypto;
+#[cfg(any(
+    feature = "Zbb",
+    feature = "Zbkb",
+    feature = "Zknd",
+    feature = "Zknh",
+    feature = "Zksh"
+))]
+use crate::uop::UnaryOp;
 use crate::{
     hart::Hart,
     uop::{BinaryOp, CmpCond},
@@ -73,6 +90,26 @@ impl BinaryOp {
                 let shamt: u32 = rhs.into();
                 Xlen::from(lhs >> (shamt % 32))
             }
+            #[cfg(feature = "RV128")]
+            BinaryOp::AddD => lhs.add(rhs).sext64(),
+            #[cfg(feature = "RV128")]
+            BinaryOp::SllD => {
+                let shamt: u32 = rhs.into();
+                (lhs << (shamt % 64)).sext64()
+            }
+            #[cfg(feature = "RV128")]
+            BinaryOp::SrlD => {
+                let shamt: u32 = rhs.into();
+                (lhs.trunc64() >> (shamt % 64)).sext64()
+            }
+            #[cfg(feature = "RV128")]
+            BinaryOp::SubD => lhs.sub(rhs).sext64(),
+            #[cfg(feature = "RV128")]
+            BinaryOp::SraD => {
+                let lhs: i64 = lhs.into();
+                let shamt: u32 = rhs.into();
+                Xlen::from(lhs >> (shamt % 64))
+            }
             #[cfg(feature = "M")]
             BinaryOp::Mul => lhs.mul(rhs),
             #[cfg(feature = "M")]
@@ -119,9 +156,39 @@ impl BinaryOp {
                 let rhs: u32 = rhs.into();
                 Xlen::from(<u32 as XlenT>::remu(lhs, rhs)).sext32()
             }
+            #[cfg(all(feature = "M", feature = "RV128"))]
+            BinaryOp::MulD => {
+                let lhs: u64 = lhs.into();
+                let rhs: u64 = rhs.into();
+                Xlen::from(lhs.wrapping_mul(rhs)).sext64()
+            }
+            #[cfg(all(feature = "M", feature = "RV128"))]
+            BinaryOp::DivD => {
+                let lhs: u64 = lhs.into();
+                let rhs: u64 = rhs.into();
+                Xlen::from(<u64 as XlenT>::div(lhs, rhs)).sext64()
+            }
+            #[cfg(all(feature = "M", feature = "RV128"))]
+            BinaryOp::DivUD => {
+                let lhs: u64 = lhs.into();
+                let rhs: u64 = rhs.into();
+                Xlen::from(<u64 as XlenT>::divu(lhs, rhs)).sext64()
+            }
+            #[cfg(all(feature = "M", feature = "RV128"))]
+            BinaryOp::RemD => {
+                let lhs: u64 = lhs.into();
+                let rhs: u64 = rhs.into();
+                Xlen::from(<u64 as XlenT>::rem(lhs, rhs)).sext64()
+            }
+            #[cfg(all(feature = "M", feature = "RV128"))]
+            BinaryOp::RemUD => {
+                let lhs: u64 = lhs.into();
+                let rhs: u64 = rhs.into();
+                Xlen::from(<u64 as XlenT>::remu(lhs, rhs)).sext64()
+            }
             #[cfg(feature = "A")]
             BinaryOp::Second => rhs,
-            #[cfg(feature = "A")]
+            #[cfg(any(feature = "A", feature = "Zbb"))]
             BinaryOp::Max => {
                 if lhs.scmp(rhs) == Ordering::Greater {
                     lhs
@@ -129,9 +196,9 @@ impl BinaryOp {
                     rhs
                 }
             }
-            #[cfg(feature = "A")]
+            #[cfg(any(feature = "A", feature = "Zbb"))]
             BinaryOp::MaxU => lhs.max(rhs),
-            #[cfg(feature = "A")]
+            #[cfg(any(feature = "A", feature = "Zbb"))]
             BinaryOp::Min => {
                 if lhs.scmp(rhs) == Ordering::Less {
                     lhs
@@ -139,8 +206,275 @@ impl BinaryOp {
                     rhs
                 }
             }
-            #[cfg(feature = "A")]
+            #[cfg(any(feature = "A", feature = "Zbb"))]
             BinaryOp::MinU => lhs.min(rhs),
+            #[cfg(feature = "Zba")]
+            BinaryOp::Sh1Add => (lhs << 1).add(rhs),
+            #[cfg(feature = "Zba")]
+            BinaryOp::Sh2Add => (lhs << 2).add(rhs),
+            #[cfg(feature = "Zba")]
+            BinaryOp::Sh3Add => (lhs << 3).add(rhs),
+            #[cfg(all(feature = "Zba", feature = "RV64"))]
+            BinaryOp::AddUW => lhs.trunc32().add(rhs),
+            #[cfg(all(feature = "Zba", feature = "RV64"))]
+            BinaryOp::Sh1AddUW => (lhs.trunc32() << 1).add(rhs),
+            #[cfg(all(feature = "Zba", feature = "RV64"))]
+            BinaryOp::Sh2AddUW => (lhs.trunc32() << 2).add(rhs),
+            #[cfg(all(feature = "Zba", feature = "RV64"))]
+            BinaryOp::Sh3AddUW => (lhs.trunc32() << 3).add(rhs),
+            #[cfg(all(feature = "Zba", feature = "RV64"))]
+            BinaryOp::SllUW => {
+                let shamt: u32 = rhs.into();
+                lhs.trunc32() << (shamt % Xlen::XLEN)
+            }
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            BinaryOp::Andn => lhs & !rhs,
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            BinaryOp::Orn => lhs | !rhs,
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            BinaryOp::Xnor => !(lhs ^ rhs),
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            BinaryOp::Rol => {
+                let shamt: u32 = rhs.into();
+                lhs.rol(shamt % Xlen::XLEN)
+            }
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            BinaryOp::Ror => {
+                let shamt: u32 = rhs.into();
+                lhs.ror(shamt % Xlen::XLEN)
+            }
+            #[cfg(all(any(feature = "Zbb", feature = "Zbkb"), feature = "RV64"))]
+            BinaryOp::RolW => {
+                let lhs: u32 = lhs.into();
+                let shamt: u32 = rhs.into();
+                Xlen::from(lhs.rol(shamt % 32)).sext32()
+            }
+            #[cfg(all(any(feature = "Zbb", feature = "Zbkb"), feature = "RV64"))]
+            BinaryOp::RorW => {
+                let lhs: u32 = lhs.into();
+                let shamt: u32 = rhs.into();
+                Xlen::from(lhs.ror(shamt % 32)).sext32()
+            }
+            #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+            BinaryOp::Clmul => lhs.clmul(rhs),
+            #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+            BinaryOp::Clmulh => lhs.clmulh(rhs),
+            #[cfg(feature = "Zbc")]
+            BinaryOp::Clmulr => lhs.clmulr(rhs),
+            #[cfg(feature = "Zbs")]
+            BinaryOp::Bclr => lhs & !bit(rhs),
+            #[cfg(feature = "Zbs")]
+            BinaryOp::Bext => (lhs >> bit_idx(rhs)) & Xlen::from(1),
+            #[cfg(feature = "Zbs")]
+            BinaryOp::Binv => lhs ^ bit(rhs),
+            #[cfg(feature = "Zbs")]
+            BinaryOp::Bset => lhs | bit(rhs),
+            #[cfg(feature = "Zbkb")]
+            BinaryOp::Pack => {
+                let half = Xlen::XLEN / 2;
+                (lhs << half >> half) | (rhs << half)
+            }
+            #[cfg(feature = "Zbkb")]
+            BinaryOp::PackH => (lhs & Xlen::from(0xff)) | ((rhs & Xlen::from(0xff)) << 8),
+            #[cfg(all(feature = "Zbkb", feature = "RV64"))]
+            BinaryOp::PackW => {
+                let lhs: u16 = lhs.into();
+                let rhs: u16 = rhs.into();
+                Xlen::from(((rhs as u32) << 16 | lhs as u32) as i32)
+            }
+            #[cfg(feature = "Zbkx")]
+            BinaryOp::Xperm4 => xperm(lhs, rhs, 4),
+            #[cfg(feature = "Zbkx")]
+            BinaryOp::Xperm8 => xperm(lhs, rhs, 8),
+            #[cfg(feature = "Zicond")]
+            BinaryOp::CzeroEqz => {
+                if rhs == Xlen::from(0) {
+                    Xlen::from(0)
+                } else {
+                    lhs
+                }
+            }
+            #[cfg(feature = "Zicond")]
+            BinaryOp::CzeroNez => {
+                if rhs == Xlen::from(0) {
+                    lhs
+                } else {
+                    Xlen::from(0)
+                }
+            }
+            #[cfg(feature = "Zkne")]
+            BinaryOp::Aes32Esi(bs) => {
+                word(lhs, rhs, |rs1, rs2| crypto::aes32_es(rs1, rs2, bs, false))
+            }
+            #[cfg(feature = "Zkne")]
+            BinaryOp::Aes32Esmi(bs) => {
+                word(lhs, rhs, |rs1, rs2| crypto::aes32_es(rs1, rs2, bs, true))
+            }
+            #[cfg(feature = "Zknd")]
+            BinaryOp::Aes32Dsi(bs) => {
+                word(lhs, rhs, |rs1, rs2| crypto::aes32_ds(rs1, rs2, bs, false))
+            }
+            #[cfg(feature = "Zknd")]
+            BinaryOp::Aes32Dsmi(bs) => {
+                word(lhs, rhs, |rs1, rs2| crypto::aes32_ds(rs1, rs2, bs, true))
+            }
+            #[cfg(all(feature = "Zkne", feature = "RV64"))]
+            BinaryOp::Aes64Es => dword(lhs, rhs, |rs1, rs2| crypto::aes64_es(rs1, rs2, false)),
+            #[cfg(all(feature = "Zkne", feature = "RV64"))]
+            BinaryOp::Aes64Esm => dword(lhs, rhs, |rs1, rs2| crypto::aes64_es(rs1, rs2, true)),
+            #[cfg(all(feature = "Zknd", feature = "RV64"))]
+            BinaryOp::Aes64Ds => dword(lhs, rhs, |rs1, rs2| crypto::aes64_ds(rs1, rs2, false)),
+            #[cfg(all(feature = "Zknd", feature = "RV64"))]
+            BinaryOp::Aes64Dsm => dword(lhs, rhs, |rs1, rs2| crypto::aes64_ds(rs1, rs2, true)),
+            #[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+            BinaryOp::Aes64Ks1i => {
+                dword(lhs, rhs, |rs1, rnum| crypto::aes64_ks1i(rs1, rnum as u32))
+            }
+            #[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+            BinaryOp::Aes64Ks2 => dword(lhs, rhs, crypto::aes64_ks2),
+            #[cfg(feature = "Zknh")]
+            BinaryOp::Sha512Sig0H => word(lhs, rhs, crypto::sha512_sig0h),
+            #[cfg(feature = "Zknh")]
+            BinaryOp::Sha512Sig0L => word(lhs, rhs, crypto::sha512_sig0l),
+            #[cfg(feature = "Zknh")]
+            BinaryOp::Sha512Sig1H => word(lhs, rhs, crypto::sha512_sig1h),
+            #[cfg(feature = "Zknh")]
+            BinaryOp::Sha512Sig1L => word(lhs, rhs, crypto::sha512_sig1l),
+            #[cfg(feature = "Zknh")]
+            BinaryOp::Sha512Sum0R => word(lhs, rhs, crypto::sha512_sum0r),
+            #[cfg(feature = "Zknh")]
+            BinaryOp::Sha512Sum1R => word(lhs, rhs, crypto::sha512_sum1r),
+            #[cfg(feature = "Zksed")]
+            BinaryOp::Sm4Ed(bs) => word(lhs, rhs, |rs1, rs2| crypto::sm4_ed(rs1, rs2, bs)),
+            #[cfg(feature = "Zksed")]
+            BinaryOp::Sm4Ks(bs) => word(lhs, rhs, |rs1, rs2| crypto::sm4_ks(rs1, rs2, bs)),
+        }
+    }
+}
+
+/// single bit index of bclr / bext / binv / bset
+#[cfg(feature = "Zbs")]
+fn bit_idx<Xlen: XlenT>(rhs: Xlen) -> u32 {
+    let idx: u32 = rhs.into();
+    idx % Xlen::XLEN
+}
+
+#[cfg(feature = "Zbs")]
+fn bit<Xlen: XlenT>(rhs: Xlen) -> Xlen {
+    Xlen::from(1) << bit_idx(rhs)
+}
+
+/// nibble / byte lookup of `lhs` indexed by `rhs`, out of range reads zero
+#[cfg(feature = "Zbkx")]
+fn xperm<Xlen: XlenT>(lhs: Xlen, rhs: Xlen, width: u32) -> Xlen {
+    let mask = Xlen::from((1u32 << width) - 1);
+    (0..Xlen::XLEN)
+        .step_by(width as usize)
+        .fold(Xlen::from(0), |res, pos| {
+            let idx: u32 = ((rhs >> pos) & mask).into();
+            if idx * width < Xlen::XLEN {
+                res | (((lhs >> (idx * width)) & mask) << pos)
+            } else {
+                res
+            }
+        })
+}
+
+/// 32 bits operation, result sign-extended
+#[cfg(any(
+    feature = "Zkne",
+    feature = "Zknd",
+    feature = "Zknh",
+    feature = "Zksed"
+))]
+fn word<Xlen: XlenT>(lhs: Xlen, rhs: Xlen, f: impl FnOnce(u32, u32) -> u32) -> Xlen {
+    Xlen::from(f(lhs.into(), rhs.into()) as i32)
+}
+
+/// 64 bits operation of rv64
+#[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+fn dword<Xlen: XlenT>(lhs: Xlen, rhs: Xlen, f: impl FnOnce(u64, u64) -> u64) -> Xlen {
+    Xlen::from(f(lhs.into(), rhs.into()))
+}
+
+#[cfg(any(
+    feature = "Zbb",
+    feature = "Zbkb",
+    feature = "Zknd",
+    feature = "Zknh",
+    feature = "Zksh"
+))]
+impl UnaryOp {
+    pub fn exec<Xlen: XlenT>(self, val: Xlen) -> Xlen {
+        match self {
+            #[cfg(feature = "Zbb")]
+            UnaryOp::Clz => Xlen::from(val.clz()),
+            #[cfg(feature = "Zbb")]
+            UnaryOp::Ctz => Xlen::from(val.ctz()),
+            #[cfg(feature = "Zbb")]
+            UnaryOp::Cpop => Xlen::from(val.cpop()),
+            #[cfg(all(feature = "Zbb", feature = "RV64"))]
+            UnaryOp::ClzW => {
+                let val: u32 = val.into();
+                Xlen::from(val.clz())
+            }
+            #[cfg(all(feature = "Zbb", feature = "RV64"))]
+            UnaryOp::CtzW => {
+                let val: u32 = val.into();
+                Xlen::from(val.ctz())
+            }
+            #[cfg(all(feature = "Zbb", feature = "RV64"))]
+            UnaryOp::CpopW => {
+                let val: u32 = val.into();
+                Xlen::from(val.cpop())
+            }
+            #[cfg(feature = "Zbb")]
+            UnaryOp::SextB => {
+                let val: i8 = val.into();
+                Xlen::from(val)
+            }
+            #[cfg(feature = "Zbb")]
+            UnaryOp::SextH => {
+                let val: i16 = val.into();
+                Xlen::from(val)
+            }
+            #[cfg(feature = "Zbb")]
+            UnaryOp::ZextH => {
+                let val: u16 = val.into();
+                Xlen::from(val)
+            }
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            UnaryOp::Rev8 => val.rev8(),
+            #[cfg(feature = "Zbb")]
+            UnaryOp::OrcB => val.orc_b(),
+            #[cfg(feature = "Zbkb")]
+            UnaryOp::Brev8 => val.brev8(),
+            #[cfg(feature = "Zbkb")]
+            UnaryOp::Zip => Xlen::from(crypto::zip(val.into())),
+            #[cfg(feature = "Zbkb")]
+            UnaryOp::Unzip => Xlen::from(crypto::unzip(val.into())),
+            #[cfg(all(feature = "Zknd", feature = "RV64"))]
+            UnaryOp::Aes64Im => Xlen::from(crypto::aes64_im(val.into())),
+            #[cfg(feature = "Zknh")]
+            UnaryOp::Sha256Sig0 => Xlen::from(crypto::sha256_sig0(val.into()) as i32),
+            #[cfg(feature = "Zknh")]
+            UnaryOp::Sha256Sig1 => Xlen::from(crypto::sha256_sig1(val.into()) as i32),
+            #[cfg(feature = "Zknh")]
+            UnaryOp::Sha256Sum0 => Xlen::from(crypto::sha256_sum0(val.into()) as i32),
+            #[cfg(feature = "Zknh")]
+            UnaryOp::Sha256Sum1 => Xlen::from(crypto::sha256_sum1(val.into()) as i32),
+            #[cfg(all(feature = "Zknh", feature = "RV64"))]
+            UnaryOp::Sha512Sig0 => Xlen::from(crypto::sha512_sig0(val.into())),
+            #[cfg(all(feature = "Zknh", feature = "RV64"))]
+            UnaryOp::Sha512Sig1 => Xlen::from(crypto::sha512_sig1(val.into())),
+            #[cfg(all(feature = "Zknh", feature = "RV64"))]
+            UnaryOp::Sha512Sum0 => Xlen::from(crypto::sha512_sum0(val.into())),
+            #[cfg(all(feature = "Zknh", feature = "RV64"))]
+            UnaryOp::Sha512Sum1 => Xlen::from(crypto::sha512_sum1(val.into())),
+            #[cfg(feature = "Zksh")]
+            UnaryOp::Sm3P0 => Xlen::from(crypto::sm3_p0(val.into()) as i32),
+            #[cfg(feature = "Zksh")]
+            UnaryOp::Sm3P1 => Xlen::from(crypto::sm3_p1(val.into()) as i32),
         }
     }
 }
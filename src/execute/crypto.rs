@@ -0,0 +1,310 @@
+// scalar cryptography, rv32 forms work on 32 bits words, rv64 forms on 64 bits words
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(any(feature = "Zkne", all(feature = "Zknd", feature = "RV64")))]
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+#[cfg(feature = "Zknd")]
+const AES_INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3, 0xd7, 0xfb,
+    0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44, 0xc4, 0xde, 0xe9, 0xcb,
+    0x54, 0x7b, 0x94, 0x32, 0xa6, 0xc2, 0x23, 0x3d, 0xee, 0x4c, 0x95, 0x0b, 0x42, 0xfa, 0xc3, 0x4e,
+    0x08, 0x2e, 0xa1, 0x66, 0x28, 0xd9, 0x24, 0xb2, 0x76, 0x5b, 0xa2, 0x49, 0x6d, 0x8b, 0xd1, 0x25,
+    0x72, 0xf8, 0xf6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xd4, 0xa4, 0x5c, 0xcc, 0x5d, 0x65, 0xb6, 0x92,
+    0x6c, 0x70, 0x48, 0x50, 0xfd, 0xed, 0xb9, 0xda, 0x5e, 0x15, 0x46, 0x57, 0xa7, 0x8d, 0x9d, 0x84,
+    0x90, 0xd8, 0xab, 0x00, 0x8c, 0xbc, 0xd3, 0x0a, 0xf7, 0xe4, 0x58, 0x05, 0xb8, 0xb3, 0x45, 0x06,
+    0xd0, 0x2c, 0x1e, 0x8f, 0xca, 0x3f, 0x0f, 0x02, 0xc1, 0xaf, 0xbd, 0x03, 0x01, 0x13, 0x8a, 0x6b,
+    0x3a, 0x91, 0x11, 0x41, 0x4f, 0x67, 0xdc, 0xea, 0x97, 0xf2, 0xcf, 0xce, 0xf0, 0xb4, 0xe6, 0x73,
+    0x96, 0xac, 0x74, 0x22, 0xe7, 0xad, 0x35, 0x85, 0xe2, 0xf9, 0x37, 0xe8, 0x1c, 0x75, 0xdf, 0x6e,
+    0x47, 0xf1, 0x1a, 0x71, 0x1d, 0x29, 0xc5, 0x89, 0x6f, 0xb7, 0x62, 0x0e, 0xaa, 0x18, 0xbe, 0x1b,
+    0xfc, 0x56, 0x3e, 0x4b, 0xc6, 0xd2, 0x79, 0x20, 0x9a, 0xdb, 0xc0, 0xfe, 0x78, 0xcd, 0x5a, 0xf4,
+    0x1f, 0xdd, 0xa8, 0x33, 0x88, 0x07, 0xc7, 0x31, 0xb1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xec, 0x5f,
+    0x60, 0x51, 0x7f, 0xa9, 0x19, 0xb5, 0x4a, 0x0d, 0x2d, 0xe5, 0x7a, 0x9f, 0x93, 0xc9, 0x9c, 0xef,
+    0xa0, 0xe0, 0x3b, 0x4d, 0xae, 0x2a, 0xf5, 0xb0, 0xc8, 0xeb, 0xbb, 0x3c, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2b, 0x04, 0x7e, 0xba, 0x77, 0xd6, 0x26, 0xe1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0c, 0x7d,
+];
+
+#[cfg(feature = "Zksed")]
+const SM4_SBOX: [u8; 256] = [
+    0xd6, 0x90, 0xe9, 0xfe, 0xcc, 0xe1, 0x3d, 0xb7, 0x16, 0xb6, 0x14, 0xc2, 0x28, 0xfb, 0x2c, 0x05,
+    0x2b, 0x67, 0x9a, 0x76, 0x2a, 0xbe, 0x04, 0xc3, 0xaa, 0x44, 0x13, 0x26, 0x49, 0x86, 0x06, 0x99,
+    0x9c, 0x42, 0x50, 0xf4, 0x91, 0xef, 0x98, 0x7a, 0x33, 0x54, 0x0b, 0x43, 0xed, 0xcf, 0xac, 0x62,
+    0xe4, 0xb3, 0x1c, 0xa9, 0xc9, 0x08, 0xe8, 0x95, 0x80, 0xdf, 0x94, 0xfa, 0x75, 0x8f, 0x3f, 0xa6,
+    0x47, 0x07, 0xa7, 0xfc, 0xf3, 0x73, 0x17, 0xba, 0x83, 0x59, 0x3c, 0x19, 0xe6, 0x85, 0x4f, 0xa8,
+    0x68, 0x6b, 0x81, 0xb2, 0x71, 0x64, 0xda, 0x8b, 0xf8, 0xeb, 0x0f, 0x4b, 0x70, 0x56, 0x9d, 0x35,
+    0x1e, 0x24, 0x0e, 0x5e, 0x63, 0x58, 0xd1, 0xa2, 0x25, 0x22, 0x7c, 0x3b, 0x01, 0x21, 0x78, 0x87,
+    0xd4, 0x00, 0x46, 0x57, 0x9f, 0xd3, 0x27, 0x52, 0x4c, 0x36, 0x02, 0xe7, 0xa0, 0xc4, 0xc8, 0x9e,
+    0xea, 0xbf, 0x8a, 0xd2, 0x40, 0xc7, 0x38, 0xb5, 0xa3, 0xf7, 0xf2, 0xce, 0xf9, 0x61, 0x15, 0xa1,
+    0xe0, 0xae, 0x5d, 0xa4, 0x9b, 0x34, 0x1a, 0x55, 0xad, 0x93, 0x32, 0x30, 0xf5, 0x8c, 0xb1, 0xe3,
+    0x1d, 0xf6, 0xe2, 0x2e, 0x82, 0x66, 0xca, 0x60, 0xc0, 0x29, 0x23, 0xab, 0x0d, 0x53, 0x4e, 0x6f,
+    0xd5, 0xdb, 0x37, 0x45, 0xde, 0xfd, 0x8e, 0x2f, 0x03, 0xff, 0x6a, 0x72, 0x6d, 0x6c, 0x5b, 0x51,
+    0x8d, 0x1b, 0xaf, 0x92, 0xbb, 0xdd, 0xbc, 0x7f, 0x11, 0xd9, 0x5c, 0x41, 0x1f, 0x10, 0x5a, 0xd8,
+    0x0a, 0xc1, 0x31, 0x88, 0xa5, 0xcd, 0x7b, 0xbd, 0x2d, 0x74, 0xd0, 0x12, 0xb8, 0xe5, 0xb4, 0xb0,
+    0x89, 0x69, 0x97, 0x4a, 0x0c, 0x96, 0x77, 0x7e, 0x65, 0xb9, 0xf1, 0x09, 0xc5, 0x6e, 0xc6, 0x84,
+    0x18, 0xf0, 0x7d, 0xec, 0x3a, 0xdc, 0x4d, 0x20, 0x79, 0xee, 0x5f, 0x3e, 0xd7, 0xcb, 0x39, 0x48,
+];
+
+/// multiply in GF(2^8) modulo the aes polynomial
+#[cfg(any(feature = "Zkne", feature = "Zknd"))]
+fn gf_mul(mut lhs: u8, mut rhs: u8) -> u8 {
+    let mut res = 0;
+    while rhs != 0 {
+        if rhs & 1 != 0 {
+            res ^= lhs;
+        }
+        lhs = (lhs << 1) ^ if lhs & 0x80 != 0 { 0x1b } else { 0 };
+        rhs >>= 1;
+    }
+    res
+}
+
+/// mix columns of a single column, row 0 in the lowest byte
+#[cfg(feature = "Zkne")]
+fn aes_mix_fwd(col: u32) -> u32 {
+    let [b0, b1, b2, b3] = col.to_le_bytes();
+    u32::from_le_bytes([
+        gf_mul(b0, 2) ^ gf_mul(b1, 3) ^ b2 ^ b3,
+        b0 ^ gf_mul(b1, 2) ^ gf_mul(b2, 3) ^ b3,
+        b0 ^ b1 ^ gf_mul(b2, 2) ^ gf_mul(b3, 3),
+        gf_mul(b0, 3) ^ b1 ^ b2 ^ gf_mul(b3, 2),
+    ])
+}
+
+/// inverse mix columns of a single column
+#[cfg(feature = "Zknd")]
+fn aes_mix_inv(col: u32) -> u32 {
+    let [b0, b1, b2, b3] = col.to_le_bytes();
+    u32::from_le_bytes([
+        gf_mul(b0, 14) ^ gf_mul(b1, 11) ^ gf_mul(b2, 13) ^ gf_mul(b3, 9),
+        gf_mul(b0, 9) ^ gf_mul(b1, 14) ^ gf_mul(b2, 11) ^ gf_mul(b3, 13),
+        gf_mul(b0, 13) ^ gf_mul(b1, 9) ^ gf_mul(b2, 14) ^ gf_mul(b3, 11),
+        gf_mul(b0, 11) ^ gf_mul(b1, 13) ^ gf_mul(b2, 9) ^ gf_mul(b3, 14),
+    ])
+}
+
+/// sub bytes of byte `bs` of `rs2`, (mixed) and rotated back in place, xor-ed into `rs1`
+#[cfg(feature = "Zkne")]
+pub fn aes32_es(rs1: u32, rs2: u32, bs: u8, mix: bool) -> u32 {
+    let shamt = bs as u32 * 8;
+    let so = AES_SBOX[(rs2 >> shamt) as u8 as usize] as u32;
+    let mixed = if mix { aes_mix_fwd(so) } else { so };
+    rs1 ^ mixed.rotate_left(shamt)
+}
+
+/// inverse of `aes32_es`
+#[cfg(feature = "Zknd")]
+pub fn aes32_ds(rs1: u32, rs2: u32, bs: u8, mix: bool) -> u32 {
+    let shamt = bs as u32 * 8;
+    let so = AES_INV_SBOX[(rs2 >> shamt) as u8 as usize] as u32;
+    let mixed = if mix { aes_mix_inv(so) } else { so };
+    rs1 ^ mixed.rotate_left(shamt)
+}
+
+/// low two columns of (inverse) shift rows of state `rs2`:`rs1`
+#[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+fn aes64_shift_rows(rs1: u64, rs2: u64, inv: bool) -> [u8; 8] {
+    let state = ((rs2 as u128) << 64 | rs1 as u128).to_le_bytes();
+    std::array::from_fn(|idx| {
+        let (row, col) = (idx % 4, idx / 4);
+        let col = if inv { col + 4 - row } else { col + row };
+        state[row + (col % 4) * 4]
+    })
+}
+
+/// apply `mix` to both columns of `val`
+#[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+fn aes64_mix(val: u64, mix: fn(u32) -> u32) -> u64 {
+    (mix((val >> 32) as u32) as u64) << 32 | mix(val as u32) as u64
+}
+
+/// shift rows, sub bytes and optionally mix columns, low half of state
+#[cfg(all(feature = "Zkne", feature = "RV64"))]
+pub fn aes64_es(rs1: u64, rs2: u64, mix: bool) -> u64 {
+    let bytes = aes64_shift_rows(rs1, rs2, false).map(|val| AES_SBOX[val as usize]);
+    let res = u64::from_le_bytes(bytes);
+    if mix {
+        aes64_mix(res, aes_mix_fwd)
+    } else {
+        res
+    }
+}
+
+/// inverse of `aes64_es`
+#[cfg(all(feature = "Zknd", feature = "RV64"))]
+pub fn aes64_ds(rs1: u64, rs2: u64, mix: bool) -> u64 {
+    let bytes = aes64_shift_rows(rs1, rs2, true).map(|val| AES_INV_SBOX[val as usize]);
+    let res = u64::from_le_bytes(bytes);
+    if mix {
+        aes64_mix(res, aes_mix_inv)
+    } else {
+        res
+    }
+}
+
+/// inverse mix columns, for decryption round keys
+#[cfg(all(feature = "Zknd", feature = "RV64"))]
+pub fn aes64_im(rs1: u64) -> u64 {
+    aes64_mix(rs1, aes_mix_inv)
+}
+
+/// key schedule step on high word of `rs1`, `rnum` in 0..=10
+#[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+pub fn aes64_ks1i(rs1: u64, rnum: u32) -> u64 {
+    const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+    let word = (rs1 >> 32) as u32;
+    // round 10 is the aes-256 step without rotation and round constant
+    let (word, rcon) = match RCON.get(rnum as usize) {
+        Some(&rcon) => (word.rotate_right(8), rcon),
+        None => (word, 0),
+    };
+    let bytes = word.to_le_bytes().map(|val| AES_SBOX[val as usize]);
+    let word = u32::from_le_bytes(bytes) ^ rcon as u32;
+    (word as u64) << 32 | word as u64
+}
+
+/// key schedule step, xor-ing words of previous round key
+#[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+pub fn aes64_ks2(rs1: u64, rs2: u64) -> u64 {
+    let w0 = (rs1 >> 32) as u32 ^ rs2 as u32;
+    let w1 = w0 ^ (rs2 >> 32) as u32;
+    (w1 as u64) << 32 | w0 as u64
+}
+
+#[cfg(feature = "Zknh")]
+pub fn sha256_sig0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+#[cfg(feature = "Zknh")]
+pub fn sha256_sig1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+#[cfg(feature = "Zknh")]
+pub fn sha256_sum0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+#[cfg(feature = "Zknh")]
+pub fn sha256_sum1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+#[cfg(all(feature = "Zknh", feature = "RV64"))]
+pub fn sha512_sig0(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+
+#[cfg(all(feature = "Zknh", feature = "RV64"))]
+pub fn sha512_sig1(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+#[cfg(all(feature = "Zknh", feature = "RV64"))]
+pub fn sha512_sum0(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+
+#[cfg(all(feature = "Zknh", feature = "RV64"))]
+pub fn sha512_sum1(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+
+/// high word of sigma0, `rs1` high and `rs2` low word of input
+#[cfg(feature = "Zknh")]
+pub fn sha512_sig0h(rs1: u32, rs2: u32) -> u32 {
+    (rs1 >> 1) ^ (rs1 >> 7) ^ (rs1 >> 8) ^ (rs2 << 31) ^ (rs2 << 24)
+}
+
+/// low word of sigma0, `rs1` low and `rs2` high word of input
+#[cfg(feature = "Zknh")]
+pub fn sha512_sig0l(rs1: u32, rs2: u32) -> u32 {
+    (rs1 >> 1) ^ (rs1 >> 7) ^ (rs1 >> 8) ^ (rs2 << 31) ^ (rs2 << 25) ^ (rs2 << 24)
+}
+
+/// high word of sigma1, `rs1` high and `rs2` low word of input
+#[cfg(feature = "Zknh")]
+pub fn sha512_sig1h(rs1: u32, rs2: u32) -> u32 {
+    (rs1 << 3) ^ (rs1 >> 6) ^ (rs1 >> 19) ^ (rs2 >> 29) ^ (rs2 << 13)
+}
+
+/// low word of sigma1, `rs1` low and `rs2` high word of input
+#[cfg(feature = "Zknh")]
+pub fn sha512_sig1l(rs1: u32, rs2: u32) -> u32 {
+    (rs1 << 3) ^ (rs1 >> 6) ^ (rs1 >> 19) ^ (rs2 >> 29) ^ (rs2 << 26) ^ (rs2 << 13)
+}
+
+/// one word of sum0, `rs2` the other word of input
+#[cfg(feature = "Zknh")]
+pub fn sha512_sum0r(rs1: u32, rs2: u32) -> u32 {
+    (rs1 << 25) ^ (rs1 << 30) ^ (rs1 >> 28) ^ (rs2 >> 7) ^ (rs2 >> 2) ^ (rs2 << 4)
+}
+
+/// one word of sum1, `rs2` the other word of input
+#[cfg(feature = "Zknh")]
+pub fn sha512_sum1r(rs1: u32, rs2: u32) -> u32 {
+    (rs1 << 23) ^ (rs1 >> 14) ^ (rs1 >> 18) ^ (rs2 >> 9) ^ (rs2 << 18) ^ (rs2 << 14)
+}
+
+#[cfg(feature = "Zksh")]
+pub fn sm3_p0(x: u32) -> u32 {
+    x ^ x.rotate_left(9) ^ x.rotate_left(17)
+}
+
+#[cfg(feature = "Zksh")]
+pub fn sm3_p1(x: u32) -> u32 {
+    x ^ x.rotate_left(15) ^ x.rotate_left(23)
+}
+
+/// sbox and linear transform of byte `bs` of `rs2`, xor-ed into `rs1`
+#[cfg(feature = "Zksed")]
+pub fn sm4_ed(rs1: u32, rs2: u32, bs: u8) -> u32 {
+    let shamt = bs as u32 * 8;
+    let x = SM4_SBOX[(rs2 >> shamt) as u8 as usize] as u32;
+    let y = x ^ (x << 2) ^ (x << 10) ^ (x << 18) ^ (x << 24);
+    rs1 ^ y.rotate_left(shamt)
+}
+
+/// same as `sm4_ed` with the key schedule linear transform
+#[cfg(feature = "Zksed")]
+pub fn sm4_ks(rs1: u32, rs2: u32, bs: u8) -> u32 {
+    let shamt = bs as u32 * 8;
+    let x = SM4_SBOX[(rs2 >> shamt) as u8 as usize] as u32;
+    let y = x ^ (x << 13) ^ (x << 23);
+    rs1 ^ y.rotate_left(shamt)
+}
+
+/// interleave high half bits into odd and low half bits into even positions
+#[cfg(feature = "Zbkb")]
+pub fn zip(rs1: u32) -> u32 {
+    (0..16).fold(0, |res, idx| {
+        res | (rs1 >> idx & 1) << (2 * idx) | (rs1 >> (idx + 16) & 1) << (2 * idx + 1)
+    })
+}
+
+/// inverse of `zip`
+#[cfg(feature = "Zbkb")]
+pub fn unzip(rs1: u32) -> u32 {
+    (0..16).fold(0, |res, idx| {
+        res | (rs1 >> (2 * idx) & 1) << idx | (rs1 >> (2 * idx + 1) & 1) << (idx + 16)
+    })
+}
@@ -0,0 +1,510 @@
+use crate::uop::BinaryOp;
+#[cfg(any(feature = "Zbkb", feature = "Zknd", feature = "Zknh", feature = "Zksh"))]
+use crate::uop::UnaryOp;
+
+/// message padded to whole blocks, bit length in the last `block / 8` bytes
+#[cfg(feature = "Zknh")]
+fn pad(msg: &[u8], block: usize) -> Vec<u8> {
+    let len_bytes = block / 8;
+    let mut res = msg.to_vec();
+    res.push(0x80);
+    while res.len() % block != block - len_bytes {
+        res.push(0);
+    }
+    let bits = (msg.len() as u128 * 8).to_be_bytes();
+    res.extend_from_slice(&bits[16 - len_bytes..]);
+    res
+}
+
+#[cfg(feature = "Zknh")]
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+#[cfg(feature = "Zknh")]
+const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+#[cfg(feature = "Zknh")]
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+#[cfg(feature = "Zknh")]
+const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+#[cfg(feature = "Zknh")]
+#[test]
+fn sha256() {
+    let op = |op: UnaryOp, val: u32| op.exec(val);
+    let mut state = SHA256_IV;
+    for block in pad(b"abc", 64).chunks(64) {
+        let mut w = [0u32; 64];
+        for (idx, word) in block.chunks(4).enumerate() {
+            w[idx] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for idx in 16..64 {
+            w[idx] = op(UnaryOp::Sha256Sig1, w[idx - 2])
+                .wrapping_add(w[idx - 7])
+                .wrapping_add(op(UnaryOp::Sha256Sig0, w[idx - 15]))
+                .wrapping_add(w[idx - 16]);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for idx in 0..64 {
+            let t1 = h
+                .wrapping_add(op(UnaryOp::Sha256Sum1, e))
+                .wrapping_add((e & f) ^ (!e & g))
+                .wrapping_add(SHA256_K[idx])
+                .wrapping_add(w[idx]);
+            let t2 = op(UnaryOp::Sha256Sum0, a).wrapping_add((a & b) ^ (a & c) ^ (b & c));
+            (h, g, f, e) = (g, f, e, d.wrapping_add(t1));
+            (d, c, b, a) = (c, b, a, t1.wrapping_add(t2));
+        }
+        for (val, new) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *val = val.wrapping_add(new);
+        }
+    }
+    let expect = [
+        0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+        0xf20015ad,
+    ];
+    assert_eq!(state, expect);
+}
+
+/// sigma0, sigma1, sum0, sum1 of sha512
+#[cfg(feature = "Zknh")]
+type Sha512Ops = [fn(u64) -> u64; 4];
+
+#[cfg(feature = "Zknh")]
+fn sha512(msg: &[u8], ops: Sha512Ops) -> [u64; 8] {
+    let [sig0, sig1, sum0, sum1] = ops;
+    let mut state = SHA512_IV;
+    for block in pad(msg, 128).chunks(128) {
+        let mut w = [0u64; 80];
+        for (idx, word) in block.chunks(8).enumerate() {
+            w[idx] = u64::from_be_bytes(word.try_into().unwrap());
+        }
+        for idx in 16..80 {
+            w[idx] = sig1(w[idx - 2])
+                .wrapping_add(w[idx - 7])
+                .wrapping_add(sig0(w[idx - 15]))
+                .wrapping_add(w[idx - 16]);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for idx in 0..80 {
+            let t1 = h
+                .wrapping_add(sum1(e))
+                .wrapping_add((e & f) ^ (!e & g))
+                .wrapping_add(SHA512_K[idx])
+                .wrapping_add(w[idx]);
+            let t2 = sum0(a).wrapping_add((a & b) ^ (a & c) ^ (b & c));
+            (h, g, f, e) = (g, f, e, d.wrapping_add(t1));
+            (d, c, b, a) = (c, b, a, t1.wrapping_add(t2));
+        }
+        for (val, new) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *val = val.wrapping_add(new);
+        }
+    }
+    state
+}
+
+#[cfg(feature = "Zknh")]
+const SHA512_ABC: [u64; 8] = [
+    0xddaf35a193617aba,
+    0xcc417349ae204131,
+    0x12e6fa4e89a97ea2,
+    0x0a9eeee64b55d39a,
+    0x2192992a274fc1a8,
+    0x36ba3c23a3feebbd,
+    0x454d4423643ce80e,
+    0x2a9ac94fa54ca49f,
+];
+
+#[cfg(all(feature = "Zknh", feature = "RV64"))]
+#[test]
+fn sha512_rv64() {
+    let ops: Sha512Ops = [
+        |val| UnaryOp::Sha512Sig0.exec(val),
+        |val| UnaryOp::Sha512Sig1.exec(val),
+        |val| UnaryOp::Sha512Sum0.exec(val),
+        |val| UnaryOp::Sha512Sum1.exec(val),
+    ];
+    assert_eq!(sha512(b"abc", ops), SHA512_ABC);
+}
+
+/// 64 bits result from rv32 high and low word instructions
+#[cfg(feature = "Zknh")]
+fn rv32_pair(val: u64, high: BinaryOp, low: BinaryOp) -> u64 {
+    let (lo, hi) = (val as u32, (val >> 32) as u32);
+    (high.exec(hi, lo) as u64) << 32 | low.exec(lo, hi) as u64
+}
+
+#[cfg(feature = "Zknh")]
+#[test]
+fn sha512_rv32() {
+    let ops: Sha512Ops = [
+        |val| rv32_pair(val, BinaryOp::Sha512Sig0H, BinaryOp::Sha512Sig0L),
+        |val| rv32_pair(val, BinaryOp::Sha512Sig1H, BinaryOp::Sha512Sig1L),
+        |val| rv32_pair(val, BinaryOp::Sha512Sum0R, BinaryOp::Sha512Sum0R),
+        |val| rv32_pair(val, BinaryOp::Sha512Sum1R, BinaryOp::Sha512Sum1R),
+    ];
+    assert_eq!(sha512(b"abc", ops), SHA512_ABC);
+}
+
+#[cfg(feature = "Zksh")]
+#[test]
+fn sm3() {
+    let p0 = |val: u32| UnaryOp::Sm3P0.exec(val);
+    let p1 = |val: u32| UnaryOp::Sm3P1.exec(val);
+    let mut state: [u32; 8] = [
+        0x7380166f, 0x4914b2b9, 0x172442d7, 0xda8a0600, 0xa96f30bc, 0x163138aa, 0xe38dee4d,
+        0xb0fb0e4e,
+    ];
+    // same padding as sha256
+    let mut block = [0u8; 64];
+    block[..3].copy_from_slice(b"abc");
+    block[3] = 0x80;
+    block[63] = 24;
+    let mut w = [0u32; 68];
+    for (idx, word) in block.chunks(4).enumerate() {
+        w[idx] = u32::from_be_bytes(word.try_into().unwrap());
+    }
+    for idx in 16..68 {
+        let val = w[idx - 16] ^ w[idx - 9] ^ w[idx - 3].rotate_left(15);
+        w[idx] = p1(val) ^ w[idx - 13].rotate_left(7) ^ w[idx - 6];
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for idx in 0..64 {
+        let (t, ff, gg) = if idx < 16 {
+            (0x79cc4519u32, a ^ b ^ c, e ^ f ^ g)
+        } else {
+            (0x7a879d8a, (a & b) | (a & c) | (b & c), (e & f) | (!e & g))
+        };
+        let ss1 = a
+            .rotate_left(12)
+            .wrapping_add(e)
+            .wrapping_add(t.rotate_left(idx % 32))
+            .rotate_left(7);
+        let ss2 = ss1 ^ a.rotate_left(12);
+        let tt1 = ff
+            .wrapping_add(d)
+            .wrapping_add(ss2)
+            .wrapping_add(w[idx as usize] ^ w[idx as usize + 4]);
+        let tt2 = gg
+            .wrapping_add(h)
+            .wrapping_add(ss1)
+            .wrapping_add(w[idx as usize]);
+        (d, c, b, a) = (c, b.rotate_left(9), a, tt1);
+        (h, g, f, e) = (g, f.rotate_left(19), e, p0(tt2));
+    }
+    for (val, new) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *val ^= new;
+    }
+    let expect = [
+        0x66c7f0f4, 0x62eeedd9, 0xd1f2d46b, 0xdc10e4e2, 0x4167c487, 0x5cf2f7a2, 0x297da02b,
+        0x8f4ba8e0,
+    ];
+    assert_eq!(state, expect);
+}
+
+#[cfg(feature = "Zksed")]
+#[test]
+fn sm4() {
+    // GB/T 32907 example, key and plaintext are the same
+    let data = [0x01234567u32, 0x89abcdef, 0xfedcba98, 0x76543210];
+    let fk = [0xa3b1bac6u32, 0x56aa3350, 0x677d9197, 0xb27022dc];
+    // apply t / t' on `rs2`, xor-ed into `rs1`
+    let round = |op: fn(u8) -> BinaryOp, rs1: u32, rs2: u32| {
+        (0..4).fold(rs1, |acc, bs| op(bs).exec(acc, rs2))
+    };
+    let mut k: Vec<u32> = data.iter().zip(fk).map(|(key, fk)| key ^ fk).collect();
+    for idx in 0..32 {
+        let ck = u32::from_be_bytes(std::array::from_fn(|byte| ((idx * 4 + byte) * 7) as u8));
+        let rs2 = k[idx + 1] ^ k[idx + 2] ^ k[idx + 3] ^ ck;
+        k.push(round(BinaryOp::Sm4Ks, k[idx], rs2));
+    }
+    let mut x = data.to_vec();
+    for idx in 0..32 {
+        let rs2 = x[idx + 1] ^ x[idx + 2] ^ x[idx + 3] ^ k[idx + 4];
+        x.push(round(BinaryOp::Sm4Ed, x[idx], rs2));
+    }
+    assert_eq!(x[32..], [0x536e4246, 0x86b3e94f, 0xd206965e, 0x681edf34]);
+}
+
+/// FIPS 197 appendix C.1
+#[cfg(any(feature = "Zkne", feature = "Zknd"))]
+const AES128_KEY: [u8; 16] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+];
+#[cfg(any(feature = "Zkne", feature = "Zknd"))]
+const AES128_PT: [u8; 16] = [
+    0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+];
+#[cfg(any(feature = "Zkne", feature = "Zknd"))]
+const AES128_CT: [u8; 16] = [
+    0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a,
+];
+
+#[cfg(all(feature = "Zkne", feature = "Zknd"))]
+#[test]
+fn aes32() {
+    let words = |bytes: [u8; 16]| -> [u32; 4] {
+        std::array::from_fn(|idx| {
+            u32::from_le_bytes(bytes[idx * 4..idx * 4 + 4].try_into().unwrap())
+        })
+    };
+    // sum of per byte instructions over all four byte selects
+    let fold = |op: fn(u8) -> BinaryOp, rs1: u32, rs2: u32| {
+        (0..4).fold(rs1, |acc, bs| op(bs).exec(acc, rs2))
+    };
+    let mut rk = words(AES128_KEY).to_vec();
+    let rcon = [
+        0x01u32, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36,
+    ];
+    for idx in 4..44 {
+        let prev = rk[idx - 1];
+        let word = if idx % 4 == 0 {
+            fold(
+                BinaryOp::Aes32Esi,
+                rk[idx - 4] ^ rcon[idx / 4 - 1],
+                prev.rotate_right(8),
+            )
+        } else {
+            rk[idx - 4] ^ prev
+        };
+        rk.push(word);
+    }
+
+    let mut state = words(AES128_PT);
+    state.iter_mut().zip(&rk).for_each(|(val, key)| *val ^= key);
+    for round in 1..=10 {
+        let op = if round == 10 {
+            BinaryOp::Aes32Esi
+        } else {
+            BinaryOp::Aes32Esmi
+        };
+        state = std::array::from_fn(|col| {
+            (0..4).fold(rk[round * 4 + col], |acc, row| {
+                op(row as u8).exec(acc, state[(col + row) % 4])
+            })
+        });
+    }
+    assert_eq!(state, words(AES128_CT));
+
+    // equivalent inverse cipher, middle round keys through inverse mix columns
+    let inv_mix = |key: u32| fold(BinaryOp::Aes32Dsmi, 0, fold(BinaryOp::Aes32Esi, 0, key));
+    state
+        .iter_mut()
+        .zip(&rk[40..])
+        .for_each(|(val, key)| *val ^= key);
+    for round in (0..10).rev() {
+        let op = if round == 0 {
+            BinaryOp::Aes32Dsi
+        } else {
+            BinaryOp::Aes32Dsmi
+        };
+        state = std::array::from_fn(|col| {
+            let key = rk[round * 4 + col];
+            let key = if round == 0 { key } else { inv_mix(key) };
+            (0..4).fold(key, |acc, row| {
+                op(row as u8).exec(acc, state[(col + 4 - row) % 4])
+            })
+        });
+    }
+    assert_eq!(state, words(AES128_PT));
+}
+
+#[cfg(all(feature = "Zkne", feature = "Zknd", feature = "RV64"))]
+#[test]
+fn aes64() {
+    let dwords = |bytes: [u8; 16]| -> [u64; 2] {
+        std::array::from_fn(|idx| {
+            u64::from_le_bytes(bytes[idx * 8..idx * 8 + 8].try_into().unwrap())
+        })
+    };
+    let mut rk = vec![dwords(AES128_KEY)];
+    for rnum in 0..10 {
+        let [k0, k1] = rk[rnum];
+        let tmp = BinaryOp::Aes64Ks1i.exec(k1, rnum as u64);
+        let k0 = BinaryOp::Aes64Ks2.exec(tmp, k0);
+        let k1 = BinaryOp::Aes64Ks2.exec(k0, k1);
+        rk.push([k0, k1]);
+    }
+
+    let [mut lo, mut hi] = dwords(AES128_PT);
+    (lo, hi) = (lo ^ rk[0][0], hi ^ rk[0][1]);
+    for (round, [k0, k1]) in rk.iter().enumerate().skip(1) {
+        let op = if round == 10 {
+            BinaryOp::Aes64Es
+        } else {
+            BinaryOp::Aes64Esm
+        };
+        (lo, hi) = (op.exec(lo, hi) ^ k0, op.exec(hi, lo) ^ k1);
+    }
+    assert_eq!([lo, hi], dwords(AES128_CT));
+
+    (lo, hi) = (lo ^ rk[10][0], hi ^ rk[10][1]);
+    for (round, &[k0, k1]) in rk.iter().enumerate().take(10).rev() {
+        let (op, k0, k1) = if round == 0 {
+            (BinaryOp::Aes64Ds, k0, k1)
+        } else {
+            let im = UnaryOp::Aes64Im;
+            (BinaryOp::Aes64Dsm, im.exec(k0), im.exec(k1))
+        };
+        (lo, hi) = (op.exec(lo, hi) ^ k0, op.exec(hi, lo) ^ k1);
+    }
+    assert_eq!([lo, hi], dwords(AES128_PT));
+}
+
+#[cfg(feature = "Zbkb")]
+#[test]
+fn bit_manip() {
+    assert_eq!(UnaryOp::Zip.exec(0xffff_0000u32), 0xaaaa_aaaa);
+    assert_eq!(UnaryOp::Zip.exec(0x0000_ffffu32), 0x5555_5555);
+    assert_eq!(UnaryOp::Unzip.exec(0xaaaa_aaaau32), 0xffff_0000);
+    assert_eq!(
+        UnaryOp::Unzip.exec(UnaryOp::Zip.exec(0x1234_5678u32)),
+        0x1234_5678
+    );
+    assert_eq!(UnaryOp::Brev8.exec(0x0001_0080u32), 0x0080_0001);
+    assert_eq!(
+        BinaryOp::Pack.exec(0x1111_2222u32, 0x3333_4444),
+        0x4444_2222
+    );
+    assert_eq!(BinaryOp::PackH.exec(0x1111_2222u32, 0x3333_4444), 0x4422);
+    #[cfg(feature = "RV64")]
+    {
+        let (lhs, rhs) = (0x1111_2222_3333_4444u64, 0x5555_6666_7777_8888u64);
+        assert_eq!(BinaryOp::Pack.exec(lhs, rhs), 0x7777_8888_3333_4444);
+        assert_eq!(BinaryOp::PackW.exec(lhs, rhs), 0xffff_ffff_8888_4444);
+        assert_eq!(
+            UnaryOp::Brev8.exec(0x0000_0001_0000_0080u64),
+            0x0000_0080_0000_0001
+        );
+    }
+}
+
+#[cfg(feature = "Zbkx")]
+#[test]
+fn xperm() {
+    assert_eq!(
+        BinaryOp::Xperm4.exec(0x7654_3210u32, 0x0123_4567),
+        0x0123_4567
+    );
+    assert_eq!(
+        BinaryOp::Xperm4.exec(0x7654_3219u32, 0x0000_00f0),
+        0x9999_9909
+    );
+    assert_eq!(
+        BinaryOp::Xperm8.exec(0x0403_0201u32, 0x0004_0003),
+        0x0100_0104
+    );
+    #[cfg(feature = "RV64")]
+    {
+        let lhs = 0x0807_0605_0403_0201u64;
+        assert_eq!(
+            BinaryOp::Xperm8.exec(lhs, 0x0001_0203_0405_0607),
+            0x0102_0304_0506_0708
+        );
+        assert_eq!(
+            BinaryOp::Xperm8.exec(lhs, 0x0000_0000_0000_0809),
+            0x0101_0101_0101_0000
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    hart::Hart,
+    hart::{Hart, Idle},
     uop::*,
     utils::Maybe,
     xlen::{Cast, XlenT},
@@ -21,6 +21,17 @@ impl Instr {
                 hart.op(rd, rs1, rs2, op);
                 hart.advance_pc(4)
             }
+            #[cfg(any(
+                feature = "Zbb",
+                feature = "Zbkb",
+                feature = "Zknd",
+                feature = "Zknh",
+                feature = "Zksh"
+            ))]
+            Instr::OpUnary(rd, rs1, op) => {
+                hart.op_unary(rd, rs1, op);
+                hart.advance_pc(4)
+            }
             Instr::Auipc(rd, imm) => {
                 hart.wr_gpr(rd, hart.get_pc().add(imm));
                 hart.advance_pc(4)
@@ -33,18 +44,95 @@ impl Instr {
                 hart.store(rs1, rs2, offset, width)?;
                 hart.advance_pc(4)
             }
+            // translated as VS / VU-mode until done, traps included
+            #[cfg(feature = "H")]
+            Instr::HLoad(rd, rs1, width, hlvx) => {
+                hart.check_hlsv()?;
+                hart.priv_ctrl.hlsv = Some(hlvx);
+                let res = hart.load(rd, rs1, 0, width);
+                hart.priv_ctrl.hlsv = None;
+                res?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "H")]
+            Instr::HStore(rs1, rs2, width) => {
+                hart.check_hlsv()?;
+                hart.priv_ctrl.hlsv = Some(false);
+                let res = hart.store(rs1, rs2, 0, width);
+                hart.priv_ctrl.hlsv = None;
+                res?;
+                hart.advance_pc(4)
+            }
             Instr::MiscMem(op) => {
                 match op {
                     MiscMemOp::Fence(pred, succ) => hart.fence(pred, succ),
                     MiscMemOp::FenceTso => hart.fence_tso(),
                     #[cfg(feature = "Zifencei")]
                     MiscMemOp::FenceI => hart.fence_i(),
+                    // give up the rest of the turn
+                    #[cfg(feature = "Zihintpause")]
+                    MiscMemOp::Pause => hart.yield_tok = true,
                 }
                 hart.advance_pc(4)
             }
             Instr::Branch(rs1, rs2, offset, cond) => hart.branch(rs1, rs2, offset, cond, 4),
             Instr::Jal(rd, offset) => hart.jal(rd, offset, 4),
             Instr::Jalr(rd, rs1, offset) => hart.jalr(rd, rs1, offset, 4),
+            Instr::System(op) => match op {
+                #[cfg(feature = "S")]
+                SystemOp::Sret => hart.sret(),
+                SystemOp::Mret => hart.mret(),
+                SystemOp::Wfi => {
+                    hart.check_tw()?;
+                    hart.idle = Idle::Wfi;
+                    hart.advance_pc(4)
+                }
+                #[cfg(feature = "Zawrs")]
+                SystemOp::WrsNto | SystemOp::WrsSto => {
+                    // completes at once without a reservation
+                    if hart.reserved() {
+                        let sto = op == SystemOp::WrsSto;
+                        if !sto {
+                            hart.check_tw()?;
+                        }
+                        hart.idle = Idle::Wrs(sto);
+                    }
+                    hart.advance_pc(4)
+                }
+                #[cfg(feature = "S")]
+                SystemOp::SfenceVma(rs1, _) => {
+                    hart.check_sfence()?;
+                    // no TLB, translations are walked on every access,
+                    // only decoded uops depend on mappings
+                    if rs1 == 0 {
+                        hart.fe.flush();
+                    } else {
+                        let addr = hart.rd_gpr(rs1);
+                        hart.fe.flush_page(addr);
+                    }
+                    hart.advance_pc(4)
+                }
+                #[cfg(feature = "H")]
+                SystemOp::HfenceVvma(..) | SystemOp::HfenceGvma(..) => {
+                    hart.check_hfence(matches!(op, SystemOp::HfenceGvma(..)))?;
+                    hart.fe.flush();
+                    hart.advance_pc(4)
+                }
+            },
+
+            // no cache hierarchy to hint
+            #[cfg(feature = "Zihintntl")]
+            Instr::Ntl(_) => hart.advance_pc(4),
+            #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+            Instr::Cbo(rs1, op) => {
+                hart.cbo(hart.rd_gpr(rs1), op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "Zicbop")]
+            Instr::Prefetch(rs1, offset, prot) => {
+                hart.prefetch(hart.rd_gpr(rs1).add(offset), prot);
+                hart.advance_pc(4)
+            }
 
             #[cfg(feature = "Zicsr")]
             Instr::Csr(rd, rs1_uimm, addr, op) => {
@@ -57,6 +145,16 @@ impl Instr {
                     CsrOp::Rs | CsrOp::Rsi => hart.csr_set(addr, val)?,
                     CsrOp::Rc | CsrOp::Rci => hart.csr_clr(addr, val)?,
                 };
+                // set / clear with zero mask doesn't write,
+                // the reference logs the value after WARL legalization
+                let written = matches!(op, CsrOp::Rw | CsrOp::Rwi) || val != Xlen::from(0);
+                if hart.cosim.is_some() && written {
+                    if let Ok(val) = hart.csr_rd(addr) {
+                        if let Some(cosim) = hart.cosim.as_mut() {
+                            cosim.wr_csr(addr, val);
+                        }
+                    }
+                }
                 hart.wr_gpr(rd, res);
                 hart.advance_pc(4)
             }
@@ -91,6 +189,10 @@ impl Instr {
                 let addr = hart.rd_gpr(rs1);
                 let data = hart.rd_gpr(rs2);
                 let res = match width {
+                    #[cfg(feature = "Zabha")]
+                    MemWidth::B => Xlen::from(hart.amo8(addr, order, data.into(), op)? as i8),
+                    #[cfg(feature = "Zabha")]
+                    MemWidth::H => Xlen::from(hart.amo16(addr, order, data.into(), op)? as i16),
                     MemWidth::W => Xlen::from(hart.amo32(addr, order, data.into(), op)? as i32),
                     #[cfg(feature = "RV64")]
                     MemWidth::D => Xlen::from(hart.amo64(addr, order, data.into(), op)? as i64),
@@ -99,59 +201,203 @@ impl Instr {
                 hart.wr_gpr(rd, res);
                 hart.advance_pc(4)
             }
+            #[cfg(feature = "Zacas")]
+            Instr::AmoCas(rd, rs1, rs2, order, width) => {
+                hart.amocas(rd, rs1, rs2, order, width)?;
+                hart.advance_pc(4)
+            }
 
             #[cfg(feature = "F")]
             Instr::LoadFp(rd, rs1, offset, pr) => {
+                hart.fp_enable(true)?;
                 hart.load_fp(rd, rs1, offset, pr)?;
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::StoreFp(rs1, rs2, offset, pr) => {
+                hart.fp_enable(false)?;
                 hart.store_fp(rs1, rs2, offset, pr)?;
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::FpOp3(rd, rs1, rs2, rs3, rm, pr, op) => {
+                hart.fp_enable(true)?;
                 hart.set_rt_rm(rm)?;
+                hart.inx_rd(&[rs1, rs2, rs3], pr);
                 hart.fpu.ternary_op(rd, rs1, rs2, rs3, pr, op);
+                hart.inx_wr(rd, pr);
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::FpOp2(rd, rs1, rs2, rm, pr, op) => {
+                hart.fp_enable(true)?;
                 hart.set_rt_rm(rm)?;
+                hart.inx_rd(&[rs1, rs2], pr);
                 hart.fpu.binary_op(rd, rs1, rs2, pr, op);
+                hart.inx_wr(rd, pr);
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::FpOp(rd, rs1, rm, pr, op) => {
+                hart.fp_enable(true)?;
                 hart.set_rt_rm(rm)?;
+                hart.inx_rd(&[rs1], pr);
                 hart.fpu.unary_op(rd, rs1, pr, op);
+                hart.inx_wr(rd, pr);
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::FpCvtGp(rd, rs1, rm, pr, op) => {
+                hart.fp_enable(true)?;
                 hart.set_rt_rm(rm)?;
-                let val = hart.fpu.fp_cvt_gp(rs1, pr, op);
+                hart.inx_rd(&[rs1], pr);
+                let val = match hart.xlen() {
+                    #[cfg(feature = "RV64")]
+                    32 => Xlen::from(hart.fpu.fp_cvt_gp::<u32>(rs1, pr, op)),
+                    _ => hart.fpu.fp_cvt_gp(rs1, pr, op),
+                };
                 hart.wr_gpr(rd, val);
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::GpCvtFp(rd, rs1, rm, pr, op) => {
+                hart.fp_enable(true)?;
                 hart.set_rt_rm(rm)?;
                 let rs1 = hart.rd_gpr(rs1);
                 hart.fpu.gp_cvt_fp(rd, rs1, pr, op);
+                hart.inx_wr(rd, pr);
                 hart.advance_pc(4)
             }
             #[cfg(feature = "F")]
             Instr::FpCmp(rd, rs1, rs2, pr, op) => {
+                hart.fp_enable(true)?;
+                hart.inx_rd(&[rs1, rs2], pr);
                 let val = hart.fpu.fp_cmp(rs1, rs2, pr, op);
                 hart.wr_gpr(rd, Xlen::from(val));
                 hart.advance_pc(4)
             }
-            #[cfg(feature = "D")]
+            #[cfg(feature = "F")]
             Instr::FpCvtFp(rd, rs1, rm, from, to) => {
+                hart.fp_enable(true)?;
                 hart.set_rt_rm(rm)?;
+                hart.inx_rd(&[rs1], from);
                 hart.fpu.fp_cvt_fp(rd, rs1, from, to);
+                hart.inx_wr(rd, to);
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "Zfa")]
+            Instr::FpLi(rd, idx, pr) => {
+                hart.fp_enable(true)?;
+                hart.fpu.fp_li(rd, idx, pr);
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "Zfa")]
+            Instr::FpMvP(rd, rs1, rs2, pr) => {
+                hart.fp_enable(true)?;
+                let lo = hart.rd_gpr(rs1);
+                let hi = hart.rd_gpr(rs2);
+                match hart.xlen() {
+                    #[cfg(feature = "RV64")]
+                    32 => hart.fpu.gp_mvp_fp::<u32>(rd, lo.into(), hi.into(), pr),
+                    _ => hart.fpu.gp_mvp_fp(rd, lo, hi, pr),
+                }
+                hart.advance_pc(4)
+            }
+
+            #[cfg(feature = "V")]
+            Instr::VSetVl(rd, cfg) => {
+                hart.v_enable(true)?;
+                hart.vsetvl(rd, cfg)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VLoad(op) => {
+                hart.v_enable(true)?;
+                hart.v_load(op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VStore(op) => {
+                hart.v_enable(true)?;
+                hart.v_store(op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VOp(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_op(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VOpW(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_op_wide(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VCmp(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_cmp(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VRed(vd, vs2, vs1, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_red(vd, vs2, vs1, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VMask(vd, vs2, vs1, op) => {
+                hart.v_enable(true)?;
+                hart.v_mask(vd, vs2, vs1, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VUnary(vd, vs2, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_unary(vd, vs2, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VPerm(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_perm(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VMv(dst, src, op) => {
+                hart.v_enable(true)?;
+                hart.v_mv(dst, src, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VFOp(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_fop(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VFOpW(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_fop_wide(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VFCmp(vd, vs2, src, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_fcmp(vd, vs2, src, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VFRed(vd, vs2, vs1, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_fred(vd, vs2, vs1, vm, op)?;
+                hart.advance_pc(4)
+            }
+            #[cfg(feature = "V")]
+            Instr::VFUnary(vd, vs2, vm, op) => {
+                hart.v_enable(true)?;
+                hart.v_funary(vd, vs2, vm, op)?;
                 hart.advance_pc(4)
             }
 
@@ -181,13 +427,45 @@ impl Instr {
             Instr::CJal(rd, offset) => hart.jal(rd, offset, 2),
             #[cfg(feature = "C")]
             Instr::CJalr(rd, rs1) => hart.jalr(rd, rs1, 0, 2),
+            #[cfg(all(feature = "C", feature = "Zihintntl"))]
+            Instr::CNtl(_) => hart.advance_pc(2),
+            #[cfg(all(feature = "Zcb", feature = "Zbb"))]
+            Instr::COpUnary(rd_rs1, op) => {
+                hart.op_unary(rd_rs1, rd_rs1, op);
+                hart.advance_pc(2)
+            }
+            #[cfg(feature = "Zcmp")]
+            Instr::CPush(count, stack_adj) => {
+                hart.push(count, stack_adj)?;
+                hart.advance_pc(2)
+            }
+            #[cfg(feature = "Zcmp")]
+            Instr::CPop(count, stack_adj, op) => hart.pop(count, stack_adj, op),
+            #[cfg(feature = "Zcmp")]
+            Instr::CMvSA01(rd1, rd2) => {
+                let (a0, a1) = (hart.rd_gpr(10), hart.rd_gpr(11));
+                hart.wr_gpr(rd1, a0);
+                hart.wr_gpr(rd2, a1);
+                hart.advance_pc(2)
+            }
+            #[cfg(feature = "Zcmp")]
+            Instr::CMvA01S(rs1, rs2) => {
+                let (s1, s2) = (hart.rd_gpr(rs1), hart.rd_gpr(rs2));
+                hart.wr_gpr(10, s1);
+                hart.wr_gpr(11, s2);
+                hart.advance_pc(2)
+            }
+            #[cfg(feature = "Zcmt")]
+            Instr::CTableJump(index) => hart.table_jump(index),
             #[cfg(all(feature = "C", feature = "F"))]
             Instr::CLoadFp(rd, rs1, offset, pr) => {
+                hart.fp_enable(true)?;
                 hart.load_fp(rd, rs1, offset, pr)?;
                 hart.advance_pc(2)
             }
             #[cfg(all(feature = "C", feature = "F"))]
             Instr::CStoreFp(rs1, rs2, offset, pr) => {
+                hart.fp_enable(false)?;
                 hart.store_fp(rs1, rs2, offset, pr)?;
                 hart.advance_pc(2)
             }
@@ -195,47 +473,121 @@ impl Instr {
     }
 }
 
+/// ra, s0 - s11 in Zcmp register list order
+#[cfg(feature = "Zcmp")]
+const ZCMP_RLIST: [u8; 13] = [1, 8, 9, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+
 impl<Xlen: XlenT> Hart<Xlen> {
-    fn rd_gpr(&self, reg: u8) -> Xlen {
+    pub fn rd_gpr(&self, reg: u8) -> Xlen {
         if reg == 0 {
             Xlen::from(0)
         } else {
             self.gprs[reg as usize]
         }
     }
-    fn wr_gpr(&mut self, reg: u8, val: Xlen) {
+    /// results narrower than `Xlen` are sign extended
+    pub fn wr_gpr(&mut self, reg: u8, val: Xlen) {
+        let val = self.sext_xlen(val);
         self.gprs[reg as usize] = val;
+        if let Some(cosim) = self.cosim.as_mut() {
+            cosim.wr_gpr(reg, val);
+        }
+    }
+    /// even-odd register pair, x0 pair reads zero
+    #[cfg(any(feature = "Zacas", feature = "Zfinx"))]
+    pub fn rd_gpr_pair(&self, reg: u8) -> u128 {
+        if reg == 0 {
+            return 0;
+        }
+        let xlen = self.xlen();
+        let mask = u128::MAX >> (128 - xlen);
+        let lo: u128 = self.rd_gpr(reg).into();
+        let hi: u128 = self.rd_gpr(reg + 1).into();
+        lo & mask | (hi & mask) << xlen
+    }
+    /// even-odd register pair, writes to x0 pair are discarded
+    #[cfg(any(feature = "Zacas", feature = "Zfinx"))]
+    pub fn wr_gpr_pair(&mut self, reg: u8, val: u128) {
+        if reg != 0 {
+            self.wr_gpr(reg, Xlen::from(val));
+            self.wr_gpr(reg + 1, Xlen::from(val >> self.xlen()));
+        }
+    }
+    /// sign-extend from effective xlen
+    fn sext_xlen(&self, val: Xlen) -> Xlen {
+        match self.xlen() {
+            #[cfg(feature = "RV64")]
+            32 => val.sext32(),
+            #[cfg(feature = "RV128")]
+            64 => val.sext64(),
+            _ => val,
+        }
     }
     fn advance_pc<T>(&mut self, offset: T) -> Maybe<()>
     where
         Xlen: Cast<T>,
     {
-        self.pc = self.pc.add(offset);
+        self.pc = self.sext_xlen(self.pc.add(offset));
         Err(())
     }
     pub fn set_pc(&mut self, addr: Xlen) -> Maybe<()> {
-        self.pc = addr;
+        self.pc = self.sext_xlen(addr);
         Err(())
     }
     pub fn get_pc(&self) -> Xlen {
         self.pc
     }
+    /// alu operations at effective xlen ignore the upper source bits
+    fn alu(&self, op: BinaryOp, lhs: Xlen, rhs: Xlen) -> Xlen {
+        match self.xlen() {
+            #[cfg(feature = "RV64")]
+            32 => Xlen::from(op.exec::<u32>(lhs.into(), rhs.into())),
+            #[cfg(feature = "RV128")]
+            64 => Xlen::from(op.exec::<u64>(lhs.into(), rhs.into())),
+            _ => op.exec(lhs, rhs),
+        }
+    }
     fn op_imm(&mut self, rd: u8, rs1: u8, imm: i32, op: BinaryOp) {
         let lhs = self.rd_gpr(rs1);
         let rhs = Xlen::from(imm);
-        let res = op.exec(lhs, rhs);
+        let res = self.alu(op, lhs, rhs);
         self.wr_gpr(rd, res);
     }
     fn op(&mut self, rd: u8, rs1: u8, rs2: u8, op: BinaryOp) {
         let lhs = self.rd_gpr(rs1);
         let rhs = self.rd_gpr(rs2);
-        let res = op.exec(lhs, rhs);
+        let res = self.alu(op, lhs, rhs);
+        self.wr_gpr(rd, res);
+    }
+    #[cfg(any(
+        feature = "Zbb",
+        feature = "Zbkb",
+        feature = "Zknd",
+        feature = "Zknh",
+        feature = "Zksh"
+    ))]
+    fn op_unary(&mut self, rd: u8, rs1: u8, op: UnaryOp) {
+        let val = self.rd_gpr(rs1);
+        let res = match self.xlen() {
+            #[cfg(feature = "RV64")]
+            32 => Xlen::from(op.exec::<u32>(val.into())),
+            #[cfg(feature = "RV128")]
+            64 => Xlen::from(op.exec::<u64>(val.into())),
+            _ => op.exec(val),
+        };
         self.wr_gpr(rd, res);
     }
     fn branch(&mut self, rs1: u8, rs2: u8, offset: i32, cond: CmpCond, step: u8) -> Maybe<()> {
         let lhs = self.rd_gpr(rs1);
         let rhs = self.rd_gpr(rs2);
-        if cond.test(lhs, rhs) {
+        let taken = match self.xlen() {
+            #[cfg(feature = "RV64")]
+            32 => cond.test::<u32>(lhs.into(), rhs.into()),
+            #[cfg(feature = "RV128")]
+            64 => cond.test::<u64>(lhs.into(), rhs.into()),
+            _ => cond.test(lhs, rhs),
+        };
+        if taken {
             self.advance_pc(offset)
         } else {
             self.advance_pc(step)
@@ -246,7 +598,7 @@ impl<Xlen: XlenT> Hart<Xlen> {
         self.advance_pc(offset)
     }
     fn jalr(&mut self, rd: u8, rs1: u8, offset: i32, step: u8) -> Maybe<()> {
-        let addr = self.rd_gpr(rs1).add(offset);
+        let addr = self.rd_gpr(rs1).add(offset) & !Xlen::from(1);
         self.wr_gpr(rd, self.get_pc().add(step));
         self.set_pc(addr)
     }
@@ -262,6 +614,12 @@ impl<Xlen: XlenT> Hart<Xlen> {
             MemWidth::HU => Xlen::from(self.rd_mem16(addr)?),
             #[cfg(feature = "RV64")]
             MemWidth::WU => Xlen::from(self.rd_mem32(addr)?),
+            #[cfg(feature = "RV128")]
+            MemWidth::DU => Xlen::from(self.rd_mem64(addr)?),
+            #[cfg(feature = "RV128")]
+            MemWidth::Q => Xlen::from(self.rd_mem128(addr)?),
+            #[allow(unreachable_patterns)]
+            _ => panic!("bad uop"),
         };
         self.wr_gpr(rd, res);
         Ok(())
@@ -275,9 +633,96 @@ impl<Xlen: XlenT> Hart<Xlen> {
             MemWidth::W => self.wr_mem32(addr, data.into()),
             #[cfg(feature = "RV64")]
             MemWidth::D => self.wr_mem64(addr, data.into()),
+            #[cfg(feature = "RV128")]
+            MemWidth::Q => self.wr_mem128(addr, data.into()),
             _ => panic!("bad uop"),
         }
     }
+    /// register list goes below sp, last register on top,
+    /// sp only moves once all stores succeed
+    #[cfg(feature = "Zcmp")]
+    fn push(&mut self, count: u8, stack_adj: u32) -> Maybe<()> {
+        let (width, bytes) = self.xlen_width();
+        for (i, &reg) in ZCMP_RLIST[..count as usize].iter().rev().enumerate() {
+            let offset = -(i as i32 + 1) * bytes;
+            self.store(2, reg, offset, width)?;
+        }
+        let sp = self.rd_gpr(2).add(-(stack_adj as i32));
+        self.wr_gpr(2, sp);
+        Ok(())
+    }
+    #[cfg(feature = "Zcmp")]
+    fn pop(&mut self, count: u8, stack_adj: u32, op: PopOp) -> Maybe<()> {
+        let (width, bytes) = self.xlen_width();
+        for (i, &reg) in ZCMP_RLIST[..count as usize].iter().rev().enumerate() {
+            let offset = stack_adj as i32 - (i as i32 + 1) * bytes;
+            self.load(reg, 2, offset, width)?;
+        }
+        let sp = self.rd_gpr(2).add(stack_adj);
+        self.wr_gpr(2, sp);
+        if op == PopOp::PopRetZ {
+            self.wr_gpr(10, Xlen::from(0));
+        }
+        match op {
+            PopOp::Pop => self.advance_pc(2),
+            // returns like jalr x0, 0(ra)
+            PopOp::PopRet | PopOp::PopRetZ => self.set_pc(self.rd_gpr(1) & !Xlen::from(1)),
+        }
+    }
+    /// width and bytes of an xlen register in memory
+    #[cfg(feature = "Zcmp")]
+    fn xlen_width(&self) -> (MemWidth, i32) {
+        match self.xlen() {
+            #[cfg(feature = "RV64")]
+            64 => (MemWidth::D, 8),
+            _ => (MemWidth::W, 4),
+        }
+    }
+    /// entries of the table at jvt are fetched like instructions
+    #[cfg(feature = "Zcmt")]
+    fn table_jump(&mut self, index: u8) -> Maybe<()> {
+        let base = self.priv_ctrl.jvt & !Xlen::from(63);
+        let addr = base.add(index as u32 * self.xlen() / 8);
+        let target = self.fetch_jvt(addr)?;
+        // cm.jalt
+        if index >= 32 {
+            self.wr_gpr(1, self.get_pc().add(2));
+        }
+        self.set_pc(target & !Xlen::from(1))
+    }
+    /// rd holds the compare value and receives the old value
+    #[cfg(feature = "Zacas")]
+    fn amocas(&mut self, rd: u8, rs1: u8, rs2: u8, order: MemOrder, width: MemWidth) -> Maybe<()> {
+        let addr = self.rd_gpr(rs1);
+        let cmp = self.rd_gpr(rd);
+        let data = self.rd_gpr(rs2);
+        let res = match width {
+            #[cfg(feature = "Zabha")]
+            MemWidth::B => Xlen::from(self.amo_cas8(addr, order, cmp.into(), data.into())? as i8),
+            #[cfg(feature = "Zabha")]
+            MemWidth::H => Xlen::from(self.amo_cas16(addr, order, cmp.into(), data.into())? as i16),
+            MemWidth::W => Xlen::from(self.amo_cas32(addr, order, cmp.into(), data.into())? as i32),
+            MemWidth::D if self.xlen() == 32 => {
+                let cmp = self.rd_gpr_pair(rd) as u64;
+                let data = self.rd_gpr_pair(rs2) as u64;
+                let old = self.amo_cas64(addr, order, cmp, data)?;
+                self.wr_gpr_pair(rd, old as u128);
+                return Ok(());
+            }
+            MemWidth::D => Xlen::from(self.amo_cas64(addr, order, cmp.into(), data.into())? as i64),
+            #[cfg(feature = "RV64")]
+            MemWidth::Q => {
+                let cmp = self.rd_gpr_pair(rd);
+                let data = self.rd_gpr_pair(rs2);
+                let old = self.amo_cas128(addr, order, cmp, data)?;
+                self.wr_gpr_pair(rd, old);
+                return Ok(());
+            }
+            _ => panic!("bad uop"),
+        };
+        self.wr_gpr(rd, res);
+        Ok(())
+    }
     #[cfg(feature = "F")]
     fn load_fp(&mut self, rd: u8, rs1: u8, offset: i32, pr: Precision) -> Maybe<()> {
         let addr = self.rd_gpr(rs1).add(offset);
@@ -291,6 +736,16 @@ impl<Xlen: XlenT> Hart<Xlen> {
                 let val = self.rd_mem64(addr)?;
                 self.fpu.u64_mv_f64(rd, val);
             }
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => {
+                let val = self.rd_mem16(addr)?;
+                self.fpu.u16_mv_f16(rd, val);
+            }
+            #[cfg(feature = "Q")]
+            Precision::Q => {
+                let val = self.rd_mem128(addr)?;
+                self.fpu.u128_mv_f128(rd, val);
+            }
         };
         Ok(())
     }
@@ -307,6 +762,16 @@ impl<Xlen: XlenT> Hart<Xlen> {
                 let data = self.fpu.f64_mv_u64(rs2);
                 self.wr_mem64(addr, data)
             }
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => {
+                let data = self.fpu.f16_mv_u16(rs2);
+                self.wr_mem16(addr, data)
+            }
+            #[cfg(feature = "Q")]
+            Precision::Q => {
+                let data = self.fpu.f128_mv_u128(rs2);
+                self.wr_mem128(addr, data)
+            }
         }
     }
 }
@@ -1,6 +1,19 @@
-use crate::{hart::Hart, utils::Maybe, xlen::XlenT};
+use crate::{
+    hart::{Hart, Idle},
+    utils::Maybe,
+    xlen::XlenT,
+};
 
 mod alu;
+#[cfg(any(
+    feature = "Zbkb",
+    feature = "Zknd",
+    feature = "Zkne",
+    feature = "Zknh",
+    feature = "Zksed",
+    feature = "Zksh"
+))]
+mod crypto;
 mod dispatch;
 
 impl<Xlen: XlenT> Hart<Xlen> {
@@ -9,7 +22,66 @@ impl<Xlen: XlenT> Hart<Xlen> {
             let _ = self.exec_cycle();
         }
     }
+    /// execute at most `n` instructions, returns number of cycles executed,
+    /// fewer when stopped, paused or idle
+    pub fn step(&mut self, n: u64) -> u64 {
+        // wrs.sto times out after stalling through the next turn
+        #[cfg(feature = "Zawrs")]
+        if self.idle == Idle::Wrs(true) && !self.wake_pending() {
+            self.idle = Idle::Run;
+            return 0;
+        }
+        for cycle in 0..n {
+            if self.stop_tok {
+                return cycle;
+            }
+            if self.idle != Idle::Run {
+                if !self.wake_pending() {
+                    return cycle;
+                }
+                self.idle = Idle::Run;
+            }
+            let _ = self.exec_cycle();
+            #[cfg(feature = "Zihintpause")]
+            if self.yield_tok {
+                self.yield_tok = false;
+                return cycle + 1;
+            }
+        }
+        n
+    }
+    /// idle hart resumes on a locally enabled pending interrupt, even if
+    /// globally disabled, wrs.* also on losing the reservation
+    fn wake_pending(&self) -> bool {
+        let p = &self.priv_ctrl;
+        if p.mip & p.mie != Xlen::from(0) {
+            return true;
+        }
+        match self.idle {
+            Idle::Run => true,
+            Idle::Wfi => false,
+            #[cfg(feature = "Zawrs")]
+            Idle::Wrs(_) => !self.reserved(),
+        }
+    }
+    /// stalled until another hart or the timer wakes it,
+    /// time may skip ahead when every hart waits
+    pub fn waiting(&self) -> bool {
+        // wrs.sto times out on its own
+        #[cfg(feature = "Zawrs")]
+        if self.idle == Idle::Wrs(true) {
+            return false;
+        }
+        self.idle != Idle::Run && !self.wake_pending()
+    }
     fn exec_cycle(&mut self) -> Maybe<()> {
+        self.check_irq()?;
+        if self.cosim.is_some() {
+            self.cosim_begin();
+            let res = self.fetch_uop().and_then(|ins| ins.exec(self));
+            self.cosim_retire();
+            return res;
+        }
         let ins = self.fetch_uop()?;
         ins.exec(self)
     }
@@ -58,6 +58,18 @@ impl Fpu {
                     }
                 }
             }
+            #[cfg(target_arch = "aarch64")]
+            {
+                // fix fminnm / fmaxnm return nan on snan,
+                // should return the other operand
+                // use is_nan to raise exception if snan
+                match (_a.is_nan(), _b.is_nan()) {
+                    (false, false) => (),
+                    (false, true) => return Some(_a),
+                    (true, false) => return Some(_b),
+                    (true, true) => return Some(T::canonical_nan()),
+                }
+            }
         }
         None
     }
@@ -65,6 +77,27 @@ impl Fpu {
     pub fn pre_unary_op<T: FpOp>(&mut self, _a: T, _op: FpUnaryOp) -> Option<T> {
         None
     }
+
+    /// aarch64 detects tininess before rounding, risc-v after,
+    /// fold pending flags so underflow of the next op can be told apart
+    pub fn pre_tiny(&mut self) {
+        #[cfg(target_arch = "aarch64")]
+        self.sync_fpe();
+    }
+
+    /// a result rounded up to ±min normal may be tiny only before rounding,
+    /// redo it in software for the underflow flag
+    pub fn post_tiny<T: FpOp>(&mut self, res: T, _soft: impl FnOnce(&mut FpExcept) -> u128) -> T {
+        #[cfg(target_arch = "aarch64")]
+        if res.to_u128() & !T::FMT.sign() == 1 << T::FMT.frac {
+            let mut fpe = FPE;
+            _soft(&mut fpe);
+            if !fpe.uf {
+                clr_uf();
+            }
+        }
+        res
+    }
 }
 
 #[cfg(target_feature = "sse")]
@@ -87,6 +120,7 @@ pub fn clr_fpe() {
 #[cfg(target_feature = "sse")]
 pub fn set_rm(rm: RoundMode) {
     let rc = match rm {
+        // rmm is not supported by sse, ops using it go to software
         RoundMode::Rne | RoundMode::Rmm => arch::_MM_ROUND_NEAREST,
         RoundMode::Rtz => arch::_MM_ROUND_TOWARD_ZERO,
         RoundMode::Rdn => arch::_MM_ROUND_DOWN,
@@ -97,3 +131,85 @@ pub fn set_rm(rm: RoundMode) {
         arch::_MM_SET_ROUNDING_MODE(rc);
     }
 }
+
+// fpsr cumulative exception bits
+#[cfg(target_arch = "aarch64")]
+const FPSR_IOC: u64 = 1 << 0;
+#[cfg(target_arch = "aarch64")]
+const FPSR_DZC: u64 = 1 << 1;
+#[cfg(target_arch = "aarch64")]
+const FPSR_OFC: u64 = 1 << 2;
+#[cfg(target_arch = "aarch64")]
+const FPSR_UFC: u64 = 1 << 3;
+#[cfg(target_arch = "aarch64")]
+const FPSR_IXC: u64 = 1 << 4;
+#[cfg(target_arch = "aarch64")]
+const FPSR_EXC: u64 = 0x1f;
+// fpcr rounding mode field
+#[cfg(target_arch = "aarch64")]
+const FPCR_RMODE_SHIFT: u64 = 22;
+#[cfg(target_arch = "aarch64")]
+const FPCR_RMODE_MASK: u64 = 0b11 << FPCR_RMODE_SHIFT;
+
+#[cfg(target_arch = "aarch64")]
+pub fn get_fpe() -> FpExcept {
+    let stat: u64;
+    unsafe { std::arch::asm!("mrs {}, fpsr", out(reg) stat) };
+    FpExcept {
+        nv: flag_set(stat, FPSR_IOC),
+        dz: flag_set(stat, FPSR_DZC),
+        of: flag_set(stat, FPSR_OFC),
+        uf: flag_set(stat, FPSR_UFC),
+        nx: flag_set(stat, FPSR_IXC),
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn clr_fpe() {
+    unsafe {
+        let stat: u64;
+        std::arch::asm!("mrs {}, fpsr", out(reg) stat);
+        std::arch::asm!("msr fpsr, {}", in(reg) stat & !FPSR_EXC);
+    }
+}
+
+/// drop underflow of the last op, flags before it are already folded
+#[cfg(target_arch = "aarch64")]
+fn clr_uf() {
+    unsafe {
+        let stat: u64;
+        std::arch::asm!("mrs {}, fpsr", out(reg) stat);
+        std::arch::asm!("msr fpsr, {}", in(reg) stat & !FPSR_UFC);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+pub fn set_rm(rm: RoundMode) {
+    let rc: u64 = match rm {
+        // rmm is not supported by fpcr, ops using it go to software
+        RoundMode::Rne | RoundMode::Rmm => 0b00,
+        RoundMode::Rup => 0b01,
+        RoundMode::Rdn => 0b10,
+        RoundMode::Rtz => 0b11,
+        _ => panic!("bad round mode"),
+    };
+    unsafe {
+        let ctrl: u64;
+        std::arch::asm!("mrs {}, fpcr", out(reg) ctrl);
+        let ctrl = (ctrl & !FPCR_RMODE_MASK) | (rc << FPCR_RMODE_SHIFT);
+        std::arch::asm!("msr fpcr, {}", in(reg) ctrl);
+    }
+}
+
+// no host fpu support, everything is done in software
+
+#[cfg(not(any(target_feature = "sse", target_arch = "aarch64")))]
+pub fn get_fpe() -> FpExcept {
+    FPE
+}
+
+#[cfg(not(any(target_feature = "sse", target_arch = "aarch64")))]
+pub fn clr_fpe() {}
+
+#[cfg(not(any(target_feature = "sse", target_arch = "aarch64")))]
+pub fn set_rm(_rm: RoundMode) {}
@@ -0,0 +1,9 @@
+// IEEE 754 binary16 for Zfh / Zfhmin, host has no native half-precision
+
+use super::{soft::BINARY16, *};
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct F16(u16);
+
+soft_fp!(F16, u16, BINARY16);
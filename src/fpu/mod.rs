@@ -1,4 +1,12 @@
-use crate::{hart::Hart, uop::*, utils::Maybe, xlen::XlenT};
+#[cfg(feature = "H")]
+use crate::privilege::MSTATUS_FS;
+use crate::{
+    hart::Hart,
+    privilege::{FCSR, FFLAGS, FRM},
+    uop::*,
+    utils::Maybe,
+    xlen::XlenT,
+};
 use std::{
     cmp::PartialOrd,
     mem::size_of,
@@ -8,15 +16,54 @@ use std::{
 
 mod arch;
 #[cfg(test)]
+mod testfloat;
+#[cfg(test)]
 mod tests;
 
+/// fli constants, min normal at 1 depends on format
+#[cfg(feature = "Zfa")]
+const FLI: [f64; 32] = [
+    -1.0,
+    f64::MIN_POSITIVE,
+    1.52587890625e-5,
+    3.0517578125e-5,
+    0.00390625,
+    0.0078125,
+    0.0625,
+    0.125,
+    0.25,
+    0.3125,
+    0.375,
+    0.4375,
+    0.5,
+    0.625,
+    0.75,
+    0.875,
+    1.0,
+    1.25,
+    1.5,
+    1.75,
+    2.0,
+    2.5,
+    3.0,
+    4.0,
+    8.0,
+    16.0,
+    128.0,
+    256.0,
+    32768.0,
+    65536.0,
+    f64::INFINITY,
+    f64::NAN,
+];
+
 /// floating-point register size
 const FPLEN: usize = {
     #[cfg(feature = "Q")]
     {
         16
     }
-    #[cfg(feature = "D")]
+    #[cfg(all(feature = "D", not(feature = "Q")))]
     {
         8
     }
@@ -26,6 +73,13 @@ const FPLEN: usize = {
     }
 };
 
+/// use the software backend for all precisions,
+/// selected by feature or when host fpu is not supported
+const SOFT_BACKEND: bool = cfg!(any(
+    feature = "softfp",
+    not(any(target_feature = "sse", target_arch = "aarch64"))
+));
+
 #[repr(align(16))]
 #[derive(Debug, Clone)]
 pub struct Fpu {
@@ -35,10 +89,13 @@ pub struct Fpu {
     fprs: [[u8; FPLEN]; 32],
     /// system fpu exceptions
     fpe: FpExcept,
-    /// dynamic rounding mode
-    dyn_rm: RoundMode,
+    /// dynamic rounding mode, frm csr
+    /// may hold reserved values
+    frm: u8,
     /// system fpu rounding mode
     rm: RoundMode,
+    /// mstatus.FS
+    fs: FpState,
 }
 
 impl Default for Fpu {
@@ -46,8 +103,9 @@ impl Default for Fpu {
         Self {
             fprs: Default::default(),
             fpe: Default::default(),
-            dyn_rm: RoundMode::Rne,
+            frm: 0,
             rm: RoundMode::None,
+            fs: FpState::Initial,
         }
     }
 }
@@ -87,20 +145,108 @@ impl FpExcept {
         }
         e
     }
+
+    pub fn from_u8(e: u8) -> Self {
+        Self {
+            nx: e & 1 != 0,
+            uf: e & 2 != 0,
+            of: e & 4 != 0,
+            dz: e & 8 != 0,
+            nv: e & 16 != 0,
+        }
+    }
+}
+
+impl RoundMode {
+    /// round mode in frm, reserved values are None
+    pub fn from_frm(frm: u8) -> Self {
+        match frm {
+            0b000 => RoundMode::Rne,
+            0b001 => RoundMode::Rtz,
+            0b010 => RoundMode::Rdn,
+            0b011 => RoundMode::Rup,
+            0b100 => RoundMode::Rmm,
+            _ => RoundMode::None,
+        }
+    }
+
+    /// encoding in frm, dyn is invalid there
+    pub fn frm(self) -> u8 {
+        match self {
+            RoundMode::Rne => 0b000,
+            RoundMode::Rtz => 0b001,
+            RoundMode::Rdn => 0b010,
+            RoundMode::Rup => 0b011,
+            RoundMode::Rmm => 0b100,
+            _ => 0b111,
+        }
+    }
+}
+
+// ops without host equivalent, always done in software
+
+impl FpBinaryOp {
+    fn soft_only(self) -> bool {
+        match self {
+            #[cfg(feature = "Zfa")]
+            FpBinaryOp::MinM | FpBinaryOp::MaxM => true,
+            _ => false,
+        }
+    }
 }
 
-const FPE: FpExcept = FpExcept {
+impl FpUnaryOp {
+    fn soft_only(self) -> bool {
+        match self {
+            #[cfg(feature = "Zfa")]
+            FpUnaryOp::Round | FpUnaryOp::RoundNX => true,
+            _ => false,
+        }
+    }
+}
+
+impl FpCmpCond {
+    fn soft_only(self) -> bool {
+        match self {
+            #[cfg(feature = "Zfa")]
+            FpCmpCond::LtQ | FpCmpCond::LeQ => true,
+            _ => false,
+        }
+    }
+}
+
+/// context status in mstatus.FS, also mstatus.VS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpState {
+    Off = 0,
+    Initial = 1,
+    Clean = 2,
+    Dirty = 3,
+}
+
+impl FpState {
+    pub fn from_bits(bits: u32) -> Self {
+        match bits & 3 {
+            0 => FpState::Off,
+            1 => FpState::Initial,
+            2 => FpState::Clean,
+            _ => FpState::Dirty,
+        }
+    }
+}
+
+pub const FPE: FpExcept = FpExcept {
     nv: false,
     dz: false,
     of: false,
     uf: false,
     nx: false,
 };
-const FPE_NV: FpExcept = FpExcept { nv: true, ..FPE };
-const FPE_DZ: FpExcept = FpExcept { dz: true, ..FPE };
-const FPE_OF: FpExcept = FpExcept { of: true, ..FPE };
-const FPE_UF: FpExcept = FpExcept { uf: true, ..FPE };
-const FPE_NX: FpExcept = FpExcept { nx: true, ..FPE };
+pub const FPE_NV: FpExcept = FpExcept { nv: true, ..FPE };
+pub const FPE_DZ: FpExcept = FpExcept { dz: true, ..FPE };
+pub const FPE_OF: FpExcept = FpExcept { of: true, ..FPE };
+pub const FPE_UF: FpExcept = FpExcept { uf: true, ..FPE };
+pub const FPE_NX: FpExcept = FpExcept { nx: true, ..FPE };
 
 impl BitOr for FpExcept {
     type Output = FpExcept;
@@ -176,6 +322,10 @@ macro_rules! pr_switch {
             Precision::S => f32::$func($($e), *),
             #[cfg(feature = "D")]
             Precision::D => f64::$func($($e), *),
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => F16::$func($($e), *),
+            #[cfg(feature = "Q")]
+            Precision::Q => F128::$func($($e), *),
         }
     };
 }
@@ -197,17 +347,38 @@ pub trait FpOp:
 
     fn no_nan_box(self) -> Self {
         // TODO: some arch can bypass this
-        if self.is_nan() {
+        // test the bits, llvm treats nan payloads as unspecified
+        // and folds a float is_nan select away in release builds
+        if Self::FMT.is_nan(self.to_u128()) {
             Self::canonical_nan()
         } else {
             self
         }
     }
 
+    /// compute in software instead of host fpu
+    fn soft(fpu: &Fpu) -> bool {
+        Self::SOFT || fpu.soft()
+    }
+
+    fn to_u128(self) -> u128 {
+        self.to_bits().into()
+    }
+
     fn ternary_op(fpu: &mut Fpu, rd: u8, rs1: u8, rs2: u8, rs3: u8, op: FpTernaryOp) {
+        if Self::soft(fpu) {
+            let rs1 = Self::rd_fpr(fpu, rs1).to_u128();
+            let rs2 = Self::rd_fpr(fpu, rs2).to_u128();
+            let rs3 = Self::rd_fpr(fpu, rs3).to_u128();
+            let mut fpe = FPE;
+            let res = soft::ternary(Self::FMT, rs1, rs2, rs3, op, fpu.rm, &mut fpe);
+            fpu.set_fpe(fpe);
+            return Self::wr_fpr(fpu, rd, Self::from_u128(res));
+        }
         let rs1 = Self::rd_fpr(fpu, rs1);
         let mut rs2 = Self::rd_fpr(fpu, rs2);
         let mut rs3 = Self::rd_fpr(fpu, rs3);
+        let (a, b, c, rm) = (rs1.to_u128(), rs2.to_u128(), rs3.to_u128(), fpu.rm);
         // FMADD.S computes (rs1 × rs2) + rs3
         // FMSUB.S computes (rs1 × rs2) - rs3
         // FNMSUB.S computes -(rs1 × rs2) + rs3
@@ -218,16 +389,28 @@ pub trait FpOp:
         if op == FpTernaryOp::MSub || op == FpTernaryOp::NMAdd {
             rs3 = -rs3;
         }
+        fpu.pre_tiny();
         let res = fpu
             .pre_fma(rs1, rs2, rs3)
-            .unwrap_or_else(|| rs1.mul_add(rs2, rs3))
+            .unwrap_or_else(|| rs1.mul_add(rs2, rs3));
+        let res = fpu
+            .post_tiny(res, |fpe| soft::ternary(Self::FMT, a, b, c, op, rm, fpe))
             .no_nan_box();
         Self::wr_fpr(fpu, rd, res);
     }
 
     fn binary_op(fpu: &mut Fpu, rd: u8, rs1: u8, rs2: u8, op: FpBinaryOp) {
+        if Self::soft(fpu) || op.soft_only() {
+            let rs1 = Self::rd_fpr(fpu, rs1).to_u128();
+            let rs2 = Self::rd_fpr(fpu, rs2).to_u128();
+            let mut fpe = FPE;
+            let res = soft::binary(Self::FMT, rs1, rs2, op, fpu.rm, &mut fpe);
+            fpu.set_fpe(fpe);
+            return Self::wr_fpr(fpu, rd, Self::from_u128(res));
+        }
         let rs1 = Self::rd_fpr(fpu, rs1);
         let rs2 = Self::rd_fpr(fpu, rs2);
+        fpu.pre_tiny();
         let res = fpu.pre_binary_op(rs1, rs2, op).unwrap_or_else(|| match op {
             FpBinaryOp::Add => rs1 + rs2,
             FpBinaryOp::Sub => rs1 - rs2,
@@ -252,7 +435,11 @@ pub trait FpOp:
                 println!("{:?} {:?} fix me", rs1, rs2);
                 rs1.max(rs2)
             }
+            #[cfg(feature = "Zfa")]
+            FpBinaryOp::MinM | FpBinaryOp::MaxM => unreachable!(),
         });
+        let (a, b, rm) = (rs1.to_u128(), rs2.to_u128(), fpu.rm);
+        let res = fpu.post_tiny(res, |fpe| soft::binary(Self::FMT, a, b, op, rm, fpe));
         let res = match op {
             FpBinaryOp::SgnJ | FpBinaryOp::SgnJN | FpBinaryOp::SgnJX => res,
             _ => res.no_nan_box(),
@@ -261,6 +448,19 @@ pub trait FpOp:
     }
 
     fn unary_op(fpu: &mut Fpu, rd: u8, rs1: u8, op: FpUnaryOp) {
+        if Self::soft(fpu) || op.soft_only() {
+            let rs1 = Self::rd_fpr(fpu, rs1).to_u128();
+            let mut fpe = FPE;
+            let res = match op {
+                FpUnaryOp::Sqrt => soft::sqrt(Self::FMT, rs1, fpu.rm, &mut fpe),
+                #[cfg(feature = "Zfa")]
+                FpUnaryOp::Round => soft::round_int(Self::FMT, rs1, fpu.rm, false, &mut fpe),
+                #[cfg(feature = "Zfa")]
+                FpUnaryOp::RoundNX => soft::round_int(Self::FMT, rs1, fpu.rm, true, &mut fpe),
+            };
+            fpu.set_fpe(fpe);
+            return Self::wr_fpr(fpu, rd, Self::from_u128(res));
+        }
         let rs1 = Self::rd_fpr(fpu, rs1);
         let res = fpu
             .pre_unary_op(rs1, op)
@@ -268,12 +468,17 @@ pub trait FpOp:
                 // TODO: check for exception correctness and -0.0
                 // codegen seems fine
                 FpUnaryOp::Sqrt => rs1.sqrt(),
+                #[cfg(feature = "Zfa")]
+                FpUnaryOp::Round | FpUnaryOp::RoundNX => unreachable!(),
             })
             .no_nan_box();
         Self::wr_fpr(fpu, rd, res);
     }
 
     fn class(fpu: &Fpu, rs1: u8) -> u32 {
+        if Self::soft(fpu) {
+            return soft::class(Self::FMT, Self::rd_fpr(fpu, rs1).to_u128());
+        }
         let rs1 = Self::rd_fpr(fpu, rs1);
         // TODO: check exception correctness
         // codegen seems fine
@@ -291,31 +496,88 @@ pub trait FpOp:
         }
     }
 
-    fn cmp(fpu: &Fpu, rs1: u8, rs2: u8, op: FpCmpCond) -> bool {
+    fn cmp(fpu: &mut Fpu, rs1: u8, rs2: u8, op: FpCmpCond) -> bool {
+        if Self::soft(fpu) || op.soft_only() {
+            let rs1 = Self::rd_fpr(fpu, rs1).to_u128();
+            let rs2 = Self::rd_fpr(fpu, rs2).to_u128();
+            let mut fpe = FPE;
+            let res = soft::cmp(Self::FMT, rs1, rs2, op, &mut fpe);
+            fpu.set_fpe(fpe);
+            return res;
+        }
         let rs1 = Self::rd_fpr(fpu, rs1);
         let rs2 = Self::rd_fpr(fpu, rs2);
-        // TODO: check exception correctness
-        // likely to be broken on lt, le
+        // host compares are quiet, only snan raises,
+        // lt and le signal on any nan
+        if op != FpCmpCond::Eq && (rs1.is_nan() || rs2.is_nan()) {
+            fpu.set_fpe(FPE_NV);
+        }
         match op {
             FpCmpCond::Eq => rs1 == rs2,
             FpCmpCond::Lt => rs1 < rs2,
             FpCmpCond::Le => rs1 <= rs2,
+            #[cfg(feature = "Zfa")]
+            FpCmpCond::LtQ | FpCmpCond::LeQ => unreachable!(),
         }
     }
 
-    fn fp_to_i32(fpu: &Fpu, rs1: u8) -> i32;
-    fn fp_to_u32(fpu: &Fpu, rs1: u8) -> u32;
-    fn fp_to_i64(fpu: &Fpu, rs1: u8) -> i64;
-    fn fp_to_u64(fpu: &Fpu, rs1: u8) -> i32;
-    fn i32_to_fp(fpu: &mut Fpu, rd: u8, rs1: i32);
-    fn u32_to_fp(fpu: &mut Fpu, rd: u8, rs1: u32);
-    fn i64_to_fp(fpu: &mut Fpu, rd: u8, rs1: i64);
-    fn u64_to_fp(fpu: &mut Fpu, rd: u8, rs1: u64);
+    // integer conversions are always done in software,
+    // host conversions saturate differently and don't raise exceptions
+    fn soft_to_int(fpu: &mut Fpu, rs1: u8, width: u32, signed: bool) -> i128 {
+        let val = Self::rd_fpr(fpu, rs1).to_u128();
+        let mut fpe = FPE;
+        let res = soft::to_int(Self::FMT, val, fpu.rm, width, signed, &mut fpe);
+        fpu.set_fpe(fpe);
+        res
+    }
+
+    fn soft_from_int(fpu: &mut Fpu, rd: u8, neg: bool, mag: u128) {
+        let mut fpe = FPE;
+        let res = soft::from_int(Self::FMT, neg, mag, fpu.rm, &mut fpe);
+        fpu.set_fpe(fpe);
+        Self::wr_fpr(fpu, rd, Self::from_u128(res));
+    }
+
+    fn fp_to_i32(fpu: &mut Fpu, rs1: u8) -> i32 {
+        Self::soft_to_int(fpu, rs1, 32, true) as i32
+    }
+
+    fn fp_to_u32(fpu: &mut Fpu, rs1: u8) -> u32 {
+        Self::soft_to_int(fpu, rs1, 32, false) as u32
+    }
+
+    fn fp_to_i64(fpu: &mut Fpu, rs1: u8) -> i64 {
+        Self::soft_to_int(fpu, rs1, 64, true) as i64
+    }
+
+    fn fp_to_u64(fpu: &mut Fpu, rs1: u8) -> u64 {
+        Self::soft_to_int(fpu, rs1, 64, false) as u64
+    }
+
+    fn i32_to_fp(fpu: &mut Fpu, rd: u8, rs1: i32) {
+        Self::soft_from_int(fpu, rd, rs1 < 0, rs1.unsigned_abs() as u128);
+    }
+
+    fn u32_to_fp(fpu: &mut Fpu, rd: u8, rs1: u32) {
+        Self::soft_from_int(fpu, rd, false, rs1 as u128);
+    }
+
+    fn i64_to_fp(fpu: &mut Fpu, rd: u8, rs1: i64) {
+        Self::soft_from_int(fpu, rd, rs1 < 0, rs1.unsigned_abs() as u128);
+    }
+
+    fn u64_to_fp(fpu: &mut Fpu, rd: u8, rs1: u64) {
+        Self::soft_from_int(fpu, rd, false, rs1 as u128);
+    }
 
     // type def
-    type BitRep;
+    type BitRep: Copy + Into<u128>;
     const QNAN_BITS: Self::BitRep;
     const Q_BIT: Self::BitRep;
+    /// interchange format for the software backend
+    const FMT: Fmt;
+    /// host has no support for the type
+    const SOFT: bool = false;
 
     // impl required
     fn rd_fpr(fpu: &Fpu, reg: u8) -> Self;
@@ -325,6 +587,7 @@ pub trait FpOp:
     fn is_quiet(self) -> bool;
     fn is_qnan_safe(self) -> bool;
     fn is_zero(self) -> bool;
+    fn from_u128(val: u128) -> Self;
 
     // fp use std macro
     fn mul_add(self, a: Self, b: Self) -> Self;
@@ -388,45 +651,33 @@ macro_rules! fp_common {
         fn is_zero(self) -> bool {
             self.to_bits() << 1 == 0
         }
-
-        fn fp_to_i32(fpu: &Fpu, rs1: u8) -> i32 {
-            todo!()
-        }
-
-        fn fp_to_u32(fpu: &Fpu, rs1: u8) -> u32 {
-            todo!()
-        }
-
-        fn fp_to_i64(fpu: &Fpu, rs1: u8) -> i64 {
-            todo!()
-        }
-
-        fn fp_to_u64(fpu: &Fpu, rs1: u8) -> i32 {
-            todo!()
-        }
-
-        fn i32_to_fp(fpu: &mut Fpu, rd: u8, rs1: i32) {
-            todo!()
-        }
-
-        fn u32_to_fp(fpu: &mut Fpu, rd: u8, rs1: u32) {
-            todo!()
-        }
-
-        fn i64_to_fp(fpu: &mut Fpu, rd: u8, rs1: i64) {
-            todo!()
-        }
-
-        fn u64_to_fp(fpu: &mut Fpu, rd: u8, rs1: u64) {
-            todo!()
+        fn from_u128(val: u128) -> Self {
+            Self::from_bits(val as Self::BitRep)
         }
     };
 }
 
+// after the macros above, which they use
+#[macro_use]
+pub mod soft;
+#[cfg(feature = "Zfhmin")]
+mod half;
+#[cfg(feature = "Q")]
+mod quad;
+
+#[cfg(feature = "Zfhmin")]
+use half::F16;
+#[cfg(feature = "Q")]
+use quad::F128;
+#[cfg(any(feature = "D", feature = "Zfa"))]
+use soft::BINARY64;
+use soft::{Fmt, BINARY32};
+
 impl FpOp for f32 {
     type BitRep = u32;
     const QNAN_BITS: u32 = 0x7fc00000;
     const Q_BIT: u32 = 0x400000;
+    const FMT: Fmt = BINARY32;
 
     fn rd_fpr(fpu: &Fpu, reg: u8) -> Self {
         let fpr = fpu.fprs[reg as usize];
@@ -446,7 +697,8 @@ impl FpOp for f32 {
 impl FpOp for f64 {
     type BitRep = u64;
     const QNAN_BITS: u64 = 0x7ff8000000000000;
-    const Q_BIT: u64 = 0x7ff0000000000000;
+    const Q_BIT: u64 = 0x8000000000000;
+    const FMT: Fmt = BINARY64;
 
     fn rd_fpr(fpu: &Fpu, reg: u8) -> Self {
         let fpr = fpu.fprs[reg as usize];
@@ -483,23 +735,65 @@ impl Fpu {
         pr_switch!(pr, unary_op, self, rd, rs1, op);
     }
 
-    pub fn fp_cvt_gp<Xlen: XlenT>(&self, rs1: u8, pr: Precision, op: FpGpOp) -> Xlen {
+    pub fn fp_cvt_gp<Xlen: XlenT>(&mut self, rs1: u8, pr: Precision, op: FpGpOp) -> Xlen {
         match op {
             FpGpOp::W => Xlen::from(pr_switch!(pr, fp_to_i32, self, rs1)),
-            FpGpOp::WU => Xlen::from(pr_switch!(pr, fp_to_u32, self, rs1)),
+            // sign extended
+            FpGpOp::WU => Xlen::from(pr_switch!(pr, fp_to_u32, self, rs1) as i32),
             #[cfg(feature = "RV64")]
             FpGpOp::L => Xlen::from(pr_switch!(pr, fp_to_i64, self, rs1)),
             #[cfg(feature = "RV64")]
             FpGpOp::LU => Xlen::from(pr_switch!(pr, fp_to_u64, self, rs1)),
             FpGpOp::MV => match pr {
-                Precision::S => Xlen::from(self.f32_mv_u32(rs1)),
+                // sign extended
+                Precision::S => Xlen::from(self.f32_mv_u32(rs1) as i32),
                 #[cfg(feature = "D")]
                 Precision::D => Xlen::from(self.f64_mv_u64(rs1)),
+                #[cfg(feature = "Zfhmin")]
+                Precision::H => Xlen::from(self.f16_mv_u16(rs1) as i16),
+                #[cfg(feature = "Q")]
+                Precision::Q => Xlen::from(self.f128_mv_u128(rs1)),
             },
             FpGpOp::Class => Xlen::from(pr_switch!(pr, class, self, rs1)),
+            #[cfg(feature = "Zfa")]
+            FpGpOp::ModW => {
+                let val = self.rd_raw(rs1, pr);
+                let mut fpe = FPE;
+                let res = soft::to_int_mod(Fmt::of(pr), val, &mut fpe);
+                self.set_fpe(fpe);
+                Xlen::from(res)
+            }
+            #[cfg(feature = "Zfa")]
+            FpGpOp::MvH => Xlen::from(self.rd_raw(rs1, pr) >> Xlen::XLEN),
         }
     }
 
+    /// move pair of gp registers as low / high half
+    #[cfg(feature = "Zfa")]
+    pub fn gp_mvp_fp<Xlen: XlenT>(&mut self, rd: u8, lo: Xlen, hi: Xlen, pr: Precision) {
+        let lo: u128 = lo.into();
+        let hi: u128 = hi.into();
+        let mask = u128::MAX >> (128 - Xlen::XLEN);
+        self.wr_raw(rd, pr, (hi & mask) << Xlen::XLEN | lo & mask);
+    }
+
+    /// load constant from the fli table
+    #[cfg(feature = "Zfa")]
+    pub fn fp_li(&mut self, rd: u8, idx: u8, pr: Precision) {
+        let fmt = Fmt::of(pr);
+        let val = match idx {
+            // minimum positive normal
+            1 => 1 << fmt.frac,
+            _ => {
+                let val = FLI[idx as usize].to_bits() as u128;
+                // exact except for 2 ^ 16 overflowing half, no exceptions
+                let mut fpe = FPE;
+                soft::convert(BINARY64, fmt, val, RoundMode::Rne, &mut fpe)
+            }
+        };
+        self.wr_raw(rd, pr, val);
+    }
+
     pub fn gp_cvt_fp<Xlen: XlenT>(&mut self, rd: u8, rs1: Xlen, pr: Precision, op: GpFpOp) {
         match op {
             GpFpOp::W => pr_switch!(pr, i32_to_fp, self, rd, rs1.into()),
@@ -510,8 +804,12 @@ impl Fpu {
             GpFpOp::LU => pr_switch!(pr, u64_to_fp, self, rd, rs1.into()),
             GpFpOp::MV => match pr {
                 Precision::S => self.u32_mv_f32(rd, rs1.into()),
-                #[cfg(feature = "RV64")]
+                #[cfg(feature = "D")]
                 Precision::D => self.u64_mv_f64(rd, rs1.into()),
+                #[cfg(feature = "Zfhmin")]
+                Precision::H => self.u16_mv_f16(rd, rs1.into()),
+                #[cfg(feature = "Q")]
+                Precision::Q => self.u128_mv_f128(rd, rs1.into()),
             },
         }
     }
@@ -538,30 +836,118 @@ impl Fpu {
         val.to_bits()
     }
 
-    pub fn fp_cmp(&self, rs1: u8, rs2: u8, pr: Precision, op: FpCmpCond) -> u32 {
+    #[cfg(feature = "Zfhmin")]
+    pub fn u16_mv_f16(&mut self, rd: u8, val: u16) {
+        let val = F16::from_bits(val);
+        F16::wr_fpr(self, rd, val);
+    }
+
+    #[cfg(feature = "Zfhmin")]
+    pub fn f16_mv_u16(&self, rs1: u8) -> u16 {
+        let val = F16::rd_fpr(self, rs1);
+        val.to_bits()
+    }
+
+    #[cfg(feature = "Q")]
+    pub fn u128_mv_f128(&mut self, rd: u8, val: u128) {
+        let val = F128::from_bits(val);
+        F128::wr_fpr(self, rd, val);
+    }
+
+    #[cfg(feature = "Q")]
+    pub fn f128_mv_u128(&self, rs1: u8) -> u128 {
+        let val = F128::rd_fpr(self, rs1);
+        val.to_bits()
+    }
+
+    /// value of `pr` in register as raw bits
+    pub fn rd_raw(&self, reg: u8, pr: Precision) -> u128 {
+        match pr {
+            Precision::S => self.f32_mv_u32(reg) as u128,
+            #[cfg(feature = "D")]
+            Precision::D => self.f64_mv_u64(reg) as u128,
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => self.f16_mv_u16(reg) as u128,
+            #[cfg(feature = "Q")]
+            Precision::Q => self.f128_mv_u128(reg),
+        }
+    }
+
+    pub fn wr_raw(&mut self, reg: u8, pr: Precision, val: u128) {
+        match pr {
+            Precision::S => self.u32_mv_f32(reg, val as u32),
+            #[cfg(feature = "D")]
+            Precision::D => self.u64_mv_f64(reg, val as u64),
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => self.u16_mv_f16(reg, val as u16),
+            #[cfg(feature = "Q")]
+            Precision::Q => self.u128_mv_f128(reg, val),
+        }
+    }
+
+    /// raw register content as little endian integer
+    pub fn fpr_bits(&self, reg: u8) -> u128 {
+        let mut buf = [0u8; 16];
+        buf[..FPLEN].copy_from_slice(&self.fprs[reg as usize]);
+        u128::from_le_bytes(buf)
+    }
+
+    pub fn fp_cmp(&mut self, rs1: u8, rs2: u8, pr: Precision, op: FpCmpCond) -> u32 {
         pr_switch!(pr, cmp, self, rs1, rs2, op) as u32
     }
 
-    #[cfg(feature = "D")]
     pub fn fp_cvt_fp(&mut self, rd: u8, rs1: u8, from: Precision, to: Precision) {
         match (from, to) {
-            (Precision::S, Precision::D) => {
-                let val = f32::rd_fpr(self, rs1);
-                // TODO: check for error
-                f64::wr_fpr(self, rd, val as f64);
+            #[cfg(feature = "D")]
+            (Precision::S, Precision::D) if !self.soft() => {
+                // host keeps nan payload, risc-v doesn't
+                let val = (f32::rd_fpr(self, rs1) as f64).no_nan_box();
+                f64::wr_fpr(self, rd, val);
             }
-            (Precision::D, Precision::S) => {
-                let val = f64::rd_fpr(self, rs1);
-                // TODO: check for error
-                f32::wr_fpr(self, rd, val as f32);
+            #[cfg(feature = "D")]
+            (Precision::D, Precision::S) if !self.soft() => {
+                let src = f64::rd_fpr(self, rs1);
+                self.pre_tiny();
+                let (val, rm) = (src as f32, self.rm);
+                let val = self.post_tiny(val, |fpe| {
+                    soft::convert(BINARY64, BINARY32, src.to_u128(), rm, fpe)
+                });
+                f32::wr_fpr(self, rd, val.no_nan_box());
+            }
+            // formats host doesn't have are converted in software
+            _ => {
+                let val = self.rd_raw(rs1, from);
+                let mut fpe = FPE;
+                let res = soft::convert(Fmt::of(from), Fmt::of(to), val, self.rm, &mut fpe);
+                self.set_fpe(fpe);
+                self.wr_raw(rd, to, res);
             }
-            _ => panic!("bad uop"),
         }
     }
 
     // set round mode for dynamic rounding
     pub fn set_dyn_rm(&mut self, rm: RoundMode) {
-        self.dyn_rm = rm;
+        self.frm = rm.frm();
+    }
+
+    pub fn get_dyn_rm(&self) -> RoundMode {
+        RoundMode::from_frm(self.frm)
+    }
+
+    pub fn set_frm(&mut self, frm: u8) {
+        self.frm = frm & 7;
+    }
+
+    pub fn get_frm(&self) -> u8 {
+        self.frm
+    }
+
+    pub fn set_fs(&mut self, fs: FpState) {
+        self.fs = fs;
+    }
+
+    pub fn get_fs(&self) -> FpState {
+        self.fs
     }
 
     pub fn get_fpe(&mut self) -> FpExcept {
@@ -584,6 +970,21 @@ impl Fpu {
         self.fpe = Default::default();
     }
 
+    /// host fpu state is per host thread, shared by the harts running on it,
+    /// restore round mode and clear flags before a turn of this hart
+    pub fn enter_turn(&mut self) {
+        arch::clr_fpe();
+        if self.rm != RoundMode::None {
+            arch::set_rm(self.rm);
+        }
+    }
+
+    /// fold flags raised during the turn, leave none for the next hart
+    pub fn end_turn(&mut self) {
+        self.sync_fpe();
+        arch::clr_fpe();
+    }
+
     fn sync_fpe(&mut self) {
         self.fpe = self.fpe | arch::get_fpe();
     }
@@ -592,6 +993,12 @@ impl Fpu {
         self.rm = rm;
         arch::set_rm(rm);
     }
+
+    /// host fpu can't be used, either by choice, lack of support,
+    /// or a round mode it doesn't have
+    pub fn soft(&self) -> bool {
+        SOFT_BACKEND || self.rm == RoundMode::Rmm
+    }
 }
 
 impl<Xlen: XlenT> Hart<Xlen> {
@@ -603,10 +1010,11 @@ impl<Xlen: XlenT> Hart<Xlen> {
                 // subsequent attempt to execute a floating-point
                 // operation with a dynamic rounding mode will
                 // raise an illegal instruction exception
-                if self.fpu.dyn_rm == RoundMode::None {
+                let dyn_rm = self.fpu.get_dyn_rm();
+                if dyn_rm == RoundMode::None {
                     return self.raise(Exception::IllegalInstr);
-                } else if self.fpu.dyn_rm != self.fpu.rm {
-                    self.fpu.set_rm(self.fpu.dyn_rm);
+                } else if dyn_rm != self.fpu.rm {
+                    self.fpu.set_rm(dyn_rm);
                 }
             }
             // op with no round mode
@@ -616,4 +1024,107 @@ impl<Xlen: XlenT> Hart<Xlen> {
         }
         Ok(())
     }
+
+    /// fflags, frm and fcsr, inaccessible when fpu is off
+    pub fn fcsr_rd(&mut self, addr: u16) -> Maybe<Xlen> {
+        if self.fpu.get_fs() == FpState::Off || self.vs_fs_off() {
+            return Err(());
+        }
+        let fflags = self.fpu.get_fpe().as_u8();
+        let frm = self.fpu.get_frm();
+        Ok(Xlen::from(match addr {
+            FFLAGS => fflags,
+            FRM => frm,
+            _ => frm << 5 | fflags,
+        }))
+    }
+
+    pub fn fcsr_write(&mut self, addr: u16, val: Xlen) {
+        let val: u64 = val.into();
+        if addr != FRM {
+            // clears host flags too
+            let fflags = FpExcept::from_u8(val as u8 & 0x1f);
+            self.fpu.clr_fpe(!fflags);
+            self.fpu.set_fpe(fflags);
+        }
+        match addr {
+            FRM => self.fpu.set_frm(val as u8),
+            FCSR => self.fpu.set_frm((val >> 5) as u8),
+            _ => (),
+        }
+        self.fpu.set_fs(FpState::Dirty);
+        self.vs_fs_dirty();
+    }
+
+    /// Zfinx, fp values are held in gprs
+    pub fn fp_inx(&self) -> bool {
+        self.fe.isa.fp_inx()
+    }
+
+    /// Zfinx stages fp operands from gprs into the fpu,
+    /// narrower values are taken from the low bits
+    pub fn inx_rd(&mut self, regs: &[u8], pr: Precision) {
+        #[cfg(feature = "Zfinx")]
+        if self.fp_inx() {
+            for &reg in regs {
+                let val = if Fmt::of(pr).width() > self.xlen() {
+                    self.rd_gpr_pair(reg)
+                } else {
+                    self.rd_gpr(reg).into()
+                };
+                self.fpu.wr_raw(reg, pr, val);
+            }
+        }
+    }
+
+    /// Zfinx writes fp result back to gprs,
+    /// narrower values are sign extended
+    pub fn inx_wr(&mut self, rd: u8, pr: Precision) {
+        #[cfg(feature = "Zfinx")]
+        if self.fp_inx() {
+            let val = self.fpu.rd_raw(rd, pr);
+            let width = Fmt::of(pr).width();
+            if width > self.xlen() {
+                self.wr_gpr_pair(rd, val);
+            } else {
+                let shamt = 128 - width;
+                self.wr_gpr(rd, Xlen::from((val << shamt) as i128 >> shamt));
+            }
+        }
+    }
+
+    /// fp instructions are illegal when fpu is off,
+    /// `write` marks fpu state dirty
+    pub fn fp_enable(&mut self, write: bool) -> Maybe<()> {
+        if self.vs_fs_off() {
+            return self.raise(Exception::IllegalInstr);
+        }
+        match self.fpu.get_fs() {
+            FpState::Off => return self.raise(Exception::IllegalInstr),
+            _ if write => {
+                self.fpu.set_fs(FpState::Dirty);
+                self.vs_fs_dirty();
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// FS of vsstatus also turns the fpu off in VS / VU-mode
+    fn vs_fs_off(&self) -> bool {
+        #[cfg(feature = "H")]
+        if self.priv_ctrl.is_virt() && !self.fe.isa.fp_inx() {
+            return self.priv_ctrl.vsstatus & Xlen::from(MSTATUS_FS) == Xlen::from(0);
+        }
+        false
+    }
+
+    /// fp writes in VS / VU-mode also dirty vsstatus
+    fn vs_fs_dirty(&mut self) {
+        #[cfg(feature = "H")]
+        if self.priv_ctrl.is_virt() && !self.fe.isa.fp_inx() {
+            let p = &mut self.priv_ctrl;
+            p.vsstatus = p.vsstatus | Xlen::from(MSTATUS_FS);
+        }
+    }
 }
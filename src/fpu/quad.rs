@@ -0,0 +1,9 @@
+// IEEE 754 binary128 for Q, host has no native quad-precision
+
+use super::{soft::BINARY128, *};
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct F128(u128);
+
+soft_fp!(F128, u128, BINARY128);
@@ -0,0 +1,974 @@
+// software IEEE 754 binary floating-point
+//
+// operands are raw bits of any interchange format up to binary128,
+// every result is computed exactly and rounded once
+// with RISC-V nan and exception flag semantics
+
+use super::{FpExcept, FPE_DZ, FPE_NV, FPE_NX, FPE_OF, FPE_UF};
+use crate::uop::{FpBinaryOp, FpCmpCond, FpTernaryOp, Precision, RoundMode};
+use std::{
+    cmp::Ordering,
+    num::FpCategory,
+    ops::{Add, Shl, Shr, Sub},
+};
+
+/// interchange format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fmt {
+    /// exponent bits
+    pub exp: u32,
+    /// trailing significand bits
+    pub frac: u32,
+}
+
+pub const BINARY16: Fmt = Fmt { exp: 5, frac: 10 };
+pub const BINARY32: Fmt = Fmt { exp: 8, frac: 23 };
+pub const BINARY64: Fmt = Fmt { exp: 11, frac: 52 };
+pub const BINARY128: Fmt = Fmt { exp: 15, frac: 112 };
+
+impl Fmt {
+    pub const fn of(pr: Precision) -> Fmt {
+        match pr {
+            Precision::S => BINARY32,
+            #[cfg(feature = "D")]
+            Precision::D => BINARY64,
+            #[cfg(feature = "Zfhmin")]
+            Precision::H => BINARY16,
+            #[cfg(feature = "Q")]
+            Precision::Q => BINARY128,
+        }
+    }
+
+    const fn bias(self) -> i32 {
+        (1 << (self.exp - 1)) - 1
+    }
+
+    /// total bits of the format
+    pub const fn width(self) -> u32 {
+        1 + self.exp + self.frac
+    }
+
+    pub const fn sign(self) -> u128 {
+        1 << (self.exp + self.frac)
+    }
+
+    const fn exp_mask(self) -> u128 {
+        ((1 << self.exp) - 1) << self.frac
+    }
+
+    const fn frac_mask(self) -> u128 {
+        (1 << self.frac) - 1
+    }
+
+    pub const fn q_bit(self) -> u128 {
+        1 << (self.frac - 1)
+    }
+
+    pub const fn qnan(self) -> u128 {
+        self.exp_mask() | self.q_bit()
+    }
+
+    /// exponent of subnormal lsb
+    const fn min_exp(self) -> i32 {
+        1 - self.bias() - self.frac as i32
+    }
+
+    fn signed(self, neg: bool, bits: u128) -> u128 {
+        if neg {
+            bits | self.sign()
+        } else {
+            bits
+        }
+    }
+
+    pub fn is_neg(self, a: u128) -> bool {
+        a & self.sign() != 0
+    }
+
+    pub fn is_nan(self, a: u128) -> bool {
+        a & self.exp_mask() == self.exp_mask() && a & self.frac_mask() != 0
+    }
+
+    pub fn is_snan(self, a: u128) -> bool {
+        self.is_nan(a) && a & self.q_bit() == 0
+    }
+
+    pub fn is_inf(self, a: u128) -> bool {
+        a & !self.sign() == self.exp_mask()
+    }
+
+    pub fn is_zero(self, a: u128) -> bool {
+        a & !self.sign() == 0
+    }
+
+    pub fn classify(self, a: u128) -> FpCategory {
+        match (a & self.exp_mask(), a & self.frac_mask()) {
+            (0, 0) => FpCategory::Zero,
+            (0, _) => FpCategory::Subnormal,
+            (e, 0) if e == self.exp_mask() => FpCategory::Infinite,
+            (e, _) if e == self.exp_mask() => FpCategory::Nan,
+            _ => FpCategory::Normal,
+        }
+    }
+
+    /// finite value as (sign, significand, exponent)
+    fn unpack(self, a: u128) -> (bool, u128, i32) {
+        let biased = ((a & self.exp_mask()) >> self.frac) as i32;
+        let frac = a & self.frac_mask();
+        if biased == 0 {
+            (self.is_neg(a), frac, self.min_exp())
+        } else {
+            let sig = frac | 1 << self.frac;
+            (self.is_neg(a), sig, biased + self.min_exp() - 1)
+        }
+    }
+}
+
+/// unsigned 256-bit integer for exact intermediate results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+impl U256 {
+    fn from(val: u128) -> Self {
+        Self { hi: 0, lo: val }
+    }
+
+    fn is_zero(self) -> bool {
+        self.hi == 0 && self.lo == 0
+    }
+
+    fn bits(self) -> u32 {
+        if self.hi != 0 {
+            256 - self.hi.leading_zeros()
+        } else {
+            128 - self.lo.leading_zeros()
+        }
+    }
+
+    fn mul(lhs: u128, rhs: u128) -> Self {
+        const M: u128 = u64::MAX as u128;
+        let (l1, l0) = (lhs >> 64, lhs & M);
+        let (r1, r0) = (rhs >> 64, rhs & M);
+        let p00 = l0 * r0;
+        let p01 = l0 * r1;
+        let p10 = l1 * r0;
+        let mid = (p00 >> 64) + (p01 & M) + (p10 & M);
+        Self {
+            hi: l1 * r1 + (p01 >> 64) + (p10 >> 64) + (mid >> 64),
+            lo: (p00 & M) | mid << 64,
+        }
+    }
+
+    /// shift right, bits shifted out are or-ed into lsb
+    fn shr_jam(self, n: u32) -> Self {
+        if n >= 256 {
+            return Self::from(!self.is_zero() as u128);
+        }
+        let res = self >> n;
+        let lost = res << n != self;
+        Self {
+            lo: res.lo | lost as u128,
+            ..res
+        }
+    }
+
+    /// shift so `bits` is `width`, bits shifted out are or-ed into lsb
+    fn normalize(self, exp: i32, width: u32) -> (Self, i32) {
+        let bits = self.bits();
+        if bits > width {
+            (self.shr_jam(bits - width), exp + (bits - width) as i32)
+        } else {
+            (self << (width - bits), exp - (width - bits) as i32)
+        }
+    }
+}
+
+impl Add for U256 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        let (lo, carry) = self.lo.overflowing_add(rhs.lo);
+        Self {
+            hi: self.hi + rhs.hi + carry as u128,
+            lo,
+        }
+    }
+}
+
+impl Sub for U256 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        let (lo, borrow) = self.lo.overflowing_sub(rhs.lo);
+        Self {
+            hi: self.hi - rhs.hi - borrow as u128,
+            lo,
+        }
+    }
+}
+
+impl Shl<u32> for U256 {
+    type Output = Self;
+    fn shl(self, n: u32) -> Self {
+        match n {
+            0 => self,
+            1..=127 => Self {
+                hi: self.hi << n | self.lo >> (128 - n),
+                lo: self.lo << n,
+            },
+            128..=255 => Self {
+                hi: self.lo << (n - 128),
+                lo: 0,
+            },
+            _ => Self::from(0),
+        }
+    }
+}
+
+impl Shr<u32> for U256 {
+    type Output = Self;
+    fn shr(self, n: u32) -> Self {
+        match n {
+            0 => self,
+            1..=127 => Self {
+                hi: self.hi >> n,
+                lo: self.lo >> n | self.hi << (128 - n),
+            },
+            128..=255 => Self {
+                hi: 0,
+                lo: self.hi >> (n - 128),
+            },
+            _ => Self::from(0),
+        }
+    }
+}
+
+/// `sig` >> `shift` rounded by `rm`, and if inexact,
+/// `sig` is at most 126 bits
+fn round_sig(neg: bool, sig: u128, shift: i32, rm: RoundMode) -> (u128, bool) {
+    if shift <= 0 {
+        return (sig << -shift, false);
+    }
+    if shift > 126 {
+        // nonzero but below half ulp
+        return round_sig(neg, (sig != 0) as u128, 2, rm);
+    }
+    let q = sig >> shift;
+    let rem = sig & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let inexact = rem != 0;
+    let up = match rm {
+        RoundMode::Rtz => false,
+        RoundMode::Rdn => inexact && neg,
+        RoundMode::Rup => inexact && !neg,
+        RoundMode::Rmm => rem >= half,
+        _ => rem > half || (rem == half && q & 1 == 1),
+    };
+    (q + up as u128, inexact)
+}
+
+/// round (-1 ^ `neg`) * `sig` * 2 ^ `exp` to `fmt`,
+/// `sig` is nonzero and at most 126 bits, its lsb may be sticky
+fn round_pack(fmt: Fmt, neg: bool, sig: u128, exp: i32, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    let frac = fmt.frac as i32;
+    // exponent of msb
+    let e = 127 - sig.leading_zeros() as i32 + exp;
+    let lsb = (e - frac).max(fmt.min_exp());
+    let (q, inexact) = round_sig(neg, sig, lsb - exp, rm);
+    // carry out of significand bumps the exponent field naturally
+    let field = (lsb - fmt.min_exp()) as u128;
+    let bits = if field < (1 << fmt.exp) - 1 {
+        (field << fmt.frac) + q
+    } else {
+        fmt.exp_mask()
+    };
+    if bits >= fmt.exp_mask() {
+        *fpe = *fpe | FPE_OF | FPE_NX;
+        let inf = match rm {
+            RoundMode::Rtz => false,
+            RoundMode::Rdn => neg,
+            RoundMode::Rup => !neg,
+            _ => true,
+        };
+        let bits = if inf {
+            fmt.exp_mask()
+        } else {
+            fmt.exp_mask() - 1
+        };
+        return fmt.signed(neg, bits);
+    }
+    if inexact {
+        *fpe = *fpe | FPE_NX;
+        // tininess is detected after rounding
+        let emin = 1 - fmt.bias();
+        let tiny = e < emin - 1
+            || e == emin - 1 && round_sig(neg, sig, e - frac - exp, rm).0 < 1 << (frac + 1);
+        if tiny {
+            *fpe = *fpe | FPE_UF;
+        }
+    }
+    fmt.signed(neg, bits)
+}
+
+/// `round_pack` for any width of `sig`
+fn round_wide(fmt: Fmt, neg: bool, sig: U256, exp: i32, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    let (sig, exp) = if sig.bits() > 126 {
+        sig.normalize(exp, 126)
+    } else {
+        (sig, exp)
+    };
+    round_pack(fmt, neg, sig.lo, exp, rm, fpe)
+}
+
+/// canonical nan, invalid if any operand is signaling nan
+fn nan_of(fmt: Fmt, ops: &[u128], fpe: &mut FpExcept) -> u128 {
+    if ops.iter().any(|&v| fmt.is_snan(v)) {
+        *fpe = *fpe | FPE_NV;
+    }
+    fmt.qnan()
+}
+
+fn invalid(fmt: Fmt, fpe: &mut FpExcept) -> u128 {
+    *fpe = *fpe | FPE_NV;
+    fmt.qnan()
+}
+
+/// round sum of two finite values, (sign, significand, exponent)
+fn add_finite(
+    fmt: Fmt,
+    a: (bool, U256, i32),
+    b: (bool, U256, i32),
+    rm: RoundMode,
+    fpe: &mut FpExcept,
+) -> u128 {
+    let (na, sa, ea) = a;
+    let (nb, sb, eb) = b;
+    match (sa.is_zero(), sb.is_zero()) {
+        (true, true) => {
+            // exact zero is +0 unless both are -0, or rounding down
+            let neg = if na == nb { na } else { rm == RoundMode::Rdn };
+            return fmt.signed(neg, 0);
+        }
+        (true, false) => return round_wide(fmt, nb, sb, eb, rm, fpe),
+        (false, true) => return round_wide(fmt, na, sa, ea, rm, fpe),
+        _ => (),
+    }
+    // leave room for carry
+    let (sa, ea) = sa.normalize(ea, 254);
+    let (sb, eb) = sb.normalize(eb, 254);
+    let ((na, sa, ea), (nb, sb, eb)) = if ea >= eb {
+        ((na, sa, ea), (nb, sb, eb))
+    } else {
+        ((nb, sb, eb), (na, sa, ea))
+    };
+    let sb = sb.shr_jam((ea - eb) as u32);
+    let (neg, sig) = if na == nb {
+        (na, sa + sb)
+    } else if sa >= sb {
+        (na, sa - sb)
+    } else {
+        (nb, sb - sa)
+    };
+    if sig.is_zero() {
+        return fmt.signed(rm == RoundMode::Rdn, 0);
+    }
+    round_wide(fmt, neg, sig, ea, rm, fpe)
+}
+
+fn wide(fmt: Fmt, a: u128) -> (bool, U256, i32) {
+    let (neg, sig, exp) = fmt.unpack(a);
+    (neg, U256::from(sig), exp)
+}
+
+pub fn add(fmt: Fmt, a: u128, b: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return nan_of(fmt, &[a, b], fpe);
+    }
+    match (fmt.is_inf(a), fmt.is_inf(b)) {
+        (true, true) if fmt.is_neg(a) != fmt.is_neg(b) => invalid(fmt, fpe),
+        (true, _) => a,
+        (_, true) => b,
+        _ => add_finite(fmt, wide(fmt, a), wide(fmt, b), rm, fpe),
+    }
+}
+
+pub fn sub(fmt: Fmt, a: u128, b: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    add(fmt, a, b ^ fmt.sign(), rm, fpe)
+}
+
+pub fn mul(fmt: Fmt, a: u128, b: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return nan_of(fmt, &[a, b], fpe);
+    }
+    let neg = fmt.is_neg(a) != fmt.is_neg(b);
+    if fmt.is_inf(a) || fmt.is_inf(b) {
+        if fmt.is_zero(a) || fmt.is_zero(b) {
+            return invalid(fmt, fpe);
+        }
+        return fmt.signed(neg, fmt.exp_mask());
+    }
+    if fmt.is_zero(a) || fmt.is_zero(b) {
+        return fmt.signed(neg, 0);
+    }
+    let (_, sa, ea) = fmt.unpack(a);
+    let (_, sb, eb) = fmt.unpack(b);
+    round_wide(fmt, neg, U256::mul(sa, sb), ea + eb, rm, fpe)
+}
+
+pub fn div(fmt: Fmt, a: u128, b: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return nan_of(fmt, &[a, b], fpe);
+    }
+    let neg = fmt.is_neg(a) != fmt.is_neg(b);
+    match (fmt.is_inf(a), fmt.is_inf(b)) {
+        (true, true) => return invalid(fmt, fpe),
+        (true, false) => return fmt.signed(neg, fmt.exp_mask()),
+        (false, true) => return fmt.signed(neg, 0),
+        _ => (),
+    }
+    match (fmt.is_zero(a), fmt.is_zero(b)) {
+        (true, true) => return invalid(fmt, fpe),
+        (true, false) => return fmt.signed(neg, 0),
+        (false, true) => {
+            *fpe = *fpe | FPE_DZ;
+            return fmt.signed(neg, fmt.exp_mask());
+        }
+        _ => (),
+    }
+    let (_, sa, ea) = fmt.unpack(a);
+    let (_, sb, eb) = fmt.unpack(b);
+    // align msb to bit 112, dividend not less than divisor
+    let (mut r, mut ea) = (
+        sa << sa.leading_zeros() >> 15,
+        ea + 15 - sa.leading_zeros() as i32,
+    );
+    let (sb, eb) = (
+        sb << sb.leading_zeros() >> 15,
+        eb + 15 - sb.leading_zeros() as i32,
+    );
+    if r < sb {
+        r <<= 1;
+        ea -= 1;
+    }
+    // restoring division, one quotient bit a step
+    let mut q = 0u128;
+    for _ in 0..116 {
+        q <<= 1;
+        if r >= sb {
+            r -= sb;
+            q |= 1;
+        }
+        r <<= 1;
+    }
+    round_pack(fmt, neg, q | (r != 0) as u128, ea - eb - 115, rm, fpe)
+}
+
+pub fn sqrt(fmt: Fmt, a: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_nan(a) {
+        return nan_of(fmt, &[a], fpe);
+    }
+    if fmt.is_zero(a) {
+        return a;
+    }
+    if fmt.is_neg(a) {
+        return invalid(fmt, fpe);
+    }
+    if fmt.is_inf(a) {
+        return a;
+    }
+    let (_, sig, exp) = fmt.unpack(a);
+    // align msb to bit 112 or 113 for even exponent
+    let (mut sig, mut exp) = (
+        sig << sig.leading_zeros() >> 15,
+        exp + 15 - sig.leading_zeros() as i32,
+    );
+    if exp & 1 != 0 {
+        sig <<= 1;
+        exp -= 1;
+    }
+    // digit by digit on sig * 2 ^ 120, two radicand bits a step
+    let (mut rem, mut root) = (0u128, 0u128);
+    for i in (0..117).rev() {
+        let pos = 2 * i - 120;
+        let pair = if pos >= 0 { (sig >> pos) & 3 } else { 0 };
+        rem = rem << 2 | pair;
+        let trial = root << 2 | 1;
+        if rem >= trial {
+            rem -= trial;
+            root = root << 1 | 1;
+        } else {
+            root <<= 1;
+        }
+    }
+    round_pack(
+        fmt,
+        false,
+        root | (rem != 0) as u128,
+        (exp - 120) / 2,
+        rm,
+        fpe,
+    )
+}
+
+/// `a` * `b` + `c` with single rounding
+pub fn fma(fmt: Fmt, a: u128, b: u128, c: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    let inf_zero = (fmt.is_inf(a) && fmt.is_zero(b)) || (fmt.is_zero(a) && fmt.is_inf(b));
+    if fmt.is_nan(a) || fmt.is_nan(b) || fmt.is_nan(c) {
+        // 0 * inf is invalid even with a quiet nan addend
+        if inf_zero {
+            *fpe = *fpe | FPE_NV;
+        }
+        return nan_of(fmt, &[a, b, c], fpe);
+    }
+    if inf_zero {
+        return invalid(fmt, fpe);
+    }
+    let np = fmt.is_neg(a) != fmt.is_neg(b);
+    if fmt.is_inf(a) || fmt.is_inf(b) {
+        if fmt.is_inf(c) && fmt.is_neg(c) != np {
+            return invalid(fmt, fpe);
+        }
+        return fmt.signed(np, fmt.exp_mask());
+    }
+    if fmt.is_inf(c) {
+        return c;
+    }
+    let (_, sa, ea) = fmt.unpack(a);
+    let (_, sb, eb) = fmt.unpack(b);
+    let prod = (np, U256::mul(sa, sb), ea + eb);
+    add_finite(fmt, prod, wide(fmt, c), rm, fpe)
+}
+
+/// minimumNumber / maximumNumber, -0 is less than +0
+pub fn min_max(fmt: Fmt, a: u128, b: u128, max: bool, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_snan(a) || fmt.is_snan(b) {
+        *fpe = *fpe | FPE_NV;
+    }
+    match (fmt.is_nan(a), fmt.is_nan(b)) {
+        (true, true) => fmt.qnan(),
+        (true, false) => b,
+        (false, true) => a,
+        _ => {
+            let lt = match compare(fmt, a, b) {
+                Some(Ordering::Equal) => fmt.is_neg(a),
+                ord => ord == Some(Ordering::Less),
+            };
+            if lt != max {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+/// minimum / maximum, nan if any operand is nan
+#[cfg(feature = "Zfa")]
+pub fn min_max_nan(fmt: Fmt, a: u128, b: u128, max: bool, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return nan_of(fmt, &[a, b], fpe);
+    }
+    min_max(fmt, a, b, max, fpe)
+}
+
+/// quiet comparison, none if unordered
+pub fn compare(fmt: Fmt, a: u128, b: u128) -> Option<Ordering> {
+    if fmt.is_nan(a) || fmt.is_nan(b) {
+        return None;
+    }
+    if fmt.is_zero(a) && fmt.is_zero(b) {
+        return Some(Ordering::Equal);
+    }
+    // sign magnitude ordering
+    let key = |v: u128| {
+        let mag = (v & !fmt.sign()) as i128;
+        if fmt.is_neg(v) {
+            -mag
+        } else {
+            mag
+        }
+    };
+    Some(key(a).cmp(&key(b)))
+}
+
+/// feq, fltq and fleq are quiet, flt and fle are signaling
+pub fn cmp(fmt: Fmt, a: u128, b: u128, op: FpCmpCond, fpe: &mut FpExcept) -> bool {
+    let ord = compare(fmt, a, b);
+    let signaling = match op {
+        FpCmpCond::Lt | FpCmpCond::Le => ord.is_none(),
+        _ => fmt.is_snan(a) || fmt.is_snan(b),
+    };
+    if signaling {
+        *fpe = *fpe | FPE_NV;
+    }
+    match op {
+        FpCmpCond::Eq => ord == Some(Ordering::Equal),
+        FpCmpCond::Lt => ord == Some(Ordering::Less),
+        FpCmpCond::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+        #[cfg(feature = "Zfa")]
+        FpCmpCond::LtQ => ord == Some(Ordering::Less),
+        #[cfg(feature = "Zfa")]
+        FpCmpCond::LeQ => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+pub fn binary(
+    fmt: Fmt,
+    a: u128,
+    b: u128,
+    op: FpBinaryOp,
+    rm: RoundMode,
+    fpe: &mut FpExcept,
+) -> u128 {
+    let sign = fmt.sign();
+    match op {
+        FpBinaryOp::Add => add(fmt, a, b, rm, fpe),
+        FpBinaryOp::Sub => sub(fmt, a, b, rm, fpe),
+        FpBinaryOp::Mul => mul(fmt, a, b, rm, fpe),
+        FpBinaryOp::Div => div(fmt, a, b, rm, fpe),
+        FpBinaryOp::SgnJ => (a & !sign) | (b & sign),
+        FpBinaryOp::SgnJN => (a & !sign) | (!b & sign),
+        FpBinaryOp::SgnJX => a ^ (b & sign),
+        FpBinaryOp::Min => min_max(fmt, a, b, false, fpe),
+        FpBinaryOp::Max => min_max(fmt, a, b, true, fpe),
+        #[cfg(feature = "Zfa")]
+        FpBinaryOp::MinM => min_max_nan(fmt, a, b, false, fpe),
+        #[cfg(feature = "Zfa")]
+        FpBinaryOp::MaxM => min_max_nan(fmt, a, b, true, fpe),
+    }
+}
+
+pub fn ternary(
+    fmt: Fmt,
+    a: u128,
+    b: u128,
+    c: u128,
+    op: FpTernaryOp,
+    rm: RoundMode,
+    fpe: &mut FpExcept,
+) -> u128 {
+    let sign = fmt.sign();
+    match op {
+        FpTernaryOp::MAdd => fma(fmt, a, b, c, rm, fpe),
+        FpTernaryOp::MSub => fma(fmt, a, b, c ^ sign, rm, fpe),
+        FpTernaryOp::NMSub => fma(fmt, a, b ^ sign, c, rm, fpe),
+        FpTernaryOp::NMAdd => fma(fmt, a, b ^ sign, c ^ sign, rm, fpe),
+    }
+}
+
+/// fclass result
+pub fn class(fmt: Fmt, a: u128) -> u32 {
+    let neg = fmt.is_neg(a);
+    match fmt.classify(a) {
+        FpCategory::Infinite => 1 << if neg { 0 } else { 7 },
+        FpCategory::Normal => 1 << if neg { 1 } else { 6 },
+        FpCategory::Subnormal => 1 << if neg { 2 } else { 5 },
+        FpCategory::Zero => 1 << if neg { 3 } else { 4 },
+        FpCategory::Nan => 1 << if fmt.is_snan(a) { 8 } else { 9 },
+    }
+}
+
+/// conversion between formats
+pub fn convert(from: Fmt, to: Fmt, a: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    let neg = from.is_neg(a);
+    if from.is_nan(a) {
+        nan_of(from, &[a], fpe);
+        return to.qnan();
+    }
+    if from.is_inf(a) {
+        return to.signed(neg, to.exp_mask());
+    }
+    if from.is_zero(a) {
+        return to.signed(neg, 0);
+    }
+    let (_, sig, exp) = from.unpack(a);
+    round_pack(to, neg, sig, exp, rm, fpe)
+}
+
+/// round to integer of `width` bits, out of range saturates and is invalid
+pub fn to_int(
+    fmt: Fmt,
+    a: u128,
+    rm: RoundMode,
+    width: u32,
+    signed: bool,
+    fpe: &mut FpExcept,
+) -> i128 {
+    let (min, max) = if signed {
+        (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+    } else {
+        (0, (1i128 << width) - 1)
+    };
+    if fmt.is_nan(a) {
+        *fpe = *fpe | FPE_NV;
+        return max;
+    }
+    let (neg, sig, exp) = fmt.unpack(a);
+    let sat = if neg { min } else { max };
+    // 2 ^ 65 or more is out of range for sure
+    if fmt.is_inf(a) || 128 - sig.leading_zeros() as i32 + exp > 65 {
+        *fpe = *fpe | FPE_NV;
+        return sat;
+    }
+    let (q, inexact) = round_sig(neg, sig, -exp, rm);
+    let val = if neg { -(q as i128) } else { q as i128 };
+    if val < min || val > max {
+        *fpe = *fpe | FPE_NV;
+        return sat;
+    }
+    if inexact {
+        *fpe = *fpe | FPE_NX;
+    }
+    val
+}
+
+/// truncate to integer modulo 2 ^ 32,
+/// invalid if out of 32-bit signed range
+#[cfg(feature = "Zfa")]
+pub fn to_int_mod(fmt: Fmt, a: u128, fpe: &mut FpExcept) -> i32 {
+    if fmt.is_nan(a) || fmt.is_inf(a) {
+        *fpe = *fpe | FPE_NV;
+        return 0;
+    }
+    let (neg, sig, exp) = fmt.unpack(a);
+    // low 64 bits of integer magnitude, and if it is any wider
+    let (mag, inexact, wide) = if exp >= 0 {
+        let wide = 128 - sig.leading_zeros() as i32 + exp > 64;
+        let mag = if exp >= 64 { 0 } else { (sig as u64) << exp };
+        (mag, false, wide)
+    } else {
+        let (q, inexact) = round_sig(neg, sig, -exp, RoundMode::Rtz);
+        (q as u64, inexact, q >> 64 != 0)
+    };
+    if wide || mag > i32::MAX as u64 + neg as u64 {
+        *fpe = *fpe | FPE_NV;
+    } else if inexact {
+        *fpe = *fpe | FPE_NX;
+    }
+    let val = if neg {
+        (mag as u32).wrapping_neg()
+    } else {
+        mag as u32
+    };
+    val as i32
+}
+
+/// round to integral value in `fmt`, inexact is raised if `exact`
+#[cfg(feature = "Zfa")]
+pub fn round_int(fmt: Fmt, a: u128, rm: RoundMode, exact: bool, fpe: &mut FpExcept) -> u128 {
+    if fmt.is_nan(a) {
+        return nan_of(fmt, &[a], fpe);
+    }
+    if fmt.is_inf(a) || fmt.is_zero(a) {
+        return a;
+    }
+    let (neg, sig, exp) = fmt.unpack(a);
+    // already integral
+    if exp >= 0 {
+        return a;
+    }
+    let (q, inexact) = round_sig(neg, sig, -exp, rm);
+    if inexact && exact {
+        *fpe = *fpe | FPE_NX;
+    }
+    if q == 0 {
+        return fmt.signed(neg, 0);
+    }
+    // representable, won't raise
+    round_pack(fmt, neg, q, 0, rm, fpe)
+}
+
+/// integer (-1 ^ `neg`) * `mag` to `fmt`
+pub fn from_int(fmt: Fmt, neg: bool, mag: u128, rm: RoundMode, fpe: &mut FpExcept) -> u128 {
+    if mag == 0 {
+        return 0;
+    }
+    round_wide(fmt, neg, U256::from(mag), 0, rm, fpe)
+}
+
+/// implement `FpOp` and std operators on top of the software routines,
+/// std operators round to nearest even and discard exceptions
+/// (type, bit representation, format)
+macro_rules! soft_fp {
+    ($t:ident, $bits:ty, $fmt:expr) => {
+        impl $t {
+            fn raw(self) -> u128 {
+                self.0 as u128
+            }
+
+            fn from_raw(val: u128) -> Self {
+                $t(val as $bits)
+            }
+
+            pub fn from_le_bytes(bytes: [u8; size_of::<$bits>()]) -> Self {
+                $t(<$bits>::from_le_bytes(bytes))
+            }
+
+            pub fn to_le_bytes(self) -> [u8; size_of::<$bits>()] {
+                self.0.to_le_bytes()
+            }
+        }
+
+        impl PartialEq for $t {
+            fn eq(&self, other: &Self) -> bool {
+                self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+            }
+        }
+
+        impl PartialOrd for $t {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                soft::compare($fmt, self.raw(), other.raw())
+            }
+        }
+
+        impl Neg for $t {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self::from_raw(self.raw() ^ $fmt.sign())
+            }
+        }
+
+        impl Add for $t {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::add(
+                    $fmt,
+                    self.raw(),
+                    rhs.raw(),
+                    RoundMode::Rne,
+                    &mut fpe,
+                ))
+            }
+        }
+
+        impl Sub for $t {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::sub(
+                    $fmt,
+                    self.raw(),
+                    rhs.raw(),
+                    RoundMode::Rne,
+                    &mut fpe,
+                ))
+            }
+        }
+
+        impl Mul for $t {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::mul(
+                    $fmt,
+                    self.raw(),
+                    rhs.raw(),
+                    RoundMode::Rne,
+                    &mut fpe,
+                ))
+            }
+        }
+
+        impl Div for $t {
+            type Output = Self;
+            fn div(self, rhs: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::div(
+                    $fmt,
+                    self.raw(),
+                    rhs.raw(),
+                    RoundMode::Rne,
+                    &mut fpe,
+                ))
+            }
+        }
+
+        impl FpOp for $t {
+            type BitRep = $bits;
+            const QNAN_BITS: $bits = $fmt.qnan() as $bits;
+            const Q_BIT: $bits = $fmt.q_bit() as $bits;
+            const FMT: Fmt = $fmt;
+            const SOFT: bool = true;
+
+            fn rd_fpr(fpu: &Fpu, reg: u8) -> Self {
+                let fpr = fpu.fprs[reg as usize];
+                read_fpr_as!(fpr, $t)
+            }
+
+            fn wr_fpr(fpu: &mut Fpu, reg: u8, val: Self) {
+                let fpr = &mut fpu.fprs[reg as usize];
+                write_fpr_as!(fpr, $t, val);
+            }
+
+            fn is_quiet(self) -> bool {
+                self.0 & Self::Q_BIT != 0
+            }
+
+            fn is_qnan_safe(self) -> bool {
+                self.0 & Self::QNAN_BITS == Self::QNAN_BITS
+            }
+
+            fn is_zero(self) -> bool {
+                $fmt.is_zero(self.raw())
+            }
+
+            fn from_u128(val: u128) -> Self {
+                Self::from_raw(val)
+            }
+
+            fn mul_add(self, a: Self, b: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::fma(
+                    $fmt,
+                    self.raw(),
+                    a.raw(),
+                    b.raw(),
+                    RoundMode::Rne,
+                    &mut fpe,
+                ))
+            }
+
+            fn sqrt(self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::sqrt($fmt, self.raw(), RoundMode::Rne, &mut fpe))
+            }
+
+            fn copysign(self, rhs: Self) -> Self {
+                let sign = $fmt.sign();
+                Self::from_raw((self.raw() & !sign) | (rhs.raw() & sign))
+            }
+
+            fn max(self, rhs: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::min_max($fmt, self.raw(), rhs.raw(), true, &mut fpe))
+            }
+
+            fn min(self, rhs: Self) -> Self {
+                let mut fpe = FPE;
+                Self::from_raw(soft::min_max($fmt, self.raw(), rhs.raw(), false, &mut fpe))
+            }
+
+            fn is_neg(self) -> bool {
+                $fmt.is_neg(self.raw())
+            }
+
+            fn classify(self) -> FpCategory {
+                $fmt.classify(self.raw())
+            }
+
+            fn is_nan(self) -> bool {
+                $fmt.is_nan(self.raw())
+            }
+
+            fn from_bits(val: $bits) -> Self {
+                $t(val)
+            }
+
+            fn to_bits(self) -> $bits {
+                self.0
+            }
+        }
+    };
+}
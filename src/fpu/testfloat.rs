@@ -0,0 +1,558 @@
+// testfloat style conformance suite
+//
+// operands come from a generator instead of hand-picked tables:
+// every sign and exponent boundary combined with edge significands,
+// plus a deterministic random stream. each operation goes through
+// `Fpu` the same way instructions do, result bits and flags are
+// compared against the bit-exact `soft` routines for every precision
+// and every rounding mode
+//
+// `soft` can't vouch for itself where the host has no equivalent,
+// half, quad and rmm are also checked against testgen/vectors.txt,
+// rounded from exact rationals by testgen/refgen.py
+
+use super::*;
+
+const RMS: [RoundMode; 5] = [
+    RoundMode::Rne,
+    RoundMode::Rtz,
+    RoundMode::Rdn,
+    RoundMode::Rup,
+    RoundMode::Rmm,
+];
+
+const PRECISIONS: &[Precision] = &[
+    Precision::S,
+    #[cfg(feature = "D")]
+    Precision::D,
+    #[cfg(feature = "Zfhmin")]
+    Precision::H,
+    #[cfg(feature = "Q")]
+    Precision::Q,
+];
+
+/// random operands per case on top of the edge values
+const N_RAND: usize = 256;
+/// mismatches reported before giving up
+const MAX_ERRS: usize = 16;
+
+/// xorshift64*, same stream on every run
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    fn next_u128(&mut self) -> u128 {
+        (self.next() as u128) << 64 | self.next() as u128
+    }
+
+    /// random value of `fmt`, biased towards exponents near 1.0
+    /// so operands interact and results need rounding
+    fn fp(&mut self, fmt: Fmt) -> u128 {
+        let bits = self.next_u128();
+        let frac = bits & ((1 << fmt.frac) - 1);
+        let sign = bits >> 127;
+        let emax = (1u128 << fmt.exp) - 1;
+        let exp = match self.next() % 4 {
+            // anything, including nan and inf
+            0 => (bits >> fmt.frac) & emax,
+            // subnormal range
+            1 => self.next() as u128 % 3,
+            _ => (emax >> 1) - 8 + self.next() as u128 % 16,
+        };
+        sign << (fmt.width() - 1) | exp << fmt.frac | frac
+    }
+}
+
+/// edge values of `fmt`, sign x exponent x significand
+fn edges(fmt: Fmt) -> Vec<u128> {
+    let emax = (1u128 << fmt.exp) - 1;
+    let bias = emax >> 1;
+    let frac = fmt.frac as u128;
+    let fmask = (1u128 << frac) - 1;
+    let exps = [
+        0,
+        1,
+        2,
+        bias - frac,
+        bias - 1,
+        bias,
+        bias + 1,
+        bias + frac,
+        emax - 1,
+        emax,
+    ];
+    let sigs = [0, 1, fmt.q_bit(), fmt.q_bit() | 1, fmask];
+    let mut vals = Vec::new();
+    for sign in [0, fmt.sign()] {
+        for exp in exps {
+            for sig in sigs {
+                vals.push(sign | exp << frac | sig);
+            }
+        }
+    }
+    vals
+}
+
+/// a smaller set for three operands
+fn edges_small(fmt: Fmt) -> Vec<u128> {
+    let emax = (1u128 << fmt.exp) - 1;
+    let bias = emax >> 1;
+    let frac = fmt.frac as u128;
+    let fmask = (1u128 << frac) - 1;
+    let mut vals = Vec::new();
+    for sign in [0, fmt.sign()] {
+        for exp in [0, 1, bias, emax - 1, emax] {
+            for sig in [0, 1, fmask] {
+                vals.push(sign | exp << frac | sig);
+            }
+        }
+    }
+    // signaling and quiet nan
+    vals.push(emax << frac | 1);
+    vals.push(fmt.qnan());
+    vals
+}
+
+fn rd_bits(fpu: &mut Fpu, pr: Precision) -> (u128, u8) {
+    (fpu.rd_raw(0, pr), fpu.get_fpe().as_u8())
+}
+
+/// collect mismatches, panic with the first few at the end
+#[derive(Default)]
+struct Report {
+    errs: Vec<String>,
+    cases: usize,
+}
+
+impl Report {
+    fn check<T: PartialEq + std::fmt::Debug>(
+        &mut self,
+        what: impl FnOnce() -> String,
+        exp: (T, u8),
+        res: (T, u8),
+    ) {
+        self.cases += 1;
+        if exp != res && self.errs.len() < MAX_ERRS {
+            self.errs.push(format!(
+                "{}\n  expected: {:#x?} flags {:#04x}\n  result:   {:#x?} flags {:#04x}",
+                what(),
+                exp.0,
+                exp.1,
+                res.0,
+                res.1
+            ));
+        }
+    }
+
+    fn finish(self, name: &str) {
+        if !self.errs.is_empty() {
+            panic!(
+                "{}: mismatches in {} cases\n{}",
+                name,
+                self.cases,
+                self.errs.join("\n")
+            );
+        }
+    }
+}
+
+#[test]
+fn tf_binary() {
+    let ops = [
+        FpBinaryOp::Add,
+        FpBinaryOp::Sub,
+        FpBinaryOp::Mul,
+        FpBinaryOp::Div,
+        FpBinaryOp::SgnJ,
+        FpBinaryOp::SgnJN,
+        FpBinaryOp::SgnJX,
+        FpBinaryOp::Min,
+        FpBinaryOp::Max,
+        #[cfg(feature = "Zfa")]
+        FpBinaryOp::MinM,
+        #[cfg(feature = "Zfa")]
+        FpBinaryOp::MaxM,
+    ];
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    let mut report = Report::default();
+    for &pr in PRECISIONS {
+        let fmt = Fmt::of(pr);
+        let vals = edges(fmt);
+        let mut pairs: Vec<_> = vals
+            .iter()
+            .flat_map(|&a| vals.iter().map(move |&b| (a, b)))
+            .collect();
+        pairs.extend((0..N_RAND).map(|_| (rng.fp(fmt), rng.fp(fmt))));
+        for op in ops {
+            for rm in RMS {
+                fpu.set_rm(rm);
+                for &(a, b) in &pairs {
+                    let mut fpe = FPE;
+                    let exp = soft::binary(fmt, a, b, op, rm, &mut fpe);
+                    fpu.wr_raw(1, pr, a);
+                    fpu.wr_raw(2, pr, b);
+                    fpu.clr_all_fpe();
+                    fpu.binary_op(0, 1, 2, pr, op);
+                    report.check(
+                        || format!("{:?} {:?} {:?} {:#x} {:#x}", op, pr, rm, a, b),
+                        (exp, fpe.as_u8()),
+                        rd_bits(&mut fpu, pr),
+                    );
+                }
+            }
+        }
+    }
+    report.finish("tf_binary");
+}
+
+#[test]
+fn tf_ternary() {
+    let ops = [
+        FpTernaryOp::MAdd,
+        FpTernaryOp::MSub,
+        FpTernaryOp::NMSub,
+        FpTernaryOp::NMAdd,
+    ];
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0xd1b54a32d192ed03);
+    let mut report = Report::default();
+    for &pr in PRECISIONS {
+        let fmt = Fmt::of(pr);
+        let vals = edges_small(fmt);
+        let mut triples = Vec::new();
+        for &a in &vals {
+            for &b in &vals {
+                for &c in &vals {
+                    triples.push((a, b, c));
+                }
+            }
+        }
+        triples.extend((0..N_RAND).map(|_| (rng.fp(fmt), rng.fp(fmt), rng.fp(fmt))));
+        for op in ops {
+            for rm in RMS {
+                fpu.set_rm(rm);
+                for &(a, b, c) in &triples {
+                    let mut fpe = FPE;
+                    let exp = soft::ternary(fmt, a, b, c, op, rm, &mut fpe);
+                    fpu.wr_raw(1, pr, a);
+                    fpu.wr_raw(2, pr, b);
+                    fpu.wr_raw(3, pr, c);
+                    fpu.clr_all_fpe();
+                    fpu.ternary_op(0, 1, 2, 3, pr, op);
+                    report.check(
+                        || format!("{:?} {:?} {:?} {:#x} {:#x} {:#x}", op, pr, rm, a, b, c),
+                        (exp, fpe.as_u8()),
+                        rd_bits(&mut fpu, pr),
+                    );
+                }
+            }
+        }
+    }
+    report.finish("tf_ternary");
+}
+
+#[test]
+fn tf_unary() {
+    let ops = [
+        FpUnaryOp::Sqrt,
+        #[cfg(feature = "Zfa")]
+        FpUnaryOp::Round,
+        #[cfg(feature = "Zfa")]
+        FpUnaryOp::RoundNX,
+    ];
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0xbf58476d1ce4e5b9);
+    let mut report = Report::default();
+    for &pr in PRECISIONS {
+        let fmt = Fmt::of(pr);
+        let mut vals = edges(fmt);
+        vals.extend((0..N_RAND).map(|_| rng.fp(fmt)));
+        for op in ops {
+            for rm in RMS {
+                fpu.set_rm(rm);
+                for &a in &vals {
+                    let mut fpe = FPE;
+                    let exp = match op {
+                        FpUnaryOp::Sqrt => soft::sqrt(fmt, a, rm, &mut fpe),
+                        #[cfg(feature = "Zfa")]
+                        FpUnaryOp::Round => soft::round_int(fmt, a, rm, false, &mut fpe),
+                        #[cfg(feature = "Zfa")]
+                        FpUnaryOp::RoundNX => soft::round_int(fmt, a, rm, true, &mut fpe),
+                    };
+                    fpu.wr_raw(1, pr, a);
+                    fpu.clr_all_fpe();
+                    fpu.unary_op(0, 1, pr, op);
+                    report.check(
+                        || format!("{:?} {:?} {:?} {:#x}", op, pr, rm, a),
+                        (exp, fpe.as_u8()),
+                        rd_bits(&mut fpu, pr),
+                    );
+                }
+            }
+        }
+    }
+    report.finish("tf_unary");
+}
+
+#[test]
+fn tf_cmp() {
+    let conds = [
+        FpCmpCond::Eq,
+        FpCmpCond::Lt,
+        FpCmpCond::Le,
+        #[cfg(feature = "Zfa")]
+        FpCmpCond::LtQ,
+        #[cfg(feature = "Zfa")]
+        FpCmpCond::LeQ,
+    ];
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0x94d049bb133111eb);
+    let mut report = Report::default();
+    for &pr in PRECISIONS {
+        let fmt = Fmt::of(pr);
+        let vals = edges(fmt);
+        let mut pairs: Vec<_> = vals
+            .iter()
+            .flat_map(|&a| vals.iter().map(move |&b| (a, b)))
+            .collect();
+        pairs.extend((0..N_RAND).map(|_| (rng.fp(fmt), rng.fp(fmt))));
+        for cond in conds {
+            for &(a, b) in &pairs {
+                let mut fpe = FPE;
+                let exp = soft::cmp(fmt, a, b, cond, &mut fpe) as u32;
+                fpu.wr_raw(1, pr, a);
+                fpu.wr_raw(2, pr, b);
+                fpu.clr_all_fpe();
+                let res = fpu.fp_cmp(1, 2, pr, cond);
+                report.check(
+                    || format!("{:?} {:?} {:#x} {:#x}", cond, pr, a, b),
+                    (exp, fpe.as_u8()),
+                    (res, fpu.get_fpe().as_u8()),
+                );
+            }
+        }
+    }
+    report.finish("tf_cmp");
+}
+
+#[test]
+fn tf_cvt_fp() {
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    let mut report = Report::default();
+    for &from in PRECISIONS {
+        let ffmt = Fmt::of(from);
+        let mut vals = edges(ffmt);
+        vals.extend((0..N_RAND).map(|_| rng.fp(ffmt)));
+        for &to in PRECISIONS.iter().filter(|&&to| to != from) {
+            let tfmt = Fmt::of(to);
+            for rm in RMS {
+                fpu.set_rm(rm);
+                for &a in &vals {
+                    let mut fpe = FPE;
+                    let exp = soft::convert(ffmt, tfmt, a, rm, &mut fpe);
+                    fpu.wr_raw(1, from, a);
+                    fpu.clr_all_fpe();
+                    fpu.fp_cvt_fp(0, 1, from, to);
+                    report.check(
+                        || format!("{:?} -> {:?} {:?} {:#x}", from, to, rm, a),
+                        (exp, fpe.as_u8()),
+                        rd_bits(&mut fpu, to),
+                    );
+                }
+            }
+        }
+    }
+    report.finish("tf_cvt_fp");
+}
+
+#[cfg(feature = "RV64")]
+#[test]
+fn tf_cvt_gp() {
+    let ops = [
+        FpGpOp::W,
+        FpGpOp::WU,
+        FpGpOp::L,
+        FpGpOp::LU,
+        FpGpOp::MV,
+        FpGpOp::Class,
+        #[cfg(feature = "Zfa")]
+        FpGpOp::ModW,
+        #[cfg(feature = "Zfa")]
+        FpGpOp::MvH,
+    ];
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0x853c49e6748fea9b);
+    let mut report = Report::default();
+    for &pr in PRECISIONS {
+        let fmt = Fmt::of(pr);
+        let mut vals = edges(fmt);
+        vals.extend((0..N_RAND).map(|_| rng.fp(fmt)));
+        // integers just around the conversion limits
+        for shift in [31, 32, 63, 64] {
+            let lim = (1u128 << shift) as f64;
+            for val in [lim, -lim, lim - 0.5, -lim - 0.5, lim + 1.0] {
+                let mut fpe = FPE;
+                let bits = val.to_bits() as u128;
+                vals.push(soft::convert(
+                    soft::BINARY64,
+                    fmt,
+                    bits,
+                    RoundMode::Rne,
+                    &mut fpe,
+                ));
+            }
+        }
+        for op in ops {
+            // only moves wider than xlen have a high half,
+            // and fcvtmod is double only
+            match op {
+                #[cfg(feature = "Zfa")]
+                FpGpOp::MvH if fmt.width() <= 64 => continue,
+                #[cfg(feature = "Zfa")]
+                FpGpOp::ModW if fmt != soft::BINARY64 => continue,
+                _ => (),
+            }
+            for rm in RMS {
+                fpu.set_rm(rm);
+                for &a in &vals {
+                    let mut fpe = FPE;
+                    let exp = match op {
+                        FpGpOp::W => soft::to_int(fmt, a, rm, 32, true, &mut fpe) as u64,
+                        FpGpOp::WU => {
+                            soft::to_int(fmt, a, rm, 32, false, &mut fpe) as u32 as i32 as u64
+                        }
+                        FpGpOp::L => soft::to_int(fmt, a, rm, 64, true, &mut fpe) as u64,
+                        FpGpOp::LU => soft::to_int(fmt, a, rm, 64, false, &mut fpe) as u64,
+                        // narrower values are sign extended
+                        FpGpOp::MV => {
+                            let shamt = 128 - fmt.width();
+                            ((a << shamt) as i128 >> shamt) as u64
+                        }
+                        FpGpOp::Class => soft::class(fmt, a) as u64,
+                        #[cfg(feature = "Zfa")]
+                        FpGpOp::ModW => soft::to_int_mod(fmt, a, &mut fpe) as u64,
+                        #[cfg(feature = "Zfa")]
+                        FpGpOp::MvH => (a >> 64) as u64,
+                    };
+                    fpu.wr_raw(1, pr, a);
+                    fpu.clr_all_fpe();
+                    let res = fpu.fp_cvt_gp::<u64>(1, pr, op);
+                    report.check(
+                        || format!("{:?} {:?} {:?} {:#x}", op, pr, rm, a),
+                        (exp, fpe.as_u8()),
+                        (res, fpu.get_fpe().as_u8()),
+                    );
+                }
+            }
+        }
+    }
+    report.finish("tf_cvt_gp");
+}
+
+#[cfg(feature = "RV64")]
+#[test]
+fn tf_gp_cvt() {
+    let ops = [GpFpOp::W, GpFpOp::WU, GpFpOp::L, GpFpOp::LU, GpFpOp::MV];
+    let mut fpu = Fpu::default();
+    let mut rng = Rng(0xda942042e4dd58b5);
+    let mut report = Report::default();
+    // powers of two and their neighbours hit every rounding boundary
+    let mut ints = vec![0, u64::MAX];
+    for shift in 0..64 {
+        let pow = 1u64 << shift;
+        ints.extend([pow - 1, pow, pow + 1, pow.wrapping_neg(), !pow]);
+    }
+    ints.extend((0..N_RAND).map(|_| rng.next()));
+    for &pr in PRECISIONS {
+        let fmt = Fmt::of(pr);
+        for op in ops {
+            for rm in RMS {
+                fpu.set_rm(rm);
+                for &val in &ints {
+                    let mut fpe = FPE;
+                    let exp = match op {
+                        GpFpOp::W => {
+                            let val = val as i32;
+                            soft::from_int(fmt, val < 0, val.unsigned_abs() as u128, rm, &mut fpe)
+                        }
+                        GpFpOp::WU => soft::from_int(fmt, false, val as u32 as u128, rm, &mut fpe),
+                        GpFpOp::L => {
+                            let val = val as i64;
+                            soft::from_int(fmt, val < 0, val.unsigned_abs() as u128, rm, &mut fpe)
+                        }
+                        GpFpOp::LU => soft::from_int(fmt, false, val as u128, rm, &mut fpe),
+                        GpFpOp::MV => val as u128 & (u128::MAX >> (128 - fmt.width())),
+                    };
+                    fpu.clr_all_fpe();
+                    fpu.gp_cvt_fp::<u64>(0, val, pr, op);
+                    report.check(
+                        || format!("{:?} {:?} {:?} {:#x}", op, pr, rm, val),
+                        (exp, fpe.as_u8()),
+                        rd_bits(&mut fpu, pr),
+                    );
+                }
+            }
+        }
+    }
+    report.finish("tf_gp_cvt");
+}
+
+/// replay the independent reference vectors from testgen/refgen.py,
+/// the formats and rounding mode the host can't check
+#[test]
+fn tf_vectors() {
+    let mut fpu = Fpu::default();
+    let mut report = Report::default();
+    for line in include_str!("testgen/vectors.txt").lines() {
+        let cols: Vec<_> = line.split_whitespace().collect();
+        let pr = match cols[1] {
+            "s" => Precision::S,
+            #[cfg(feature = "D")]
+            "d" => Precision::D,
+            #[cfg(feature = "Zfhmin")]
+            "h" => Precision::H,
+            #[cfg(feature = "Q")]
+            "q" => Precision::Q,
+            _ => continue,
+        };
+        let rm = match cols[2] {
+            "rne" => RoundMode::Rne,
+            "rtz" => RoundMode::Rtz,
+            "rdn" => RoundMode::Rdn,
+            "rup" => RoundMode::Rup,
+            "rmm" => RoundMode::Rmm,
+            _ => panic!("bad round mode {}", line),
+        };
+        let hex = |s: &str| u128::from_str_radix(s, 16).unwrap();
+        let (ops, exp) = cols[3..].split_at(cols.len() - 5);
+        let ops: Vec<_> = ops.iter().map(|s| hex(s)).collect();
+        for (reg, &val) in ops.iter().enumerate() {
+            fpu.wr_raw(reg as u8 + 1, pr, val);
+        }
+        fpu.set_rm(rm);
+        fpu.clr_all_fpe();
+        match cols[0] {
+            "add" => fpu.binary_op(0, 1, 2, pr, FpBinaryOp::Add),
+            "sub" => fpu.binary_op(0, 1, 2, pr, FpBinaryOp::Sub),
+            "mul" => fpu.binary_op(0, 1, 2, pr, FpBinaryOp::Mul),
+            "div" => fpu.binary_op(0, 1, 2, pr, FpBinaryOp::Div),
+            "sqrt" => fpu.unary_op(0, 1, pr, FpUnaryOp::Sqrt),
+            "fma" => fpu.ternary_op(0, 1, 2, 3, pr, FpTernaryOp::MAdd),
+            _ => panic!("bad op {}", line),
+        }
+        report.check(
+            || line.to_string(),
+            (hex(exp[0]), hex(exp[1]) as u8),
+            rd_bits(&mut fpu, pr),
+        );
+    }
+    report.finish("tf_vectors");
+}
@@ -0,0 +1,301 @@
+#!/usr/bin/env python3
+# reference vector generator for the fpu conformance tests
+#
+# every result is computed with exact rationals and rounded once,
+# sharing no code with the crate's soft backend. covers the formats
+# and the rounding mode without a host equivalent (rmm).
+#
+# output, one case per line, all values hex:
+#   <op> <fmt> <rm> <operands...> <result> <flags>
+# flags use the fflags layout, nv dz of uf nx
+#
+# usage: refgen.py > vectors.txt
+
+import math
+import random
+from fractions import Fraction
+
+NX, UF, OF, DZ, NV = 1, 2, 4, 8, 16
+RMS = ["rne", "rtz", "rdn", "rup", "rmm"]
+
+
+class Fmt:
+    def __init__(self, name, exp, frac):
+        self.name = name
+        self.exp = exp
+        self.frac = frac
+        self.width = 1 + exp + frac
+        self.bias = (1 << (exp - 1)) - 1
+        self.emin = 1 - self.bias
+        self.emax = self.bias
+        self.p = frac + 1
+        self.emask = (1 << exp) - 1
+
+    def sign(self, a):
+        return a >> (self.width - 1)
+
+    def qnan(self):
+        return self.emask << self.frac | 1 << (self.frac - 1)
+
+    def inf(self, neg):
+        return neg << (self.width - 1) | self.emask << self.frac
+
+    def zero(self, neg):
+        return neg << (self.width - 1)
+
+    def max_finite(self, neg):
+        return neg << (self.width - 1) | (self.emask - 1) << self.frac | ((1 << self.frac) - 1)
+
+    def decode(self, a):
+        """("nan", snan) / ("inf", neg) / ("num", neg, magnitude)"""
+        neg = self.sign(a)
+        e = a >> self.frac & self.emask
+        f = a & ((1 << self.frac) - 1)
+        if e == self.emask:
+            if f:
+                return ("nan", not f >> (self.frac - 1))
+            return ("inf", neg)
+        if e == 0:
+            return ("num", neg, Fraction(f) * Fraction(2) ** (self.emin - self.frac))
+        return ("num", neg, Fraction(f | 1 << self.frac) * Fraction(2) ** (e - self.bias - self.frac))
+
+
+FMTS = [Fmt("h", 5, 10), Fmt("s", 8, 23), Fmt("d", 11, 52), Fmt("q", 15, 112)]
+
+
+def ilog2(x):
+    """floor(log2(x)) for a positive rational"""
+    e = x.numerator.bit_length() - x.denominator.bit_length()
+    if Fraction(2) ** e > x:
+        e -= 1
+    return e
+
+
+def rnd_int(y, neg, rm):
+    """round a non-negative rational to an integer"""
+    fl = y.numerator // y.denominator
+    rem = y - fl
+    if rem == 0:
+        return fl
+    half = Fraction(1, 2)
+    up = {
+        "rne": rem > half or (rem == half and fl & 1),
+        "rmm": rem >= half,
+        "rtz": False,
+        "rdn": neg,
+        "rup": not neg,
+    }[rm]
+    return fl + 1 if up else fl
+
+
+def rnd(fmt, neg, x, rm):
+    """round a non-zero magnitude, return (bits, flags)"""
+    e = ilog2(x)
+    # tininess after rounding, as with unbounded exponent
+    q = Fraction(2) ** (e - fmt.p + 1)
+    tiny = rnd_int(x / q, neg, rm) * q < Fraction(2) ** fmt.emin
+    e = max(e, fmt.emin)
+    q = Fraction(2) ** (e - fmt.p + 1)
+    m = rnd_int(x / q, neg, rm)
+    r = m * q
+    flags = NX if r != x else 0
+    if r >= Fraction(2) ** (fmt.emax + 1):
+        away = rm in ("rne", "rmm") or (rm == "rup" and not neg) or (rm == "rdn" and neg)
+        return (fmt.inf(neg) if away else fmt.max_finite(neg)), OF | NX
+    if tiny and flags:
+        flags |= UF
+    if r == 0:
+        return fmt.zero(neg), flags
+    if r < Fraction(2) ** fmt.emin:
+        return fmt.zero(neg) | m, flags
+    e = ilog2(r)
+    m = r / Fraction(2) ** (e - fmt.p + 1)
+    return fmt.zero(neg) | (e + fmt.bias) << fmt.frac | (int(m) - (1 << fmt.frac)), flags
+
+
+def exact_zero(rm):
+    """sign of an exact zero sum of opposite signs"""
+    return 1 if rm == "rdn" else 0
+
+
+def nan_in(fmt, *ops):
+    """canonical nan and nv for nan operands, None otherwise"""
+    vals = [fmt.decode(a) for a in ops]
+    if not any(v[0] == "nan" for v in vals):
+        return None
+    snan = any(v[0] == "nan" and v[1] for v in vals)
+    return fmt.qnan(), NV if snan else 0
+
+
+def signed(v):
+    return -v[2] if v[1] else v[2]
+
+
+def add(fmt, a, b, rm):
+    res = nan_in(fmt, a, b)
+    if res:
+        return res
+    x, y = fmt.decode(a), fmt.decode(b)
+    if x[0] == "inf" and y[0] == "inf":
+        if x[1] != y[1]:
+            return fmt.qnan(), NV
+        return fmt.inf(x[1]), 0
+    if x[0] == "inf":
+        return fmt.inf(x[1]), 0
+    if y[0] == "inf":
+        return fmt.inf(y[1]), 0
+    s = signed(x) + signed(y)
+    if s == 0:
+        if x[2] == 0 and y[2] == 0 and x[1] == y[1]:
+            return fmt.zero(x[1]), 0
+        return fmt.zero(exact_zero(rm)), 0
+    return rnd(fmt, s < 0, abs(s), rm)
+
+
+def sub(fmt, a, b, rm):
+    if fmt.decode(b)[0] == "nan":
+        return add(fmt, a, b, rm)
+    return add(fmt, a, b ^ 1 << (fmt.width - 1), rm)
+
+
+def mul(fmt, a, b, rm):
+    res = nan_in(fmt, a, b)
+    if res:
+        return res
+    x, y = fmt.decode(a), fmt.decode(b)
+    neg = x[1] ^ y[1]
+    if x[0] == "inf" or y[0] == "inf":
+        if (x[0] == "num" and x[2] == 0) or (y[0] == "num" and y[2] == 0):
+            return fmt.qnan(), NV
+        return fmt.inf(neg), 0
+    p = x[2] * y[2]
+    if p == 0:
+        return fmt.zero(neg), 0
+    return rnd(fmt, neg, p, rm)
+
+
+def div(fmt, a, b, rm):
+    res = nan_in(fmt, a, b)
+    if res:
+        return res
+    x, y = fmt.decode(a), fmt.decode(b)
+    neg = x[1] ^ y[1]
+    if x[0] == "inf":
+        if y[0] == "inf":
+            return fmt.qnan(), NV
+        return fmt.inf(neg), 0
+    if y[0] == "inf":
+        return fmt.zero(neg), 0
+    if y[2] == 0:
+        if x[2] == 0:
+            return fmt.qnan(), NV
+        return fmt.inf(neg), DZ
+    if x[2] == 0:
+        return fmt.zero(neg), 0
+    return rnd(fmt, neg, x[2] / y[2], rm)
+
+
+def sqrt(fmt, a, rm):
+    res = nan_in(fmt, a)
+    if res:
+        return res
+    x = fmt.decode(a)
+    if x[0] == "inf":
+        return (fmt.qnan(), NV) if x[1] else (fmt.inf(0), 0)
+    if x[2] == 0:
+        return fmt.zero(x[1]), 0
+    if x[1]:
+        return fmt.qnan(), NV
+    # scale so the integer root carries p + 8 bits,
+    # an inexact root sits strictly between two lattice points
+    # and rounds like their midpoint
+    k = fmt.p + 8 - ilog2(x[2]) // 2
+    big = x[2] * Fraction(4) ** k
+    fl = big.numerator // big.denominator
+    r = math.isqrt(fl)
+    if r * r == big:
+        return rnd(fmt, 0, Fraction(r) / Fraction(2) ** k, rm)
+    return rnd(fmt, 0, Fraction(2 * r + 1) / Fraction(2) ** (k + 1), rm)
+
+
+def fma(fmt, a, b, c, rm):
+    x, y, z = fmt.decode(a), fmt.decode(b), fmt.decode(c)
+    # inf * 0 is invalid even with a quiet nan addend
+    inf_zero = (x[0] == "inf" and y[0] == "num" and y[2] == 0) or (
+        y[0] == "inf" and x[0] == "num" and x[2] == 0
+    )
+    if inf_zero:
+        return fmt.qnan(), NV
+    res = nan_in(fmt, a, b, c)
+    if res:
+        return res
+    neg = x[1] ^ y[1]
+    if x[0] == "inf" or y[0] == "inf":
+        if z[0] == "inf" and z[1] != neg:
+            return fmt.qnan(), NV
+        return fmt.inf(neg), 0
+    if z[0] == "inf":
+        return fmt.inf(z[1]), 0
+    p = x[2] * y[2]
+    s = (-p if neg else p) + signed(z)
+    if s == 0:
+        if p == 0 and z[2] == 0 and neg == z[1]:
+            return fmt.zero(neg), 0
+        return fmt.zero(exact_zero(rm)), 0
+    return rnd(fmt, s < 0, abs(s), rm)
+
+
+OPS = [("add", 2, add), ("sub", 2, sub), ("mul", 2, mul), ("div", 2, div), ("sqrt", 1, sqrt), ("fma", 3, fma)]
+
+
+def edges(fmt):
+    fmask = (1 << fmt.frac) - 1
+    q = 1 << (fmt.frac - 1)
+    vals = []
+    for sign in (0, 1):
+        for e in (0, 1, fmt.bias - 1, fmt.bias, fmt.bias + 1, fmt.emask - 1, fmt.emask):
+            for f in (0, 1, q, fmask):
+                vals.append(sign << (fmt.width - 1) | e << fmt.frac | f)
+    return vals
+
+
+def rand(rng, fmt):
+    """biased towards exponents near 1.0 so results need rounding"""
+    sign = rng.getrandbits(1)
+    frac = rng.getrandbits(fmt.frac)
+    kind = rng.randrange(4)
+    if kind == 0:
+        e = rng.randrange(fmt.emask + 1)
+    elif kind == 1:
+        e = rng.randrange(3)
+    else:
+        e = fmt.bias - 8 + rng.randrange(16)
+    return sign << (fmt.width - 1) | e << fmt.frac | frac
+
+
+# cases per op, format and rounding mode
+N_CASES = 48
+
+
+def main():
+    rng = random.Random(0x5EED)
+    for fmt in FMTS:
+        # formats with a host fpu only need the mode it lacks
+        rms = RMS if fmt.name in ("h", "q") else ["rmm"]
+        digits = fmt.width // 4
+        vals = edges(fmt)
+        for name, n, fn in OPS:
+            for rm in rms:
+                for i in range(N_CASES):
+                    # walk the edges first, then random operands
+                    ops = [vals[(i * 5 + j * 11) % len(vals)] if i < N_CASES // 2 else rand(rng, fmt) for j in range(n)]
+                    if i >= N_CASES // 2 and rng.randrange(4) == 0:
+                        ops[0] = vals[rng.randrange(len(vals))]
+                    res, flags = fn(fmt, *ops, rm)
+                    cols = [name, fmt.name, rm] + ["%0*x" % (digits, v) for v in ops]
+                    cols += ["%0*x" % (digits, res), "%02x" % flags]
+                    print(" ".join(cols))
+
+
+if __name__ == "__main__":
+    main()
@@ -1636,3 +1636,634 @@ fn op2_sanity() {
     ];
     f32_op2_check(&vals, &exps, FpBinaryOp::Min);
 }
+
+#[cfg(feature = "Zfh")]
+fn f16_op2(v1: u16, v2: u16, rm: RoundMode, op: FpBinaryOp) -> (u16, u8) {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(rm);
+    fpu.u16_mv_f16(1, v1);
+    fpu.u16_mv_f16(2, v2);
+    fpu.clr_all_fpe();
+    fpu.binary_op(0, 1, 2, Precision::H, op);
+    (fpu.f16_mv_u16(0), fpu.get_fpe().as_u8())
+}
+
+#[cfg(feature = "Zfh")]
+#[test]
+fn f16_arith() {
+    use FpBinaryOp::*;
+    use RoundMode::*;
+    // 1.0 + 2^-11 is a tie
+    assert_eq!(f16_op2(0x3c00, 0x1000, Rne, Add), (0x3c00, 0x1));
+    assert_eq!(f16_op2(0x3c00, 0x1000, Rmm, Add), (0x3c01, 0x1));
+    assert_eq!(f16_op2(0x3c00, 0x1000, Rup, Add), (0x3c01, 0x1));
+    assert_eq!(f16_op2(0xbc00, 0x9000, Rup, Add), (0xbc00, 0x1));
+    // overflow
+    assert_eq!(f16_op2(0x7bff, 0x7bff, Rne, Add), (0x7c00, 0x5));
+    assert_eq!(f16_op2(0x7bff, 0x7bff, Rtz, Add), (0x7bff, 0x5));
+    assert_eq!(f16_op2(0xfbff, 0x7bff, Rup, Mul), (0xfbff, 0x5));
+    // exact zero sign
+    assert_eq!(f16_op2(0x3c00, 0x3c00, Rne, Sub), (0x0000, 0x0));
+    assert_eq!(f16_op2(0x3c00, 0x3c00, Rdn, Sub), (0x8000, 0x0));
+    assert_eq!(f16_op2(0x8000, 0x0000, Rne, Mul), (0x8000, 0x0));
+    // underflow after rounding
+    assert_eq!(f16_op2(0x0001, 0x3800, Rne, Mul), (0x0000, 0x3));
+    assert_eq!(f16_op2(0x0001, 0x3800, Rup, Mul), (0x0001, 0x3));
+    assert_eq!(f16_op2(0x0400, 0x3800, Rne, Mul), (0x0200, 0x0));
+    assert_eq!(f16_op2(0x03ff, 0x3c01, Rne, Mul), (0x0400, 0x1));
+    // division
+    assert_eq!(f16_op2(0x3c00, 0x4200, Rne, Div), (0x3555, 0x1));
+    assert_eq!(f16_op2(0xbc00, 0x0000, Rne, Div), (0xfc00, 0x8));
+    assert_eq!(f16_op2(0x0000, 0x0000, Rne, Div), (0x7e00, 0x10));
+    assert_eq!(f16_op2(0x7c00, 0x7c00, Rne, Div), (0x7e00, 0x10));
+    // nan
+    assert_eq!(f16_op2(0x7c00, 0xfc00, Rne, Add), (0x7e00, 0x10));
+    assert_eq!(f16_op2(0x7e55, 0x3c00, Rne, Add), (0x7e00, 0x0));
+    assert_eq!(f16_op2(0x7c01, 0x3c00, Rne, Add), (0x7e00, 0x10));
+    // min max
+    assert_eq!(f16_op2(0x0000, 0x8000, Rne, Min), (0x8000, 0x0));
+    assert_eq!(f16_op2(0x8000, 0x0000, Rne, Max), (0x0000, 0x0));
+    assert_eq!(f16_op2(0x7e00, 0x3c00, Rne, Max), (0x3c00, 0x0));
+    assert_eq!(f16_op2(0x7c01, 0x3c00, Rne, Min), (0x3c00, 0x10));
+    assert_eq!(f16_op2(0x7c01, 0x7e00, Rne, Min), (0x7e00, 0x10));
+    // sign injection keeps nan payload
+    assert_eq!(f16_op2(0x7c01, 0x8000, Rne, SgnJ), (0xfc01, 0x0));
+    assert_eq!(f16_op2(0xbc00, 0x8000, Rne, SgnJX), (0x3c00, 0x0));
+
+    let mut fpu = Fpu::default();
+    fpu.set_rm(Rne);
+    fpu.clr_all_fpe();
+    fpu.u16_mv_f16(1, 0x4000);
+    fpu.unary_op(0, 1, Precision::H, FpUnaryOp::Sqrt);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x3da8, 0x1));
+    fpu.clr_all_fpe();
+    fpu.u16_mv_f16(1, 0x4400);
+    fpu.unary_op(0, 1, Precision::H, FpUnaryOp::Sqrt);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x4000, 0x0));
+    fpu.u16_mv_f16(1, 0xbc00);
+    fpu.unary_op(0, 1, Precision::H, FpUnaryOp::Sqrt);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x7e00, 0x10));
+
+    // (1 + 2^-10)^2 - 1 rounds once
+    fpu.set_rm(Rup);
+    fpu.clr_all_fpe();
+    fpu.u16_mv_f16(1, 0x3c01);
+    fpu.u16_mv_f16(2, 0x3c00);
+    fpu.ternary_op(0, 1, 1, 2, Precision::H, FpTernaryOp::MSub);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x1801, 0x1));
+    // inf * 0 is invalid even with a quiet nan addend
+    fpu.clr_all_fpe();
+    fpu.u16_mv_f16(1, 0x7c00);
+    fpu.u16_mv_f16(2, 0x0000);
+    fpu.u16_mv_f16(3, 0x7e00);
+    fpu.ternary_op(0, 1, 2, 3, Precision::H, FpTernaryOp::MAdd);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x7e00, 0x10));
+
+    fpu.u16_mv_f16(1, 0x8000);
+    fpu.u16_mv_f16(2, 0x0000);
+    assert_eq!(fpu.fp_cmp(1, 2, Precision::H, FpCmpCond::Eq), 1);
+    assert_eq!(fpu.fp_cmp(1, 2, Precision::H, FpCmpCond::Lt), 0);
+    fpu.u16_mv_f16(1, 0x0001);
+    fpu.u16_mv_f16(2, 0x7c01);
+    assert_eq!(fpu.fp_cvt_gp::<u32>(1, Precision::H, FpGpOp::Class), 1 << 5);
+    assert_eq!(fpu.fp_cvt_gp::<u32>(2, Precision::H, FpGpOp::Class), 1 << 8);
+}
+
+#[cfg(feature = "Zfhmin")]
+#[test]
+fn f16_cvt() {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(RoundMode::Rne);
+    let mut cvt = |from, to, val: u64| {
+        fpu.clr_all_fpe();
+        match from {
+            Precision::H => fpu.u16_mv_f16(1, val as u16),
+            _ => fpu.u32_mv_f32(1, val as u32),
+        }
+        fpu.fp_cvt_fp(0, 1, from, to);
+        let res = match to {
+            Precision::H => fpu.f16_mv_u16(0) as u64,
+            _ => fpu.f32_mv_u32(0) as u64,
+        };
+        (res, fpu.get_fpe().as_u8())
+    };
+    use Precision::*;
+    assert_eq!(cvt(H, S, 0x3555), (0x3eaaa000, 0x0));
+    assert_eq!(cvt(H, S, 0x8001), (0xb3800000, 0x0));
+    assert_eq!(cvt(H, S, 0xfc00), (0xff800000, 0x0));
+    assert_eq!(cvt(H, S, 0x7c01), (0x7fc00000, 0x10));
+    assert_eq!(cvt(S, H, 0x3eaaaaab), (0x3555, 0x1));
+    assert_eq!(cvt(S, H, 0x477fe000), (0x7bff, 0x0));
+    // 65520 ties to even, which is out of range
+    assert_eq!(cvt(S, H, 0x477ff000), (0x7c00, 0x5));
+    assert_eq!(cvt(S, H, 0x2edbe6ff), (0x0000, 0x3));
+    assert_eq!(cvt(S, H, 0x387fc000), (0x03ff, 0x0));
+    assert_eq!(cvt(S, H, 0x7f800001), (0x7e00, 0x10));
+    assert_eq!(cvt(S, H, 0x80000000), (0x8000, 0x0));
+
+    // narrower value is nan boxed
+    fpu.u16_mv_f16(2, 0x3c00);
+    assert_eq!(fpu.fpr_bits(2) as u32, 0xffff3c00);
+    fpu.u32_mv_f32(2, 0x3c00);
+    assert_eq!(fpu.f16_mv_u16(2), 0x7e00);
+    // fmv.x.h sign extends
+    fpu.u16_mv_f16(1, 0x8000);
+    assert_eq!(
+        fpu.fp_cvt_gp::<u32>(1, Precision::H, FpGpOp::MV),
+        0xffff8000
+    );
+}
+
+#[cfg(all(feature = "Zfhmin", feature = "D"))]
+#[test]
+fn f16_cvt_d() {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(RoundMode::Rtz);
+    fpu.clr_all_fpe();
+    fpu.u64_mv_f64(1, 0x3ff0000000000001);
+    fpu.fp_cvt_fp(0, 1, Precision::D, Precision::H);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x3c00, 0x1));
+    fpu.clr_all_fpe();
+    fpu.u64_mv_f64(1, 0x7ff0000000000000);
+    fpu.fp_cvt_fp(0, 1, Precision::D, Precision::H);
+    assert_eq!((fpu.f16_mv_u16(0), fpu.get_fpe().as_u8()), (0x7c00, 0x0));
+    fpu.u16_mv_f16(1, 0xc500);
+    fpu.fp_cvt_fp(0, 1, Precision::H, Precision::D);
+    assert_eq!(fpu.f64_mv_u64(0), 0xc014000000000000);
+}
+
+#[cfg(feature = "Q")]
+fn f128_op2(v1: u128, v2: u128, rm: RoundMode, op: FpBinaryOp) -> (u128, u8) {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(rm);
+    fpu.u128_mv_f128(1, v1);
+    fpu.u128_mv_f128(2, v2);
+    fpu.clr_all_fpe();
+    fpu.binary_op(0, 1, 2, Precision::Q, op);
+    (fpu.f128_mv_u128(0), fpu.get_fpe().as_u8())
+}
+
+#[cfg(feature = "Q")]
+const Q_ONE: u128 = 0x3fff_0000_0000_0000_0000_0000_0000_0000;
+#[cfg(feature = "Q")]
+const Q_THREE: u128 = 0x4000_8000_0000_0000_0000_0000_0000_0000;
+#[cfg(feature = "Q")]
+const Q_MAX: u128 = 0x7ffe_ffff_ffff_ffff_ffff_ffff_ffff_ffff;
+#[cfg(feature = "Q")]
+const Q_QNAN: u128 = 0x7fff_8000_0000_0000_0000_0000_0000_0000;
+
+#[cfg(feature = "Q")]
+#[test]
+fn f128_arith() {
+    use FpBinaryOp::*;
+    use RoundMode::*;
+    // 1.0 + 2^-113 is a tie
+    assert_eq!(f128_op2(Q_ONE, 0x3f8e << 112, Rne, Add), (Q_ONE, 0x1));
+    assert_eq!(f128_op2(Q_ONE, 0x3f8e << 112, Rup, Add), (Q_ONE + 1, 0x1));
+    assert_eq!(
+        f128_op2(Q_ONE, Q_THREE, Rne, Div),
+        (0x3ffd_5555_5555_5555_5555_5555_5555_5555, 0x1)
+    );
+    assert_eq!(f128_op2(Q_MAX, Q_MAX, Rne, Add), (0x7fff << 112, 0x5));
+    assert_eq!(f128_op2(Q_MAX, Q_MAX, Rtz, Mul), (Q_MAX, 0x5));
+    // min normal / 3
+    assert_eq!(
+        f128_op2(1 << 112, Q_THREE, Rne, Div),
+        (0x0000_5555_5555_5555_5555_5555_5555_5555, 0x3)
+    );
+    assert_eq!(f128_op2(Q_THREE, Q_THREE, Rdn, Sub), (1 << 127, 0x0));
+    assert_eq!(f128_op2(Q_ONE, 0, Rne, Div), (0x7fff << 112, 0x8));
+    assert_eq!(f128_op2(0x7fff << 112 | 1, Q_ONE, Rne, Add), (Q_QNAN, 0x10));
+    assert_eq!(f128_op2(Q_QNAN, Q_ONE, Rne, Max), (Q_ONE, 0x0));
+
+    let mut fpu = Fpu::default();
+    fpu.set_rm(Rne);
+    fpu.clr_all_fpe();
+    fpu.u128_mv_f128(1, 0x4000 << 112);
+    fpu.unary_op(0, 1, Precision::Q, FpUnaryOp::Sqrt);
+    assert_eq!(
+        (fpu.f128_mv_u128(0), fpu.get_fpe().as_u8()),
+        (0x3fff_6a09_e667_f3bc_c908_b2fb_1366_ea95, 0x1)
+    );
+    fpu.clr_all_fpe();
+    fpu.u128_mv_f128(1, 0x4001 << 112);
+    fpu.unary_op(0, 1, Precision::Q, FpUnaryOp::Sqrt);
+    assert_eq!(
+        (fpu.f128_mv_u128(0), fpu.get_fpe().as_u8()),
+        (0x4000 << 112, 0x0)
+    );
+
+    // (1 + 2^-112)^2 - 1 rounds once
+    fpu.set_rm(Rup);
+    fpu.u128_mv_f128(1, Q_ONE + 1);
+    fpu.u128_mv_f128(2, Q_ONE);
+    fpu.ternary_op(0, 1, 1, 2, Precision::Q, FpTernaryOp::MSub);
+    assert_eq!(
+        (fpu.f128_mv_u128(0), fpu.get_fpe().as_u8()),
+        (0x3f90_0000_0000_0000_0000_0000_0000_0001, 0x1)
+    );
+
+    // flt is signaling, feq is quiet
+    fpu.clr_all_fpe();
+    fpu.u128_mv_f128(1, Q_QNAN);
+    assert_eq!(fpu.fp_cmp(1, 2, Precision::Q, FpCmpCond::Eq), 0);
+    assert_eq!(fpu.get_fpe().as_u8(), 0x0);
+    assert_eq!(fpu.fp_cmp(1, 2, Precision::Q, FpCmpCond::Lt), 0);
+    assert_eq!(fpu.get_fpe().as_u8(), 0x10);
+    assert_eq!(fpu.fp_cmp(2, 2, Precision::Q, FpCmpCond::Le), 1);
+    assert_eq!(fpu.fp_cvt_gp::<u32>(2, Precision::Q, FpGpOp::Class), 1 << 6);
+}
+
+#[cfg(feature = "Q")]
+#[test]
+fn f128_cvt() {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(RoundMode::Rne);
+    fpu.clr_all_fpe();
+    fpu.u64_mv_f64(1, 0x3fb999999999999a);
+    fpu.fp_cvt_fp(0, 1, Precision::D, Precision::Q);
+    assert_eq!(
+        (fpu.f128_mv_u128(0), fpu.get_fpe().as_u8()),
+        (0x3ffb_9999_9999_9999_a000_0000_0000_0000, 0x0)
+    );
+    fpu.u128_mv_f128(1, 0x3ffd_5555_5555_5555_5555_5555_5555_5555);
+    fpu.fp_cvt_fp(0, 1, Precision::Q, Precision::D);
+    assert_eq!(
+        (fpu.f64_mv_u64(0), fpu.get_fpe().as_u8()),
+        (0x3fd5555555555555, 0x1)
+    );
+    fpu.clr_all_fpe();
+    fpu.u128_mv_f128(1, 0x7fff << 112 | 1);
+    fpu.fp_cvt_fp(0, 1, Precision::Q, Precision::S);
+    assert_eq!(
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8()),
+        (0x7fc00000, 0x10)
+    );
+
+    // integer conversions
+    let mut to_w = |val: u128, rm, op| {
+        fpu.set_rm(rm);
+        fpu.clr_all_fpe();
+        fpu.u128_mv_f128(1, val);
+        let res = fpu.fp_cvt_gp::<u32>(1, Precision::Q, op);
+        (res, fpu.get_fpe().as_u8())
+    };
+    // 2.5
+    assert_eq!(to_w(0x40004 << 108, RoundMode::Rne, FpGpOp::W), (2, 0x1));
+    assert_eq!(to_w(0x40004 << 108, RoundMode::Rmm, FpGpOp::W), (3, 0x1));
+    assert_eq!(
+        to_w(0xc0004 << 108, RoundMode::Rdn, FpGpOp::W),
+        (-3i32 as u32, 0x1)
+    );
+    // -1e10 and -1.0
+    assert_eq!(
+        to_w(0xc020_2a05_f200 << 80, RoundMode::Rne, FpGpOp::W),
+        (i32::MIN as u32, 0x10)
+    );
+    assert_eq!(
+        to_w(Q_ONE | 1 << 127, RoundMode::Rne, FpGpOp::WU),
+        (0, 0x10)
+    );
+    // -0.25
+    assert_eq!(to_w(0xbffd << 112, RoundMode::Rtz, FpGpOp::WU), (0, 0x1));
+    assert_eq!(
+        to_w(Q_QNAN, RoundMode::Rne, FpGpOp::W),
+        (i32::MAX as u32, 0x10)
+    );
+
+    fpu.clr_all_fpe();
+    fpu.gp_cvt_fp::<u32>(0, -7i32 as u32, Precision::Q, GpFpOp::W);
+    assert_eq!(
+        (fpu.f128_mv_u128(0), fpu.get_fpe().as_u8()),
+        (0xc001_c000_0000_0000_0000_0000_0000_0000, 0x0)
+    );
+    #[cfg(feature = "RV64")]
+    {
+        fpu.gp_cvt_fp::<u64>(0, u64::MAX, Precision::Q, GpFpOp::LU);
+        assert_eq!(fpu.fp_cvt_gp::<u64>(0, Precision::Q, FpGpOp::LU), u64::MAX);
+        assert_eq!(fpu.get_fpe().as_u8(), 0x0);
+    }
+}
+
+/// interesting f32 values, plus some pseudo random ones
+fn f32_samples() -> Vec<u32> {
+    let mut vals = vec![
+        0x00000000, 0x80000000, 0x00000001, 0x807fffff, 0x00800000, 0x3f800000, 0xbf800000,
+        0x3f800001, 0x3fffffff, 0x40400000, 0x4b800000, 0x33800000, 0x7f7fffff, 0xff7fffff,
+        0x7f800000, 0xff800000, 0x7fc00000, 0x7f800001, 0x0c800000, 0x72000000,
+    ];
+    let mut x = 0x2545f491u32;
+    for _ in 0..20 {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        vals.push(x);
+    }
+    vals
+}
+
+#[test]
+fn soft_vs_host() {
+    // host result is the reference
+    if SOFT_BACKEND {
+        return;
+    }
+    let vals = f32_samples();
+    let mut fpu = Fpu::default();
+    for rm in [
+        RoundMode::Rne,
+        RoundMode::Rtz,
+        RoundMode::Rdn,
+        RoundMode::Rup,
+    ] {
+        fpu.set_rm(rm);
+        for &a in &vals {
+            fpu.u32_mv_f32(1, a);
+            fpu.clr_all_fpe();
+            fpu.unary_op(0, 1, Precision::S, FpUnaryOp::Sqrt);
+            let host = (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8());
+            let mut fpe = FPE;
+            let res = soft::sqrt(BINARY32, a as u128, rm, &mut fpe) as u32;
+            assert_eq!((res, fpe.as_u8()), host, "sqrt {:?} {:#x}", rm, a);
+            for &b in &vals {
+                fpu.u32_mv_f32(2, b);
+                for op in [
+                    FpBinaryOp::Add,
+                    FpBinaryOp::Sub,
+                    FpBinaryOp::Mul,
+                    FpBinaryOp::Div,
+                ] {
+                    fpu.clr_all_fpe();
+                    fpu.binary_op(0, 1, 2, Precision::S, op);
+                    let host = (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8());
+                    let mut fpe = FPE;
+                    let res = soft::binary(BINARY32, a as u128, b as u128, op, rm, &mut fpe);
+                    assert_eq!(
+                        (res as u32, fpe.as_u8()),
+                        host,
+                        "{:?} {:?} {:#x} {:#x}",
+                        op,
+                        rm,
+                        a,
+                        b
+                    );
+                }
+                for &c in vals.iter().step_by(3) {
+                    fpu.u32_mv_f32(3, c);
+                    fpu.clr_all_fpe();
+                    fpu.ternary_op(0, 1, 2, 3, Precision::S, FpTernaryOp::MAdd);
+                    let host = (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8());
+                    let mut fpe = FPE;
+                    let (a, b, c) = (a as u128, b as u128, c as u128);
+                    let res = soft::fma(BINARY32, a, b, c, rm, &mut fpe) as u32;
+                    assert_eq!(
+                        (res, fpe.as_u8()),
+                        host,
+                        "fma {:?} {:#x} {:#x} {:#x}",
+                        rm,
+                        a,
+                        b,
+                        c
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn f32_rmm() {
+    let mut fpu = Fpu::default();
+    let mut add = |rm, a: u32, b: u32| {
+        fpu.set_rm(rm);
+        fpu.u32_mv_f32(1, a);
+        fpu.u32_mv_f32(2, b);
+        fpu.clr_all_fpe();
+        fpu.binary_op(0, 1, 2, Precision::S, FpBinaryOp::Add);
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+    };
+    // 1 + 2^-24 is a tie
+    assert_eq!(
+        add(RoundMode::Rne, 0x3f800000, 0x33800000),
+        (0x3f800000, 0x1)
+    );
+    assert_eq!(
+        add(RoundMode::Rmm, 0x3f800000, 0x33800000),
+        (0x3f800001, 0x1)
+    );
+    assert_eq!(
+        add(RoundMode::Rmm, 0xbf800000, 0xb3800000),
+        (0xbf800001, 0x1)
+    );
+    assert_eq!(
+        add(RoundMode::Rmm, 0x7f7fffff, 0x7f7fffff),
+        (0x7f800000, 0x5)
+    );
+}
+
+#[test]
+fn f32_tininess() {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(RoundMode::Rne);
+    let mut mul = |a: u32, b: u32| {
+        fpu.u32_mv_f32(1, a);
+        fpu.u32_mv_f32(2, b);
+        fpu.binary_op(0, 1, 2, Precision::S, FpBinaryOp::Mul);
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+    };
+    // 2^-126 * (1 - 2^-46) rounds to min normal, not tiny after rounding
+    assert_eq!(mul(0x3f800001, 0x007fffff), (0x00800000, 0x1));
+    // underflow of an earlier op stays
+    assert_eq!(mul(0x3f000001, 0x007fffff), (0x00400000, 0x3));
+    assert_eq!(mul(0x3f800001, 0x807fffff), (0x80800000, 0x3));
+    #[cfg(feature = "D")]
+    {
+        let mut cvt = |val: f64| {
+            fpu.u64_mv_f64(1, val.to_bits());
+            fpu.clr_all_fpe();
+            fpu.fp_cvt_fp(0, 1, Precision::D, Precision::S);
+            (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+        };
+        let min = 2f64.powi(-126);
+        assert_eq!(cvt(min * (1.0 - 2f64.powi(-26))), (0x00800000, 0x1));
+        // rounds below min normal with unbounded exponent, tiny still
+        assert_eq!(cvt(min * (1.0 - 0.75 * 2f64.powi(-24))), (0x00800000, 0x3));
+    }
+}
+
+#[cfg(feature = "RV64")]
+#[test]
+fn f32_cvt_int() {
+    let mut fpu = Fpu::default();
+    fpu.set_rm(RoundMode::Rne);
+    let mut to_int = |val: u32, op| {
+        fpu.u32_mv_f32(1, val);
+        fpu.clr_all_fpe();
+        let res = fpu.fp_cvt_gp::<u64>(1, Precision::S, op);
+        (res, fpu.get_fpe().as_u8())
+    };
+    // -1.5 ties to even
+    assert_eq!(to_int(0xbfc00000, FpGpOp::W), (-2i64 as u64, 0x1));
+    assert_eq!(to_int(0xbf800000, FpGpOp::WU), (0, 0x10));
+    assert_eq!(to_int(0x7fc00000, FpGpOp::W), (i32::MAX as u64, 0x10));
+    assert_eq!(to_int(0xff800000, FpGpOp::W), (i32::MIN as u64, 0x10));
+    assert_eq!(
+        to_int(0x4f800000, FpGpOp::WU),
+        (u32::MAX as i32 as u64, 0x10)
+    );
+    assert_eq!(to_int(0x4f800000, FpGpOp::LU), (1 << 32, 0x0));
+    assert_eq!(to_int(0xdf000000, FpGpOp::L), (i64::MIN as u64, 0x0));
+
+    let mut from_int = |val: u64, op| {
+        fpu.clr_all_fpe();
+        fpu.gp_cvt_fp::<u64>(0, val, Precision::S, op);
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+    };
+    assert_eq!(from_int(16777217, GpFpOp::W), (0x4b800000, 0x1));
+    assert_eq!(from_int(-1i64 as u64, GpFpOp::W), (0xbf800000, 0x0));
+    assert_eq!(from_int(u64::MAX, GpFpOp::LU), (0x5f800000, 0x1));
+    assert_eq!(from_int(0, GpFpOp::L), (0x00000000, 0x0));
+}
+
+#[test]
+fn f32_min_max() {
+    let mut fpu = Fpu::default();
+    let mut op = |op, a: u32, b: u32| {
+        fpu.u32_mv_f32(1, a);
+        fpu.u32_mv_f32(2, b);
+        fpu.clr_all_fpe();
+        fpu.binary_op(0, 1, 2, Precision::S, op);
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+    };
+    use FpBinaryOp::{Max, Min};
+    assert_eq!(op(Min, 0x3f800000, 0xbf800000), (0xbf800000, 0x0));
+    assert_eq!(op(Max, 0x3f800000, 0xbf800000), (0x3f800000, 0x0));
+    assert_eq!(op(Min, 0x00000000, 0x80000000), (0x80000000, 0x0));
+    assert_eq!(op(Min, 0x80000000, 0x00000000), (0x80000000, 0x0));
+    assert_eq!(op(Max, 0x00000000, 0x80000000), (0x00000000, 0x0));
+    assert_eq!(op(Max, 0x80000000, 0x00000000), (0x00000000, 0x0));
+    // nan operand returns the other one
+    assert_eq!(op(Min, 0x7fc00000, 0x3f800000), (0x3f800000, 0x0));
+    assert_eq!(op(Max, 0x3f800000, 0x7fc00000), (0x3f800000, 0x0));
+    assert_eq!(op(Min, 0x7f800001, 0x3f800000), (0x3f800000, 0x10));
+    assert_eq!(op(Max, 0x3f800000, 0x7f800001), (0x3f800000, 0x10));
+    assert_eq!(op(Min, 0x7fc00000, 0x7fc00001), (0x7fc00000, 0x0));
+    assert_eq!(op(Max, 0x7f800001, 0x7fc00000), (0x7fc00000, 0x10));
+}
+
+#[cfg(feature = "Zfa")]
+#[test]
+fn zfa_li() {
+    let mut fpu = Fpu::default();
+    let exps = [
+        (0, 0xbf800000),
+        (1, 0x00800000),
+        (2, 0x37800000),
+        (16, 0x3f800000),
+        (17, 0x3fa00000),
+        (29, 0x47800000),
+        (30, 0x7f800000),
+        (31, 0x7fc00000),
+    ];
+    for (idx, exp) in exps {
+        fpu.fp_li(0, idx, Precision::S);
+        assert_eq!(fpu.f32_mv_u32(0), exp);
+    }
+    #[cfg(feature = "D")]
+    {
+        fpu.fp_li(0, 1, Precision::D);
+        assert_eq!(fpu.f64_mv_u64(0), 0x0010000000000000);
+        fpu.fp_li(0, 9, Precision::D);
+        assert_eq!(fpu.f64_mv_u64(0), 0x3fd4000000000000);
+    }
+    // 2 ^ 16 is not representable in half
+    #[cfg(feature = "Zfh")]
+    {
+        fpu.fp_li(0, 1, Precision::H);
+        assert_eq!(fpu.f16_mv_u16(0), 0x0400);
+        fpu.fp_li(0, 29, Precision::H);
+        assert_eq!(fpu.f16_mv_u16(0), 0x7c00);
+    }
+}
+
+#[cfg(feature = "Zfa")]
+#[test]
+fn zfa_min_max_cmp() {
+    let mut fpu = Fpu::default();
+    let mut op = |op, a: u32, b: u32| {
+        fpu.u32_mv_f32(1, a);
+        fpu.u32_mv_f32(2, b);
+        fpu.clr_all_fpe();
+        fpu.binary_op(0, 1, 2, Precision::S, op);
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+    };
+    use FpBinaryOp::{MaxM, MinM};
+    assert_eq!(op(MinM, 0x3f800000, 0xbf800000), (0xbf800000, 0x0));
+    assert_eq!(op(MaxM, 0x80000000, 0x00000000), (0x00000000, 0x0));
+    // any nan operand gives canonical nan
+    assert_eq!(op(MinM, 0x7fc00001, 0x3f800000), (0x7fc00000, 0x0));
+    assert_eq!(op(MaxM, 0x3f800000, 0x7f800001), (0x7fc00000, 0x10));
+
+    let mut cmp = |op, a: u32, b: u32| {
+        fpu.u32_mv_f32(1, a);
+        fpu.u32_mv_f32(2, b);
+        fpu.clr_all_fpe();
+        let res = fpu.fp_cmp(1, 2, Precision::S, op);
+        (res, fpu.get_fpe().as_u8())
+    };
+    use FpCmpCond::{LeQ, LtQ};
+    assert_eq!(cmp(LeQ, 0x3f800000, 0x3f800000), (1, 0x0));
+    assert_eq!(cmp(LtQ, 0x3f800000, 0x3f800000), (0, 0x0));
+    // quiet compares only signal on snan
+    assert_eq!(cmp(LeQ, 0x7fc00000, 0x3f800000), (0, 0x0));
+    assert_eq!(cmp(LtQ, 0x7f800001, 0x3f800000), (0, 0x10));
+}
+
+#[cfg(feature = "Zfa")]
+#[test]
+fn zfa_round() {
+    let mut fpu = Fpu::default();
+    let mut round = |rm, val: u32, op| {
+        fpu.set_rm(rm);
+        fpu.u32_mv_f32(1, val);
+        fpu.clr_all_fpe();
+        fpu.unary_op(0, 1, Precision::S, op);
+        (fpu.f32_mv_u32(0), fpu.get_fpe().as_u8())
+    };
+    use FpUnaryOp::{Round, RoundNX};
+    use RoundMode::*;
+    // 2.5
+    assert_eq!(round(Rne, 0x40200000, Round), (0x40000000, 0x0));
+    assert_eq!(round(Rmm, 0x40200000, Round), (0x40400000, 0x0));
+    assert_eq!(round(Rne, 0x40200000, RoundNX), (0x40000000, 0x1));
+    // -0.5 keeps the sign
+    assert_eq!(round(Rup, 0xbf000000, RoundNX), (0x80000000, 0x1));
+    assert_eq!(round(Rdn, 0x3f000000, Round), (0x00000000, 0x0));
+    // integers, inf and qnan pass through quietly
+    assert_eq!(round(Rne, 0x4b800001, RoundNX), (0x4b800001, 0x0));
+    assert_eq!(round(Rne, 0xff800000, RoundNX), (0xff800000, 0x0));
+    assert_eq!(round(Rne, 0x7fc00000, RoundNX), (0x7fc00000, 0x0));
+    assert_eq!(round(Rne, 0x7f800001, Round), (0x7fc00000, 0x10));
+}
+
+#[cfg(all(feature = "Zfa", feature = "D", feature = "RV64"))]
+#[test]
+fn zfa_cvt_mod() {
+    let mut fpu = Fpu::default();
+    let mut cvt = |val: f64| {
+        fpu.u64_mv_f64(1, val.to_bits());
+        fpu.clr_all_fpe();
+        let res = fpu.fp_cvt_gp::<u64>(1, Precision::D, FpGpOp::ModW);
+        (res, fpu.get_fpe().as_u8())
+    };
+    assert_eq!(cvt(-1.5), (-1i64 as u64, 0x1));
+    assert_eq!(cvt(2147483647.0), (i32::MAX as u64, 0x0));
+    assert_eq!(cvt(-2147483648.0), (i32::MIN as u64, 0x0));
+    // out of range wraps modulo 2 ^ 32
+    assert_eq!(cvt(4294967301.0), (5, 0x10));
+    assert_eq!(cvt(2147483648.0), (i32::MIN as u64, 0x10));
+    assert_eq!(cvt(-4294967296.5), (0, 0x10));
+    assert_eq!(cvt(1e300), (0, 0x10));
+    assert_eq!(cvt(f64::INFINITY), (0, 0x10));
+    assert_eq!(cvt(f64::NAN), (0, 0x10));
+}
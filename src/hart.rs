@@ -1,7 +1,24 @@
-use crate::{decode::FrontEnd, memory::Mem, privilege::PrivCtrl, utils::Maybe, xlen::XlenT};
+use crate::{
+    cosim::Cosim, decode::FrontEnd, memory::Mem, privilege::PrivCtrl, utils::Maybe, xlen::XlenT,
+};
 
 #[cfg(feature = "F")]
 use crate::fpu::Fpu;
+#[cfg(feature = "V")]
+use crate::vpu::Vpu;
+
+/// why a hart is stalled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Idle {
+    #[default]
+    Run,
+    /// until a locally enabled interrupt is pending
+    Wfi,
+    /// until the reservation is lost or an interrupt is pending,
+    /// `true` for wrs.sto which also times out after the hart's next turn
+    #[cfg(feature = "Zawrs")]
+    Wrs(bool),
+}
 
 /// holds state of the hart
 #[derive(Debug, Clone, Default)]
@@ -14,10 +31,17 @@ pub struct Hart<Xlen: XlenT> {
     pub vpu: Vpu,
     pub fe: FrontEnd<Xlen>,
     pub mem: Mem,
-    pub priv_ctrl: PrivCtrl,
+    pub priv_ctrl: PrivCtrl<Xlen>,
     /// program counter
     pub pc: Xlen,
     pub stop_tok: bool,
+    /// set by pause, ends the current turn early
+    #[cfg(feature = "Zihintpause")]
+    pub yield_tok: bool,
+    /// stalled by wfi / wrs.*, no cycles until woken
+    pub idle: Idle,
+    /// reference trace checker, see `cosim`
+    pub cosim: Option<Box<Cosim<Xlen>>>,
 }
 
 impl<Xlen: XlenT> Hart<Xlen> {
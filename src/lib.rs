@@ -6,13 +6,18 @@
 #[cfg(feature = "F")]
 mod fpu;
 
+mod cosim;
 mod decode;
+mod device;
 mod execute;
 mod hart;
+mod machine;
 mod memory;
 mod privilege;
 mod uop;
 mod utils;
+#[cfg(feature = "V")]
+mod vpu;
 mod xlen;
 
 // use {hart::Hart, xlen::XlenT};
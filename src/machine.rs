@@ -0,0 +1,1289 @@
+#[cfg(all(feature = "Zacas", feature = "RV64"))]
+use crate::memory::ram::Ram;
+#[cfg(any(feature = "Zicbom", feature = "Zicboz", feature = "Zicbop"))]
+use crate::memory::CacheModel;
+use crate::{
+    device::{Clint, CLINT_SIZE},
+    hart::Hart,
+    memory::Bus,
+    privilege::{IRQ_MSI, IRQ_MTI},
+    xlen::XlenT,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+pub const CLINT_BASE: u64 = 0x0200_0000;
+pub const RAM_BASE: u64 = 0x8000_0000;
+/// host sleep of a waiting hart thread between wake checks
+const IDLE_NAP: Duration = Duration::from_micros(100);
+
+/// how harts are interleaved on the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// one instruction per hart per turn
+    RoundRobin,
+    /// given number of instructions per hart per turn
+    Quantum(u64),
+    /// every hart on its own host thread,
+    /// interrupt lines are sampled every given number of instructions
+    Parallel(u64),
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule::Quantum(64)
+    }
+}
+
+/// multi-hart system sharing physical memory and devices
+#[derive(Debug, Clone)]
+pub struct Machine<Xlen: XlenT> {
+    pub harts: Vec<Hart<Xlen>>,
+    pub bus: Arc<Bus>,
+    pub clint: Arc<Clint>,
+    pub sched: Schedule,
+}
+
+impl<Xlen: XlenT> Machine<Xlen> {
+    /// `nharts` harts with `ram_size` bytes of ram at `RAM_BASE`,
+    /// harts start at `RAM_BASE` with mhartid 0 ..= `nharts` - 1
+    pub fn new(nharts: usize, ram_size: u64) -> Self {
+        let clint = Arc::new(Clint::new(nharts));
+        let mut bus = Bus::new(nharts, RAM_BASE, ram_size);
+        bus.add_device(CLINT_BASE, CLINT_SIZE, Box::new(clint.clone()));
+        let bus = Arc::new(bus);
+        let harts = (0..nharts)
+            .map(|hart_id| {
+                let mut hart = Hart::<Xlen>::default();
+                hart.priv_ctrl.hart_id = hart_id;
+                hart.mem.bus = Some(bus.clone());
+                hart.pc = Xlen::from(RAM_BASE);
+                // amocas.q has to be atomic against plain accesses of other harts
+                #[cfg(all(feature = "Zacas", feature = "RV64"))]
+                if Xlen::XLEN == 64 && !Ram::has_cas128() {
+                    hart.fe.isa.Zacas = false;
+                }
+                hart
+            })
+            .collect();
+        Self {
+            harts,
+            bus,
+            clint,
+            sched: Schedule::default(),
+        }
+    }
+
+    /// share a cache model between all harts
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz", feature = "Zicbop"))]
+    pub fn attach_cache(&mut self, cache: Arc<dyn CacheModel>) {
+        for hart in self.harts.iter_mut() {
+            hart.mem.cache = Some(cache.clone());
+        }
+    }
+
+    /// copy image into ram at physical address `addr`
+    pub fn load(&self, addr: u64, image: &[u8]) {
+        self.bus.load(addr, image).expect("image outside of ram");
+    }
+
+    /// all harts stopped
+    pub fn stopped(&self) -> bool {
+        self.harts.iter().all(|hart| hart.stop_tok)
+    }
+
+    /// every running hart waits for another hart or the timer
+    fn waiting(&mut self) -> bool {
+        let clint = &self.clint;
+        self.harts
+            .iter_mut()
+            .filter(|hart| !hart.stop_tok)
+            .all(|hart| {
+                Self::sync_irq(clint, hart);
+                hart.waiting()
+            })
+    }
+
+    /// route device interrupt lines to hart
+    fn sync_irq(clint: &Clint, hart: &mut Hart<Xlen>) {
+        let id = hart.priv_ctrl.hart_id;
+        hart.set_irq(IRQ_MSI, clint.msip(id));
+        hart.set_irq(IRQ_MTI, clint.mtip(id));
+    }
+
+    /// run a hart for up to `quantum` instructions, host fpu state
+    /// belongs to the hart for the turn only
+    fn turn(clint: &Clint, hart: &mut Hart<Xlen>, quantum: u64) -> u64 {
+        Self::sync_irq(clint, hart);
+        #[cfg(feature = "F")]
+        hart.fpu.enter_turn();
+        let ran = hart.step(quantum);
+        #[cfg(feature = "F")]
+        hart.fpu.end_turn();
+        ran
+    }
+
+    fn quantum(&self) -> u64 {
+        match self.sched {
+            Schedule::RoundRobin => 1,
+            Schedule::Quantum(n) | Schedule::Parallel(n) => n,
+        }
+    }
+
+    /// run every hart for one turn, mtime advances by the turn length,
+    /// or to the next timer event when every hart waits
+    pub fn step(&mut self) {
+        let quantum = self.quantum();
+        if let Schedule::Parallel(_) = self.sched {
+            let clint = &self.clint;
+            thread::scope(|scope| {
+                for hart in self.harts.iter_mut().filter(|hart| !hart.stop_tok) {
+                    scope.spawn(move || {
+                        Self::turn(clint, hart, quantum);
+                    });
+                }
+            });
+        } else {
+            for hart in self.harts.iter_mut() {
+                if hart.stop_tok {
+                    continue;
+                }
+                Self::turn(&self.clint, hart, quantum);
+            }
+        }
+        self.clint.tick(quantum);
+        if self.waiting() {
+            if let Some(time) = self.clint.next_timer() {
+                self.clint.skip_to(time);
+            }
+        }
+    }
+
+    /// run until all harts stop, or wait with nothing left to wake them
+    pub fn run(&mut self) {
+        if let Schedule::Parallel(quantum) = self.sched {
+            return self.run_parallel(quantum);
+        }
+        while !self.stopped() {
+            self.step();
+            if self.waiting() && self.clint.next_timer().is_none() {
+                break;
+            }
+        }
+    }
+
+    /// harts run free on their own threads, mtime is advanced by the
+    /// lowest numbered running hart that isn't waiting after each of its
+    /// turns, a paused hart yields its host thread and a waiting one sleeps,
+    /// mtime skips to the next timer event when every hart waits,
+    /// and the threads return when no timer is left to wake them
+    fn run_parallel(&mut self, quantum: u64) {
+        let clint = &self.clint;
+        let stopped: Box<[AtomicBool]> = self
+            .harts
+            .iter()
+            .map(|hart| AtomicBool::new(hart.stop_tok))
+            .collect();
+        let waiting: Box<[AtomicBool]> =
+            self.harts.iter().map(|_| AtomicBool::new(false)).collect();
+        // turns that executed anything, and the count each hart read
+        // before its last turn, a waiting hart that saw the latest count
+        // can't have missed a store that wakes it
+        let progress = AtomicU64::new(0);
+        let seen: Box<[AtomicU64]> = self
+            .harts
+            .iter()
+            .map(|_| AtomicU64::new(u64::MAX))
+            .collect();
+        let (stopped, waiting, progress, seen) = (&stopped, &waiting, &progress, &seen);
+        let stuck = move || {
+            let now = progress.load(Ordering::SeqCst);
+            clint.next_timer().is_none()
+                && (0..stopped.len()).all(|id| {
+                    stopped[id].load(Ordering::SeqCst)
+                        || waiting[id].load(Ordering::SeqCst)
+                            && seen[id].load(Ordering::SeqCst) == now
+                })
+        };
+        thread::scope(|scope| {
+            for (id, hart) in self.harts.iter_mut().enumerate() {
+                scope.spawn(move || {
+                    while !hart.stop_tok {
+                        let count = progress.load(Ordering::SeqCst);
+                        let ran = Self::turn(clint, hart, quantum);
+                        if ran > 0 {
+                            progress.fetch_add(1, Ordering::SeqCst);
+                        }
+                        let wait = ran < quantum && hart.waiting();
+                        waiting[id].store(wait, Ordering::SeqCst);
+                        seen[id].store(count, Ordering::SeqCst);
+                        let leader = stopped.iter().zip(waiting.iter()).position(|(stop, wait)| {
+                            !stop.load(Ordering::Relaxed) && !wait.load(Ordering::Relaxed)
+                        });
+                        if leader == Some(id) {
+                            clint.tick(quantum);
+                        } else if leader.is_none() {
+                            if let Some(time) = clint.next_timer() {
+                                clint.skip_to(time);
+                            } else if stuck() {
+                                return;
+                            }
+                        }
+                        if wait {
+                            thread::sleep(IDLE_NAP);
+                        } else if ran < quantum {
+                            // short turn on pause, let other harts have the host
+                            thread::yield_now();
+                        }
+                    }
+                    stopped[id].store(true, Ordering::SeqCst);
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hart::Idle;
+
+    fn load_prog<Xlen: XlenT>(machine: &mut Machine<Xlen>, prog: &[u32]) {
+        let image: Vec<u8> = prog.iter().flat_map(|ins| ins.to_le_bytes()).collect();
+        machine.load(RAM_BASE, &image);
+        for hart in machine.harts.iter_mut() {
+            hart.priv_ctrl.hooked = true;
+        }
+    }
+
+    #[cfg(feature = "Zicsr")]
+    #[test]
+    fn smp() {
+        let prog = [
+            0xf1402573, // csrr a0, mhartid
+            0x00150593, // addi a1, a0, 1
+            0x00251613, // slli a2, a0, 2
+            0x800016b7, // lui a3, 0x80001
+            0x00c686b3, // add a3, a3, a2
+            0x00b6a023, // sw a1, 0(a3)
+            0x00100073, // ebreak
+        ];
+        for sched in [
+            Schedule::RoundRobin,
+            Schedule::Quantum(3),
+            Schedule::Parallel(3),
+        ] {
+            let mut machine = Machine::<u32>::new(4, 0x2000);
+            machine.sched = sched;
+            load_prog(&mut machine, &prog);
+            machine.run();
+            for id in 0..4 {
+                let val = machine.bus.read(0x80001000 + id * 4, 4);
+                assert_eq!(val, Ok(id + 1));
+            }
+        }
+    }
+
+    #[cfg(feature = "Zicsr")]
+    #[test]
+    fn ipi() {
+        let prog = [
+            0xf1402573, // csrr a0, mhartid
+            0x00051a63, // bnez a0, hart1
+            0x020002b7, // lui t0, 0x2000
+            0x00100313, // li t1, 1
+            0x0062a223, // sw t1, 4(t0)
+            0x00100073, // ebreak
+            // hart1:
+            0x00000297, // auipc t0, 0
+            0x01c28293, // addi t0, t0, 28
+            0x30529073, // csrw mtvec, t0
+            0x00800313, // li t1, 8
+            0x30431073, // csrw mie, t1
+            0x30046073, // csrsi mstatus, 8
+            // loop:
+            0x0000006f, // j loop
+            // handler:
+            0x342023f3, // csrr t2, mcause
+            0x800012b7, // lui t0, 0x80001
+            0x0072a023, // sw t2, 0(t0)
+            0x020002b7, // lui t0, 0x2000
+            0x0002a223, // sw zero, 4(t0)
+            0x00000013, // nop
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(2, 0x2000);
+        machine.sched = Schedule::Quantum(16);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        assert_eq!(machine.bus.read(0x80001000, 4), Ok(0x80000003));
+        assert!(!machine.clint.msip(1));
+        assert_eq!(machine.harts[1].priv_ctrl.mepc, 0x80000030);
+    }
+
+    #[cfg(feature = "F")]
+    #[test]
+    fn fp_state() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x03428293, // addi t0, t0, 52
+            0x30529073, // csrw mtvec, t0
+            0x30002573, // csrr a0, mstatus
+            0x00000053, // fadd.s ft0, ft0, ft0, rne
+            0x300025f3, // csrr a1, mstatus
+            0x0020d073, // csrwi frm, 1
+            0x001fd073, // csrwi fflags, 0x1f
+            0x00302673, // csrr a2, fcsr
+            0x00006337, // lui t1, 0x6
+            0x30033073, // csrc mstatus, t1
+            0x300026f3, // csrr a3, mstatus
+            0x00000053, // fadd.s ft0, ft0, ft0, rne
+            // handler:
+            0x34202773, // csrr a4, mcause
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        // VS sits at initial alongside
+        let vs = if cfg!(feature = "V") { 0x200 } else { 0 };
+        // initial, then dirty with SD set
+        assert_eq!(hart.gprs[10], 0x00002000 | vs);
+        assert_eq!(hart.gprs[11], 0x80006000 | vs);
+        assert_eq!(hart.gprs[12], 0x3f);
+        // fp instructions are illegal once off
+        assert_eq!(hart.gprs[13], vs);
+        assert_eq!(hart.gprs[14], 2);
+        assert_eq!(hart.priv_ctrl.mepc, 0x80000030);
+    }
+
+    #[cfg(feature = "V")]
+    #[test]
+    fn v_state() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x04028293, // addi t0, t0, 64
+            0x30529073, // csrw mtvec, t0
+            0x30002573, // csrr a0, mstatus
+            0x0d007357, // vsetvli t1, zero, e32, m1, ta, ma
+            0x300025f3, // csrr a1, mstatus
+            0x60000313, // li t1, 0x600
+            0x30033073, // csrc mstatus, t1
+            0x30002673, // csrr a2, mstatus
+            0xc20026f3, // csrr a3, vl
+            0x0d007357, // vsetvli t1, zero, e32, m1, ta, ma
+            0x40000313, // li t1, 0x400
+            0x30032073, // csrs mstatus, t1
+            0x00a0d073, // csrwi vxrm, 1
+            0x300026f3, // csrr a3, mstatus
+            0x00100073, // ebreak
+            // handler:
+            0x00148493, // addi s1, s1, 1
+            0x341023f3, // csrr t2, mepc
+            0x00438393, // addi t2, t2, 4
+            0x34139073, // csrw mepc, t2
+            0x30200073, // mret
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        // initial, then dirty with SD set
+        assert_eq!(hart.gprs[10], 0x00002200);
+        assert_eq!(hart.gprs[11], 0x80002600);
+        // vector csrs and instructions are illegal once off
+        assert_eq!(hart.gprs[12], 0x00002000);
+        assert_eq!(hart.gprs[9], 2);
+        // clean, dirtied by a vector csr write, MPIE from mret
+        assert_eq!(hart.gprs[13], 0x80002680);
+        assert_eq!(hart.vpu.vxrm, 1);
+    }
+
+    #[cfg(feature = "F")]
+    #[test]
+    fn fp_turns() {
+        let prog = [
+            0xf1402573, // csrr a0, mhartid
+            0x00051e63, // bnez a0, hart1
+            0x00100293, // li t0, 1
+            0xd0028053, // fcvt.s.w ft0, t0, rne
+            0x00300293, // li t0, 3
+            0xd00280d3, // fcvt.s.w ft1, t0, rne
+            0x18102153, // fdiv.s ft2, ft0, ft1, rdn
+            0x00100073, // ebreak
+            // hart1:
+            0x00100293, // li t0, 1
+            0xd0028053, // fcvt.s.w ft0, t0, rne
+            0x00300293, // li t0, 3
+            0xd00280d3, // fcvt.s.w ft1, t0, rne
+            0x00000013, // nop
+            0x001025f3, // csrr a1, fflags
+            0x18107153, // fdiv.s ft2, ft0, ft1
+            0xe0010653, // fmv.x.w a2, ft2
+            0x001026f3, // csrr a3, fflags
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(2, 0x1000);
+        machine.sched = Schedule::RoundRobin;
+        load_prog(&mut machine, &prog);
+        machine.run();
+        // hart 0 keeps its inexact, hart 1 doesn't see it
+        assert_eq!(machine.harts[0].fpu.get_fpe().as_u8(), 1);
+        let hart = &machine.harts[1];
+        assert_eq!(hart.gprs[11], 0);
+        // rne, not the rdn hart 0 left on the host
+        assert_eq!(hart.gprs[12], 0x3eaaaaab);
+        assert_eq!(hart.gprs[13], 1);
+    }
+
+    #[cfg(feature = "F")]
+    #[test]
+    fn fp_threads() {
+        let prog = [
+            0x00215073, // csrwi frm, 2
+            0x00100293, // li t0, 1
+            0xd002f053, // fcvt.s.w ft0, t0
+            0x00300293, // li t0, 3
+            0xd002f0d3, // fcvt.s.w ft1, t0
+            0x18107153, // fdiv.s ft2, ft0, ft1
+            0xe0010653, // fmv.x.w a2, ft2
+            0x001026f3, // csrr a3, fflags
+            0x00100073, // ebreak
+        ];
+        // a new host thread every turn by step, one for the run by run
+        for threads_per_turn in [true, false] {
+            let mut machine = Machine::<u32>::new(1, 0x1000);
+            machine.sched = Schedule::Parallel(1);
+            load_prog(&mut machine, &prog);
+            if threads_per_turn {
+                while !machine.stopped() {
+                    machine.step();
+                }
+            } else {
+                machine.run();
+            }
+            let hart = &machine.harts[0];
+            // rdn of frm, inexact carried over from the fdiv turn
+            assert_eq!(hart.gprs[12], 0x3eaaaaaa);
+            assert_eq!(hart.gprs[13], 1);
+        }
+    }
+
+    #[cfg(all(feature = "Zfinx", feature = "D"))]
+    #[test]
+    fn zfinx() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x03028293, // addi t0, t0, 48
+            0x30529073, // csrw mtvec, t0
+            0x3f800537, // lui a0, 0x3f800
+            0x00a505d3, // fadd.s a1, a0, a0, rne
+            0x42058653, // fcvt.d.s a2, a1
+            0x02c60753, // fadd.d a4, a2, a2, rne
+            0xc2071853, // fcvt.w.d a6, a4, rtz
+            0xa0b508d3, // fle.s a7, a0, a1
+            0x30002373, // csrr t1, mstatus
+            0x20a51953, // fneg.s s2, a0
+            0x00002007, // flw ft0, 0(zero)
+            // handler:
+            0x342023f3, // csrr t2, mcause
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        machine.harts[0].fe.isa.Zfinx = true;
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        assert_eq!(hart.gprs[11], 0x40000000);
+        // doubles in even-odd pairs
+        assert_eq!(hart.gprs[12..16], [0, 0x40000000, 0, 0x40100000]);
+        assert_eq!(hart.gprs[16], 4);
+        assert_eq!(hart.gprs[17], 1);
+        // FS is read-only zero, VS stays initial
+        let vs = if cfg!(feature = "V") { 0x200 } else { 0 };
+        assert_eq!(hart.gprs[6], vs);
+        assert_eq!(hart.gprs[18], 0xbf800000);
+        // no fp loads
+        assert_eq!(hart.gprs[7], 2);
+        assert_eq!(hart.priv_ctrl.mepc, 0x8000002c);
+
+        // single registers on rv64, narrower values sign extended
+        #[cfg(feature = "RV64")]
+        {
+            let mut machine = Machine::<u64>::new(1, 0x1000);
+            machine.harts[0].fe.isa.Zfinx = true;
+            load_prog(&mut machine, &prog);
+            machine.run();
+            let hart = &machine.harts[0];
+            assert_eq!(hart.gprs[12], 0x4000000000000000);
+            assert_eq!(hart.gprs[14], 0x4010000000000000);
+            assert_eq!(hart.gprs[16], 4);
+            assert_eq!(hart.gprs[18], 0xffffffffbf800000);
+        }
+    }
+
+    #[cfg(all(feature = "M", feature = "RV128"))]
+    #[test]
+    fn rv128() {
+        let prog = [
+            0xfff00513, // li a0, -1
+            0x04055593, // srli a1, a0, 64
+            0x02b58633, // mul a2, a1, a1
+            0x02a536b3, // mulhu a3, a0, a0
+            0x02b55733, // divu a4, a0, a1
+            0x02b567b3, // rem a5, a0, a1
+            0x00000297, // auipc t0, 0
+            0x10028293, // addi t0, t0, 256
+            0x00c2c023, // sq a2, 0(t0)
+            0x0002a80f, // lq a6, 0(t0)
+            0x0082f883, // ldu a7, 8(t0)
+            0x0082b903, // ld s2, 8(t0)
+            0x03f599db, // sllid s3, a1, 63
+            0x00300313, // li t1, 3
+            0x02658a7b, // muld s4, a1, t1
+            0x0265dafb, // divud s5, a1, t1
+            0x30102b73, // csrr s6, misa
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u128>::new(1, 0x1000);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        assert_eq!(hart.gprs[11], u64::MAX as u128);
+        assert_eq!(hart.gprs[12], 0xfffffffffffffffe0000000000000001);
+        assert_eq!(hart.gprs[13], u128::MAX - 1);
+        assert_eq!(hart.gprs[14], 0x10000000000000001);
+        assert_eq!(hart.gprs[15], u128::MAX);
+        assert_eq!(hart.gprs[16], hart.gprs[12]);
+        assert_eq!(hart.gprs[17], 0xfffffffffffffffe);
+        assert_eq!(hart.gprs[18], u128::MAX - 1);
+        assert_eq!(hart.gprs[19], 0xffffffffffffffff8000000000000000);
+        assert_eq!(hart.gprs[20], u128::MAX - 2);
+        assert_eq!(hart.gprs[21], 0x5555555555555555);
+        assert_eq!(hart.gprs[22] >> 126, 3);
+    }
+
+    #[cfg(all(feature = "C", feature = "RV64"))]
+    #[test]
+    fn uxl() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x05828293, // addi t0, t0, 88
+            0x30529073, // csrw mtvec, t0
+            0x300024f3, // csrr s1, mstatus
+            0x00300313, // li t1, 3
+            0x02031313, // slli t1, t1, 32
+            0x30033073, // csrc mstatus, t1
+            0x30002973, // csrr s2, mstatus
+            0x006943b3, // xor t2, s2, t1
+            0x30039073, // csrw mstatus, t2
+            0x00000397, // auipc t2, 0
+            0x01038393, // addi t2, t2, 16
+            0x34139073, // csrw mepc, t2
+            0x30200073, // mret
+            // rv32 user code:
+            0xfff00513, // li a0, -1
+            0x00155593, // srli a1, a0, 1
+            0x00158613, // addi a2, a1, 1
+            0x00000697, // auipc a3, 0
+            0x800012b7, // lui t0, 0x80001
+            0x00b2a023, // sw a1, 0(t0)
+            0x00012009, // c.jal 2; c.nop
+            0x00000073, // ecall
+            // handler:
+            0x34202473, // csrr s0, mcause
+            0x341029f3, // csrr s3, mepc
+            0xfff00713, // li a4, -1
+            0x00175793, // srli a5, a4, 1
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u64>::new(1, 0x2000);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        // UXL resets to 64, writing 0 is ignored
+        assert_eq!((hart.gprs[9] >> 32) & 3, 2);
+        assert_eq!((hart.gprs[18] >> 32) & 3, 2);
+        // results and pc sign extended from 32 bit
+        assert_eq!(hart.gprs[10], u64::MAX);
+        assert_eq!(hart.gprs[11], 0x7fffffff);
+        assert_eq!(hart.gprs[12], 0xffffffff80000000);
+        assert_eq!(hart.gprs[13], 0xffffffff80000044);
+        assert_eq!(hart.gprs[1], 0xffffffff80000052);
+        // upper address bits are ignored
+        assert_eq!(machine.bus.read(0x80001000, 4), Ok(0x7fffffff));
+        assert_eq!(hart.gprs[8], 8);
+        assert_eq!(hart.gprs[19], 0xffffffff80000054);
+        // back to rv64 in machine mode
+        assert_eq!(hart.gprs[15], 0x7fffffffffffffff);
+    }
+
+    #[cfg(all(feature = "Zicond", feature = "Zihintpause"))]
+    #[test]
+    fn pause() {
+        let prog = [
+            0xf1402573, // csrr a0, mhartid
+            0x800015b7, // lui a1, 0x80001
+            0x00051a63, // bnez a0, writer
+            // spin:
+            0x0005a603, // lw a2, 0(a1)
+            0x0100000f, // pause
+            0xfe060ce3, // beqz a2, spin
+            0x00100073, // ebreak
+            // writer:
+            0x00100613, // li a2, 1
+            0x0ec5d6b3, // czero.eqz a3, a1, a2
+            0x0ec5f733, // czero.nez a4, a1, a2
+            0x00c5a023, // sw a2, 0(a1)
+            0x00100073, // ebreak
+        ];
+        // pause ends the turn early
+        let mut machine = Machine::<u32>::new(1, 0x2000);
+        load_prog(&mut machine, &prog);
+        assert_eq!(machine.harts[0].step(64), 5);
+
+        for sched in [Schedule::Quantum(64), Schedule::Parallel(64)] {
+            let mut machine = Machine::<u32>::new(2, 0x2000);
+            machine.sched = sched;
+            load_prog(&mut machine, &prog);
+            machine.run();
+            assert_eq!(machine.harts[0].gprs[12], 1);
+            let writer = &machine.harts[1];
+            assert_eq!(writer.gprs[13], 0x80001000);
+            assert_eq!(writer.gprs[14], 0);
+        }
+    }
+
+    #[cfg(all(
+        feature = "Zicbom",
+        feature = "Zicboz",
+        feature = "Zicbop",
+        feature = "Zicsr"
+    ))]
+    #[test]
+    fn cbo() {
+        use crate::uop::{CboOp, MemProtect};
+        use std::sync::Mutex;
+
+        #[derive(Debug, Default)]
+        struct Log(Mutex<Vec<(CboOp, u64)>>, Mutex<Vec<(u64, MemProtect)>>);
+        impl CacheModel for Log {
+            fn cbo(&self, _hart: usize, op: CboOp, paddr: u64) {
+                self.0.lock().unwrap().push((op, paddr));
+            }
+            fn prefetch(&self, _hart: usize, paddr: u64, prot: MemProtect) {
+                self.1.lock().unwrap().push((paddr, prot));
+            }
+        }
+
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x02828293, // addi t0, t0, 40
+            0x30529073, // csrw mtvec, t0
+            0x80001537, // lui a0, 0x80001
+            0x02450513, // addi a0, a0, 0x24
+            0x100005b7, // lui a1, 0x10000
+            0x00156013, // prefetch.r 0(a0)
+            0x0045200f, // cbo.zero (a0)
+            0x0025200f, // cbo.flush (a0)
+            0x0015a00f, // cbo.clean (a1)
+            // handler:
+            0x342023f3, // csrr t2, mcause
+            0x34302e73, // csrr t3, mtval
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x2000);
+        let log = Arc::new(Log::default());
+        machine.attach_cache(log.clone());
+        load_prog(&mut machine, &prog);
+        machine.load(0x80001000, &[0xff; 0x80]);
+        machine.run();
+        // the whole aligned block, nothing past it
+        for off in (0..0x40).step_by(4) {
+            assert_eq!(machine.bus.read(0x80001000 + off, 4), Ok(0));
+        }
+        assert_eq!(machine.bus.read(0x80001040, 4), Ok(0xffffffff));
+        assert_eq!(
+            *log.0.lock().unwrap(),
+            [(CboOp::Zero, 0x80001000), (CboOp::Flush, 0x80001000)]
+        );
+        assert_eq!(*log.1.lock().unwrap(), [(0x80001000, MemProtect::R)]);
+        // unmapped block faults with the rs1 address
+        let hart = &machine.harts[0];
+        assert_eq!(hart.gprs[7], 7);
+        assert_eq!(hart.gprs[28], 0x10000000);
+        assert_eq!(hart.priv_ctrl.mepc, 0x80000024);
+    }
+
+    #[cfg(all(feature = "Zicbom", feature = "Zicboz", feature = "Zicsr"))]
+    #[test]
+    fn cbo_envcfg() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x04028293, // addi t0, t0, 64
+            0x30529073, // csrw mtvec, t0
+            0xfff00313, // li t1, -1
+            0x30a31073, // csrw menvcfg, t1
+            0x30a025f3, // csrr a1, menvcfg
+            0x06000313, // li t1, 0x60
+            0x30a31073, // csrw menvcfg, t1
+            0x30a02673, // csrr a2, menvcfg
+            0x80001537, // lui a0, 0x80001
+            0x00000297, // auipc t0, 0
+            0x01028293, // addi t0, t0, 16
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+            // user:
+            0x0015200f, // cbo.clean (a0)
+            0x0005200f, // cbo.inval (a0)
+            // handler:
+            0x342023f3, // csrr t2, mcause
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x2000);
+        load_prog(&mut machine, &prog);
+        // senvcfg enables all, menvcfg decides
+        #[cfg(feature = "S")]
+        {
+            machine.harts[0].priv_ctrl.senvcfg = 0xf0;
+        }
+        machine.run();
+        let hart = &machine.harts[0];
+        assert_eq!(hart.gprs[11], 0xf0);
+        // reserved CBIE reads back as off
+        assert_eq!(hart.gprs[12], 0x40);
+        // clean enabled by CBCFE, inval disabled
+        assert_eq!(hart.gprs[7], 2);
+        assert_eq!(hart.priv_ctrl.mepc, 0x8000003c);
+    }
+
+    #[cfg(feature = "Zicsr")]
+    #[test]
+    fn wfi() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x03028293, // addi t0, t0, 48
+            0x30529073, // csrw mtvec, t0
+            0x02004337, // lui t1, 0x2004
+            0x001003b7, // lui t2, 0x100
+            0x00732023, // sw t2, 0(t1)
+            0x00032223, // sw zero, 4(t1)
+            0x08000313, // li t1, 0x80
+            0x30431073, // csrw mie, t1
+            0x30046073, // csrsi mstatus, 8
+            0x10500073, // wfi
+            0x00100073, // ebreak
+            // handler:
+            0x342023f3, // csrr t2, mcause
+            0x00100073, // ebreak
+        ];
+        for sched in [Schedule::Quantum(64), Schedule::Parallel(64)] {
+            let mut machine = Machine::<u32>::new(1, 0x1000);
+            machine.sched = sched;
+            load_prog(&mut machine, &prog);
+            machine.run();
+            let hart = &machine.harts[0];
+            assert_eq!(hart.gprs[7], 0x80000007);
+            assert_eq!(hart.priv_ctrl.mepc, 0x8000002c);
+            // skipped ahead instead of stepping through the wait
+            let mtime = machine.clint.mtime();
+            assert!((0x100000..0x101000).contains(&mtime));
+        }
+
+        // nothing can wake the harts
+        for sched in [Schedule::Quantum(64), Schedule::Parallel(64)] {
+            let mut machine = Machine::<u32>::new(2, 0x1000);
+            machine.sched = sched;
+            load_prog(&mut machine, &prog[10..]);
+            machine.run();
+            for hart in &machine.harts {
+                assert!(!hart.stop_tok);
+                assert_eq!(hart.idle, Idle::Wfi);
+                assert_eq!(hart.pc, 0x80000004);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "S", feature = "Zicsr"))]
+    #[test]
+    fn s_mode() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x09828293, // addi t0, t0, 152
+            0x30529073, // csrw mtvec, t0
+            0x00000297, // auipc t0, 0
+            0x05828293, // addi t0, t0, 88
+            0x10529073, // csrw stvec, t0
+            0x10000313, // li t1, 0x100
+            0x30231073, // csrw medeleg, t1
+            0x00200313, // li t1, 2
+            0x30331073, // csrw mideleg, t1
+            0x30431073, // csrw mie, t1
+            0x00100313, // li t1, 1
+            0x00b31313, // slli t1, t1, 11
+            0x30032073, // csrs mstatus, t1
+            0x00000297, // auipc t0, 0
+            0x01028293, // addi t0, t0, 16
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+            // super:
+            0x10002473, // csrr s0, sstatus
+            0x00000297, // auipc t0, 0
+            0x01028293, // addi t0, t0, 16
+            0x14129073, // csrw sepc, t0
+            0x10200073, // sret
+            // user:
+            0x00000073, // ecall
+            0x10500073, // wfi
+            // s_trap:
+            0x02049263, // bnez s1, s_irq
+            0x14202573, // csrr a0, scause
+            0x00100493, // li s1, 1
+            0x141025f3, // csrr a1, sepc
+            0x00458593, // addi a1, a1, 4
+            0x14159073, // csrw sepc, a1
+            0x14416073, // csrsi sip, 2
+            0x14402673, // csrr a2, sip
+            0x10200073, // sret
+            // s_irq:
+            0x142026f3, // csrr a3, scause
+            0x14102773, // csrr a4, sepc
+            0x14417073, // csrci sip, 2
+            0x10200073, // sret
+            // m_trap:
+            0x342027f3, // csrr a5, mcause
+            0x34102873, // csrr a6, mepc
+            0x300028f3, // csrr a7, mstatus
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        // sstatus hides the machine fields
+        assert_eq!(hart.gprs[8] & 0x80, 0);
+        // delegated ecall from U
+        assert_eq!(hart.gprs[10], 8);
+        assert_eq!(hart.gprs[11], 0x80000060);
+        assert_eq!(hart.gprs[12], 2);
+        // SSIP masked in S, taken on the return to U
+        assert_eq!(hart.gprs[13], 0x80000001);
+        assert_eq!(hart.gprs[14], 0x80000060);
+        // wfi from U is illegal with S-mode, not delegated
+        assert_eq!(hart.gprs[15], 2);
+        assert_eq!(hart.gprs[16], 0x80000060);
+        assert_eq!(hart.gprs[17] & 0x1800, 0);
+    }
+
+    #[cfg(all(feature = "S", feature = "Zicsr"))]
+    #[test]
+    fn sv32() {
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x05428293, // addi t0, t0, 84
+            0x30529073, // csrw mtvec, t0
+            0x80080337, // lui t1, 0x80080
+            0x00130313, // addi t1, t1, 1
+            0x18031073, // csrw satp, t1
+            0x00100313, // li t1, 1
+            0x00b31313, // slli t1, t1, 11
+            0x30032073, // csrs mstatus, t1
+            0x00000297, // auipc t0, 0
+            0x01828293, // addi t0, t0, 24
+            0x40000337, // lui t1, 0x40000
+            0x406282b3, // sub t0, t0, t1
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+            // super:
+            0x00000417, // auipc s0, 0
+            0x180024f3, // csrr s1, satp
+            0x400013b7, // lui t2, 0x40001
+            0x0003a983, // lw s3, 0(t2)
+            0x00000073, // ecall
+            0x18002973, // csrr s2, satp
+            // m_trap:
+            0x02061463, // bnez a2, m_done
+            0x34202573, // csrr a0, mcause
+            0x341025f3, // csrr a1, mepc
+            0x00458593, // addi a1, a1, 4
+            0x34159073, // csrw mepc, a1
+            0x00100313, // li t1, 1
+            0x01431313, // slli t1, t1, 20
+            0x30032073, // csrs mstatus, t1
+            0x00100613, // li a2, 1
+            0x30200073, // mret
+            // m_done:
+            0x342026f3, // csrr a3, mcause
+            0x34102773, // csrr a4, mepc
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x10000);
+        load_prog(&mut machine, &prog);
+        // 0x4000_0000 -> code, 0x4000_1000 -> data, both supervisor pages
+        let tables = [
+            (RAM_BASE + 0x1400, (RAM_BASE + 0x2000) >> 2 | 0x01),
+            (RAM_BASE + 0x2000, RAM_BASE >> 2 | 0xcb),
+            (RAM_BASE + 0x2004, (RAM_BASE + 0x3000) >> 2 | 0xc3),
+        ];
+        for (addr, pte) in tables {
+            machine.bus.write(0, addr, 4, pte).unwrap();
+        }
+        machine.bus.write(0, RAM_BASE + 0x3000, 4, 0xfeed).unwrap();
+        machine.run();
+        let hart = &machine.harts[0];
+        // fetched and loaded through the page tables
+        assert_eq!(hart.gprs[8], 0x4000003c);
+        assert_eq!(hart.gprs[9], 0x80080001);
+        assert_eq!(hart.gprs[19], 0xfeed);
+        assert_eq!(hart.gprs[10], 9);
+        assert_eq!(hart.gprs[11], 0x40000050);
+        // satp is trapped by TVM
+        assert_eq!(hart.gprs[13], 2);
+        assert_eq!(hart.gprs[14], 0x40000050);
+    }
+
+    #[cfg(all(feature = "H", feature = "Zicsr"))]
+    #[test]
+    fn hypervisor() {
+        use crate::privilege::*;
+
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x0b028293, // addi t0, t0, 176
+            0x30529073, // csrw mtvec, t0
+            0x00000297, // auipc t0, 0
+            0x08428293, // addi t0, t0, 132
+            0x10529073, // csrw stvec, t0
+            0x40400313, // li t1, 0x404
+            0x30231073, // csrw medeleg, t1
+            0x00400313, // li t1, 4
+            0x60231073, // csrw hedeleg, t1
+            0x80080337, // lui t1, 0x80080
+            0x00430313, // addi t1, t1, 4
+            0x68031073, // csrw hgatp, t1
+            0x00100313, // li t1, 1
+            0x00b31313, // slli t1, t1, 11
+            0x30032073, // csrs mstatus, t1
+            0x08000313, // li t1, 0x80
+            0x31032073, // csrs mstatush, t1
+            0x80008437, // lui s0, 0x80008
+            0x00000297, // auipc t0, 0
+            0x01028293, // addi t0, t0, 16
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+            // guest:
+            0x00000297, // auipc t0, 0
+            0x02028293, // addi t0, t0, 32
+            0x10529073, // csrw stvec, t0
+            0x900003b7, // lui t2, 0x90000
+            0x0003ae03, // lw t3, 0(t2)
+            0x60002e73, // csrr t3, hstatus
+            0x00000000, // .word 0
+            0x00000073, // ecall
+            // vs_trap:
+            0x14202573, // csrr a0, scause
+            0x141025f3, // csrr a1, sepc
+            0x00458593, // addi a1, a1, 4
+            0x14159073, // csrw sepc, a1
+            0x10200073, // sret
+            // hs_trap:
+            0x14202673, // csrr a2, scause
+            0x600026f3, // csrr a3, hstatus
+            0x00000397, // auipc t2, 0
+            0xfc438393, // addi t2, t2, -60
+            0x6803c773, // hlv.w a4, (t2)
+            0x900003b7, // lui t2, 0x90000
+            0x6803c7f3, // hlv.w a5, (t2)
+            0x00100073, // ebreak
+            // m_trap:
+            0x34202ef3, // csrr t4, mcause
+            0x01d42023, // sw t4, 0(s0)
+            0x34302ef3, // csrr t4, mtval
+            0x01d42223, // sw t4, 4(s0)
+            0x34b02ef3, // csrr t4, mtval2
+            0x01d42423, // sw t4, 8(s0)
+            0x31002ef3, // csrr t4, mstatush
+            0x01d42623, // sw t4, 12(s0)
+            0x01040413, // addi s0, s0, 16
+            0x34102ef3, // csrr t4, mepc
+            0x004e8e93, // addi t4, t4, 4
+            0x341e9073, // csrw mepc, t4
+            0x30200073, // mret
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x10000);
+        load_prog(&mut machine, &prog);
+        // G-stage maps the guest physical megapage of ram onto itself
+        let pte = (RAM_BASE >> 2) as u32 | 0xdf;
+        machine
+            .bus
+            .write(0, RAM_BASE + 0x4800, 4, pte as u64)
+            .unwrap();
+        machine.run();
+        let hart = &machine.harts[0];
+        // stvec of VS-mode goes to vstvec
+        assert_eq!(hart.priv_ctrl.stvec, 0x80000090);
+        assert_eq!(hart.priv_ctrl.vstvec, 0x8000007c);
+        // illegal instruction delegated on to VS-mode
+        assert_eq!(hart.gprs[10], 2);
+        assert_eq!(hart.gprs[11], 0x80000078);
+        // ecall from VS-mode in HS-mode, then hlv at SPVP
+        assert_eq!(hart.gprs[12], 10);
+        assert_eq!(hart.gprs[13], 0x180);
+        assert_eq!(hart.gprs[14], 0x00000297);
+        // (mcause, mtval, mtval2, mstatush) of traps into M-mode:
+        // guest-page fault of VS-mode, virtual instruction, then of hlv
+        let traps = [
+            (21, 0x9000_0000, 0x2400_0000, 0xc0),
+            (22, 0, 0, 0x80),
+            (21, 0x9000_0000, 0x2400_0000, 0x40),
+        ];
+        for (i, trap) in traps.into_iter().enumerate() {
+            let addr = RAM_BASE + 0x8000 + i as u64 * 16;
+            let read = |off| machine.bus.read(addr + off, 4).unwrap();
+            assert_eq!((read(0), read(4), read(8), read(12)), trap);
+        }
+        assert_eq!(hart.priv_ctrl.mode, PrivMode::S);
+        assert!(!hart.priv_ctrl.virt);
+
+        // VS-level interrupts wait in HS-mode, then go to VS-mode as S-level
+        let hart = &mut machine.harts[0];
+        let p = &mut hart.priv_ctrl;
+        p.hideleg = MIP_VSSIP as u32;
+        p.mie = MIP_VSSIP as u32;
+        p.mip = MIP_VSSIP as u32;
+        p.vsstatus = MSTATUS_SIE as u32;
+        assert_eq!(hart.check_irq(), Ok(()));
+        hart.priv_ctrl.virt = true;
+        assert_eq!(hart.check_irq(), Err(()));
+        assert_eq!(hart.priv_ctrl.vscause, 1 << 31 | IRQ_SSI);
+        assert_eq!(hart.priv_ctrl.vsstatus, (MSTATUS_SPIE | MSTATUS_SPP) as u32);
+        assert_eq!(hart.pc, 0x8000007c);
+        assert!(hart.priv_ctrl.virt);
+
+        // VS of vsstatus also turns the vpu off in VS-mode,
+        // vector writes dirty it with SD set
+        #[cfg(feature = "V")]
+        {
+            assert!(hart.v_enable(false).is_err());
+            hart.priv_ctrl.virt = true;
+            hart.priv_ctrl.vsstatus = 1 << MSTATUS_VS_SHIFT;
+            hart.v_enable(true).unwrap();
+            let sd_vs = 1 << 31 | MSTATUS_VS as u32;
+            assert_eq!(hart.csr_rd(VSSTATUS).unwrap() & sd_vs, sd_vs);
+        }
+    }
+
+    #[cfg(feature = "Zawrs")]
+    #[test]
+    fn zawrs() {
+        let prog = [
+            0xf1402573, // csrr a0, mhartid
+            0x800015b7, // lui a1, 0x80001
+            0x00051e63, // bnez a0, hart1
+            0x0c800293, // li t0, 200
+            // loop:
+            0xfff28293, // addi t0, t0, -1
+            0xfe029ee3, // bnez t0, loop
+            0x00100313, // li t1, 1
+            0x0065a023, // sw t1, 0(a1)
+            0x00100073, // ebreak
+            // hart1:
+            0x1005a32f, // lr.w t1, (a1)
+            0x00031663, // bnez t1, done
+            0x00d00073, // wrs.nto
+            0xff5ff06f, // j hart1
+            // done:
+            0x00030613, // mv a2, t1
+            0x00100073, // ebreak
+        ];
+        for sched in [
+            Schedule::RoundRobin,
+            Schedule::Quantum(16),
+            Schedule::Parallel(16),
+        ] {
+            let mut machine = Machine::<u32>::new(2, 0x2000);
+            machine.sched = sched;
+            load_prog(&mut machine, &prog);
+            machine.run();
+            assert_eq!(machine.harts[1].gprs[12], 1);
+        }
+
+        // wrs.sto stalls through the next turn, then times out
+        let prog = [
+            0x800015b7, // lui a1, 0x80001
+            0x1005a32f, // lr.w t1, (a1)
+            0x01d00073, // wrs.sto
+            0x00100613, // li a2, 1
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x2000);
+        load_prog(&mut machine, &prog);
+        let hart = &mut machine.harts[0];
+        assert_eq!(hart.step(16), 3);
+        assert_eq!(hart.idle, Idle::Wrs(true));
+        assert!(!hart.waiting());
+        assert_eq!(hart.step(16), 0);
+        assert_eq!(hart.pc, 0x8000000c);
+        assert_eq!(hart.step(16), 2);
+        assert_eq!(hart.gprs[12], 1);
+        assert!(hart.stop_tok);
+
+        // TW traps waits of user mode that could stall
+        let prog = [
+            0x00000297, // auipc t0, 0
+            0x03028293, // addi t0, t0, 48
+            0x30529073, // csrw mtvec, t0
+            0x00200337, // lui t1, 0x200
+            0x30032073, // csrs mstatus, t1
+            0x00000297, // auipc t0, 0
+            0x01028293, // addi t0, t0, 16
+            0x34129073, // csrw mepc, t0
+            0x30200073, // mret
+            // user:
+            0x01d00073, // wrs.sto
+            0x00d00073, // wrs.nto
+            0x10500073, // wfi
+            // handler:
+            0x342023f3, // csrr t2, mcause
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        // no reservation, wrs.* complete at once
+        assert_eq!(hart.gprs[7], 2);
+        assert_eq!(hart.priv_ctrl.mepc, 0x8000002c);
+    }
+
+    #[cfg(feature = "Zcmp")]
+    #[test]
+    fn popret_odd_ra() {
+        let prog = [
+            0x80001137, // lui sp, 0x80001
+            0x00000097, // auipc ra, 0
+            0x01508093, // addi ra, ra, 21
+            0x0001b842, // cm.push {ra}, -16; c.nop
+            0x00000093, // li ra, 0
+            0x0001be42, // cm.popret {ra}, 16; c.nop
+            0x00000297, // auipc t0, 0
+            0x00d28567, // jalr a0, 13(t0)
+            0x00100073, // ebreak
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        machine.harts[0].fe.isa.Zcmp = true;
+        load_prog(&mut machine, &prog);
+        // bounded, an odd pc runs astray
+        machine.step();
+        let hart = &machine.harts[0];
+        assert!(hart.stop_tok);
+        // bit 0 of both targets is cleared
+        assert_eq!(hart.gprs[5], 0x80000018);
+        assert_eq!(hart.gprs[10], 0x80000020);
+        assert_eq!(hart.get_pc(), 0x80000024);
+    }
+
+    #[cfg(all(feature = "Zcmp", feature = "Zcmt"))]
+    #[test]
+    fn zcmp_zcmt() {
+        let prog = [
+            0x80001137, // lui sp, 0x80001
+            0x00100093, // li ra, 1
+            0x00200413, // li s0, 2
+            0x00300493, // li s1, 3
+            0x0001b866, // cm.push {ra, s0-s1}, -32; c.nop
+            0x00000093, // li ra, 0
+            0x00000413, // li s0, 0
+            0x00000493, // li s1, 0
+            0x00700513, // li a0, 7
+            0x00800593, // li a1, 8
+            0xadeaad2e, // cm.mvsa01 s2, s3; cm.mva01s s3, s2
+            0x00010a93, // mv s5, sp
+            0x0001ba66, // cm.pop {ra, s0-s1}, 32; c.nop
+            0x800002b7, // lui t0, 0x80000
+            0x10028293, // addi t0, t0, 256
+            0x01729073, // csrw jvt, t0
+            0x00000317, // auipc t1, 0
+            0x01430313, // addi t1, t1, 20
+            0x0862a023, // sw t1, 128(t0)
+            0x0001a082, // cm.jalt 32; c.nop
+            0x00100073, // ebreak
+            0x01702a73, // csrr s4, jvt
+            0x00100073, // ebreak
+        ];
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        machine.harts[0].fe.isa.Zcmp = true;
+        machine.harts[0].fe.isa.Zcmt = true;
+        load_prog(&mut machine, &prog);
+        machine.run();
+        let hart = &machine.harts[0];
+        // last register of the list on top
+        assert_eq!(machine.bus.read(0x80000ff4, 4), Ok(1));
+        assert_eq!(machine.bus.read(0x80000ff8, 4), Ok(2));
+        assert_eq!(machine.bus.read(0x80000ffc, 4), Ok(3));
+        assert_eq!(hart.gprs[21], 0x80000fe0);
+        assert_eq!(hart.gprs[2], 0x80001000);
+        assert_eq!(hart.gprs[8..10], [2, 3]);
+        assert_eq!(hart.gprs[18..20], [7, 8]);
+        assert_eq!(hart.gprs[10..12], [8, 7]);
+        assert_eq!(hart.gprs[1], 0x8000004e);
+        assert_eq!(hart.gprs[20], 0x80000100);
+        assert_eq!(hart.get_pc(), 0x80000058);
+    }
+
+    #[test]
+    fn reservation() {
+        let machine = Machine::<u32>::new(2, 0x1000);
+        let bus = &machine.bus;
+        bus.reserve(0, 0x80000100);
+        bus.write(1, 0x80000108, 4, 0).unwrap();
+        assert!(!bus.take_reservation(0, 0x80000100));
+        bus.reserve(0, 0x80000100);
+        bus.write(0, 0x80000100, 4, 0).unwrap();
+        bus.write(1, 0x80000200, 4, 0).unwrap();
+        assert!(bus.take_reservation(0, 0x80000100));
+        assert!(!bus.take_reservation(0, 0x80000100));
+    }
+
+    #[test]
+    fn host_atomics() {
+        let machine = Machine::<u32>::new(4, 0x1000);
+        let bus = &machine.bus;
+        thread::scope(|scope| {
+            for hart in 0..4 {
+                scope.spawn(move || {
+                    for _ in 0..1000 {
+                        bus.amo(hart, 0x80000000, 4, Ordering::SeqCst, |val| val + 1)
+                            .unwrap();
+                        // lr / sc retry loop on a counter in the same granule
+                        loop {
+                            let val = bus.load_reserved(hart, 0x80000008, 8, Ordering::Acquire);
+                            let val = val.unwrap();
+                            if bus.store_cond(hart, 0x80000008, 8, val + 1, Ordering::Release)
+                                == Ok(true)
+                            {
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(bus.read(0x80000000, 4), Ok(4000));
+        assert_eq!(bus.read(0x80000008, 8), Ok(4000));
+        assert!(bus
+            .amo(0, 0x80000002, 4, Ordering::SeqCst, |val| val)
+            .is_err());
+        assert!(bus
+            .load_reserved(0, CLINT_BASE, 4, Ordering::Relaxed)
+            .is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{memory::ram::Ram, utils::Maybe};
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// reservation set size of lr / sc
+pub const RSRV_GRANULE: u64 = 64;
+/// marks a valid reservation in `Bus::rsrv`
+const RSRV_VALID: u64 = 1;
+
+/// memory mapped device\
+/// accesses take `&self` since devices are shared between harts
+pub trait Device: Debug + Send + Sync {
+    /// read `size` (1, 2, 4, 8) bytes at `offset` from device base
+    fn read(&self, offset: u64, size: u8) -> Maybe<u64>;
+    /// write `size` (1, 2, 4, 8) bytes at `offset` from device base
+    fn write(&self, offset: u64, size: u8, data: u64) -> Maybe<()>;
+}
+
+impl<T: Device> Device for Arc<T> {
+    fn read(&self, offset: u64, size: u8) -> Maybe<u64> {
+        self.as_ref().read(offset, size)
+    }
+    fn write(&self, offset: u64, size: u8, data: u64) -> Maybe<()> {
+        self.as_ref().write(offset, size, data)
+    }
+}
+
+#[derive(Debug)]
+struct Mmio {
+    base: u64,
+    size: u64,
+    dev: Box<dyn Device>,
+}
+
+/// physical address space shared by all harts of a machine
+#[derive(Debug)]
+pub struct Bus {
+    ram: Ram,
+    mmio: Vec<Mmio>,
+    /// per hart lr / sc reservation, granule address | `RSRV_VALID`
+    rsrv: Box<[AtomicU64]>,
+    /// value loaded by the last lr of each hart, only touched by its owner
+    rsrv_val: Box<[AtomicU64]>,
+}
+
+impl Bus {
+    pub fn new(nharts: usize, ram_base: u64, ram_size: u64) -> Self {
+        Self {
+            ram: Ram::new(ram_base, ram_size),
+            mmio: Vec::new(),
+            rsrv: (0..nharts).map(|_| AtomicU64::new(0)).collect(),
+            rsrv_val: (0..nharts).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// map device at \[`base`, `base` + `size`)
+    pub fn add_device(&mut self, base: u64, size: u64, dev: Box<dyn Device>) {
+        self.mmio.push(Mmio { base, size, dev });
+    }
+
+    pub fn ram(&self) -> &Ram {
+        &self.ram
+    }
+
+    pub fn nharts(&self) -> usize {
+        self.rsrv.len()
+    }
+
+    fn mmio(&self, addr: u64, size: u8) -> Option<(&Mmio, u64)> {
+        self.mmio.iter().find_map(|mmio| {
+            let off = addr.checked_sub(mmio.base)?;
+            if off + size as u64 <= mmio.size {
+                Some((mmio, off))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// \[`addr`, `addr` + `size`) is backed by ram
+    pub fn in_ram(&self, addr: u64, size: u64) -> bool {
+        self.ram.offset(addr, size).is_some()
+    }
+
+    /// \[`addr`, `addr` + `size`) is backed by ram or a single device
+    pub fn mapped(&self, addr: u64, size: u8) -> bool {
+        self.in_ram(addr, size as u64) || self.mmio(addr, size).is_some()
+    }
+
+    /// copy image into ram at physical address `addr`
+    pub fn load(&self, addr: u64, data: &[u8]) -> Maybe<()> {
+        let off = self.ram.offset(addr, data.len() as u64).ok_or(())?;
+        self.ram.load(off, data);
+        Ok(())
+    }
+
+    /// little endian read of `size` bytes
+    pub fn read(&self, addr: u64, size: u8) -> Maybe<u64> {
+        self.read_ordered(addr, size, Ordering::Relaxed)
+    }
+
+    /// little endian write of `size` bytes by `hart`
+    pub fn write(&self, hart: usize, addr: u64, size: u8, data: u64) -> Maybe<()> {
+        self.write_ordered(hart, addr, size, data, Ordering::Relaxed)
+    }
+
+    /// read with host ordering `ord` on ram, devices order themselves
+    pub fn read_ordered(&self, addr: u64, size: u8, ord: Ordering) -> Maybe<u64> {
+        if let Some(off) = self.ram.offset(addr, size as u64) {
+            return Ok(self.ram.read(off, size, ord));
+        }
+        let (mmio, off) = self.mmio(addr, size).ok_or(())?;
+        mmio.dev.read(off, size)
+    }
+
+    /// write with host ordering `ord` on ram, devices order themselves
+    pub fn write_ordered(
+        &self,
+        hart: usize,
+        addr: u64,
+        size: u8,
+        data: u64,
+        ord: Ordering,
+    ) -> Maybe<()> {
+        if let Some(off) = self.ram.offset(addr, size as u64) {
+            self.ram.write(off, size, data, ord);
+            self.invalidate(hart, addr, size);
+            return Ok(());
+        }
+        let (mmio, off) = self.mmio(addr, size).ok_or(())?;
+        mmio.dev.write(off, size, data)
+    }
+
+    /// offset in ram of a naturally aligned atomic access,
+    /// atomics to devices are not supported
+    fn atomic_offset(&self, addr: u64, size: u8) -> Maybe<usize> {
+        if !addr.is_multiple_of(size as u64) {
+            return Err(());
+        }
+        self.ram.offset(addr, size as u64).ok_or(())
+    }
+
+    /// load `size` bytes and reserve the granule containing `addr` for `hart`
+    pub fn load_reserved(&self, hart: usize, addr: u64, size: u8, ord: Ordering) -> Maybe<u64> {
+        let off = self.atomic_offset(addr, size)?;
+        self.reserve(hart, addr);
+        let val = self.ram.read(off, size, ord);
+        self.rsrv_val[hart].store(val, Ordering::Relaxed);
+        Ok(val)
+    }
+
+    /// store `data` if `hart` still holds a reservation covering `addr`,
+    /// the reservation is consumed either way\
+    /// returns true on success
+    pub fn store_cond(
+        &self,
+        hart: usize,
+        addr: u64,
+        size: u8,
+        data: u64,
+        ord: Ordering,
+    ) -> Maybe<bool> {
+        let off = self.atomic_offset(addr, size)?;
+        if !self.take_reservation(hart, addr) {
+            return Ok(false);
+        }
+        // stores of other harts kill the reservation, but one may land between
+        // the check above and the store below, the compare and swap against
+        // the reserved value catches it (short of ABA)
+        let old = self.rsrv_val[hart].load(Ordering::Relaxed);
+        if self.ram.cas(off, size, old, data, ord).is_err() {
+            return Ok(false);
+        }
+        self.invalidate(hart, addr, size);
+        Ok(true)
+    }
+
+    /// atomic read-modify-write of `size` bytes, returns the old value
+    pub fn amo(
+        &self,
+        hart: usize,
+        addr: u64,
+        size: u8,
+        ord: Ordering,
+        f: impl Fn(u64) -> u64,
+    ) -> Maybe<u64> {
+        let off = self.atomic_offset(addr, size)?;
+        let old = self.ram.fetch_update(off, size, ord, f);
+        self.invalidate(hart, addr, size);
+        Ok(old)
+    }
+
+    /// atomic compare and swap of `size` (up to 16) bytes, returns the old value
+    pub fn amo_cas(
+        &self,
+        hart: usize,
+        addr: u64,
+        size: u8,
+        old: u128,
+        new: u128,
+        ord: Ordering,
+    ) -> Maybe<u128> {
+        let off = self.atomic_offset(addr, size)?;
+        let res = if size == 16 {
+            self.ram.cas128(off, old, new)
+        } else {
+            self.ram
+                .cas(off, size, old as u64, new as u64, ord)
+                .map(|val| val as u128)
+                .map_err(|val| val as u128)
+        };
+        // a failed cas doesn't store
+        if res.is_ok() {
+            self.invalidate(hart, addr, size);
+        }
+        Ok(res.unwrap_or_else(|val| val))
+    }
+
+    /// place reservation of `hart` on the granule containing `addr`
+    pub fn reserve(&self, hart: usize, addr: u64) {
+        let granule = addr & !(RSRV_GRANULE - 1);
+        self.rsrv[hart].store(granule | RSRV_VALID, Ordering::Release);
+    }
+
+    /// drop reservation of `hart`,
+    /// returns true if it was valid and covered `addr`
+    pub fn take_reservation(&self, hart: usize, addr: u64) -> bool {
+        let granule = addr & !(RSRV_GRANULE - 1);
+        self.rsrv[hart].swap(0, Ordering::AcqRel) == granule | RSRV_VALID
+    }
+
+    /// `hart` holds a valid reservation
+    pub fn reserved(&self, hart: usize) -> bool {
+        self.rsrv[hart].load(Ordering::Acquire) & RSRV_VALID != 0
+    }
+
+    /// drop reservation of `hart`
+    pub fn clear_reservation(&self, hart: usize) {
+        self.rsrv[hart].store(0, Ordering::Release);
+    }
+
+    /// a store from `hart` kills reservations of other harts on the same granules
+    fn invalidate(&self, hart: usize, addr: u64, size: u8) {
+        let first = addr & !(RSRV_GRANULE - 1);
+        let last = (addr + size as u64 - 1) & !(RSRV_GRANULE - 1);
+        for (id, rsrv) in self.rsrv.iter().enumerate() {
+            if id == hart {
+                continue;
+            }
+            for granule in [first, last] {
+                let _ = rsrv.compare_exchange(
+                    granule | RSRV_VALID,
+                    0,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                );
+            }
+        }
+    }
+}
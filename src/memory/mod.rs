@@ -1,55 +1,263 @@
-use crate::{hart::Hart, uop::BinaryOp, utils::Maybe, xlen::XlenT};
+use crate::{
+    hart::Hart,
+    uop::{BinaryOp, Exception, MemProtect},
+    utils::Maybe,
+    xlen::{Cast, XlenT},
+};
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{self, Ordering},
+        Arc,
+    },
+};
 
 #[cfg(feature = "A")]
-use crate::uop::MemOrder;
+use crate::{uop::MemOrder, utils::is_aligned};
+
+pub mod bus;
+#[cfg(feature = "S")]
+pub mod paging;
+pub mod ram;
+
+pub use bus::{Bus, Device};
+#[cfg(feature = "S")]
+use paging::PAGE_SIZE;
+
+#[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+use crate::uop::CboOp;
+
+/// exception of a failed translation, and the guest physical address
+/// of a guest-page fault
+pub type Fault = (Exception, u64);
+
+/// cache-block size of cbo.* and prefetch.*
+#[cfg(any(feature = "Zicbom", feature = "Zicboz", feature = "Zicbop"))]
+pub const CACHE_BLOCK: u64 = 64;
+
+/// observer of cache-block operations, eg. a cache model,
+/// called with the physical block address after permission checks
+#[cfg(any(feature = "Zicbom", feature = "Zicboz", feature = "Zicbop"))]
+pub trait CacheModel: Debug + Send + Sync {
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+    fn cbo(&self, hart: usize, op: CboOp, paddr: u64);
+    #[cfg(feature = "Zicbop")]
+    fn prefetch(&self, _hart: usize, _paddr: u64, _prot: MemProtect) {}
+}
 
 /// holds state of memory subsystem  
 #[derive(Debug, Clone, Default)]
 pub struct Mem {
     /// is big endian
     be: bool,
+    /// physical address space, shared with other harts of the machine
+    pub bus: Option<Arc<Bus>>,
+    /// cache model shared with other harts
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz", feature = "Zicbop"))]
+    pub cache: Option<Arc<dyn CacheModel>>,
     #[cfg(test)]
     pub hook_mem: Option<Box<[u8]>>,
 }
 
 impl<Xlen: XlenT> Hart<Xlen> {
+    /// address bits above effective xlen are ignored,
+    /// the fault to raise if it can't be translated
+    fn xlate(&self, addr: Xlen, prot: MemProtect) -> Result<u64, Fault> {
+        let va: u64 = addr.into();
+        let va = if self.xlen() == 32 {
+            va as u32 as u64
+        } else {
+            va
+        };
+        #[cfg(feature = "S")]
+        return self.translate(va, prot);
+        #[cfg(not(feature = "S"))]
+        {
+            let _ = prot;
+            Ok(va)
+        }
+    }
+    fn paddr(&mut self, addr: Xlen, prot: MemProtect) -> Maybe<u64> {
+        match self.xlate(addr, prot) {
+            Ok(paddr) => Ok(paddr),
+            Err(fault) => {
+                self.raise_fault(fault, addr)?;
+                Err(()) // unreachable
+            }
+        }
+    }
+    /// bytes on the first page and physical address of the rest,
+    /// if the access continues on a non-contiguous page
+    #[cfg(feature = "S")]
+    fn page_split(
+        &mut self,
+        addr: Xlen,
+        paddr: u64,
+        size: u8,
+        prot: MemProtect,
+    ) -> Maybe<Option<(u8, u64)>> {
+        let off: u64 = addr.into();
+        let first = (PAGE_SIZE - off % PAGE_SIZE).min(size as u64) as u8;
+        if first == size {
+            return Ok(None);
+        }
+        let next = self.paddr(addr.add(first), prot)?;
+        Ok((next != paddr + first as u64).then_some((first, next)))
+    }
+    /// host ordering of plain loads, acquire under Ztso
+    fn ld_ord(&self) -> Ordering {
+        if self.fe.isa.tso() {
+            Ordering::Acquire
+        } else {
+            Ordering::Relaxed
+        }
+    }
+    /// host ordering of plain stores, release under Ztso
+    fn st_ord(&self) -> Ordering {
+        if self.fe.isa.tso() {
+            Ordering::Release
+        } else {
+            Ordering::Relaxed
+        }
+    }
+    /// Ztso implies aq on lr, rl on sc and aqrl on amos
+    #[cfg(feature = "A")]
+    fn tso_ord(&self, ord: MemOrder, implied: MemOrder) -> MemOrder {
+        if self.fe.isa.tso() {
+            ord.with(implied)
+        } else {
+            ord
+        }
+    }
+    fn phys_rd(&mut self, addr: Xlen, size: u8, prot: MemProtect) -> Maybe<u64> {
+        let paddr = self.paddr(addr, prot)?;
+        // both pages are translated before either is accessed
+        #[cfg(feature = "S")]
+        if let Some((first, next)) = self.page_split(addr, paddr, size, prot)? {
+            let low = self.bus_rd(addr, paddr, first, prot)?;
+            let high = self.bus_rd(addr, next, size - first, prot)?;
+            return Ok(low | high << (first * 8));
+        }
+        self.bus_rd(addr, paddr, size, prot)
+    }
+    fn phys_wr(&mut self, addr: Xlen, size: u8, data: u64) -> Maybe<()> {
+        let paddr = self.paddr(addr, MemProtect::W)?;
+        #[cfg(feature = "S")]
+        if let Some((first, next)) = self.page_split(addr, paddr, size, MemProtect::W)? {
+            self.bus_wr(addr, paddr, first, data)?;
+            return self.bus_wr(addr, next, size - first, data >> (first * 8));
+        }
+        self.bus_wr(addr, paddr, size, data)
+    }
+    /// access fault reports the virtual address `addr`
+    fn bus_rd(&mut self, addr: Xlen, paddr: u64, size: u8, prot: MemProtect) -> Maybe<u64> {
+        let ord = self.ld_ord();
+        match self
+            .mem
+            .bus
+            .as_ref()
+            .map(|bus| bus.read_ordered(paddr, size, ord))
+        {
+            Some(Ok(val)) => Ok(val),
+            _ => {
+                self.raise_with(Exception::AccessFault(prot), addr)?;
+                Err(()) // unreachable
+            }
+        }
+    }
+    fn bus_wr(&mut self, addr: Xlen, paddr: u64, size: u8, data: u64) -> Maybe<()> {
+        let hart = self.priv_ctrl.hart_id;
+        let ord = self.st_ord();
+        match self
+            .mem
+            .bus
+            .as_ref()
+            .map(|bus| bus.write_ordered(hart, paddr, size, data, ord))
+        {
+            Some(Ok(())) => Ok(()),
+            _ => {
+                self.raise_with(Exception::AccessFault(MemProtect::W), addr)?;
+                Err(()) // unreachable
+            }
+        }
+    }
+    fn data_rd(&mut self, addr: Xlen, size: u8) -> Maybe<u64> {
+        let val = self.phys_rd(addr, size, MemProtect::R)?;
+        Ok(self.mem.order(val, size))
+    }
+    fn data_wr(&mut self, addr: Xlen, size: u8, data: u64) -> Maybe<()> {
+        let data = self.mem.order(data, size);
+        self.phys_wr(addr, size, data)
+    }
     pub fn rd_mem8(&mut self, addr: Xlen) -> Maybe<u8> {
-        todo!()
+        Ok(self.data_rd(addr, 1)? as u8)
     }
     pub fn rd_mem16(&mut self, addr: Xlen) -> Maybe<u16> {
-        todo!()
+        Ok(self.data_rd(addr, 2)? as u16)
     }
     pub fn rd_mem32(&mut self, addr: Xlen) -> Maybe<u32> {
         #[cfg(test)]
         if let Ok(res) = self.mem.hook_rd(addr.into()) {
             return Ok(res);
         }
-        todo!()
+        Ok(self.data_rd(addr, 4)? as u32)
     }
     #[cfg(any(feature = "RV64", feature = "D"))]
     pub fn rd_mem64(&mut self, addr: Xlen) -> Maybe<u64> {
-        todo!()
+        self.data_rd(addr, 8)
     }
     pub fn wr_mem8(&mut self, addr: Xlen, data: u8) -> Maybe<()> {
-        todo!()
+        self.data_wr(addr, 1, data as u64)
     }
     pub fn wr_mem16(&mut self, addr: Xlen, data: u16) -> Maybe<()> {
-        todo!()
+        self.data_wr(addr, 2, data as u64)
     }
     pub fn wr_mem32(&mut self, addr: Xlen, data: u32) -> Maybe<()> {
         #[cfg(test)]
         if let Ok(res) = self.mem.hook_wr(addr.into(), data) {
             return Ok(res);
         }
-        todo!()
+        self.data_wr(addr, 4, data as u64)
     }
     #[cfg(any(feature = "RV64", feature = "D"))]
     pub fn wr_mem64(&mut self, addr: Xlen, data: u64) -> Maybe<()> {
-        todo!()
+        self.data_wr(addr, 8, data)
+    }
+    /// two doubleword accesses, not single-copy atomic
+    #[cfg(any(feature = "Q", feature = "RV128"))]
+    pub fn rd_mem128(&mut self, addr: Xlen) -> Maybe<u128> {
+        let first = self.rd_mem64(addr)? as u128;
+        let second = self.rd_mem64(addr.add(8))? as u128;
+        Ok(if self.mem.be {
+            first << 64 | second
+        } else {
+            second << 64 | first
+        })
+    }
+    #[cfg(any(feature = "Q", feature = "RV128"))]
+    pub fn wr_mem128(&mut self, addr: Xlen, data: u128) -> Maybe<()> {
+        let (first, second) = if self.mem.be {
+            (data >> 64, data)
+        } else {
+            (data, data >> 64)
+        };
+        self.wr_mem64(addr, first as u64)?;
+        self.wr_mem64(addr.add(8), second as u64)
     }
     /// assume align 2
+    /// read without raising exceptions, for fault-only-first loads
+    #[cfg(feature = "V")]
+    pub fn try_rd_mem(&mut self, addr: Xlen, size: u8) -> Option<u64> {
+        let paddr = self.xlate(addr, MemProtect::R).ok()?;
+        let last = self.xlate(addr.add(size - 1), MemProtect::R).ok()?;
+        if last != paddr + size as u64 - 1 {
+            return None;
+        }
+        let val = self.mem.bus.as_ref()?.read(paddr, size).ok()?;
+        Some(self.mem.order(val, size))
+    }
     pub fn fetch_mem16(&mut self, addr: Xlen) -> Maybe<u16> {
-        todo!()
+        Ok(self.phys_rd(addr, 2, MemProtect::X)? as u16)
     }
     /// assume align 4
     pub fn fetch_mem32(&mut self, addr: Xlen) -> Maybe<u32> {
@@ -57,45 +265,307 @@ impl<Xlen: XlenT> Hart<Xlen> {
         if let Ok(res) = self.mem.hook_rd(addr.into()) {
             return Ok(res);
         }
-        todo!()
+        Ok(self.phys_rd(addr, 4, MemProtect::X)? as u32)
+    }
+    /// jump table entry of effective xlen
+    #[cfg(feature = "Zcmt")]
+    pub fn fetch_jvt(&mut self, addr: Xlen) -> Maybe<Xlen> {
+        let size = (self.xlen() / 8) as u8;
+        Ok(Xlen::from(self.phys_rd(addr, size, MemProtect::X)?))
+    }
+    /// cache-block operation on the aligned block containing `addr`,
+    /// the block must be mapped, and in ram for cbo.zero
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+    pub fn cbo(&mut self, addr: Xlen, op: CboOp) -> Maybe<()> {
+        let op = match self.cbo_allowed(op) {
+            Ok(op) => op,
+            Err(cause) => return self.raise(cause),
+        };
+        let block = addr & !Xlen::from(CACHE_BLOCK - 1);
+        // cbo.zero stores, the others need load or store permission
+        let paddr = match op {
+            CboOp::Zero => self.xlate(block, MemProtect::W),
+            _ => self
+                .xlate(block, MemProtect::R)
+                .or_else(|_| self.xlate(block, MemProtect::W)),
+        };
+        let paddr = match paddr {
+            Ok(paddr) => paddr,
+            // reported as store faults
+            Err((cause, gpa)) => {
+                return self.raise_fault((cause.with_prot(MemProtect::W), gpa), addr);
+            }
+        };
+        let permitted = self.mem.bus.as_ref().is_some_and(|bus| match op {
+            CboOp::Zero => bus.in_ram(paddr, CACHE_BLOCK),
+            _ => bus.mapped(paddr, CACHE_BLOCK as u8),
+        });
+        if !permitted {
+            return self.raise_with(Exception::AccessFault(MemProtect::W), addr);
+        }
+        if op == CboOp::Zero {
+            for off in (0..CACHE_BLOCK).step_by(8) {
+                self.phys_wr(block.add(off), 8, 0)?;
+            }
+        }
+        if let Some(cache) = self.mem.cache.as_ref() {
+            cache.cbo(self.priv_ctrl.hart_id, op, paddr);
+        }
+        Ok(())
+    }
+    /// hint only, never faults
+    #[cfg(feature = "Zicbop")]
+    pub fn prefetch(&mut self, addr: Xlen, prot: MemProtect) {
+        let block = addr & !Xlen::from(CACHE_BLOCK - 1);
+        let Ok(paddr) = self.xlate(block, prot) else {
+            return;
+        };
+        if let Some(cache) = self.mem.cache.as_ref() {
+            cache.prefetch(self.priv_ctrl.hart_id, paddr, prot);
+        }
     }
     /// page fault & access fault have higher priority then misalign
     pub fn fetch_check(&mut self, addr: Xlen) -> Maybe<()> {
-        todo!()
+        self.phys_rd(addr, 1, MemProtect::X).map(|_| ())
+    }
+
+    /// naturally aligned atomic access to ram through `f(bus, paddr, hart)`,
+    /// misaligned raises address misaligned, device or unmapped raises access fault
+    #[cfg(feature = "A")]
+    fn atomic<T>(
+        &mut self,
+        addr: Xlen,
+        size: u8,
+        prot: MemProtect,
+        f: impl FnOnce(&Bus, u64, usize) -> Maybe<T>,
+    ) -> Maybe<T> {
+        if !is_aligned(addr, size as u32) {
+            self.raise_with(Exception::AddrMisalign(prot), addr)?;
+        }
+        let paddr = self.paddr(addr, prot)?;
+        let hart = self.priv_ctrl.hart_id;
+        match self.mem.bus.as_ref().map(|bus| f(bus, paddr, hart)) {
+            Some(Ok(val)) => Ok(val),
+            _ => {
+                self.raise_with(Exception::AccessFault(prot), addr)?;
+                Err(()) // unreachable
+            }
+        }
+    }
+    /// this hart still holds the reservation of its last lr
+    #[cfg(feature = "Zawrs")]
+    pub fn reserved(&self) -> bool {
+        let hart = self.priv_ctrl.hart_id;
+        self.mem.bus.as_ref().is_some_and(|bus| bus.reserved(hart))
+    }
+    #[cfg(feature = "A")]
+    fn load_rsrv(&mut self, addr: Xlen, size: u8, ord: MemOrder) -> Maybe<u64> {
+        let ord = self.tso_ord(ord, MemOrder::Acquire);
+        let val = self.atomic(addr, size, MemProtect::R, |bus, paddr, hart| {
+            bus.load_reserved(hart, paddr, size, ord.load())
+        })?;
+        Ok(self.mem.order(val, size))
+    }
+    /// 0 on success, 1 on failure
+    #[cfg(feature = "A")]
+    fn store_cond(&mut self, addr: Xlen, size: u8, ord: MemOrder, data: u64) -> Maybe<u64> {
+        let data = self.mem.order(data, size);
+        let ord = self.tso_ord(ord, MemOrder::Release);
+        let ok = self.atomic(addr, size, MemProtect::W, |bus, paddr, hart| {
+            bus.store_cond(hart, paddr, size, data, ord.store())
+        })?;
+        Ok(!ok as u64)
+    }
+    /// returns the old value
+    #[cfg(feature = "A")]
+    fn amo(&mut self, addr: Xlen, size: u8, ord: MemOrder, f: impl Fn(u64) -> u64) -> Maybe<u64> {
+        let be = self.mem.be;
+        let ord = self.tso_ord(ord, MemOrder::AcqRel);
+        let old = self.atomic(addr, size, MemProtect::W, |bus, paddr, hart| {
+            bus.amo(hart, paddr, size, ord.rmw(), |val| {
+                byte_order(be, f(byte_order(be, val, size)), size)
+            })
+        })?;
+        Ok(self.mem.order(old, size))
+    }
+    /// returns the old value, `data` is stored only if it equals `cmp`
+    #[cfg(feature = "Zacas")]
+    fn amo_cas(
+        &mut self,
+        addr: Xlen,
+        size: u8,
+        ord: MemOrder,
+        cmp: u128,
+        data: u128,
+    ) -> Maybe<u128> {
+        let be = self.mem.be;
+        let order = |val: u128| {
+            if be {
+                val.swap_bytes() >> (128 - size as u32 * 8)
+            } else {
+                val
+            }
+        };
+        let (cmp, data) = (order(cmp), order(data));
+        let ord = self.tso_ord(ord, MemOrder::AcqRel);
+        let old = self.atomic(addr, size, MemProtect::W, |bus, paddr, hart| {
+            bus.amo_cas(hart, paddr, size, cmp, data, ord.rmw())
+        })?;
+        Ok(order(old))
     }
 
     #[cfg(feature = "A")]
     pub fn load_rsrv32(&mut self, addr: Xlen, ord: MemOrder) -> Maybe<u32> {
-        todo!()
+        Ok(self.load_rsrv(addr, 4, ord)? as u32)
     }
     #[cfg(all(feature = "A", feature = "RV64"))]
     pub fn load_rsrv64(&mut self, addr: Xlen, ord: MemOrder) -> Maybe<u64> {
-        todo!()
+        self.load_rsrv(addr, 8, ord)
     }
     #[cfg(feature = "A")]
     pub fn store_cond32(&mut self, addr: Xlen, ord: MemOrder, data: u32) -> Maybe<u32> {
-        todo!()
+        Ok(self.store_cond(addr, 4, ord, data as u64)? as u32)
     }
     #[cfg(all(feature = "A", feature = "RV64"))]
     pub fn store_cond64(&mut self, addr: Xlen, ord: MemOrder, data: u64) -> Maybe<u64> {
-        todo!()
+        self.store_cond(addr, 8, ord, data)
+    }
+    #[cfg(feature = "Zabha")]
+    pub fn amo8(&mut self, addr: Xlen, ord: MemOrder, data: u8, op: BinaryOp) -> Maybe<u8> {
+        // sign of the byte is restored for signed min / max
+        let old = self.amo(addr, 1, ord, |old| {
+            op.exec(old as i8 as u32, data as i8 as u32) as u64
+        })?;
+        Ok(old as u8)
+    }
+    #[cfg(feature = "Zabha")]
+    pub fn amo16(&mut self, addr: Xlen, ord: MemOrder, data: u16, op: BinaryOp) -> Maybe<u16> {
+        let old = self.amo(addr, 2, ord, |old| {
+            op.exec(old as i16 as u32, data as i16 as u32) as u64
+        })?;
+        Ok(old as u16)
     }
     #[cfg(feature = "A")]
     pub fn amo32(&mut self, addr: Xlen, ord: MemOrder, data: u32, op: BinaryOp) -> Maybe<u32> {
-        todo!()
+        let old = self.amo(addr, 4, ord, |old| op.exec(old as u32, data) as u64)?;
+        Ok(old as u32)
     }
     #[cfg(all(feature = "A", feature = "RV64"))]
     pub fn amo64(&mut self, addr: Xlen, ord: MemOrder, data: u64, op: BinaryOp) -> Maybe<u64> {
-        todo!()
+        self.amo(addr, 8, ord, |old| op.exec(old, data))
     }
 
-    pub fn fence(&mut self, pred: u8, succ: u8) {}
-    pub fn fence_tso(&mut self) {}
+    #[cfg(all(feature = "Zacas", feature = "Zabha"))]
+    pub fn amo_cas8(&mut self, addr: Xlen, ord: MemOrder, cmp: u8, data: u8) -> Maybe<u8> {
+        Ok(self.amo_cas(addr, 1, ord, cmp as u128, data as u128)? as u8)
+    }
+    #[cfg(all(feature = "Zacas", feature = "Zabha"))]
+    pub fn amo_cas16(&mut self, addr: Xlen, ord: MemOrder, cmp: u16, data: u16) -> Maybe<u16> {
+        Ok(self.amo_cas(addr, 2, ord, cmp as u128, data as u128)? as u16)
+    }
+    #[cfg(feature = "Zacas")]
+    pub fn amo_cas32(&mut self, addr: Xlen, ord: MemOrder, cmp: u32, data: u32) -> Maybe<u32> {
+        Ok(self.amo_cas(addr, 4, ord, cmp as u128, data as u128)? as u32)
+    }
+    #[cfg(feature = "Zacas")]
+    pub fn amo_cas64(&mut self, addr: Xlen, ord: MemOrder, cmp: u64, data: u64) -> Maybe<u64> {
+        Ok(self.amo_cas(addr, 8, ord, cmp as u128, data as u128)? as u64)
+    }
+    #[cfg(all(feature = "Zacas", feature = "RV64"))]
+    pub fn amo_cas128(&mut self, addr: Xlen, ord: MemOrder, cmp: u128, data: u128) -> Maybe<u128> {
+        self.amo_cas(addr, 16, ord, cmp, data)
+    }
+
+    /// `pred` / `succ` are iorw bit sets,
+    /// io is treated as rw since devices are accessed through host atomics
+    pub fn fence(&mut self, pred: u8, succ: u8) {
+        let rd = |set: u8| set & 0b1010 != 0;
+        let wr = |set: u8| set & 0b0101 != 0;
+        let ord = if wr(pred) && rd(succ) {
+            // only a full fence orders store -> load
+            Ordering::SeqCst
+        } else if self.fe.isa.tso() {
+            // everything else is implied by acquire loads and release stores
+            return;
+        } else {
+            match (rd(pred), wr(succ)) {
+                (true, true) => Ordering::AcqRel,
+                (true, false) => Ordering::Acquire,
+                (false, true) => Ordering::Release,
+                (false, false) => return,
+            }
+        };
+        atomic::fence(ord);
+    }
+    /// r -> rw and w -> w, store -> load may be reordered,
+    /// implied by plain accesses under Ztso
+    pub fn fence_tso(&mut self) {
+        if !self.fe.isa.tso() {
+            atomic::fence(Ordering::AcqRel);
+        }
+    }
+    /// stores of other harts are only synchronized with
+    /// instruction fetch of this hart after fence.i
     #[cfg(feature = "Zifencei")]
-    pub fn fence_i(&mut self) {}
+    pub fn fence_i(&mut self) {
+        self.fe.flush();
+    }
+}
+
+/// swap little endian `size` bytes value if big endian
+fn byte_order(be: bool, val: u64, size: u8) -> u64 {
+    if be {
+        val.swap_bytes() >> (64 - size as u32 * 8)
+    } else {
+        val
+    }
+}
+
+#[cfg(feature = "A")]
+impl MemOrder {
+    /// host ordering of the load part, eg. lr\
+    /// rl on a load has no host equivalent, strengthened to seqcst
+    pub fn load(self) -> Ordering {
+        match self {
+            MemOrder::Relaxed => Ordering::Relaxed,
+            MemOrder::Acquire => Ordering::Acquire,
+            MemOrder::Release | MemOrder::AcqRel => Ordering::SeqCst,
+        }
+    }
+    /// host ordering of the store part, eg. sc\
+    /// aq on a store has no host equivalent, strengthened to seqcst
+    pub fn store(self) -> Ordering {
+        match self {
+            MemOrder::Relaxed => Ordering::Relaxed,
+            MemOrder::Release => Ordering::Release,
+            MemOrder::Acquire | MemOrder::AcqRel => Ordering::SeqCst,
+        }
+    }
+    /// host ordering of read-modify-write, eg. amo\
+    /// aqrl is sequentially consistent
+    pub fn rmw(self) -> Ordering {
+        match self {
+            MemOrder::Relaxed => Ordering::Relaxed,
+            MemOrder::Release => Ordering::Release,
+            MemOrder::Acquire => Ordering::Acquire,
+            MemOrder::AcqRel => Ordering::SeqCst,
+        }
+    }
+    /// union of both annotations
+    pub fn with(self, other: MemOrder) -> MemOrder {
+        match (self, other) {
+            (MemOrder::Relaxed, ord) | (ord, MemOrder::Relaxed) => ord,
+            (lhs, rhs) if lhs == rhs => lhs,
+            _ => MemOrder::AcqRel,
+        }
+    }
 }
 
 impl Mem {
+    /// data access byte order
+    fn order(&self, val: u64, size: u8) -> u64 {
+        byte_order(self.be, val, size)
+    }
     #[cfg(test)]
     fn hook_rd(&self, addr: usize) -> Maybe<u32> {
         if let Some(mem_region) = self.hook_mem.as_ref() {
@@ -121,3 +591,261 @@ impl Mem {
         Err(())
     }
 }
+
+#[cfg(all(test, feature = "A", feature = "RV64"))]
+mod tests {
+    use super::*;
+    use crate::machine::{Machine, CLINT_BASE, RAM_BASE};
+    use bus::RSRV_GRANULE;
+
+    const ADDR: u64 = RAM_BASE + 0x100;
+
+    fn machine() -> Machine<u64> {
+        Machine::<u64>::new(2, 0x1000)
+    }
+
+    #[test]
+    fn lr_sc() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        bus.write(0, ADDR, 4, 0xdead_beef).unwrap();
+        assert_eq!(hart.load_rsrv32(ADDR, MemOrder::Acquire), Ok(0xdead_beef));
+        assert_eq!(hart.store_cond32(ADDR, MemOrder::Release, 1), Ok(0));
+        assert_eq!(bus.read(ADDR, 4), Ok(1));
+        // reservation is consumed by sc
+        assert_eq!(hart.store_cond32(ADDR, MemOrder::Relaxed, 2), Ok(1));
+        assert_eq!(bus.read(ADDR, 4), Ok(1));
+        // sc outside of the reserved granule
+        hart.load_rsrv32(ADDR, MemOrder::Relaxed).unwrap();
+        let other = ADDR + RSRV_GRANULE;
+        assert_eq!(hart.store_cond32(other, MemOrder::Relaxed, 3), Ok(1));
+        assert_eq!(bus.read(other, 4), Ok(0));
+        // store of the same hart keeps the reservation
+        hart.load_rsrv64(ADDR, MemOrder::AcqRel).unwrap();
+        hart.wr_mem32(ADDR + 8, 5).unwrap();
+        assert_eq!(hart.store_cond64(ADDR, MemOrder::AcqRel, 4), Ok(0));
+        assert_eq!(bus.read(ADDR, 8), Ok(4));
+    }
+
+    #[cfg(feature = "Ztso")]
+    #[test]
+    fn ztso() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        assert_eq!(hart.ld_ord(), Ordering::Relaxed);
+        assert_eq!(hart.st_ord(), Ordering::Relaxed);
+        let ord = hart.tso_ord(MemOrder::Relaxed, MemOrder::AcqRel);
+        assert_eq!(ord, MemOrder::Relaxed);
+        hart.fe.isa.Ztso = true;
+        assert_eq!(hart.ld_ord(), Ordering::Acquire);
+        assert_eq!(hart.st_ord(), Ordering::Release);
+        // annotations of lr / sc / amo are strengthened, never weakened
+        let ord = hart.tso_ord(MemOrder::Relaxed, MemOrder::Acquire);
+        assert_eq!(ord, MemOrder::Acquire);
+        let ord = hart.tso_ord(MemOrder::Acquire, MemOrder::Release);
+        assert_eq!(ord, MemOrder::AcqRel);
+        let ord = hart.tso_ord(MemOrder::AcqRel, MemOrder::Release);
+        assert_eq!(ord, MemOrder::AcqRel);
+        // ordered accesses, misaligned included
+        hart.wr_mem32(ADDR + 2, 0x1234_5678).unwrap();
+        assert_eq!(hart.rd_mem32(ADDR + 2), Ok(0x1234_5678));
+        assert_eq!(bus.read(ADDR, 8), Ok(0x1234_5678_0000));
+        let old = hart.amo32(ADDR + 4, MemOrder::Relaxed, 1, BinaryOp::Add);
+        assert_eq!(old, Ok(0x1234));
+    }
+
+    #[test]
+    fn lr_sc_invalidate() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        // conflicting store of another hart anywhere in the granule
+        machine.harts[0]
+            .load_rsrv32(ADDR, MemOrder::Relaxed)
+            .unwrap();
+        machine.harts[1]
+            .wr_mem8(ADDR + RSRV_GRANULE - 1, 1)
+            .unwrap();
+        assert_eq!(
+            machine.harts[0].store_cond32(ADDR, MemOrder::Relaxed, 1),
+            Ok(1)
+        );
+        assert_eq!(bus.read(ADDR, 4), Ok(0));
+        // amo of another hart
+        machine.harts[0]
+            .load_rsrv32(ADDR, MemOrder::Relaxed)
+            .unwrap();
+        machine.harts[1]
+            .amo32(ADDR + 8, MemOrder::Relaxed, 1, BinaryOp::Add)
+            .unwrap();
+        assert_eq!(
+            machine.harts[0].store_cond32(ADDR, MemOrder::Relaxed, 1),
+            Ok(1)
+        );
+        // trap between lr and sc
+        let hart = &mut machine.harts[0];
+        hart.load_rsrv32(ADDR, MemOrder::Relaxed).unwrap();
+        assert!(hart.raise(Exception::Ecall).is_err());
+        assert_eq!(hart.store_cond32(ADDR, MemOrder::Relaxed, 1), Ok(1));
+        assert_eq!(bus.read(ADDR, 4), Ok(0));
+    }
+
+    #[test]
+    fn amo() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        let cases = [
+            (BinaryOp::Second, 5, 7, 7),
+            (BinaryOp::Add, 5, 7, 12),
+            (BinaryOp::Xor, 0b1100, 0b1010, 0b0110),
+            (BinaryOp::And, 0b1100, 0b1010, 0b1000),
+            (BinaryOp::Or, 0b1100, 0b1010, 0b1110),
+            (BinaryOp::Min, -1i64 as u64, 1, -1i64 as u64),
+            (BinaryOp::Max, -1i64 as u64, 1, 1),
+            (BinaryOp::MinU, -1i64 as u64, 1, 1),
+            (BinaryOp::MaxU, -1i64 as u64, 1, -1i64 as u64),
+        ];
+        for (op, old, data, res) in cases {
+            bus.write(0, ADDR, 8, old).unwrap();
+            assert_eq!(hart.amo64(ADDR, MemOrder::AcqRel, data, op), Ok(old));
+            assert_eq!(bus.read(ADDR, 8), Ok(res));
+            // word ops compare the low 32 bits, upper word is untouched
+            bus.write(0, ADDR + 8, 8, old as u32 as u64).unwrap();
+            let old = old as u32;
+            assert_eq!(
+                hart.amo32(ADDR + 8, MemOrder::Relaxed, data as u32, op),
+                Ok(old)
+            );
+            assert_eq!(bus.read(ADDR + 8, 8), Ok(res as u32 as u64));
+        }
+    }
+
+    #[test]
+    fn misaligned() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        let check = |hart: &mut Hart<u64>, code: u64, tval: u64| {
+            assert_eq!(hart.priv_ctrl.mcause, code);
+            assert_eq!(hart.priv_ctrl.mtval, tval);
+        };
+        assert!(hart.load_rsrv32(ADDR + 2, MemOrder::Relaxed).is_err());
+        check(hart, 4, ADDR + 2);
+        assert!(hart.load_rsrv64(ADDR + 4, MemOrder::Relaxed).is_err());
+        check(hart, 4, ADDR + 4);
+        // misaligned sc faults even without reservation
+        assert!(hart.store_cond32(ADDR + 1, MemOrder::Relaxed, 1).is_err());
+        check(hart, 6, ADDR + 1);
+        assert!(hart
+            .amo64(ADDR + 4, MemOrder::Relaxed, 1, BinaryOp::Add)
+            .is_err());
+        check(hart, 6, ADDR + 4);
+        assert_eq!(bus.read(ADDR, 8), Ok(0));
+        // atomics to devices are not supported
+        assert!(hart
+            .amo32(CLINT_BASE, MemOrder::Relaxed, 1, BinaryOp::Or)
+            .is_err());
+        check(hart, 7, CLINT_BASE);
+        assert!(hart.load_rsrv32(CLINT_BASE, MemOrder::Relaxed).is_err());
+        check(hart, 5, CLINT_BASE);
+    }
+
+    #[test]
+    #[cfg(feature = "Zabha")]
+    fn amo_byte_half() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        bus.write(0, ADDR, 8, 0x1122_3344_5566_7780).unwrap();
+        // neighbouring bytes are untouched
+        assert_eq!(
+            hart.amo8(ADDR, MemOrder::Relaxed, 0x01, BinaryOp::Max),
+            Ok(0x80)
+        );
+        assert_eq!(bus.read(ADDR, 8), Ok(0x1122_3344_5566_7701));
+        assert_eq!(
+            hart.amo8(ADDR + 1, MemOrder::Relaxed, 0x90, BinaryOp::MaxU),
+            Ok(0x77)
+        );
+        assert_eq!(bus.read(ADDR, 8), Ok(0x1122_3344_5566_9001));
+        assert_eq!(
+            hart.amo16(ADDR + 2, MemOrder::AcqRel, 0x8000, BinaryOp::Min),
+            Ok(0x5566)
+        );
+        assert_eq!(
+            hart.amo16(ADDR + 6, MemOrder::AcqRel, 0x0001, BinaryOp::Add),
+            Ok(0x1122)
+        );
+        assert_eq!(bus.read(ADDR, 8), Ok(0x1123_3344_8000_9001));
+        assert!(hart
+            .amo16(ADDR + 1, MemOrder::Relaxed, 0, BinaryOp::Add)
+            .is_err());
+        assert_eq!(hart.priv_ctrl.mcause, 6);
+    }
+
+    #[test]
+    #[cfg(feature = "Zacas")]
+    fn amo_cas() {
+        let mut machine = machine();
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        bus.write(0, ADDR, 4, 5).unwrap();
+        // mismatch returns the old value without storing
+        assert_eq!(hart.amo_cas32(ADDR, MemOrder::Relaxed, 4, 9), Ok(5));
+        assert_eq!(bus.read(ADDR, 4), Ok(5));
+        assert_eq!(hart.amo_cas32(ADDR, MemOrder::AcqRel, 5, 9), Ok(5));
+        assert_eq!(bus.read(ADDR, 4), Ok(9));
+        assert_eq!(hart.amo_cas64(ADDR, MemOrder::AcqRel, 9, !0), Ok(9));
+        assert_eq!(bus.read(ADDR, 8), Ok(!0));
+        let (old, new) = (!0u64 as u128, 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        assert_eq!(hart.amo_cas128(ADDR, MemOrder::AcqRel, old, new), Ok(old));
+        assert_eq!(bus.read(ADDR, 8), Ok(0xfedc_ba98_7654_3210));
+        assert_eq!(bus.read(ADDR + 8, 8), Ok(0x0123_4567_89ab_cdef));
+        assert_eq!(hart.amo_cas128(ADDR, MemOrder::Relaxed, old, 0), Ok(new));
+        assert_eq!(bus.read(ADDR + 8, 8), Ok(0x0123_4567_89ab_cdef));
+        // successful cas kills reservations of other harts, failed one doesn't
+        let (hart0, hart1) = machine.harts.split_at_mut(1);
+        hart1[0].load_rsrv32(ADDR, MemOrder::Relaxed).unwrap();
+        hart0[0]
+            .amo_cas32(ADDR, MemOrder::Relaxed, 0x7654_3210, 1)
+            .unwrap();
+        assert_eq!(hart1[0].store_cond32(ADDR, MemOrder::Relaxed, 2), Ok(1));
+        hart1[0].load_rsrv32(ADDR, MemOrder::Relaxed).unwrap();
+        hart0[0].amo_cas32(ADDR, MemOrder::Relaxed, 0, 3).unwrap();
+        assert_eq!(hart1[0].store_cond32(ADDR, MemOrder::Relaxed, 2), Ok(0));
+        assert!(hart0[0]
+            .amo_cas128(ADDR + 8, MemOrder::Relaxed, 0, 0)
+            .is_err());
+        assert_eq!(hart0[0].priv_ctrl.mcause, 6);
+    }
+
+    #[test]
+    #[cfg(feature = "Zacas")]
+    fn amocas_pair() {
+        use crate::uop::{Instr, MemWidth};
+        let mut machine = Machine::<u32>::new(1, 0x1000);
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        bus.write(0, ADDR, 8, 0x1111_2222_3333_4444).unwrap();
+        hart.gprs[10] = ADDR as u32;
+        (hart.gprs[12], hart.gprs[13]) = (0x3333_4444, 0x1111_2222);
+        (hart.gprs[14], hart.gprs[15]) = (0x5555_6666, 0x7777_8888);
+        let cas = Instr::AmoCas(12, 10, 14, MemOrder::AcqRel, MemWidth::D);
+        let _ = cas.exec(hart);
+        assert_eq!(bus.read(ADDR, 8), Ok(0x7777_8888_5555_6666));
+        assert_eq!((hart.gprs[12], hart.gprs[13]), (0x3333_4444, 0x1111_2222));
+        // x0 pairs read zero, not x1
+        hart.gprs[1] = 0xdead_beef;
+        (hart.gprs[12], hart.gprs[13]) = (0x5555_6666, 0x7777_8888);
+        let cas = Instr::AmoCas(12, 10, 0, MemOrder::AcqRel, MemWidth::D);
+        let _ = cas.exec(hart);
+        assert_eq!(bus.read(ADDR, 8), Ok(0));
+        // x0 pair compares against zero and discards the old value
+        let cas = Instr::AmoCas(0, 10, 14, MemOrder::AcqRel, MemWidth::D);
+        let _ = cas.exec(hart);
+        assert_eq!(hart.gprs[1], 0xdead_beef);
+        assert_eq!(bus.read(ADDR, 8), Ok(0x7777_8888_5555_6666));
+    }
+}
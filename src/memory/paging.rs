@@ -0,0 +1,563 @@
+use super::Fault;
+use crate::{
+    hart::Hart,
+    privilege::*,
+    uop::{Exception, MemProtect},
+    xlen::XlenT,
+};
+
+pub const PAGE_SIZE: u64 = 4096;
+
+// pte fields
+const PTE_V: u64 = 1 << 0;
+const PTE_R: u64 = 1 << 1;
+const PTE_W: u64 = 1 << 2;
+const PTE_X: u64 = 1 << 3;
+const PTE_U: u64 = 1 << 4;
+const PTE_A: u64 = 1 << 6;
+const PTE_D: u64 = 1 << 7;
+/// N, PBMT and reserved bits of Sv39 / Sv48, no Svnapot / Svpbmt
+const PTE_RSVD: u64 = 0x3ff << 54;
+
+/// translation scheme in satp.MODE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmMode {
+    Bare,
+    Sv32,
+    Sv39,
+    Sv48,
+}
+
+impl VmMode {
+    /// scheme of satp at SXL `sxlen`, `None` if unsupported,
+    /// rv128 uses the rv64 layout
+    pub fn from_satp(satp: u64, sxlen: u32) -> Option<Self> {
+        if sxlen == 32 {
+            return Some(if satp >> 31 & 1 != 0 {
+                VmMode::Sv32
+            } else {
+                VmMode::Bare
+            });
+        }
+        match satp >> 60 {
+            0 => Some(VmMode::Bare),
+            8 => Some(VmMode::Sv39),
+            9 => Some(VmMode::Sv48),
+            _ => None,
+        }
+    }
+    fn levels(self) -> u32 {
+        match self {
+            VmMode::Bare => 0,
+            VmMode::Sv32 => 2,
+            VmMode::Sv39 => 3,
+            VmMode::Sv48 => 4,
+        }
+    }
+    fn vpn_bits(self) -> u32 {
+        if self == VmMode::Sv32 {
+            10
+        } else {
+            9
+        }
+    }
+    fn pte_size(self) -> u8 {
+        if self == VmMode::Sv32 {
+            4
+        } else {
+            8
+        }
+    }
+    fn ppn_mask(self) -> u64 {
+        if self == VmMode::Sv32 {
+            (1 << 22) - 1
+        } else {
+            (1 << 44) - 1
+        }
+    }
+}
+
+/// satp after WARL legalization, `None` keeps the old value,
+/// ASID is read-only zero without a TLB
+pub fn satp_warl(satp: u64, sxlen: u32) -> Option<u64> {
+    let vm = VmMode::from_satp(satp, sxlen)?;
+    let (mode, ppn) = if sxlen == 32 {
+        (satp & 1 << 31, satp & VmMode::Sv32.ppn_mask())
+    } else {
+        (satp & 0xf << 60, satp & VmMode::Sv39.ppn_mask())
+    };
+    Some(if vm == VmMode::Bare { 0 } else { mode | ppn })
+}
+
+/// one stage of translation
+#[derive(Debug, Clone, Copy)]
+struct Stage {
+    vm: VmMode,
+    /// physical address of the root table
+    root: u64,
+    /// privilege the leaf permissions are checked against
+    mode: PrivMode,
+    /// S-mode may load / store user pages
+    sum: bool,
+    /// executable pages are readable
+    mxr: bool,
+    /// G-stage, translates guest physical addresses
+    #[cfg(feature = "H")]
+    guest: bool,
+    /// hlvx reads need execute permission instead
+    #[cfg(feature = "H")]
+    hlvx: bool,
+}
+
+impl Stage {
+    fn permits(&self, pte: u64, prot: MemProtect) -> bool {
+        let user = pte & PTE_U != 0;
+        let mode_ok = match self.mode {
+            PrivMode::U => user,
+            // S-mode never executes user pages
+            _ => !user || self.sum && prot != MemProtect::X,
+        };
+        #[cfg(feature = "H")]
+        let prot = if self.hlvx && prot == MemProtect::R {
+            MemProtect::X
+        } else {
+            prot
+        };
+        mode_ok
+            && match prot {
+                MemProtect::R => pte & PTE_R != 0 || self.mxr && pte & PTE_X != 0,
+                MemProtect::W => pte & PTE_W != 0,
+                MemProtect::X => pte & PTE_X != 0,
+            }
+    }
+    /// extra index bits at the root of the G-stage
+    fn root_bits(&self) -> u32 {
+        #[cfg(feature = "H")]
+        if self.guest {
+            return 2;
+        }
+        0
+    }
+    /// page fault, a guest-page fault with the address of the G-stage
+    fn fault(&self, prot: MemProtect, addr: u64) -> Fault {
+        #[cfg(feature = "H")]
+        if self.guest {
+            return (Exception::GuestPageFault(prot), addr);
+        }
+        (Exception::PageFault(prot), addr)
+    }
+}
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    /// physical address of `va` through satp, or vsatp and hgatp in
+    /// VS / VU-mode, the fault to raise on failure,
+    /// loads and stores of M-mode with MPRV are translated at MPP
+    pub fn translate(&self, va: u64, prot: MemProtect) -> Result<u64, Fault> {
+        let p = &self.priv_ctrl;
+        let mstatus: u64 = p.mstatus.into();
+        let mode = if p.mode == PrivMode::M && prot != MemProtect::X && mstatus & MSTATUS_MPRV != 0
+        {
+            let mpp = (mstatus & MSTATUS_MPP) >> MSTATUS_MPP_SHIFT;
+            PrivMode::from_bits(mpp as u32).unwrap_or(PrivMode::M)
+        } else {
+            p.mode
+        };
+        #[cfg(feature = "H")]
+        if let Some(mode) = self.guest_mode(mode) {
+            return self.translate_guest(va, prot, mode);
+        }
+        let satp: u64 = p.satp.into();
+        let vm = VmMode::from_satp(satp, Xlen::XLEN).unwrap_or(VmMode::Bare);
+        if mode == PrivMode::M || vm == VmMode::Bare {
+            return Ok(va);
+        }
+        let stage = Stage {
+            vm,
+            root: (satp & vm.ppn_mask()) * PAGE_SIZE,
+            mode,
+            sum: mstatus & MSTATUS_SUM != 0,
+            mxr: mstatus & MSTATUS_MXR != 0,
+            #[cfg(feature = "H")]
+            guest: false,
+            #[cfg(feature = "H")]
+            hlvx: false,
+        };
+        self.walk(stage, va, prot, None)
+    }
+
+    /// privilege of an access through two stages: SPVP for hlv / hsv,
+    /// MPP with MPV under MPRV, or any with V=1
+    #[cfg(feature = "H")]
+    fn guest_mode(&self, mode: PrivMode) -> Option<PrivMode> {
+        let p = &self.priv_ctrl;
+        let hstatus: u64 = p.hstatus.into();
+        if p.hlsv.is_some() {
+            return Some(if hstatus & HSTATUS_SPVP != 0 {
+                PrivMode::S
+            } else {
+                PrivMode::U
+            });
+        }
+        let virt = if p.mode == PrivMode::M {
+            mode != PrivMode::M && p.mstatush & MSTATUSH_MPV != 0
+        } else {
+            p.virt
+        };
+        virt.then_some(mode)
+    }
+
+    /// VS-stage through vsatp, its tables and result through hgatp,
+    /// the G-stage checks all accesses as U-mode
+    #[cfg(feature = "H")]
+    fn translate_guest(&self, va: u64, prot: MemProtect, mode: PrivMode) -> Result<u64, Fault> {
+        let p = &self.priv_ctrl;
+        let mstatus: u64 = p.mstatus.into();
+        let vsstatus: u64 = p.vsstatus.into();
+        let hlvx = p.hlsv == Some(true);
+        let hgatp: u64 = p.hgatp.into();
+        let vm = VmMode::from_satp(hgatp, Xlen::XLEN).unwrap_or(VmMode::Bare);
+        let g = (vm != VmMode::Bare).then_some(Stage {
+            vm,
+            root: (hgatp & vm.ppn_mask()) * PAGE_SIZE,
+            mode: PrivMode::U,
+            sum: false,
+            mxr: mstatus & MSTATUS_MXR != 0,
+            guest: true,
+            hlvx: false,
+        });
+        let vsatp: u64 = p.vsatp.into();
+        let vm = VmMode::from_satp(vsatp, Xlen::XLEN).unwrap_or(VmMode::Bare);
+        let gpa = if vm == VmMode::Bare {
+            va
+        } else {
+            let vs = Stage {
+                vm,
+                root: (vsatp & vm.ppn_mask()) * PAGE_SIZE,
+                mode,
+                sum: vsstatus & MSTATUS_SUM != 0,
+                mxr: (mstatus | vsstatus) & MSTATUS_MXR != 0,
+                guest: false,
+                hlvx,
+            };
+            self.walk(vs, va, prot, g.as_ref())?
+        };
+        match g {
+            Some(g) => self.walk(Stage { hlvx, ..g }, gpa, prot, None),
+            None => Ok(gpa),
+        }
+    }
+
+    /// page-table walk of one stage, under Svade
+    /// A / D are never set by hardware, the access faults instead,
+    /// tables of the VS-stage are read through the G-stage `g`
+    fn walk(&self, st: Stage, va: u64, prot: MemProtect, g: Option<&Stage>) -> Result<u64, Fault> {
+        let fault = st.fault(prot, va);
+        let access = (Exception::AccessFault(prot), va);
+        let vm = st.vm;
+        let (vpn_bits, size) = (vm.vpn_bits(), vm.pte_size());
+        let root_bits = st.root_bits();
+        let va_bits = 12 + vm.levels() * vpn_bits;
+        if root_bits != 0 {
+            // guest physical addresses are zero-extended
+            if va >> (va_bits + root_bits) != 0 {
+                return Err(fault);
+            }
+        } else if vm != VmMode::Sv32 {
+            // bits above the virtual address must equal its msb
+            let high = va as i64 >> (va_bits - 1);
+            if high != 0 && high != -1 {
+                return Err(fault);
+            }
+        }
+        let bus = self.mem.bus.as_ref().ok_or(access)?;
+        let mut table = st.root;
+        for level in (0..vm.levels()).rev() {
+            let shift = 12 + level * vpn_bits;
+            let bits = if level == vm.levels() - 1 {
+                vpn_bits + root_bits
+            } else {
+                vpn_bits
+            };
+            let vpn = (va >> shift) & ((1 << bits) - 1);
+            let addr = table + vpn * size as u64;
+            // implicit reads fault as the original access
+            let addr = match g {
+                Some(g) => self
+                    .walk(*g, addr, MemProtect::R, None)
+                    .map_err(|(cause, gpa)| (cause.with_prot(prot), gpa))?,
+                None => addr,
+            };
+            let pte = bus.read(addr, size).map_err(|_| access)?;
+            let pte = self.mem.order(pte, size);
+            let rsvd = if vm == VmMode::Sv32 { 0 } else { PTE_RSVD };
+            if pte & PTE_V == 0 || pte & (PTE_R | PTE_W) == PTE_W || pte & rsvd != 0 {
+                return Err(fault);
+            }
+            let ppn = (pte >> 10) & vm.ppn_mask();
+            if pte & (PTE_R | PTE_X) == 0 {
+                // pointer to the next level, A / D / U are reserved
+                if pte & (PTE_A | PTE_D | PTE_U) != 0 {
+                    return Err(fault);
+                }
+                table = ppn * PAGE_SIZE;
+                continue;
+            }
+            if !st.permits(pte, prot) {
+                return Err(fault);
+            }
+            // superpages must be aligned
+            let low = (1 << (shift - 12)) - 1;
+            if ppn & low != 0 {
+                return Err(fault);
+            }
+            if pte & PTE_A == 0 || prot == MemProtect::W && pte & PTE_D == 0 {
+                return Err(fault);
+            }
+            return Ok((ppn & !low) << 12 | va & ((1 << shift) - 1));
+        }
+        // no leaf at the last level
+        Err(fault)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::{Machine, RAM_BASE};
+
+    const ROOT: u64 = RAM_BASE + 0x1000;
+    const DATA: u64 = RAM_BASE + 0x8000;
+    const VA: u64 = 0x4000_0000;
+    const AD: u64 = PTE_A | PTE_D | PTE_V;
+
+    fn pte(pa: u64, flags: u64) -> u64 {
+        (pa >> 12) << 10 | flags
+    }
+
+    #[cfg(feature = "RV64")]
+    #[test]
+    fn sv39() {
+        let mut machine = Machine::<u64>::new(1, 0x10000);
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        let tables = [
+            (ROOT + 8, pte(RAM_BASE + 0x2000, PTE_V)),
+            (ROOT + 16, pte(RAM_BASE, PTE_R | PTE_W | PTE_X | AD)),
+            (RAM_BASE + 0x2000, pte(RAM_BASE + 0x3000, PTE_V)),
+            (RAM_BASE + 0x2008, pte(DATA, PTE_R | PTE_U | AD)),
+            (RAM_BASE + 0x3000, pte(DATA, PTE_R | PTE_W | PTE_U | AD)),
+            (RAM_BASE + 0x3008, pte(DATA + 0x2000, PTE_R | PTE_U | AD)),
+            (RAM_BASE + 0x3010, pte(DATA + 0x3000, PTE_X | PTE_U | AD)),
+            (
+                RAM_BASE + 0x3020,
+                pte(DATA, PTE_R | PTE_W | PTE_U | PTE_A | PTE_V),
+            ),
+            (RAM_BASE + 0x3028, pte(DATA, PTE_R | PTE_U | PTE_V)),
+        ];
+        for (addr, pte) in tables {
+            bus.write(0, addr, 8, pte).unwrap();
+        }
+        bus.write(0, DATA, 8, 0x1122_3344_5566_7788).unwrap();
+        hart.priv_ctrl.satp = 8 << 60 | ROOT >> 12;
+        let check = |hart: &mut Hart<u64>, code: u64, tval: u64| {
+            assert_eq!(hart.priv_ctrl.mcause, code);
+            assert_eq!(hart.priv_ctrl.mtval, tval);
+        };
+
+        // identity gigapage of S-mode
+        hart.priv_ctrl.mode = PrivMode::S;
+        assert_eq!(hart.rd_mem64(DATA), Ok(0x1122_3344_5566_7788));
+        // user pages with SUM only, never executed by S-mode
+        assert!(hart.rd_mem64(VA).is_err());
+        check(hart, 13, VA);
+        hart.priv_ctrl.mode = PrivMode::S;
+        hart.priv_ctrl.mstatus |= MSTATUS_SUM;
+        assert_eq!(hart.rd_mem64(VA), Ok(0x1122_3344_5566_7788));
+        assert!(hart.fetch_mem32(VA + 0x2000).is_err());
+        check(hart, 12, VA + 0x2000);
+
+        hart.priv_ctrl.mode = PrivMode::U;
+        assert!(hart.rd_mem8(DATA).is_err());
+        check(hart, 13, DATA);
+        hart.priv_ctrl.mode = PrivMode::U;
+        hart.wr_mem32(VA + 4, 0xaabb_ccdd).unwrap();
+        assert_eq!(bus.read(DATA + 4, 4), Ok(0xaabb_ccdd));
+        assert!(hart.wr_mem8(VA + 0x1000, 0).is_err());
+        check(hart, 15, VA + 0x1000);
+        // execute-only page is readable with MXR
+        hart.priv_ctrl.mode = PrivMode::U;
+        assert_eq!(hart.fetch_mem32(VA + 0x2000), Ok(0));
+        assert!(hart.rd_mem8(VA + 0x2000).is_err());
+        check(hart, 13, VA + 0x2000);
+        hart.priv_ctrl.mode = PrivMode::U;
+        hart.priv_ctrl.mstatus |= MSTATUS_MXR;
+        assert_eq!(hart.rd_mem8(VA + 0x2000), Ok(0));
+        // invalid, misaligned superpage, clean, not accessed, not canonical
+        let faults = [
+            (VA + 0x3000, false, 13),
+            (VA + 0x20_0000, false, 13),
+            (VA + 0x4000, true, 15),
+            (VA + 0x5000, false, 13),
+            (0x80_0000_0000, false, 13),
+        ];
+        for (addr, write, code) in faults {
+            hart.priv_ctrl.mode = PrivMode::U;
+            let res = if write {
+                hart.wr_mem8(addr, 0)
+            } else {
+                hart.rd_mem8(addr).map(|_| ())
+            };
+            assert!(res.is_err());
+            check(hart, code, addr);
+        }
+        hart.priv_ctrl.mode = PrivMode::U;
+        assert_eq!(hart.rd_mem8(VA + 0x4000), Ok(0x88));
+
+        // misaligned access across non-contiguous pages,
+        // both are translated before either is accessed
+        bus.write(0, DATA + 0xffc, 4, 0x4433_0000).unwrap();
+        bus.write(0, DATA + 0x2000, 4, 0x6655).unwrap();
+        assert_eq!(hart.rd_mem32(VA + 0xffe), Ok(0x6655_4433));
+        // tval is the part that faulted
+        assert!(hart.wr_mem32(VA + 0xffe, 0).is_err());
+        check(hart, 15, VA + 0x1000);
+        assert_eq!(bus.read(DATA + 0xffc, 4), Ok(0x4433_0000));
+
+        // loads and stores of M-mode at MPP under MPRV, fetch untranslated
+        hart.priv_ctrl.mstatus = hart.priv_ctrl.mstatus & !MSTATUS_MPP | MSTATUS_MPRV;
+        assert_eq!(hart.rd_mem32(VA + 4), Ok(0xaabb_ccdd));
+        assert_eq!(hart.fetch_mem32(DATA + 4), Ok(0xaabb_ccdd));
+        assert!(hart.rd_mem32(DATA).is_err());
+        check(hart, 13, DATA);
+
+        // page table outside of memory
+        hart.priv_ctrl.mode = PrivMode::U;
+        hart.priv_ctrl.satp = 8 << 60 | 0x1000;
+        assert!(hart.rd_mem8(VA).is_err());
+        check(hart, 5, VA);
+
+        // unsupported mode is ignored, ASID is read-only zero
+        hart.csr_write(SATP, 5 << 60).unwrap();
+        assert_eq!(hart.priv_ctrl.satp, 8 << 60 | 0x1000);
+        hart.csr_write(SATP, 9 << 60 | 0xffff << 44 | 0x123)
+            .unwrap();
+        assert_eq!(hart.priv_ctrl.satp, 9 << 60 | 0x123);
+    }
+
+    #[cfg(all(feature = "H", feature = "RV64"))]
+    #[test]
+    fn sv39x4() {
+        const GROOT: u64 = RAM_BASE + 0x4000;
+        let mut machine = Machine::<u64>::new(1, 0x10000);
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        let rwx = PTE_R | PTE_W | PTE_X | PTE_U | AD;
+        let tables = [
+            // guest physical gigapages of ram, read-write and read-only
+            (GROOT + 2 * 8, pte(RAM_BASE, rwx)),
+            (GROOT + 3 * 8, pte(RAM_BASE, PTE_R | PTE_U | AD)),
+            // VS-stage tables at their guest physical addresses
+            (ROOT + 8, pte(RAM_BASE + 0x2000, PTE_V)),
+            (ROOT + 16, pte(0x8_0000_0000, PTE_V)),
+            (RAM_BASE + 0x2000, pte(RAM_BASE + 0x3000, PTE_V)),
+            (RAM_BASE + 0x3000, pte(DATA, PTE_R | PTE_W | PTE_U | AD)),
+            (
+                RAM_BASE + 0x3008,
+                pte(0xc000_8000, PTE_R | PTE_W | PTE_U | AD),
+            ),
+            (RAM_BASE + 0x3010, pte(DATA, PTE_X | PTE_U | AD)),
+        ];
+        for (addr, pte) in tables {
+            bus.write(0, addr, 8, pte).unwrap();
+        }
+        bus.write(0, DATA, 8, 0x1122_3344_5566_7788).unwrap();
+        hart.csr_write(HGATP, 8 << 60 | 0x3fff << 44 | GROOT >> 12 | 3)
+            .unwrap();
+        assert_eq!(hart.priv_ctrl.hgatp, 8 << 60 | GROOT >> 12);
+        hart.priv_ctrl.vsatp = 8 << 60 | ROOT >> 12;
+        let check = |hart: &mut Hart<u64>, code: u64, tval: u64, gpa: u64| {
+            assert_eq!(hart.priv_ctrl.mcause, code);
+            assert_eq!(hart.priv_ctrl.mtval, tval);
+            assert_eq!(hart.priv_ctrl.mtval2, gpa >> 2);
+            // reset to VU-mode
+            hart.priv_ctrl.mode = PrivMode::U;
+            hart.priv_ctrl.virt = true;
+        };
+
+        hart.priv_ctrl.mode = PrivMode::U;
+        hart.priv_ctrl.virt = true;
+        assert_eq!(hart.rd_mem64(VA), Ok(0x1122_3344_5566_7788));
+        // read-only guest physical page
+        assert_eq!(hart.rd_mem8(VA + 0x1000), Ok(0x88));
+        assert!(hart.wr_mem8(VA + 0x1000, 0).is_err());
+        check(hart, 23, VA + 0x1000, 0xc000_8000);
+        // VS-stage table outside of guest memory faults as the access
+        assert!(hart.rd_mem8(0x8000_0000).is_err());
+        check(hart, 21, 0x8000_0000, 0x8_0000_0000);
+        assert!(hart.wr_mem8(0x8000_0000, 0).is_err());
+        check(hart, 23, 0x8000_0000, 0x8_0000_0000);
+        // execute-only page of the VS-stage
+        assert!(hart.rd_mem8(VA + 0x2000).is_err());
+        check(hart, 13, VA + 0x2000, 0);
+        // guest physical addresses above 41 bits
+        hart.priv_ctrl.vsatp = 0;
+        assert_eq!(hart.rd_mem8(DATA), Ok(0x88));
+        assert!(hart.rd_mem8(1 << 41).is_err());
+        check(hart, 21, 1 << 41, 1 << 41);
+        hart.priv_ctrl.vsatp = 8 << 60 | ROOT >> 12;
+
+        // hlvx of HS-mode reads execute-only pages of VU-mode
+        hart.priv_ctrl.mode = PrivMode::S;
+        hart.priv_ctrl.virt = false;
+        hart.priv_ctrl.hlsv = Some(true);
+        assert_eq!(hart.rd_mem32(VA + 0x2000), Ok(0x5566_7788));
+        hart.priv_ctrl.hlsv = Some(false);
+        assert!(hart.rd_mem32(VA + 0x2000).is_err());
+        check(hart, 13, VA + 0x2000, 0);
+        assert_eq!(hart.priv_ctrl.mstatush, MSTATUSH_GVA);
+
+        // loads and stores of M-mode through both stages under MPRV and MPV
+        hart.priv_ctrl.hlsv = None;
+        hart.priv_ctrl.mode = PrivMode::M;
+        hart.priv_ctrl.virt = false;
+        hart.priv_ctrl.mstatus = hart.priv_ctrl.mstatus & !MSTATUS_MPP | MSTATUS_MPRV;
+        hart.priv_ctrl.mstatush = MSTATUSH_MPV;
+        assert_eq!(hart.rd_mem64(VA), Ok(0x1122_3344_5566_7788));
+        assert_eq!(hart.fetch_mem32(DATA), Ok(0x5566_7788));
+    }
+
+    #[test]
+    fn sv32() {
+        let mut machine = Machine::<u32>::new(1, 0x10000);
+        let bus = machine.bus.clone();
+        let hart = &mut machine.harts[0];
+        let tables = [
+            (ROOT + 0x200 * 4, pte(RAM_BASE, PTE_R | PTE_W | PTE_X | AD)),
+            (ROOT + 0x100 * 4, pte(RAM_BASE + 0x2000, PTE_V)),
+            (ROOT + 0x101 * 4, pte(RAM_BASE + 0x2000, PTE_U | PTE_V)),
+            (RAM_BASE + 0x2000, pte(DATA, PTE_R | PTE_W | PTE_U | AD)),
+            (RAM_BASE + 0x2004, pte(RAM_BASE + 0x2000, PTE_V)),
+        ];
+        for (addr, pte) in tables {
+            bus.write(0, addr, 4, pte).unwrap();
+        }
+        hart.csr_write(SATP, 1 << 31 | 0x1ff << 22 | (ROOT >> 12) as u32)
+            .unwrap();
+        assert_eq!(hart.priv_ctrl.satp, 1 << 31 | (ROOT >> 12) as u32);
+        let check = |hart: &mut Hart<u32>, code: u32, tval: u32| {
+            assert_eq!(hart.priv_ctrl.mcause, code);
+            assert_eq!(hart.priv_ctrl.mtval, tval);
+        };
+
+        hart.priv_ctrl.mode = PrivMode::U;
+        hart.wr_mem32(VA as u32 + 8, 0x1234_5678).unwrap();
+        hart.priv_ctrl.mode = PrivMode::S;
+        assert_eq!(hart.rd_mem32(DATA as u32 + 8), Ok(0x1234_5678));
+        // U on a pointer, pointer at the last level
+        for addr in [0x4040_0000, VA as u32 + 0x1000] {
+            hart.priv_ctrl.mode = PrivMode::U;
+            assert!(hart.rd_mem32(addr).is_err());
+            check(hart, 13, addr);
+        }
+    }
+}
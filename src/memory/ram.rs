@@ -0,0 +1,266 @@
+use std::{
+    fmt::{self, Debug},
+    sync::atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicU8, Ordering},
+};
+
+/// 16 bytes aligned backing unit, so 16 bytes atomics are aligned on host
+#[derive(Default)]
+#[repr(C, align(16))]
+struct Line([AtomicU64; 2]);
+
+/// physical memory shared between harts\
+/// backed by atomics so it can be accessed through `&self`
+pub struct Ram {
+    base: u64,
+    size: u64,
+    /// backing store keeps every naturally aligned access aligned on host
+    lines: Box<[Line]>,
+}
+
+impl Debug for Ram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ram")
+            .field("base", &self.base)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl Ram {
+    pub fn new(base: u64, size: u64) -> Self {
+        let lines = (0..size.div_ceil(16)).map(|_| Line::default()).collect();
+        Self { base, size, lines }
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// offset of \[`addr`, `addr` + `len`) in ram
+    pub fn offset(&self, addr: u64, len: u64) -> Option<usize> {
+        let off = addr.checked_sub(self.base)?;
+        if off.checked_add(len)? <= self.size {
+            Some(off as usize)
+        } else {
+            None
+        }
+    }
+
+    fn ptr(&self, off: usize) -> *const u8 {
+        debug_assert!(off < self.lines.len() * 16);
+        unsafe { (self.lines.as_ptr() as *const u8).add(off) }
+    }
+
+    fn byte(&self, off: usize) -> &AtomicU8 {
+        unsafe { &*(self.ptr(off) as *const AtomicU8) }
+    }
+
+    /// naturally aligned atomic view, `off` must be aligned to 2
+    pub fn atomic16(&self, off: usize) -> &AtomicU16 {
+        debug_assert!(off.is_multiple_of(2));
+        unsafe { &*(self.ptr(off) as *const AtomicU16) }
+    }
+
+    /// naturally aligned atomic view, `off` must be aligned to 4
+    pub fn atomic32(&self, off: usize) -> &AtomicU32 {
+        debug_assert!(off.is_multiple_of(4));
+        unsafe { &*(self.ptr(off) as *const AtomicU32) }
+    }
+
+    /// naturally aligned atomic view, `off` must be aligned to 8
+    pub fn atomic64(&self, off: usize) -> &AtomicU64 {
+        debug_assert!(off.is_multiple_of(8));
+        unsafe { &*(self.ptr(off) as *const AtomicU64) }
+    }
+
+    /// little endian read of `size` bytes
+    pub fn read(&self, off: usize, size: u8, ord: Ordering) -> u64 {
+        match size {
+            1 => self.byte(off).load(ord) as u64,
+            2 if off.is_multiple_of(2) => u16::from_le(self.atomic16(off).load(ord)) as u64,
+            4 if off.is_multiple_of(4) => u32::from_le(self.atomic32(off).load(ord)) as u64,
+            8 if off.is_multiple_of(8) => u64::from_le(self.atomic64(off).load(ord)),
+            // spec doesn't mandate atomicity of misaligned access
+            _ => (0..size as usize).fold(0, |val, idx| {
+                val | (self.byte(off + idx).load(ord) as u64) << (idx * 8)
+            }),
+        }
+    }
+
+    /// little endian write of `size` bytes
+    pub fn write(&self, off: usize, size: u8, data: u64, ord: Ordering) {
+        match size {
+            1 => self.byte(off).store(data as u8, ord),
+            2 if off.is_multiple_of(2) => self.atomic16(off).store((data as u16).to_le(), ord),
+            4 if off.is_multiple_of(4) => self.atomic32(off).store((data as u32).to_le(), ord),
+            8 if off.is_multiple_of(8) => self.atomic64(off).store(data.to_le(), ord),
+            _ => {
+                for idx in 0..size as usize {
+                    self.byte(off + idx).store((data >> (idx * 8)) as u8, ord);
+                }
+            }
+        }
+    }
+
+    /// naturally aligned compare and swap of `size` bytes,
+    /// returns the old value, `Ok` if it matched `old` and `new` was stored
+    pub fn cas(&self, off: usize, size: u8, old: u64, new: u64, ord: Ordering) -> Result<u64, u64> {
+        let fail = failure(ord);
+        match size {
+            1 => self
+                .byte(off)
+                .compare_exchange(old as u8, new as u8, ord, fail)
+                .map(|val| val as u64)
+                .map_err(|val| val as u64),
+            2 => self
+                .atomic16(off)
+                .compare_exchange((old as u16).to_le(), (new as u16).to_le(), ord, fail)
+                .map(|val| u16::from_le(val) as u64)
+                .map_err(|val| u16::from_le(val) as u64),
+            4 => self
+                .atomic32(off)
+                .compare_exchange((old as u32).to_le(), (new as u32).to_le(), ord, fail)
+                .map(|val| u32::from_le(val) as u64)
+                .map_err(|val| u32::from_le(val) as u64),
+            8 => self
+                .atomic64(off)
+                .compare_exchange(old.to_le(), new.to_le(), ord, fail)
+                .map(u64::from_le)
+                .map_err(u64::from_le),
+            _ => panic!("bad atomic size"),
+        }
+    }
+
+    /// host has a lock-free 16 bytes compare and swap,
+    /// a lock would not order it against plain 8 bytes accesses
+    pub fn has_cas128() -> bool {
+        #[cfg(target_arch = "x86_64")]
+        return std::is_x86_feature_detected!("cmpxchg16b");
+        #[cfg(target_arch = "aarch64")]
+        return true;
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        return false;
+    }
+
+    /// 16 bytes aligned compare and swap, see `cas`,
+    /// a full barrier, only on hosts with `has_cas128`
+    pub fn cas128(&self, off: usize, old: u128, new: u128) -> Result<u128, u128> {
+        debug_assert!(off.is_multiple_of(16));
+        assert!(Self::has_cas128(), "no 16 bytes compare and swap on host");
+        let ptr = self.ptr(off) as *mut u128;
+        let val = u128::from_le(unsafe { host_cas128(ptr, old.to_le(), new.to_le()) });
+        if val == old {
+            Ok(val)
+        } else {
+            Err(val)
+        }
+    }
+
+    /// naturally aligned atomic read-modify-write of `size` bytes,
+    /// returns the old value
+    pub fn fetch_update(&self, off: usize, size: u8, ord: Ordering, f: impl Fn(u64) -> u64) -> u64 {
+        let fail = failure(ord);
+        match size {
+            1 => self
+                .byte(off)
+                .fetch_update(ord, fail, |val| Some(f(val as u64) as u8))
+                .unwrap() as u64,
+            2 => u16::from_le(
+                self.atomic16(off)
+                    .fetch_update(ord, fail, |val| {
+                        Some((f(u16::from_le(val) as u64) as u16).to_le())
+                    })
+                    .unwrap(),
+            ) as u64,
+            4 => u32::from_le(
+                self.atomic32(off)
+                    .fetch_update(ord, fail, |val| {
+                        Some((f(u32::from_le(val) as u64) as u32).to_le())
+                    })
+                    .unwrap(),
+            ) as u64,
+            8 => u64::from_le(
+                self.atomic64(off)
+                    .fetch_update(ord, fail, |val| Some(f(u64::from_le(val)).to_le()))
+                    .unwrap(),
+            ),
+            _ => panic!("bad atomic size"),
+        }
+    }
+
+    /// copy `data` into ram, used for loading images
+    pub fn load(&self, off: usize, data: &[u8]) {
+        for (idx, &val) in data.iter().enumerate() {
+            self.byte(off + idx).store(val, Ordering::Relaxed);
+        }
+    }
+}
+
+/// `lock cmpxchg16b`, a full barrier\
+/// `core::arch::x86_64::cmpxchg16b` falls back to libatomic unless
+/// the feature is enabled for the whole crate
+#[cfg(target_arch = "x86_64")]
+unsafe fn host_cas128(ptr: *mut u128, old: u128, new: u128) -> u128 {
+    let (lo, hi): (u64, u64);
+    // rbx is reserved by llvm, swap it in and out around the instruction
+    std::arch::asm!(
+        "xchg {new_lo}, rbx",
+        "lock cmpxchg16b xmmword ptr [{ptr}]",
+        "mov rbx, {new_lo}",
+        ptr = in(reg) ptr,
+        new_lo = inout(reg) new as u64 => _,
+        in("rcx") (new >> 64) as u64,
+        inout("rax") old as u64 => lo,
+        inout("rdx") (old >> 64) as u64 => hi,
+        options(nostack),
+    );
+    lo as u128 | (hi as u128) << 64
+}
+
+/// `ldaxp` / `stlxp` loop, a full barrier\
+/// a mismatch stores the old value back, the pair load is only
+/// single-copy atomic once the exclusive store succeeds
+#[cfg(target_arch = "aarch64")]
+unsafe fn host_cas128(ptr: *mut u128, old: u128, new: u128) -> u128 {
+    let (lo, hi): (u64, u64);
+    std::arch::asm!(
+        "2:",
+        "ldaxp {lo}, {hi}, [{ptr}]",
+        "cmp {lo}, {old_lo}",
+        "ccmp {hi}, {old_hi}, #0, eq",
+        "csel {st_lo}, {new_lo}, {lo}, eq",
+        "csel {st_hi}, {new_hi}, {hi}, eq",
+        "stlxp {fail:w}, {st_lo}, {st_hi}, [{ptr}]",
+        "cbnz {fail:w}, 2b",
+        ptr = in(reg) ptr,
+        old_lo = in(reg) old as u64,
+        old_hi = in(reg) (old >> 64) as u64,
+        new_lo = in(reg) new as u64,
+        new_hi = in(reg) (new >> 64) as u64,
+        lo = out(reg) lo,
+        hi = out(reg) hi,
+        st_lo = out(reg) _,
+        st_hi = out(reg) _,
+        fail = out(reg) _,
+        options(nostack),
+    );
+    lo as u128 | (hi as u128) << 64
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+unsafe fn host_cas128(_ptr: *mut u128, _old: u128, _new: u128) -> u128 {
+    unreachable!()
+}
+
+/// strongest ordering allowed for the failed load of a compare and swap
+fn failure(ord: Ordering) -> Ordering {
+    match ord {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        ord => ord,
+    }
+}
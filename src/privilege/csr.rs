@@ -0,0 +1,675 @@
+#[cfg(feature = "F")]
+use crate::fpu::FpState;
+#[cfg(feature = "S")]
+use crate::memory::paging::satp_warl;
+#[cfg(feature = "H")]
+use crate::uop::Exception;
+use crate::{
+    hart::Hart,
+    privilege::PrivMode,
+    utils::Maybe,
+    xlen::{Cast, XlenT},
+};
+
+// floating-point
+pub const FFLAGS: u16 = 0x001;
+pub const FRM: u16 = 0x002;
+pub const FCSR: u16 = 0x003;
+// table jump
+pub const JVT: u16 = 0x017;
+// vector
+pub const VSTART: u16 = 0x008;
+pub const VXSAT: u16 = 0x009;
+pub const VXRM: u16 = 0x00a;
+pub const VCSR: u16 = 0x00f;
+pub const VL: u16 = 0xc20;
+pub const VTYPE: u16 = 0xc21;
+pub const VLENB: u16 = 0xc22;
+// supervisor trap setup
+pub const SSTATUS: u16 = 0x100;
+pub const SIE: u16 = 0x104;
+pub const STVEC: u16 = 0x105;
+// supervisor configuration
+pub const SENVCFG: u16 = 0x10a;
+// supervisor trap handling
+pub const SSCRATCH: u16 = 0x140;
+pub const SEPC: u16 = 0x141;
+pub const SCAUSE: u16 = 0x142;
+pub const STVAL: u16 = 0x143;
+pub const SIP: u16 = 0x144;
+// supervisor protection and translation
+pub const SATP: u16 = 0x180;
+// virtual supervisor registers
+pub const VSSTATUS: u16 = 0x200;
+pub const VSIE: u16 = 0x204;
+pub const VSTVEC: u16 = 0x205;
+pub const VSSCRATCH: u16 = 0x240;
+pub const VSEPC: u16 = 0x241;
+pub const VSCAUSE: u16 = 0x242;
+pub const VSTVAL: u16 = 0x243;
+pub const VSIP: u16 = 0x244;
+pub const VSATP: u16 = 0x280;
+// hypervisor trap setup
+pub const HSTATUS: u16 = 0x600;
+pub const HEDELEG: u16 = 0x602;
+pub const HIDELEG: u16 = 0x603;
+pub const HIE: u16 = 0x604;
+pub const HGEIE: u16 = 0x607;
+// hypervisor configuration
+pub const HENVCFG: u16 = 0x60a;
+/// rv32 only
+pub const HENVCFGH: u16 = 0x61a;
+// hypervisor trap handling
+pub const HTVAL: u16 = 0x643;
+pub const HIP: u16 = 0x644;
+pub const HVIP: u16 = 0x645;
+pub const HTINST: u16 = 0x64a;
+pub const HGEIP: u16 = 0xe12;
+// hypervisor protection and translation
+pub const HGATP: u16 = 0x680;
+// machine information registers
+pub const MHARTID: u16 = 0xf14;
+// machine trap setup
+pub const MSTATUS: u16 = 0x300;
+pub const MISA: u16 = 0x301;
+pub const MEDELEG: u16 = 0x302;
+pub const MIDELEG: u16 = 0x303;
+pub const MIE: u16 = 0x304;
+pub const MTVEC: u16 = 0x305;
+/// rv32 only
+pub const MSTATUSH: u16 = 0x310;
+// machine configuration
+pub const MENVCFG: u16 = 0x30a;
+/// rv32 only
+pub const MENVCFGH: u16 = 0x31a;
+// machine trap handling
+pub const MSCRATCH: u16 = 0x340;
+pub const MEPC: u16 = 0x341;
+pub const MCAUSE: u16 = 0x342;
+pub const MTVAL: u16 = 0x343;
+pub const MIP: u16 = 0x344;
+pub const MTINST: u16 = 0x34a;
+pub const MTVAL2: u16 = 0x34b;
+
+// mstatus fields
+pub const MSTATUS_SIE: u64 = 1 << 1;
+pub const MSTATUS_MIE: u64 = 1 << 3;
+pub const MSTATUS_SPIE: u64 = 1 << 5;
+pub const MSTATUS_VS: u64 = 3 << 9;
+pub const MSTATUS_VS_SHIFT: u32 = 9;
+pub const MSTATUS_MPIE: u64 = 1 << 7;
+pub const MSTATUS_SPP: u64 = 1 << 8;
+pub const MSTATUS_MPP: u64 = 3 << 11;
+pub const MSTATUS_MPP_SHIFT: u32 = 11;
+pub const MSTATUS_FS: u64 = 3 << 13;
+pub const MSTATUS_FS_SHIFT: u32 = 13;
+/// loads and stores at MPP
+pub const MSTATUS_MPRV: u64 = 1 << 17;
+/// S-mode access to user pages
+pub const MSTATUS_SUM: u64 = 1 << 18;
+/// executable pages are readable
+pub const MSTATUS_MXR: u64 = 1 << 19;
+/// trap satp and sfence.vma
+pub const MSTATUS_TVM: u64 = 1 << 20;
+/// timeout wait
+pub const MSTATUS_TW: u64 = 1 << 21;
+/// trap sret
+pub const MSTATUS_TSR: u64 = 1 << 22;
+/// rv64 and wider
+pub const MSTATUS_UXL_SHIFT: u32 = 32;
+/// rv64 and wider, read-only MXL with S-mode, zero without
+pub const MSTATUS_SXL_SHIFT: u32 = 34;
+
+// mstatush fields, mstatus[63:32] on rv64
+/// guest virtual address in mtval
+pub const MSTATUSH_GVA: u32 = 1 << 6;
+/// V before the trap into M-mode
+pub const MSTATUSH_MPV: u32 = 1 << 7;
+
+/// fields of mstatus visible in sstatus, besides SD
+pub const SSTATUS_MASK: u64 = MSTATUS_SIE
+    | MSTATUS_SPIE
+    | MSTATUS_SPP
+    | MSTATUS_VS
+    | MSTATUS_FS
+    | MSTATUS_SUM
+    | MSTATUS_MXR
+    | 3 << MSTATUS_UXL_SHIFT;
+
+// hstatus fields
+/// guest virtual address in stval
+pub const HSTATUS_GVA: u64 = 1 << 6;
+/// V before the trap into HS-mode
+pub const HSTATUS_SPV: u64 = 1 << 7;
+/// privilege of hlv / hsv, VS-mode if set
+pub const HSTATUS_SPVP: u64 = 1 << 8;
+/// U-mode may hlv / hsv
+pub const HSTATUS_HU: u64 = 1 << 9;
+/// trap satp and sfence.vma in VS-mode
+pub const HSTATUS_VTVM: u64 = 1 << 20;
+/// trap wfi in VS-mode
+pub const HSTATUS_VTW: u64 = 1 << 21;
+/// trap sret in VS-mode
+pub const HSTATUS_VTSR: u64 = 1 << 22;
+/// rv64 and wider, read-only MXL
+pub const HSTATUS_VSXL_SHIFT: u32 = 32;
+
+// menvcfg fields
+pub const MENVCFG_CBIE: u64 = 3 << 4;
+pub const MENVCFG_CBIE_SHIFT: u32 = 4;
+pub const MENVCFG_CBCFE: u64 = 1 << 6;
+pub const MENVCFG_CBZE: u64 = 1 << 7;
+
+// mip / mie fields
+pub const IRQ_SSI: u32 = 1;
+pub const IRQ_VSSI: u32 = 2;
+pub const IRQ_MSI: u32 = 3;
+pub const IRQ_STI: u32 = 5;
+pub const IRQ_VSTI: u32 = 6;
+pub const IRQ_MTI: u32 = 7;
+pub const IRQ_SEI: u32 = 9;
+pub const IRQ_VSEI: u32 = 10;
+pub const IRQ_MEI: u32 = 11;
+pub const IRQ_SGEI: u32 = 12;
+pub const MIP_SSIP: u64 = 1 << IRQ_SSI;
+pub const MIP_VSSIP: u64 = 1 << IRQ_VSSI;
+pub const MIP_MSIP: u64 = 1 << IRQ_MSI;
+pub const MIP_STIP: u64 = 1 << IRQ_STI;
+pub const MIP_VSTIP: u64 = 1 << IRQ_VSTI;
+pub const MIP_MTIP: u64 = 1 << IRQ_MTI;
+pub const MIP_SEIP: u64 = 1 << IRQ_SEI;
+pub const MIP_VSEIP: u64 = 1 << IRQ_VSEI;
+pub const MIP_MEIP: u64 = 1 << IRQ_MEI;
+pub const MIP_SGEIP: u64 = 1 << IRQ_SGEI;
+/// supervisor level interrupts
+pub const MIP_S: u64 = MIP_SSIP | MIP_STIP | MIP_SEIP;
+/// virtual supervisor level interrupts
+pub const MIP_VS: u64 = MIP_VSSIP | MIP_VSTIP | MIP_VSEIP;
+
+/// exceptions medeleg can delegate, all but ecall from M-mode
+pub const MEDELEG_MASK: u64 = 0xb3ff;
+/// exceptions of H medeleg can delegate too, ecall from VS-mode,
+/// guest-page faults and virtual instruction
+pub const MEDELEG_H: u64 = 1 << 10 | 0xf << 20;
+/// exceptions hedeleg can delegate, no ecall from HS / VS / M-mode
+/// nor guest-page faults
+pub const HEDELEG_MASK: u64 = 0xb1ff;
+
+/// MXL / UXL encoding of xlen
+pub fn xl_of(xlen: u32) -> u32 {
+    xlen.trailing_zeros() - 4
+}
+
+/// xlen of MXL / UXL encoding
+pub fn xlen_of(xl: u32) -> u32 {
+    16 << xl
+}
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    /// csr address encodes lowest privilege level in \[9:8\]
+    /// and read-only in \[11:10\], HS-mode is level 2,
+    /// TVM traps satp and hgatp in HS-mode
+    pub fn csr_allowed(&self, addr: u16, write: bool) -> bool {
+        let p = &self.priv_ctrl;
+        let level = (addr >> 8) & 3;
+        let read_only = (addr >> 10) & 3 == 3;
+        let hs = p.mode == PrivMode::S && !p.is_virt();
+        let tvm = hs && p.mstatus & Xlen::from(MSTATUS_TVM) != Xlen::from(0);
+        let mode = if hs && self.fe.isa.hyp() {
+            2
+        } else {
+            p.mode as u16
+        };
+        mode >= level && !(write && read_only) && !(matches!(addr, SATP | HGATP) && tvm)
+    }
+
+    /// csr that an access with V=1 reaches, VS-mode uses VS copies of
+    /// S csrs, hypervisor and VS csrs or S csrs from VU-mode, and satp
+    /// under VTVM, are virtual instructions, others fail as in HS-mode
+    #[cfg(feature = "H")]
+    pub fn csr_virt(&mut self, addr: u16, write: bool) -> Maybe<u16> {
+        let p = &self.priv_ctrl;
+        let level = (addr >> 8) & 3;
+        let read_only = (addr >> 10) & 3 == 3;
+        if !p.virt || level == 0 || level == 3 || write && read_only {
+            return Ok(addr);
+        }
+        let vu = p.mode == PrivMode::U;
+        let vtvm = p.hstatus & Xlen::from(HSTATUS_VTVM) != Xlen::from(0);
+        if self.csr_rd(addr).is_err() {
+            return Ok(addr);
+        }
+        if level == 2 || vu || addr == SATP && vtvm {
+            self.raise(Exception::VirtualInstr)?;
+        }
+        Ok(match addr {
+            SSTATUS | SIE | STVEC | SSCRATCH | SEPC | SCAUSE | STVAL | SIP | SATP => addr + 0x100,
+            _ => addr,
+        })
+    }
+
+    /// read csr without side effects
+    pub fn csr_rd(&mut self, addr: u16) -> Maybe<Xlen> {
+        #[cfg(feature = "F")]
+        if matches!(addr, FFLAGS | FRM | FCSR) {
+            return self.fcsr_rd(addr);
+        }
+        #[cfg(feature = "S")]
+        let s_mode = self.fe.isa.s_mode();
+        #[cfg(feature = "H")]
+        let hyp = self.fe.isa.hyp();
+        let p = &self.priv_ctrl;
+        Ok(match addr {
+            MHARTID => Xlen::from(p.hart_id),
+            MSTATUS => self.mstatus(),
+            MISA => self.misa(),
+            MIE => p.mie,
+            MTVEC => p.mtvec,
+            MSCRATCH => p.mscratch,
+            MEPC => p.mepc,
+            MCAUSE => p.mcause,
+            MTVAL => p.mtval,
+            MIP => p.mip,
+            MENVCFG => p.menvcfg,
+            MENVCFGH if Xlen::XLEN == 32 => Xlen::from(0),
+            #[cfg(feature = "S")]
+            MEDELEG if s_mode => p.medeleg,
+            #[cfg(feature = "S")]
+            MIDELEG if s_mode => self.irq_deleg(),
+            #[cfg(feature = "S")]
+            SSTATUS if s_mode => self.mstatus() & sstatus_mask(),
+            #[cfg(feature = "S")]
+            SIE if s_mode => p.mie & p.mideleg,
+            #[cfg(feature = "S")]
+            STVEC if s_mode => p.stvec,
+            #[cfg(feature = "S")]
+            SENVCFG if s_mode => p.senvcfg,
+            #[cfg(feature = "S")]
+            SSCRATCH if s_mode => p.sscratch,
+            #[cfg(feature = "S")]
+            SEPC if s_mode => p.sepc,
+            #[cfg(feature = "S")]
+            SCAUSE if s_mode => p.scause,
+            #[cfg(feature = "S")]
+            STVAL if s_mode => p.stval,
+            #[cfg(feature = "S")]
+            SIP if s_mode => p.mip & p.mideleg,
+            #[cfg(feature = "S")]
+            SATP if s_mode => p.satp,
+            #[cfg(feature = "H")]
+            MSTATUSH if hyp && Xlen::XLEN == 32 => Xlen::from(p.mstatush),
+            // trapped instructions are not transformed
+            #[cfg(feature = "H")]
+            MTINST | HTINST if hyp => Xlen::from(0),
+            #[cfg(feature = "H")]
+            MTVAL2 if hyp => p.mtval2,
+            #[cfg(feature = "H")]
+            HSTATUS if hyp => self.hstatus(),
+            #[cfg(feature = "H")]
+            HEDELEG if hyp => p.hedeleg,
+            #[cfg(feature = "H")]
+            HIDELEG if hyp => p.hideleg,
+            #[cfg(feature = "H")]
+            HIE if hyp => p.mie & Xlen::from(MIP_VS | MIP_SGEIP),
+            #[cfg(feature = "H")]
+            HIP if hyp => p.mip & Xlen::from(MIP_VS | MIP_SGEIP),
+            #[cfg(feature = "H")]
+            HVIP if hyp => p.mip & Xlen::from(MIP_VS),
+            // no guest external interrupts
+            #[cfg(feature = "H")]
+            HGEIE | HGEIP if hyp => Xlen::from(0),
+            #[cfg(feature = "H")]
+            HENVCFG if hyp => p.henvcfg,
+            #[cfg(feature = "H")]
+            HENVCFGH if hyp && Xlen::XLEN == 32 => Xlen::from(0),
+            #[cfg(feature = "H")]
+            HTVAL if hyp => p.htval,
+            #[cfg(feature = "H")]
+            HGATP if hyp => p.hgatp,
+            #[cfg(feature = "H")]
+            VSSTATUS if hyp => self.vsstatus(),
+            #[cfg(feature = "H")]
+            VSIE if hyp => (p.mie & p.hideleg) >> 1,
+            #[cfg(feature = "H")]
+            VSTVEC if hyp => p.vstvec,
+            #[cfg(feature = "H")]
+            VSSCRATCH if hyp => p.vsscratch,
+            #[cfg(feature = "H")]
+            VSEPC if hyp => p.vsepc,
+            #[cfg(feature = "H")]
+            VSCAUSE if hyp => p.vscause,
+            #[cfg(feature = "H")]
+            VSTVAL if hyp => p.vstval,
+            #[cfg(feature = "H")]
+            VSIP if hyp => (p.mip & p.hideleg) >> 1,
+            #[cfg(feature = "H")]
+            VSATP if hyp => p.vsatp,
+            #[cfg(feature = "Zcmt")]
+            JVT if self.fe.isa.Zcmt => p.jvt,
+            #[cfg(feature = "V")]
+            VSTART | VXSAT | VXRM | VCSR | VL | VTYPE | VLENB if !self.v_off() => {
+                self.vcsr_rd(addr)
+            }
+            _ => return Err(()),
+        })
+    }
+
+    /// write csr with WARL legalization
+    pub fn csr_write(&mut self, addr: u16, val: Xlen) -> Maybe<()> {
+        let s_mode = self.fe.isa.s_mode();
+        let hyp = self.fe.isa.hyp();
+        let p = &mut self.priv_ctrl;
+        match addr {
+            MSTATUS => {
+                #[cfg(feature = "F")]
+                if !self.fe.isa.fp_inx() {
+                    self.fpu
+                        .set_fs(FpState::from_bits((val >> MSTATUS_FS_SHIFT).into()));
+                }
+                #[cfg(feature = "V")]
+                if self.fe.isa.vector() {
+                    self.vpu.vs = FpState::from_bits((val >> MSTATUS_VS_SHIFT).into());
+                }
+                let mut mask = MSTATUS_MIE | MSTATUS_MPIE | MSTATUS_MPP | MSTATUS_TW;
+                if s_mode {
+                    mask |= MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP | MSTATUS_TSR;
+                    mask |= MSTATUS_MPRV | MSTATUS_SUM | MSTATUS_MXR | MSTATUS_TVM;
+                }
+                let mask = Xlen::from(mask);
+                let mut new = (p.mstatus & !mask) | (val & mask);
+                // MPP is WARL, unsupported modes fall back to M
+                let mpp: u32 = (new >> MSTATUS_MPP_SHIFT).into();
+                match PrivMode::from_bits(mpp & 3) {
+                    Some(PrivMode::S) if !s_mode => new = new | Xlen::from(MSTATUS_MPP),
+                    None => new = new | Xlen::from(MSTATUS_MPP),
+                    _ => (),
+                }
+                // UXL is WARL, any xlen up to MXL
+                if Xlen::XLEN > 32 {
+                    let uxl: u32 = (val >> MSTATUS_UXL_SHIFT).into();
+                    if uxl & 3 != 0 && xlen_of(uxl & 3) <= Xlen::XLEN {
+                        let field = Xlen::from(3) << MSTATUS_UXL_SHIFT;
+                        new = (new & !field) | (Xlen::from(uxl & 3) << MSTATUS_UXL_SHIFT);
+                    }
+                }
+                p.mstatus = new;
+                #[cfg(feature = "H")]
+                if hyp && Xlen::XLEN > 32 {
+                    let high: u64 = (val >> 32).into();
+                    p.mstatush = high as u32 & (MSTATUSH_MPV | MSTATUSH_GVA);
+                }
+            }
+            // read-only zero to disable extensions
+            MISA => (),
+            MIE => {
+                let mut mask = MIP_MSIP | MIP_MTIP | MIP_MEIP;
+                if s_mode {
+                    mask |= MIP_S;
+                }
+                if hyp {
+                    mask |= MIP_VS;
+                }
+                p.mie = val & Xlen::from(mask);
+            }
+            // only direct and vectored mode
+            MTVEC => p.mtvec = val & !Xlen::from(2),
+            MSCRATCH => p.mscratch = val,
+            // IALIGN = 16 when C is supported
+            MEPC => p.mepc = val & !Xlen::from(1),
+            MCAUSE => p.mcause = val,
+            MTVAL => p.mtval = val,
+            // machine level pending bits are driven by devices,
+            // (virtual) supervisor level ones are raised by M-mode software
+            MIP => {
+                let mut mask = 0;
+                if s_mode {
+                    mask |= MIP_S;
+                }
+                if hyp {
+                    mask |= MIP_VS;
+                }
+                let mask = Xlen::from(mask);
+                p.mip = (p.mip & !mask) | (val & mask);
+            }
+            MENVCFG => self.priv_ctrl.menvcfg = self.envcfg(val),
+            MENVCFGH => (),
+            #[cfg(feature = "S")]
+            MEDELEG if s_mode => {
+                let mask = if hyp {
+                    MEDELEG_MASK | MEDELEG_H
+                } else {
+                    MEDELEG_MASK
+                };
+                p.medeleg = val & Xlen::from(mask);
+            }
+            #[cfg(feature = "S")]
+            MIDELEG if s_mode => p.mideleg = val & Xlen::from(MIP_S),
+            #[cfg(feature = "S")]
+            SSTATUS if s_mode => {
+                let mask: Xlen = sstatus_mask();
+                let val = (self.mstatus() & !mask) | (val & mask);
+                return self.csr_write(MSTATUS, val);
+            }
+            #[cfg(feature = "S")]
+            SIE if s_mode => p.mie = (p.mie & !p.mideleg) | (val & p.mideleg),
+            #[cfg(feature = "S")]
+            STVEC if s_mode => p.stvec = val & !Xlen::from(2),
+            #[cfg(feature = "S")]
+            SENVCFG if s_mode => self.priv_ctrl.senvcfg = self.envcfg(val),
+            #[cfg(feature = "S")]
+            SSCRATCH if s_mode => p.sscratch = val,
+            #[cfg(feature = "S")]
+            SEPC if s_mode => p.sepc = val & !Xlen::from(1),
+            #[cfg(feature = "S")]
+            SCAUSE if s_mode => p.scause = val,
+            #[cfg(feature = "S")]
+            STVAL if s_mode => p.stval = val,
+            // only SSIP is raised by S-mode software
+            #[cfg(feature = "S")]
+            SIP if s_mode => {
+                let mask = p.mideleg & Xlen::from(MIP_SSIP);
+                p.mip = (p.mip & !mask) | (val & mask);
+            }
+            #[cfg(feature = "S")]
+            SATP if s_mode => {
+                if let Some(satp) = satp_warl(val.into(), Xlen::XLEN) {
+                    p.satp = Xlen::from(satp);
+                }
+            }
+            #[cfg(feature = "H")]
+            MSTATUSH if hyp && Xlen::XLEN == 32 => {
+                let val: u64 = val.into();
+                p.mstatush = val as u32 & (MSTATUSH_MPV | MSTATUSH_GVA);
+            }
+            #[cfg(feature = "H")]
+            MTINST | HTINST | HGEIE | HENVCFGH if hyp => (),
+            #[cfg(feature = "H")]
+            MTVAL2 if hyp => p.mtval2 = val,
+            #[cfg(feature = "H")]
+            HSTATUS if hyp => {
+                let mask = Xlen::from(
+                    HSTATUS_GVA
+                        | HSTATUS_SPV
+                        | HSTATUS_SPVP
+                        | HSTATUS_HU
+                        | HSTATUS_VTVM
+                        | HSTATUS_VTW
+                        | HSTATUS_VTSR,
+                );
+                p.hstatus = (p.hstatus & !mask) | (val & mask);
+            }
+            #[cfg(feature = "H")]
+            HEDELEG if hyp => p.hedeleg = val & Xlen::from(HEDELEG_MASK),
+            #[cfg(feature = "H")]
+            HIDELEG if hyp => p.hideleg = val & Xlen::from(MIP_VS),
+            #[cfg(feature = "H")]
+            HIE if hyp => {
+                let mask = Xlen::from(MIP_VS);
+                p.mie = (p.mie & !mask) | (val & mask);
+            }
+            // only VSSIP is raised by HS-mode software through hip
+            #[cfg(feature = "H")]
+            HIP if hyp => {
+                let mask = Xlen::from(MIP_VSSIP);
+                p.mip = (p.mip & !mask) | (val & mask);
+            }
+            // virtual interrupts injected by HS-mode software
+            #[cfg(feature = "H")]
+            HVIP if hyp => {
+                let mask = Xlen::from(MIP_VS);
+                p.mip = (p.mip & !mask) | (val & mask);
+            }
+            #[cfg(feature = "H")]
+            HENVCFG if hyp => self.priv_ctrl.henvcfg = self.envcfg(val),
+            #[cfg(feature = "H")]
+            HTVAL if hyp => p.htval = val,
+            // root of the G-stage is 16KiB aligned, VMID is read-only zero
+            #[cfg(feature = "H")]
+            HGATP if hyp => {
+                if let Some(hgatp) = satp_warl(val.into(), Xlen::XLEN) {
+                    p.hgatp = Xlen::from(hgatp & !3);
+                }
+            }
+            #[cfg(feature = "H")]
+            VSSTATUS if hyp => {
+                let mask = Xlen::from(self.vsstatus_mask());
+                let p = &mut self.priv_ctrl;
+                p.vsstatus = (p.vsstatus & !mask) | (val & mask);
+            }
+            #[cfg(feature = "H")]
+            VSIE if hyp => p.mie = (p.mie & !p.hideleg) | ((val << 1) & p.hideleg),
+            #[cfg(feature = "H")]
+            VSTVEC if hyp => p.vstvec = val & !Xlen::from(2),
+            #[cfg(feature = "H")]
+            VSSCRATCH if hyp => p.vsscratch = val,
+            #[cfg(feature = "H")]
+            VSEPC if hyp => p.vsepc = val & !Xlen::from(1),
+            #[cfg(feature = "H")]
+            VSCAUSE if hyp => p.vscause = val,
+            #[cfg(feature = "H")]
+            VSTVAL if hyp => p.vstval = val,
+            // only VSSIP is raised by VS-mode software, as SSIP
+            #[cfg(feature = "H")]
+            VSIP if hyp => {
+                let mask = p.hideleg & Xlen::from(MIP_VSSIP);
+                p.mip = (p.mip & !mask) | ((val << 1) & mask);
+            }
+            #[cfg(feature = "H")]
+            VSATP if hyp => {
+                if let Some(vsatp) = satp_warl(val.into(), Xlen::XLEN) {
+                    p.vsatp = Xlen::from(vsatp);
+                }
+            }
+            // only jump table mode 0
+            #[cfg(feature = "Zcmt")]
+            JVT => p.jvt = val & !Xlen::from(63),
+            #[cfg(feature = "F")]
+            FFLAGS | FRM | FCSR => self.fcsr_write(addr, val),
+            #[cfg(feature = "V")]
+            VSTART | VXSAT | VXRM | VCSR => self.vcsr_write(addr, val),
+            _ => return Err(()),
+        }
+        Ok(())
+    }
+
+    /// menvcfg / senvcfg with only fields of enabled extensions,
+    /// reserved CBIE disables
+    fn envcfg(&self, val: Xlen) -> Xlen {
+        #[allow(unused_mut)]
+        let mut mask = 0;
+        #[cfg(feature = "Zicbom")]
+        if self.fe.isa.Zicbom {
+            mask |= MENVCFG_CBIE | MENVCFG_CBCFE;
+        }
+        #[cfg(feature = "Zicboz")]
+        if self.fe.isa.Zicboz {
+            mask |= MENVCFG_CBZE;
+        }
+        let new = val & Xlen::from(mask);
+        let cbie: u32 = (new >> MENVCFG_CBIE_SHIFT).into();
+        if cbie & 3 == 0b10 {
+            new & !Xlen::from(MENVCFG_CBIE)
+        } else {
+            new
+        }
+    }
+
+    /// mstatus with fields held by other units,
+    /// SD summarizes dirty context
+    fn mstatus(&self) -> Xlen {
+        #[allow(unused_mut)]
+        let mut val = self.priv_ctrl.mstatus;
+        if self.fe.isa.s_mode() && Xlen::XLEN > 32 {
+            val = val | (Xlen::from(xl_of(Xlen::XLEN)) << MSTATUS_SXL_SHIFT);
+        }
+        #[cfg(feature = "H")]
+        if self.fe.isa.hyp() && Xlen::XLEN > 32 {
+            val = val | (Xlen::from(self.priv_ctrl.mstatush) << 32);
+        }
+        #[allow(unused_mut)]
+        let mut dirty = false;
+        // FS is read-only zero with Zfinx
+        #[cfg(feature = "F")]
+        if !self.fe.isa.fp_inx() {
+            let fs = self.fpu.get_fs();
+            val = val | (Xlen::from(fs as u32) << MSTATUS_FS_SHIFT);
+            dirty |= fs == FpState::Dirty;
+        }
+        #[cfg(feature = "V")]
+        if self.fe.isa.vector() {
+            let vs = self.vpu.vs;
+            val = val | (Xlen::from(vs as u32) << MSTATUS_VS_SHIFT);
+            dirty |= vs == FpState::Dirty;
+        }
+        if dirty {
+            val = val | (Xlen::from(1) << (Xlen::XLEN - 1));
+        }
+        val
+    }
+
+    /// hstatus with read-only VSXL
+    #[cfg(feature = "H")]
+    fn hstatus(&self) -> Xlen {
+        let val = self.priv_ctrl.hstatus;
+        if Xlen::XLEN > 32 {
+            val | (Xlen::from(xl_of(Xlen::XLEN)) << HSTATUS_VSXL_SHIFT)
+        } else {
+            val
+        }
+    }
+
+    /// vsstatus with SD summarizing its own FS and VS
+    #[cfg(feature = "H")]
+    fn vsstatus(&self) -> Xlen {
+        let val = self.priv_ctrl.vsstatus;
+        let dirty = |field| val & Xlen::from(field) == Xlen::from(field);
+        if dirty(MSTATUS_FS) || dirty(MSTATUS_VS) {
+            val | Xlen::from(1) << (Xlen::XLEN - 1)
+        } else {
+            val
+        }
+    }
+
+    /// writable fields of vsstatus, FS only with an fp register file,
+    /// VS only with V, UXL is read-only MXL
+    #[cfg(feature = "H")]
+    fn vsstatus_mask(&self) -> u64 {
+        #[allow(unused_mut)]
+        let mut mask = SSTATUS_MASK & !(MSTATUS_VS | MSTATUS_FS | 3 << MSTATUS_UXL_SHIFT);
+        #[cfg(feature = "F")]
+        if !self.fe.isa.fp_inx() {
+            mask |= MSTATUS_FS;
+        }
+        #[cfg(feature = "V")]
+        if self.fe.isa.vector() {
+            mask |= MSTATUS_VS;
+        }
+        mask
+    }
+}
+
+/// sstatus view of mstatus, SD included
+#[cfg(feature = "S")]
+fn sstatus_mask<Xlen: XlenT>() -> Xlen {
+    Xlen::from(SSTATUS_MASK) | Xlen::from(1) << (Xlen::XLEN - 1)
+}
@@ -1,33 +1,811 @@
-use crate::{hart::Hart, uop::Exception, utils::Maybe, xlen::XlenT};
+#[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+use crate::uop::CboOp;
+use crate::{
+    hart::Hart,
+    memory::Fault,
+    uop::{Exception, MemProtect},
+    utils::Maybe,
+    xlen::XlenT,
+};
+
+mod csr;
+
+pub use csr::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum PrivMode {
+    U = 0,
+    S = 1,
+    #[default]
+    M = 3,
+}
+
+impl PrivMode {
+    /// supported privilege mode from its encoding
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        match bits {
+            0 => Some(PrivMode::U),
+            #[cfg(feature = "S")]
+            1 => Some(PrivMode::S),
+            3 => Some(PrivMode::M),
+            _ => None,
+        }
+    }
+}
 
 /// holds privlige state of hart  
 /// eg. privilege level,
 /// csr handler, ...
-#[derive(Debug, Clone, Default)]
-pub struct PrivCtrl {
+#[derive(Debug, Clone)]
+pub struct PrivCtrl<Xlen: XlenT> {
+    /// current privilege level
+    pub mode: PrivMode,
+    pub hart_id: usize,
+    pub mstatus: Xlen,
+    pub mie: Xlen,
+    pub mip: Xlen,
+    pub mtvec: Xlen,
+    pub mscratch: Xlen,
+    pub mepc: Xlen,
+    pub mcause: Xlen,
+    pub mtval: Xlen,
+    /// lower privilege access to cbo.*
+    pub menvcfg: Xlen,
+    /// exceptions delegated to S-mode
+    #[cfg(feature = "S")]
+    pub medeleg: Xlen,
+    /// interrupts delegated to S-mode
+    #[cfg(feature = "S")]
+    pub mideleg: Xlen,
+    #[cfg(feature = "S")]
+    pub stvec: Xlen,
+    #[cfg(feature = "S")]
+    pub sscratch: Xlen,
+    #[cfg(feature = "S")]
+    pub sepc: Xlen,
+    #[cfg(feature = "S")]
+    pub scause: Xlen,
+    #[cfg(feature = "S")]
+    pub stval: Xlen,
+    /// user access to cbo.*, under menvcfg
+    #[cfg(feature = "S")]
+    pub senvcfg: Xlen,
+    /// translation mode and root page table
+    #[cfg(feature = "S")]
+    pub satp: Xlen,
+    /// V, in VS / VU-mode
+    #[cfg(feature = "H")]
+    pub virt: bool,
+    /// MPV and GVA, at mstatus\[39:38\] on rv64
+    #[cfg(feature = "H")]
+    pub mstatush: u32,
+    /// guest physical address >> 2 of a guest-page fault in M-mode
+    #[cfg(feature = "H")]
+    pub mtval2: Xlen,
+    #[cfg(feature = "H")]
+    pub hstatus: Xlen,
+    /// exceptions delegated to VS-mode
+    #[cfg(feature = "H")]
+    pub hedeleg: Xlen,
+    /// interrupts delegated to VS-mode
+    #[cfg(feature = "H")]
+    pub hideleg: Xlen,
+    /// VS / VU access to cbo.*, under menvcfg
+    #[cfg(feature = "H")]
+    pub henvcfg: Xlen,
+    /// guest physical address >> 2 of a guest-page fault in HS-mode
+    #[cfg(feature = "H")]
+    pub htval: Xlen,
+    /// G-stage translation mode and root page table
+    #[cfg(feature = "H")]
+    pub hgatp: Xlen,
+    /// sstatus of VS-mode
+    #[cfg(feature = "H")]
+    pub vsstatus: Xlen,
+    #[cfg(feature = "H")]
+    pub vstvec: Xlen,
+    #[cfg(feature = "H")]
+    pub vsscratch: Xlen,
+    #[cfg(feature = "H")]
+    pub vsepc: Xlen,
+    #[cfg(feature = "H")]
+    pub vscause: Xlen,
+    #[cfg(feature = "H")]
+    pub vstval: Xlen,
+    /// VS-stage translation mode and root page table
+    #[cfg(feature = "H")]
+    pub vsatp: Xlen,
+    /// hlv / hsv in flight, `Some(true)` for hlvx
+    #[cfg(feature = "H")]
+    pub hlsv: Option<bool>,
+    /// table jump base and mode
+    #[cfg(feature = "Zcmt")]
+    pub jvt: Xlen,
     #[cfg(test)]
     pub hooked: bool,
 }
 
+impl<Xlen: XlenT> Default for PrivCtrl<Xlen> {
+    fn default() -> Self {
+        // UXL resets to MXL, rv32 has no UXL
+        let mstatus = if Xlen::XLEN > 32 {
+            Xlen::from(xl_of(Xlen::XLEN)) << MSTATUS_UXL_SHIFT
+        } else {
+            Xlen::from(0)
+        };
+        Self {
+            mode: PrivMode::default(),
+            hart_id: 0,
+            mstatus,
+            mie: Xlen::from(0),
+            mip: Xlen::from(0),
+            mtvec: Xlen::from(0),
+            mscratch: Xlen::from(0),
+            mepc: Xlen::from(0),
+            mcause: Xlen::from(0),
+            mtval: Xlen::from(0),
+            menvcfg: Xlen::from(0),
+            #[cfg(feature = "S")]
+            medeleg: Xlen::from(0),
+            #[cfg(feature = "S")]
+            mideleg: Xlen::from(0),
+            #[cfg(feature = "S")]
+            stvec: Xlen::from(0),
+            #[cfg(feature = "S")]
+            sscratch: Xlen::from(0),
+            #[cfg(feature = "S")]
+            sepc: Xlen::from(0),
+            #[cfg(feature = "S")]
+            scause: Xlen::from(0),
+            #[cfg(feature = "S")]
+            stval: Xlen::from(0),
+            #[cfg(feature = "S")]
+            senvcfg: Xlen::from(0),
+            #[cfg(feature = "S")]
+            satp: Xlen::from(0),
+            #[cfg(feature = "H")]
+            virt: false,
+            #[cfg(feature = "H")]
+            mstatush: 0,
+            #[cfg(feature = "H")]
+            mtval2: Xlen::from(0),
+            #[cfg(feature = "H")]
+            hstatus: Xlen::from(0),
+            #[cfg(feature = "H")]
+            hedeleg: Xlen::from(0),
+            #[cfg(feature = "H")]
+            hideleg: Xlen::from(0),
+            #[cfg(feature = "H")]
+            henvcfg: Xlen::from(0),
+            #[cfg(feature = "H")]
+            htval: Xlen::from(0),
+            #[cfg(feature = "H")]
+            hgatp: Xlen::from(0),
+            // UXL of VS-mode is fixed to MXL
+            #[cfg(feature = "H")]
+            vsstatus: mstatus,
+            #[cfg(feature = "H")]
+            vstvec: Xlen::from(0),
+            #[cfg(feature = "H")]
+            vsscratch: Xlen::from(0),
+            #[cfg(feature = "H")]
+            vsepc: Xlen::from(0),
+            #[cfg(feature = "H")]
+            vscause: Xlen::from(0),
+            #[cfg(feature = "H")]
+            vstval: Xlen::from(0),
+            #[cfg(feature = "H")]
+            vsatp: Xlen::from(0),
+            #[cfg(feature = "H")]
+            hlsv: None,
+            #[cfg(feature = "Zcmt")]
+            jvt: Xlen::from(0),
+            #[cfg(test)]
+            hooked: false,
+        }
+    }
+}
+
+impl<Xlen: XlenT> PrivCtrl<Xlen> {
+    /// V=1, in VS / VU-mode
+    pub fn is_virt(&self) -> bool {
+        #[cfg(feature = "H")]
+        return self.virt;
+        #[cfg(not(feature = "H"))]
+        false
+    }
+}
+
+impl Exception {
+    /// exception code in mcause
+    pub fn code(self, mode: PrivMode) -> u32 {
+        match self {
+            Exception::AddrMisalign(MemProtect::X) => 0,
+            Exception::AccessFault(MemProtect::X) => 1,
+            Exception::IllegalInstr => 2,
+            Exception::Ebreak => 3,
+            Exception::AddrMisalign(MemProtect::R) => 4,
+            Exception::AccessFault(MemProtect::R) => 5,
+            Exception::AddrMisalign(MemProtect::W) => 6,
+            Exception::AccessFault(MemProtect::W) => 7,
+            Exception::Ecall => 8 + mode as u32,
+            Exception::PageFault(MemProtect::X) => 12,
+            Exception::PageFault(MemProtect::R) => 13,
+            Exception::PageFault(MemProtect::W) => 15,
+            #[cfg(feature = "H")]
+            Exception::GuestPageFault(MemProtect::X) => 20,
+            #[cfg(feature = "H")]
+            Exception::GuestPageFault(MemProtect::R) => 21,
+            #[cfg(feature = "H")]
+            Exception::VirtualInstr => 22,
+            #[cfg(feature = "H")]
+            Exception::GuestPageFault(MemProtect::W) => 23,
+        }
+    }
+
+    /// same fault for another kind of access
+    #[cfg(any(feature = "S", feature = "Zicbom", feature = "Zicboz"))]
+    pub fn with_prot(self, prot: MemProtect) -> Self {
+        match self {
+            Exception::AddrMisalign(_) => Exception::AddrMisalign(prot),
+            Exception::AccessFault(_) => Exception::AccessFault(prot),
+            Exception::PageFault(_) => Exception::PageFault(prot),
+            #[cfg(feature = "H")]
+            Exception::GuestPageFault(_) => Exception::GuestPageFault(prot),
+            _ => self,
+        }
+    }
+}
+
 impl<Xlen: XlenT> Hart<Xlen> {
-    pub fn raise(&mut self, _reason: Exception) -> Maybe<()> {
+    pub fn raise(&mut self, reason: Exception) -> Maybe<()> {
+        self.raise_with(reason, Xlen::from(0))
+    }
+    /// raise exception with trap value, eg. faulting address
+    pub fn raise_with(&mut self, reason: Exception, tval: Xlen) -> Maybe<()> {
+        if let Some(cosim) = self.cosim.as_mut() {
+            cosim.squash();
+        }
         #[cfg(test)]
-        if self.priv_ctrl.hooked && _reason == Exception::Ebreak {
+        if self.priv_ctrl.hooked && reason == Exception::Ebreak {
             self.stop_tok = true;
             return Err(());
         }
-        todo!()
+        #[allow(unused_mut)]
+        let mut code = reason.code(self.priv_ctrl.mode);
+        // VS-mode is not S-mode to ecall
+        #[cfg(feature = "H")]
+        if reason == Exception::Ecall && self.priv_ctrl.virt && self.priv_ctrl.mode == PrivMode::S {
+            code = 10;
+        }
+        self.trap(Xlen::from(code), tval, None);
+        Err(())
+    }
+
+    /// raise failed translation, guest-page faults also write the
+    /// guest physical address >> 2 to mtval2 / htval
+    pub fn raise_fault(&mut self, (reason, gpa): Fault, tval: Xlen) -> Maybe<()> {
+        let res = self.raise_with(reason, tval);
+        #[cfg(feature = "H")]
+        if matches!(reason, Exception::GuestPageFault(_)) {
+            let p = &mut self.priv_ctrl;
+            let gpa = Xlen::from(gpa >> 2);
+            if p.mode == PrivMode::M {
+                p.mtval2 = gpa;
+            } else {
+                p.htval = gpa;
+            }
+        }
+        #[cfg(not(feature = "H"))]
+        let _ = gpa;
+        res
+    }
+
+    /// take highest priority pending and enabled interrupt
+    pub fn check_irq(&mut self) -> Maybe<()> {
+        let p = &self.priv_ctrl;
+        let pending = p.mip & p.mie;
+        if pending == Xlen::from(0) {
+            return Ok(());
+        }
+        let enabled = self.enabled_irqs(pending);
+        let irqs = [
+            IRQ_MEI,
+            IRQ_MSI,
+            IRQ_MTI,
+            IRQ_SEI,
+            IRQ_SSI,
+            IRQ_STI,
+            #[cfg(feature = "H")]
+            IRQ_SGEI,
+            #[cfg(feature = "H")]
+            IRQ_VSEI,
+            #[cfg(feature = "H")]
+            IRQ_VSSI,
+            #[cfg(feature = "H")]
+            IRQ_VSTI,
+        ];
+        let Some(irq) = irqs
+            .into_iter()
+            .find(|&irq| enabled & (Xlen::from(1) << irq) != Xlen::from(0))
+        else {
+            return Ok(());
+        };
+        if let Some(cosim) = self.cosim.as_mut() {
+            cosim.squash();
+        }
+        self.trap(irq_cause(irq), Xlen::from(0), Some(irq));
+        Err(())
+    }
+
+    /// pending interrupts of the highest level that can take one,
+    /// a level takes interrupts below it, or at it with its xIE set,
+    /// levels are M, HS and VS, HS-mode is below VS-mode
+    fn enabled_irqs(&self, pending: Xlen) -> Xlen {
+        let p = &self.priv_ctrl;
+        let zero = Xlen::from(0);
+        let m_on = p.mode < PrivMode::M || p.mstatus & Xlen::from(MSTATUS_MIE) != zero;
+        #[cfg(feature = "S")]
+        {
+            let deleg = self.irq_deleg();
+            let m_irqs = pending & !deleg;
+            if m_on && m_irqs != zero {
+                return m_irqs;
+            }
+            let virt = p.is_virt();
+            let s_on = virt
+                || p.mode < PrivMode::S
+                || p.mode == PrivMode::S && p.mstatus & Xlen::from(MSTATUS_SIE) != zero;
+            #[cfg(feature = "H")]
+            let (s_irqs, vs_irqs) = (pending & deleg & !p.hideleg, pending & p.hideleg);
+            #[cfg(not(feature = "H"))]
+            let s_irqs = pending & deleg;
+            if s_on && s_irqs != zero {
+                return s_irqs;
+            }
+            #[cfg(feature = "H")]
+            {
+                let vs_on =
+                    virt && (p.mode == PrivMode::U || p.vsstatus & Xlen::from(MSTATUS_SIE) != zero);
+                if vs_on {
+                    return vs_irqs;
+                }
+            }
+            zero
+        }
+        #[cfg(not(feature = "S"))]
+        if m_on {
+            pending
+        } else {
+            zero
+        }
+    }
+
+    /// set / clear machine level interrupt pending bit driven by devices
+    pub fn set_irq(&mut self, irq: u32, level: bool) {
+        let bit = Xlen::from(1) << irq;
+        let mip = &mut self.priv_ctrl.mip;
+        *mip = if level { *mip | bit } else { *mip & !bit };
+    }
+
+    /// trap into machine mode, or S-mode when delegated from below M,
+    /// or VS-mode when delegated again from VS / VU-mode
+    fn trap(&mut self, cause: Xlen, tval: Xlen, irq: Option<u32>) {
+        let pc = self.get_pc();
+        // a trap between lr and sc must fail the sc
+        if let Some(bus) = self.mem.bus.as_ref() {
+            bus.clear_reservation(self.priv_ctrl.hart_id);
+        }
+        #[cfg(feature = "H")]
+        if self.vs_delegated(cause, irq) {
+            // VS-level interrupts appear as S-level ones in VS-mode
+            let irq = irq.map(|irq| irq - 1);
+            let cause = irq.map_or(cause, irq_cause);
+            let tvec = self.trap_vs(pc, cause, tval);
+            return self.enter_trap(tvec, irq);
+        }
+        #[cfg(feature = "H")]
+        let from = (
+            self.priv_ctrl.mode,
+            self.priv_ctrl.virt,
+            self.gva(cause, irq),
+        );
+        #[cfg(feature = "S")]
+        let tvec = if self.delegated(cause, irq) {
+            self.trap_s(pc, cause, tval)
+        } else {
+            self.trap_m(pc, cause, tval)
+        };
+        #[cfg(not(feature = "S"))]
+        let tvec = self.trap_m(pc, cause, tval);
+        #[cfg(feature = "H")]
+        self.trap_h(from);
+        self.enter_trap(tvec, irq);
+    }
+
+    /// jump to the handler, vectored interrupts at base + 4 * cause
+    fn enter_trap(&mut self, tvec: Xlen, irq: Option<u32>) {
+        let base = tvec & !Xlen::from(3);
+        let vectored = tvec & Xlen::from(3) == Xlen::from(1);
+        self.pc = match irq {
+            Some(irq) if vectored => base.add(irq * 4),
+            _ => base,
+        };
+        self.switch_xlen();
+    }
+
+    /// save state into m* csrs and enter machine mode, returns mtvec
+    fn trap_m(&mut self, pc: Xlen, cause: Xlen, tval: Xlen) -> Xlen {
+        let p = &mut self.priv_ctrl;
+        p.mepc = pc;
+        p.mcause = cause;
+        p.mtval = tval;
+        let mie = p.mstatus & Xlen::from(MSTATUS_MIE) != Xlen::from(0);
+        let mut mstatus = p.mstatus & !Xlen::from(MSTATUS_MIE | MSTATUS_MPIE | MSTATUS_MPP);
+        if mie {
+            mstatus = mstatus | Xlen::from(MSTATUS_MPIE);
+        }
+        p.mstatus = mstatus | (Xlen::from(p.mode as u32) << MSTATUS_MPP_SHIFT);
+        p.mode = PrivMode::M;
+        p.mtvec
+    }
+
+    /// save state into s* csrs and enter S-mode, returns stvec
+    #[cfg(feature = "S")]
+    fn trap_s(&mut self, pc: Xlen, cause: Xlen, tval: Xlen) -> Xlen {
+        let p = &mut self.priv_ctrl;
+        p.sepc = pc;
+        p.scause = cause;
+        p.stval = tval;
+        let sie = p.mstatus & Xlen::from(MSTATUS_SIE) != Xlen::from(0);
+        let mut mstatus = p.mstatus & !Xlen::from(MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP);
+        if sie {
+            mstatus = mstatus | Xlen::from(MSTATUS_SPIE);
+        }
+        if p.mode == PrivMode::S {
+            mstatus = mstatus | Xlen::from(MSTATUS_SPP);
+        }
+        p.mstatus = mstatus;
+        p.mode = PrivMode::S;
+        p.stvec
+    }
+
+    /// save state into vs* csrs and enter VS-mode, returns vstvec
+    #[cfg(feature = "H")]
+    fn trap_vs(&mut self, pc: Xlen, cause: Xlen, tval: Xlen) -> Xlen {
+        let p = &mut self.priv_ctrl;
+        p.vsepc = pc;
+        p.vscause = cause;
+        p.vstval = tval;
+        let sie = p.vsstatus & Xlen::from(MSTATUS_SIE) != Xlen::from(0);
+        let mut vsstatus = p.vsstatus & !Xlen::from(MSTATUS_SIE | MSTATUS_SPIE | MSTATUS_SPP);
+        if sie {
+            vsstatus = vsstatus | Xlen::from(MSTATUS_SPIE);
+        }
+        if p.mode == PrivMode::S {
+            vsstatus = vsstatus | Xlen::from(MSTATUS_SPP);
+        }
+        p.vsstatus = vsstatus;
+        p.mode = PrivMode::S;
+        p.vstvec
+    }
+
+    /// V before a trap into HS / M-mode goes to SPV / MPV with
+    /// the privilege of VS / VU-mode in SPVP, GVA marks a guest
+    /// virtual address in xtval
+    #[cfg(feature = "H")]
+    fn trap_h(&mut self, (mode, virt, gva): (PrivMode, bool, bool)) {
+        let p = &mut self.priv_ctrl;
+        if p.mode == PrivMode::M {
+            let mut mstatush = p.mstatush & !(MSTATUSH_MPV | MSTATUSH_GVA);
+            if virt {
+                mstatush |= MSTATUSH_MPV;
+            }
+            if gva {
+                mstatush |= MSTATUSH_GVA;
+            }
+            p.mstatush = mstatush;
+            p.mtval2 = Xlen::from(0);
+        } else {
+            let mut hstatus = p.hstatus & !Xlen::from(HSTATUS_SPV | HSTATUS_GVA);
+            if virt {
+                hstatus = hstatus & !Xlen::from(HSTATUS_SPVP) | Xlen::from(HSTATUS_SPV);
+                if mode == PrivMode::S {
+                    hstatus = hstatus | Xlen::from(HSTATUS_SPVP);
+                }
+            }
+            if gva {
+                hstatus = hstatus | Xlen::from(HSTATUS_GVA);
+            }
+            p.hstatus = hstatus;
+            p.htval = Xlen::from(0);
+        }
+        p.virt = false;
+    }
+
+    /// xtval of the exception holds a guest virtual address,
+    /// from VS / VU-mode or hlv / hsv
+    #[cfg(feature = "H")]
+    fn gva(&self, cause: Xlen, irq: Option<u32>) -> bool {
+        // misaligned, access, page and guest-page faults
+        const ADDR_CAUSES: u64 = 0xb0_b0f3;
+        let p = &self.priv_ctrl;
+        let code: u64 = cause.into();
+        (p.virt || p.hlsv.is_some()) && irq.is_none() && ADDR_CAUSES >> code & 1 != 0
+    }
+
+    /// traps below machine mode go to S-mode when delegated by medeleg / mideleg
+    #[cfg(feature = "S")]
+    fn delegated(&self, cause: Xlen, irq: Option<u32>) -> bool {
+        let p = &self.priv_ctrl;
+        let (code, deleg) = match irq {
+            Some(irq) => (irq, self.irq_deleg()),
+            None => (cause.into(), p.medeleg),
+        };
+        p.mode < PrivMode::M && deleg & (Xlen::from(1) << code) != Xlen::from(0)
+    }
+
+    /// traps from VS / VU-mode go on to VS-mode when delegated
+    /// to S-mode and by hedeleg / hideleg
+    #[cfg(feature = "H")]
+    fn vs_delegated(&self, cause: Xlen, irq: Option<u32>) -> bool {
+        let p = &self.priv_ctrl;
+        let (code, deleg) = match irq {
+            Some(irq) => (irq, p.hideleg),
+            None => (cause.into(), p.hedeleg),
+        };
+        p.virt && self.delegated(cause, irq) && deleg & (Xlen::from(1) << code) != Xlen::from(0)
+    }
+
+    /// mideleg, VS-level interrupts are always delegated with H
+    #[cfg(feature = "S")]
+    fn irq_deleg(&self) -> Xlen {
+        let deleg = self.priv_ctrl.mideleg;
+        if self.fe.isa.hyp() {
+            deleg | Xlen::from(MIP_VS | MIP_SGEIP)
+        } else {
+            deleg
+        }
+    }
+
+    /// return from machine mode trap
+    pub fn mret(&mut self) -> Maybe<()> {
+        if self.priv_ctrl.mode != PrivMode::M {
+            return self.raise(Exception::IllegalInstr);
+        }
+        let p = &mut self.priv_ctrl;
+        let mpp: u32 = (p.mstatus >> MSTATUS_MPP_SHIFT).into();
+        let mpie = p.mstatus & Xlen::from(MSTATUS_MPIE) != Xlen::from(0);
+        let mut mstatus = p.mstatus & !Xlen::from(MSTATUS_MIE | MSTATUS_MPP);
+        if mpie {
+            mstatus = mstatus | Xlen::from(MSTATUS_MIE);
+        }
+        // MPP is set to the least-privileged supported mode
+        p.mstatus = mstatus | Xlen::from(MSTATUS_MPIE);
+        p.mode = PrivMode::from_bits(mpp & 3).unwrap_or(PrivMode::M);
+        // MPRV only applies in machine mode
+        #[cfg(feature = "S")]
+        if p.mode != PrivMode::M {
+            p.mstatus = p.mstatus & !Xlen::from(MSTATUS_MPRV);
+        }
+        #[cfg(feature = "H")]
+        {
+            p.virt = p.mode != PrivMode::M && p.mstatush & MSTATUSH_MPV != 0;
+            p.mstatush &= !MSTATUSH_MPV;
+        }
+        let mepc = p.mepc;
+        self.switch_xlen();
+        self.set_pc(mepc)
+    }
+
+    /// wfi / wrs.nto below machine mode with TW set, or in U-mode
+    /// when S-mode exists, the time limit before trapping is zero,
+    /// VTW traps them in VS-mode as virtual instructions
+    pub fn check_tw(&mut self) -> Maybe<()> {
+        let p = &self.priv_ctrl;
+        let tw = p.mstatus & Xlen::from(MSTATUS_TW) != Xlen::from(0);
+        if p.mode != PrivMode::M && tw {
+            return self.raise(Exception::IllegalInstr);
+        }
+        #[cfg(feature = "H")]
+        if p.virt && (p.mode == PrivMode::U || p.hstatus & Xlen::from(HSTATUS_VTW) != Xlen::from(0))
+        {
+            return self.raise(Exception::VirtualInstr);
+        }
+        if p.mode == PrivMode::U && self.fe.isa.s_mode() {
+            return self.raise(Exception::IllegalInstr);
+        }
+        Ok(())
+    }
+
+    /// sfence.vma is illegal in U-mode, and in HS-mode with TVM set,
+    /// a virtual instruction in VU-mode, and in VS-mode with VTVM set
+    #[cfg(feature = "S")]
+    pub fn check_sfence(&mut self) -> Maybe<()> {
+        let p = &self.priv_ctrl;
+        #[cfg(feature = "H")]
+        if p.virt
+            && (p.mode == PrivMode::U || p.hstatus & Xlen::from(HSTATUS_VTVM) != Xlen::from(0))
+        {
+            return self.raise(Exception::VirtualInstr);
+        }
+        let tvm = !p.is_virt() && p.mstatus & Xlen::from(MSTATUS_TVM) != Xlen::from(0);
+        if p.mode == PrivMode::U || p.mode == PrivMode::S && tvm {
+            return self.raise(Exception::IllegalInstr);
+        }
+        Ok(())
+    }
+
+    /// hfence.* is a virtual instruction in VS / VU-mode, illegal in U-mode,
+    /// and hfence.gvma in HS-mode with TVM set
+    #[cfg(feature = "H")]
+    pub fn check_hfence(&mut self, gvma: bool) -> Maybe<()> {
+        let p = &self.priv_ctrl;
+        if p.virt {
+            return self.raise(Exception::VirtualInstr);
+        }
+        let tvm = p.mstatus & Xlen::from(MSTATUS_TVM) != Xlen::from(0);
+        if p.mode == PrivMode::U || gvma && p.mode == PrivMode::S && tvm {
+            return self.raise(Exception::IllegalInstr);
+        }
+        Ok(())
+    }
+
+    /// hlv / hsv are virtual instructions in VS / VU-mode,
+    /// U-mode needs HU set
+    #[cfg(feature = "H")]
+    pub fn check_hlsv(&mut self) -> Maybe<()> {
+        let p = &self.priv_ctrl;
+        if p.virt {
+            return self.raise(Exception::VirtualInstr);
+        }
+        if p.mode == PrivMode::U && p.hstatus & Xlen::from(HSTATUS_HU) == Xlen::from(0) {
+            return self.raise(Exception::IllegalInstr);
+        }
+        Ok(())
+    }
+
+    /// return from S-mode trap, trapped by TSR in HS-mode,
+    /// VS-mode returns through vsstatus and vsepc, trapped by VTSR
+    #[cfg(feature = "S")]
+    pub fn sret(&mut self) -> Maybe<()> {
+        let p = &self.priv_ctrl;
+        let virt = p.is_virt();
+        #[cfg(feature = "H")]
+        if virt && (p.mode == PrivMode::U || p.hstatus & Xlen::from(HSTATUS_VTSR) != Xlen::from(0))
+        {
+            return self.raise(Exception::VirtualInstr);
+        }
+        let tsr = p.mstatus & Xlen::from(MSTATUS_TSR) != Xlen::from(0);
+        if p.mode == PrivMode::U || p.mode == PrivMode::S && !virt && tsr {
+            return self.raise(Exception::IllegalInstr);
+        }
+        let p = &mut self.priv_ctrl;
+        #[cfg(feature = "H")]
+        let (status, epc) = if virt {
+            (&mut p.vsstatus, p.vsepc)
+        } else {
+            (&mut p.mstatus, p.sepc)
+        };
+        #[cfg(not(feature = "H"))]
+        let (status, epc) = (&mut p.mstatus, p.sepc);
+        let spp = *status & Xlen::from(MSTATUS_SPP) != Xlen::from(0);
+        let spie = *status & Xlen::from(MSTATUS_SPIE) != Xlen::from(0);
+        let mut new = *status & !Xlen::from(MSTATUS_SIE | MSTATUS_SPP);
+        if spie {
+            new = new | Xlen::from(MSTATUS_SIE);
+        }
+        *status = new | Xlen::from(MSTATUS_SPIE);
+        p.mstatus = p.mstatus & !Xlen::from(MSTATUS_MPRV);
+        p.mode = if spp { PrivMode::S } else { PrivMode::U };
+        #[cfg(feature = "H")]
+        if !virt {
+            p.virt = p.hstatus & Xlen::from(HSTATUS_SPV) != Xlen::from(0);
+        }
+        self.switch_xlen();
+        self.set_pc(epc)
+    }
+
+    /// exception of an instruction that HS-mode would allow
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+    fn denied(&self) -> Exception {
+        #[cfg(feature = "H")]
+        if self.priv_ctrl.virt {
+            return Exception::VirtualInstr;
+        }
+        Exception::IllegalInstr
+    }
+
+    /// cbo.* below machine mode as enabled by menvcfg, in VS / VU-mode by
+    /// henvcfg, and by senvcfg in (V)U-mode, cbo.inval may be demoted to flush
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+    pub fn cbo_allowed(&self, op: CboOp) -> Result<CboOp, Exception> {
+        let p = &self.priv_ctrl;
+        let envcfgs = [
+            (p.mode < PrivMode::M, p.menvcfg, Exception::IllegalInstr),
+            #[cfg(feature = "H")]
+            (p.virt, p.henvcfg, Exception::VirtualInstr),
+            #[cfg(feature = "S")]
+            (
+                p.mode == PrivMode::U && self.fe.isa.s_mode(),
+                p.senvcfg,
+                self.denied(),
+            ),
+        ];
+        envcfgs
+            .into_iter()
+            .filter(|&(applies, ..)| applies)
+            .try_fold(op, |res, (_, envcfg, denied)| {
+                cbo_enable(envcfg.into(), op, res).ok_or(denied)
+            })
+    }
+
+    /// effective xlen is UXL in user mode, MXL otherwise,
+    /// UXL of VU-mode is fixed to MXL
+    fn switch_xlen(&mut self) {
+        let p = &self.priv_ctrl;
+        let xlen = if p.mode == PrivMode::U && !p.is_virt() && Xlen::XLEN > 32 {
+            let uxl: u32 = (p.mstatus >> MSTATUS_UXL_SHIFT).into();
+            xlen_of(uxl & 3)
+        } else {
+            Xlen::XLEN
+        };
+        self.fe.set_xlen(xlen);
+    }
+
+    #[cfg(feature = "Zicsr")]
+    fn csr_access(&mut self, addr: u16, write: bool, f: impl FnOnce(Xlen) -> Xlen) -> Maybe<Xlen> {
+        #[cfg(feature = "H")]
+        let target = self.csr_virt(addr, write)?;
+        #[cfg(not(feature = "H"))]
+        let target = addr;
+        if !self.csr_allowed(addr, write) {
+            self.raise(Exception::IllegalInstr)?;
+        }
+        let old = match self.csr_rd(target) {
+            Ok(old) => old,
+            Err(()) => {
+                self.raise(Exception::IllegalInstr)?;
+                return Err(()); // unreachable
+            }
+        };
+        if write {
+            self.csr_write(target, f(old))?;
+        }
+        Ok(old)
     }
     #[cfg(feature = "Zicsr")]
     pub fn csr_wr(&mut self, addr: u16, val: Xlen) -> Maybe<Xlen> {
-        todo!()
+        self.csr_access(addr, true, |_| val)
     }
     #[cfg(feature = "Zicsr")]
     pub fn csr_set(&mut self, addr: u16, mask: Xlen) -> Maybe<Xlen> {
-        todo!()
+        let write = mask != Xlen::from(0);
+        self.csr_access(addr, write, |old| old | mask)
     }
     #[cfg(feature = "Zicsr")]
     pub fn csr_clr(&mut self, addr: u16, mask: Xlen) -> Maybe<Xlen> {
-        todo!()
+        let write = mask != Xlen::from(0);
+        self.csr_access(addr, write, |old| old & !mask)
     }
 }
+
+/// mcause of an interrupt
+fn irq_cause<Xlen: XlenT>(irq: u32) -> Xlen {
+    (Xlen::from(1) << (Xlen::XLEN - 1)) | Xlen::from(irq)
+}
+
+/// `res` so far after the enables of one envcfg, `op` as issued
+#[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+fn cbo_enable(envcfg: u64, op: CboOp, res: CboOp) -> Option<CboOp> {
+    let on = match op {
+        CboOp::Inval => match (envcfg & MENVCFG_CBIE) >> MENVCFG_CBIE_SHIFT {
+            0b01 => return Some(CboOp::Flush),
+            0b11 => true,
+            _ => false,
+        },
+        CboOp::Clean | CboOp::Flush => envcfg & MENVCFG_CBCFE != 0,
+        CboOp::Zero => envcfg & MENVCFG_CBZE != 0,
+    };
+    on.then_some(res)
+}
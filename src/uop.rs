@@ -13,12 +13,17 @@ pub enum MemWidth {
     B,
     H,
     W,
-    #[cfg(feature = "RV64")]
+    #[cfg(any(feature = "RV64", feature = "Zacas"))]
     D,
+    /// rv128 lq/sq, otherwise only for amocas.q
+    #[cfg(any(feature = "RV128", feature = "Zacas"))]
+    Q,
     BU,
     HU,
     #[cfg(feature = "RV64")]
     WU,
+    #[cfg(feature = "RV128")]
+    DU,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,6 +33,53 @@ pub enum MiscMemOp {
     FenceTso,
     #[cfg(feature = "Zifencei")]
     FenceI,
+    /// fence w, 0
+    #[cfg(feature = "Zihintpause")]
+    Pause,
+}
+
+/// cache-block operations on the block containing the address
+#[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CboOp {
+    Clean,
+    Flush,
+    Inval,
+    Zero,
+}
+
+/// locality of the next memory access, from innermost private
+/// to outermost shared level
+#[cfg(feature = "Zihintntl")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NtlDomain {
+    P1,
+    Pall,
+    S1,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemOp {
+    #[cfg(feature = "S")]
+    Sret,
+    Mret,
+    Wfi,
+    /// wait on reservation set, no timeout
+    #[cfg(feature = "Zawrs")]
+    WrsNto,
+    /// wait on reservation set, short timeout
+    #[cfg(feature = "Zawrs")]
+    WrsSto,
+    /// (gp-rs1 / vaddr, gp-rs2 / asid)
+    #[cfg(feature = "S")]
+    SfenceVma(u8, u8),
+    /// (gp-rs1 / guest vaddr, gp-rs2 / asid)
+    #[cfg(feature = "H")]
+    HfenceVvma(u8, u8),
+    /// (gp-rs1 / guest paddr >> 2, gp-rs2 / vmid)
+    #[cfg(feature = "H")]
+    HfenceGvma(u8, u8),
 }
 
 // impl MiscMemOp {
@@ -59,6 +111,16 @@ pub enum BinaryOp {
     SubW,
     #[cfg(feature = "RV64")]
     SraW,
+    #[cfg(feature = "RV128")]
+    AddD,
+    #[cfg(feature = "RV128")]
+    SllD,
+    #[cfg(feature = "RV128")]
+    SrlD,
+    #[cfg(feature = "RV128")]
+    SubD,
+    #[cfg(feature = "RV128")]
+    SraD,
     #[cfg(feature = "M")]
     Mul,
     #[cfg(feature = "M")]
@@ -85,17 +147,191 @@ pub enum BinaryOp {
     RemW,
     #[cfg(all(feature = "M", feature = "RV64"))]
     RemUW,
+    #[cfg(all(feature = "M", feature = "RV128"))]
+    MulD,
+    #[cfg(all(feature = "M", feature = "RV128"))]
+    DivD,
+    #[cfg(all(feature = "M", feature = "RV128"))]
+    DivUD,
+    #[cfg(all(feature = "M", feature = "RV128"))]
+    RemD,
+    #[cfg(all(feature = "M", feature = "RV128"))]
+    RemUD,
     /// used for amo swap
     #[cfg(feature = "A")]
     Second,
-    #[cfg(feature = "A")]
+    #[cfg(any(feature = "A", feature = "Zbb"))]
     Max,
-    #[cfg(feature = "A")]
+    #[cfg(any(feature = "A", feature = "Zbb"))]
     MaxU,
-    #[cfg(feature = "A")]
+    #[cfg(any(feature = "A", feature = "Zbb"))]
     Min,
-    #[cfg(feature = "A")]
+    #[cfg(any(feature = "A", feature = "Zbb"))]
     MinU,
+    #[cfg(feature = "Zba")]
+    Sh1Add,
+    #[cfg(feature = "Zba")]
+    Sh2Add,
+    #[cfg(feature = "Zba")]
+    Sh3Add,
+    #[cfg(all(feature = "Zba", feature = "RV64"))]
+    AddUW,
+    #[cfg(all(feature = "Zba", feature = "RV64"))]
+    Sh1AddUW,
+    #[cfg(all(feature = "Zba", feature = "RV64"))]
+    Sh2AddUW,
+    #[cfg(all(feature = "Zba", feature = "RV64"))]
+    Sh3AddUW,
+    /// only as slli.uw
+    #[cfg(all(feature = "Zba", feature = "RV64"))]
+    SllUW,
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    Andn,
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    Orn,
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    Xnor,
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    Rol,
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    Ror,
+    #[cfg(all(any(feature = "Zbb", feature = "Zbkb"), feature = "RV64"))]
+    RolW,
+    #[cfg(all(any(feature = "Zbb", feature = "Zbkb"), feature = "RV64"))]
+    RorW,
+    #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+    Clmul,
+    #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+    Clmulh,
+    #[cfg(feature = "Zbc")]
+    Clmulr,
+    #[cfg(feature = "Zbs")]
+    Bclr,
+    #[cfg(feature = "Zbs")]
+    Bext,
+    #[cfg(feature = "Zbs")]
+    Binv,
+    #[cfg(feature = "Zbs")]
+    Bset,
+    #[cfg(feature = "Zbkb")]
+    Pack,
+    #[cfg(feature = "Zbkb")]
+    PackH,
+    #[cfg(all(feature = "Zbkb", feature = "RV64"))]
+    PackW,
+    #[cfg(feature = "Zbkx")]
+    Xperm4,
+    #[cfg(feature = "Zbkx")]
+    Xperm8,
+    /// zero if rhs is zero, otherwise lhs
+    #[cfg(feature = "Zicond")]
+    CzeroEqz,
+    /// zero if rhs is not zero, otherwise lhs
+    #[cfg(feature = "Zicond")]
+    CzeroNez,
+    /// (byte select)
+    #[cfg(feature = "Zkne")]
+    Aes32Esi(u8),
+    #[cfg(feature = "Zkne")]
+    Aes32Esmi(u8),
+    #[cfg(feature = "Zknd")]
+    Aes32Dsi(u8),
+    #[cfg(feature = "Zknd")]
+    Aes32Dsmi(u8),
+    #[cfg(all(feature = "Zkne", feature = "RV64"))]
+    Aes64Es,
+    #[cfg(all(feature = "Zkne", feature = "RV64"))]
+    Aes64Esm,
+    #[cfg(all(feature = "Zknd", feature = "RV64"))]
+    Aes64Ds,
+    #[cfg(all(feature = "Zknd", feature = "RV64"))]
+    Aes64Dsm,
+    /// only as aes64ks1i, rhs is round number
+    #[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+    Aes64Ks1i,
+    #[cfg(all(any(feature = "Zkne", feature = "Zknd"), feature = "RV64"))]
+    Aes64Ks2,
+    /// rv32 sha512 operating on register pairs
+    #[cfg(feature = "Zknh")]
+    Sha512Sig0H,
+    #[cfg(feature = "Zknh")]
+    Sha512Sig0L,
+    #[cfg(feature = "Zknh")]
+    Sha512Sig1H,
+    #[cfg(feature = "Zknh")]
+    Sha512Sig1L,
+    #[cfg(feature = "Zknh")]
+    Sha512Sum0R,
+    #[cfg(feature = "Zknh")]
+    Sha512Sum1R,
+    /// (byte select)
+    #[cfg(feature = "Zksed")]
+    Sm4Ed(u8),
+    #[cfg(feature = "Zksed")]
+    Sm4Ks(u8),
+}
+
+#[cfg(any(
+    feature = "Zbb",
+    feature = "Zbkb",
+    feature = "Zknd",
+    feature = "Zknh",
+    feature = "Zksh"
+))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    #[cfg(feature = "Zbb")]
+    Clz,
+    #[cfg(feature = "Zbb")]
+    Ctz,
+    #[cfg(feature = "Zbb")]
+    Cpop,
+    #[cfg(all(feature = "Zbb", feature = "RV64"))]
+    ClzW,
+    #[cfg(all(feature = "Zbb", feature = "RV64"))]
+    CtzW,
+    #[cfg(all(feature = "Zbb", feature = "RV64"))]
+    CpopW,
+    #[cfg(feature = "Zbb")]
+    SextB,
+    #[cfg(feature = "Zbb")]
+    SextH,
+    #[cfg(feature = "Zbb")]
+    ZextH,
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    Rev8,
+    #[cfg(feature = "Zbb")]
+    OrcB,
+    #[cfg(feature = "Zbkb")]
+    Brev8,
+    /// rv32 only
+    #[cfg(feature = "Zbkb")]
+    Zip,
+    /// rv32 only
+    #[cfg(feature = "Zbkb")]
+    Unzip,
+    #[cfg(all(feature = "Zknd", feature = "RV64"))]
+    Aes64Im,
+    #[cfg(feature = "Zknh")]
+    Sha256Sig0,
+    #[cfg(feature = "Zknh")]
+    Sha256Sig1,
+    #[cfg(feature = "Zknh")]
+    Sha256Sum0,
+    #[cfg(feature = "Zknh")]
+    Sha256Sum1,
+    #[cfg(all(feature = "Zknh", feature = "RV64"))]
+    Sha512Sig0,
+    #[cfg(all(feature = "Zknh", feature = "RV64"))]
+    Sha512Sig1,
+    #[cfg(all(feature = "Zknh", feature = "RV64"))]
+    Sha512Sum0,
+    #[cfg(all(feature = "Zknh", feature = "RV64"))]
+    Sha512Sum1,
+    #[cfg(feature = "Zksh")]
+    Sm3P0,
+    #[cfg(feature = "Zksh")]
+    Sm3P1,
 }
 
 #[cfg(feature = "A")]
@@ -126,6 +362,12 @@ pub enum Exception {
     IllegalInstr,
     Ecall,
     Ebreak,
+    /// G-stage fault of a guest physical address
+    #[cfg(feature = "H")]
+    GuestPageFault(MemProtect),
+    /// legal in HS-mode but not in VS / VU-mode
+    #[cfg(feature = "H")]
+    VirtualInstr,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -141,6 +383,10 @@ pub enum Precision {
     S,
     #[cfg(feature = "D")]
     D,
+    #[cfg(feature = "Zfhmin")]
+    H,
+    #[cfg(feature = "Q")]
+    Q,
 }
 
 #[cfg(feature = "F")]
@@ -183,12 +429,23 @@ pub enum FpBinaryOp {
     SgnJX,
     Min,
     Max,
+    /// nan if either operand is nan
+    #[cfg(feature = "Zfa")]
+    MinM,
+    #[cfg(feature = "Zfa")]
+    MaxM,
 }
 
 #[cfg(feature = "F")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FpUnaryOp {
     Sqrt,
+    /// round to integral value
+    #[cfg(feature = "Zfa")]
+    Round,
+    /// round to integral value, inexact raises exception
+    #[cfg(feature = "Zfa")]
+    RoundNX,
 }
 
 #[cfg(feature = "F")]
@@ -197,6 +454,12 @@ pub enum FpCmpCond {
     Eq,
     Lt,
     Le,
+    /// quiet lt
+    #[cfg(feature = "Zfa")]
+    LtQ,
+    /// quiet le
+    #[cfg(feature = "Zfa")]
+    LeQ,
 }
 
 #[cfg(feature = "F")]
@@ -210,6 +473,12 @@ pub enum FpGpOp {
     LU,
     MV,
     Class,
+    /// truncate to 32-bit modulo, fcvtmod.w.d
+    #[cfg(feature = "Zfa")]
+    ModW,
+    /// move high half, fmvh.x.{d/q}
+    #[cfg(feature = "Zfa")]
+    MvH,
 }
 
 #[cfg(feature = "F")]
@@ -224,6 +493,340 @@ pub enum GpFpOp {
     MV,
 }
 
+/// second operand of vector arithmetic
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VSrc {
+    /// vs1
+    V(u8),
+    /// gp-rs1
+    X(u8),
+    /// simm5 / uimm5
+    I(i8),
+    /// fp-rs1
+    F(u8),
+}
+
+/// avl and vtype sources of vset{i}vl{i}
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VCfg {
+    /// (gp-rs1, vtypei)
+    Vli(u8, u16),
+    /// (uimm, vtypei)
+    Ivli(u8, u16),
+    /// (gp-rs1, gp-rs2)
+    Vl(u8, u8),
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VAddrMode {
+    Unit,
+    /// unit-stride fault-only-first
+    UnitFF,
+    /// (gp-rs2 stride)
+    Strided(u8),
+    /// (vs2 offsets, ordered)
+    Indexed(u8, bool),
+    /// vlm.v / vsm.v
+    Mask,
+    /// whole register
+    Whole,
+}
+
+/// vector load / store
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VMemOp {
+    /// vd for load, vs3 for store
+    pub vd: u8,
+    /// gp-rs1 base address
+    pub rs1: u8,
+    pub mode: VAddrMode,
+    /// encoded element width in bytes
+    pub eew: u8,
+    /// number of fields (segments) or whole registers
+    pub nf: u8,
+    /// unmasked
+    pub vm: bool,
+}
+
+/// single-width integer and fixed-point
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VIntOp {
+    Add,
+    Sub,
+    RSub,
+    MinU,
+    Min,
+    MaxU,
+    Max,
+    And,
+    Or,
+    Xor,
+    Sll,
+    Srl,
+    Sra,
+    Mul,
+    MulH,
+    MulHU,
+    MulHSU,
+    DivU,
+    Div,
+    RemU,
+    Rem,
+    Macc,
+    NMSac,
+    MAdd,
+    NMSub,
+    /// add with carry-in from v0
+    Adc,
+    /// subtract with borrow-in from v0
+    Sbc,
+    /// vmerge when masked, vmv.v otherwise
+    Merge,
+    SAddU,
+    SAdd,
+    SSubU,
+    SSub,
+    AAddU,
+    AAdd,
+    ASubU,
+    ASub,
+    SMul,
+    SSrl,
+    SSra,
+}
+
+/// widening and narrowing integer and fixed-point
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VWideOp {
+    WAddU,
+    WAdd,
+    WSubU,
+    WSub,
+    WAddUW,
+    WAddW,
+    WSubUW,
+    WSubW,
+    WMulU,
+    WMulSU,
+    WMul,
+    WMaccU,
+    WMacc,
+    WMaccSU,
+    WMaccUS,
+    NSrl,
+    NSra,
+    NClipU,
+    NClip,
+}
+
+/// integer compare, writes mask
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VCmpOp {
+    Eq,
+    Ne,
+    LtU,
+    Lt,
+    LeU,
+    Le,
+    GtU,
+    Gt,
+    /// carry-out, carry-in from v0 when masked
+    Madc,
+    /// borrow-out, borrow-in from v0 when masked
+    Msbc,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VRedOp {
+    Sum,
+    And,
+    Or,
+    Xor,
+    MinU,
+    Min,
+    MaxU,
+    Max,
+    WSumU,
+    WSum,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VMaskLogic {
+    AndN,
+    And,
+    Or,
+    Xor,
+    OrN,
+    Nand,
+    Nor,
+    Xnor,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VUnaryOp {
+    /// population count of mask into gp-rd
+    Cpop,
+    /// index of first set mask bit into gp-rd
+    First,
+    /// set-before-first mask bit
+    Msbf,
+    /// set-including-first mask bit
+    Msif,
+    /// set-only-first mask bit
+    Msof,
+    Iota,
+    Id,
+    /// zero-extend from SEW / factor
+    Zext(u8),
+    /// sign-extend from SEW / factor
+    Sext(u8),
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VPermOp {
+    SlideUp,
+    SlideDown,
+    Slide1Up,
+    Slide1Down,
+    RGather,
+    RGatherEi16,
+    Compress,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VMvOp {
+    /// vmv.x.s (gp-rd, vs2)
+    XS,
+    /// vmv.s.x (vd, gp-rs1)
+    SX,
+    /// vfmv.f.s (fp-rd, vs2)
+    FS,
+    /// vfmv.s.f (vd, fp-rs1)
+    SF,
+    /// vmv<nr>r.v (vd, vs2)
+    Whole(u8),
+}
+
+/// single-width floating-point
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VFpOp {
+    Add,
+    Sub,
+    RSub,
+    Mul,
+    Div,
+    RDiv,
+    Min,
+    Max,
+    SgnJ,
+    SgnJN,
+    SgnJX,
+    /// vfmerge when masked, vfmv.v.f otherwise
+    Merge,
+    Macc,
+    NMacc,
+    MSac,
+    NMSac,
+    MAdd,
+    NMAdd,
+    MSub,
+    NMSub,
+}
+
+/// widening floating-point
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VFpWideOp {
+    WAdd,
+    WSub,
+    WAddW,
+    WSubW,
+    WMul,
+    WMacc,
+    WNMacc,
+    WMSac,
+    WNMSac,
+}
+
+/// floating-point compare, writes mask
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VFpCmpOp {
+    Eq,
+    Le,
+    Lt,
+    Ne,
+    Gt,
+    Ge,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VFpRedOp {
+    USum,
+    OSum,
+    Min,
+    Max,
+    WUSum,
+    WOSum,
+}
+
+/// conversion, `X` / `Xu` for signed / unsigned integer, `F` for float,
+/// destination comes first
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VFpCvt {
+    XuF,
+    XF,
+    FXu,
+    FX,
+    RtzXuF,
+    RtzXF,
+    FF,
+    /// round towards odd
+    RodFF,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VWidth {
+    Single,
+    Widen,
+    Narrow,
+}
+
+#[cfg(feature = "V")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VFpUnaryOp {
+    Sqrt,
+    Rsqrt7,
+    Rec7,
+    Class,
+    Cvt(VFpCvt, VWidth),
+}
+
+/// cm.pop variants, popretz also clears a0
+#[cfg(feature = "Zcmp")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopOp {
+    Pop,
+    PopRet,
+    PopRetZ,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Instr {
     #[default]
@@ -234,12 +837,27 @@ pub enum Instr {
     OpImm(u8, u8, i32, BinaryOp),
     /// (gp-rd, gp-rs1, gp-rs2, ...)
     Op(u8, u8, u8, BinaryOp),
+    /// (gp-rd, gp-rs1, ...)
+    #[cfg(any(
+        feature = "Zbb",
+        feature = "Zbkb",
+        feature = "Zknd",
+        feature = "Zknh",
+        feature = "Zksh"
+    ))]
+    OpUnary(u8, u8, UnaryOp),
     /// (gp-rd, imm)
     Auipc(u8, i32),
     /// (gp-rd, gp-rs1, offset, ...)
     Load(u8, u8, i32, MemWidth),
     /// (gp-rs1, gp-rs2, offset, ...)
     Store(u8, u8, i32, MemWidth),
+    /// hlv / hlvx, load as VS / VU-mode, (gp-rd, gp-rs1, width, hlvx)
+    #[cfg(feature = "H")]
+    HLoad(u8, u8, MemWidth, bool),
+    /// hsv, store as VS / VU-mode, (gp-rs1, gp-rs2, width)
+    #[cfg(feature = "H")]
+    HStore(u8, u8, MemWidth),
     MiscMem(MiscMemOp),
     /// (gp-rs1, gp-rs2, offset, ...)
     Branch(u8, u8, i32, CmpCond),
@@ -247,6 +865,16 @@ pub enum Instr {
     Jal(u8, i32),
     /// (gp-rd, gp-rs1, offset, ...)
     Jalr(u8, u8, i32),
+    System(SystemOp),
+    /// non-temporal hint, add x0, x0, x2 - x5
+    #[cfg(feature = "Zihintntl")]
+    Ntl(NtlDomain),
+    /// (gp-rs1, ...)
+    #[cfg(any(feature = "Zicbom", feature = "Zicboz"))]
+    Cbo(u8, CboOp),
+    /// (gp-rs1, offset, ...), prefetch hint of ori x0
+    #[cfg(feature = "Zicbop")]
+    Prefetch(u8, i32, MemProtect),
     /// (gp-rd, gp-rs1 / uimm, csr_addr, ...)
     #[cfg(feature = "Zicsr")]
     Csr(u8, u8, u16, CsrOp),
@@ -260,6 +888,10 @@ pub enum Instr {
     /// (gp-rd, gp-rs1, gp-rs2, ...)
     #[cfg(feature = "A")]
     Amo(u8, u8, u8, MemOrder, MemWidth, BinaryOp),
+    /// (gp-rd, gp-rs1, gp-rs2, ...)\
+    /// rd / rs2 are even register pairs when wider than xlen
+    #[cfg(feature = "Zacas")]
+    AmoCas(u8, u8, u8, MemOrder, MemWidth),
 
     /// (fp-rd, gp-rs1, offset, ...)
     #[cfg(feature = "F")]
@@ -286,8 +918,62 @@ pub enum Instr {
     #[cfg(feature = "F")]
     FpCmp(u8, u8, u8, Precision, FpCmpCond),
     /// (fp-rd, fp-rs1, round_mode, from_precision, to_precision)
-    #[cfg(feature = "D")]
+    #[cfg(feature = "F")]
     FpCvtFp(u8, u8, RoundMode, Precision, Precision),
+    /// (fp-rd, constant index, ...)
+    #[cfg(feature = "Zfa")]
+    FpLi(u8, u8, Precision),
+    /// (fp-rd, gp-rs1, gp-rs2, ...)\
+    /// rs1 / rs2 hold the low / high half
+    #[cfg(feature = "Zfa")]
+    FpMvP(u8, u8, u8, Precision),
+
+    /// (gp-rd, ...)
+    #[cfg(feature = "V")]
+    VSetVl(u8, VCfg),
+    #[cfg(feature = "V")]
+    VLoad(VMemOp),
+    #[cfg(feature = "V")]
+    VStore(VMemOp),
+    /// (vd, vs2, src, vm, ...), vm for unmasked
+    #[cfg(feature = "V")]
+    VOp(u8, u8, VSrc, bool, VIntOp),
+    /// (vd, vs2, src, vm, ...)
+    #[cfg(feature = "V")]
+    VOpW(u8, u8, VSrc, bool, VWideOp),
+    /// (vd, vs2, src, vm, ...)
+    #[cfg(feature = "V")]
+    VCmp(u8, u8, VSrc, bool, VCmpOp),
+    /// (vd, vs2, vs1, vm, ...)
+    #[cfg(feature = "V")]
+    VRed(u8, u8, u8, bool, VRedOp),
+    /// (vd, vs2, vs1, ...)
+    #[cfg(feature = "V")]
+    VMask(u8, u8, u8, VMaskLogic),
+    /// (vd / gp-rd, vs2, vm, ...)
+    #[cfg(feature = "V")]
+    VUnary(u8, u8, bool, VUnaryOp),
+    /// (vd, vs2, src, vm, ...)
+    #[cfg(feature = "V")]
+    VPerm(u8, u8, VSrc, bool, VPermOp),
+    /// (dst, src, ...)
+    #[cfg(feature = "V")]
+    VMv(u8, u8, VMvOp),
+    /// (vd, vs2, src, vm, ...)
+    #[cfg(feature = "V")]
+    VFOp(u8, u8, VSrc, bool, VFpOp),
+    /// (vd, vs2, src, vm, ...)
+    #[cfg(feature = "V")]
+    VFOpW(u8, u8, VSrc, bool, VFpWideOp),
+    /// (vd, vs2, src, vm, ...)
+    #[cfg(feature = "V")]
+    VFCmp(u8, u8, VSrc, bool, VFpCmpOp),
+    /// (vd, vs2, vs1, vm, ...)
+    #[cfg(feature = "V")]
+    VFRed(u8, u8, u8, bool, VFpRedOp),
+    /// (vd, vs2, vm, ...)
+    #[cfg(feature = "V")]
+    VFUnary(u8, u8, bool, VFpUnaryOp),
 
     /// (gp-rd, gp-rs1, imm, ...)
     #[cfg(feature = "C")]
@@ -310,6 +996,27 @@ pub enum Instr {
     /// (gp-rd, gp-rs1)
     #[cfg(feature = "C")]
     CJalr(u8, u8),
+    /// non-temporal hint, c.add x0, x2 - x5
+    #[cfg(all(feature = "C", feature = "Zihintntl"))]
+    CNtl(NtlDomain),
+    /// (gp-rd & gp-rs1, ...)
+    #[cfg(all(feature = "Zcb", feature = "Zbb"))]
+    COpUnary(u8, UnaryOp),
+    /// (register count of {ra, s0 - s11}, stack_adj)
+    #[cfg(feature = "Zcmp")]
+    CPush(u8, u32),
+    /// (register count of {ra, s0 - s11}, stack_adj, ...)
+    #[cfg(feature = "Zcmp")]
+    CPop(u8, u32, PopOp),
+    /// (gp-rd1, gp-rd2), from a0 / a1
+    #[cfg(feature = "Zcmp")]
+    CMvSA01(u8, u8),
+    /// (gp-rs1, gp-rs2), to a0 / a1
+    #[cfg(feature = "Zcmp")]
+    CMvA01S(u8, u8),
+    /// (jvt index), cm.jalt links ra from index 32
+    #[cfg(feature = "Zcmt")]
+    CTableJump(u8),
     /// (gp-rd, fp-rs1, offset, ...)
     #[cfg(all(feature = "C", feature = "F"))]
     CLoadFp(u8, u8, i32, Precision),
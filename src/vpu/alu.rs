@@ -0,0 +1,503 @@
+use super::*;
+
+/// shift `val` right by `d` bits, rounding by fixed-point rounding mode `vxrm`
+fn roundoff(val: i128, d: u32, vxrm: u8) -> i128 {
+    if d == 0 {
+        return val;
+    }
+    let bit = |n: u32| (val >> n) & 1 != 0;
+    let rest = |n: u32| val & ((1 << n) - 1) != 0;
+    let inc = match vxrm {
+        // round-to-nearest-up
+        0 => bit(d - 1),
+        // round-to-nearest-even
+        1 => bit(d - 1) && (rest(d - 1) || bit(d)),
+        // round-down
+        2 => false,
+        // round-to-odd
+        _ => !bit(d) && rest(d),
+    };
+    (val >> d) + inc as i128
+}
+
+/// saturate to signed element of `eew` bytes
+fn clip(val: i128, eew: usize, sat: &mut bool) -> u64 {
+    let max = (1i128 << (eew * 8 - 1)) - 1;
+    let min = -max - 1;
+    *sat |= val < min || val > max;
+    trunc(val.clamp(min, max) as u64, eew)
+}
+
+/// saturate to unsigned element of `eew` bytes
+fn clipu(val: i128, eew: usize, sat: &mut bool) -> u64 {
+    let max = (1i128 << (eew * 8)) - 1;
+    *sat |= val < 0 || val > max;
+    val.clamp(0, max) as u64
+}
+
+/// `a` from vs2, `b` from vs1 / scalar, `d` from vd, `c` carry / mask bit from v0
+#[allow(clippy::too_many_arguments)]
+fn int_op(
+    op: VIntOp,
+    a: u64,
+    b: u64,
+    d: u64,
+    c: bool,
+    eew: usize,
+    vxrm: u8,
+    sat: &mut bool,
+) -> u64 {
+    let bits = eew as u32 * 8;
+    let (sa, sb) = (sext(a, eew) as i128, sext(b, eew) as i128);
+    let (ua, ub) = (a as i128, b as i128);
+    let shamt = b as u32 & (bits - 1);
+    let res = match op {
+        VIntOp::Add => a.wrapping_add(b),
+        VIntOp::Sub => a.wrapping_sub(b),
+        VIntOp::RSub => b.wrapping_sub(a),
+        VIntOp::MinU => a.min(b),
+        VIntOp::Min => sa.min(sb) as u64,
+        VIntOp::MaxU => a.max(b),
+        VIntOp::Max => sa.max(sb) as u64,
+        VIntOp::And => a & b,
+        VIntOp::Or => a | b,
+        VIntOp::Xor => a ^ b,
+        VIntOp::Sll => a << shamt,
+        VIntOp::Srl => a >> shamt,
+        VIntOp::Sra => (sa >> shamt) as u64,
+        VIntOp::Mul => a.wrapping_mul(b),
+        VIntOp::MulH => ((sa * sb) >> bits) as u64,
+        VIntOp::MulHU => ((ua * ub) >> bits) as u64,
+        VIntOp::MulHSU => ((sa * ub) >> bits) as u64,
+        VIntOp::DivU => a.checked_div(b).unwrap_or(u64::MAX),
+        VIntOp::Div => match sb {
+            0 => u64::MAX,
+            // overflow only on most negative / -1
+            _ => (sa / sb) as u64,
+        },
+        VIntOp::RemU => a.checked_rem(b).unwrap_or(a),
+        VIntOp::Rem => match sb {
+            0 => a,
+            _ => (sa % sb) as u64,
+        },
+        VIntOp::Macc => d.wrapping_add(a.wrapping_mul(b)),
+        VIntOp::NMSac => d.wrapping_sub(a.wrapping_mul(b)),
+        VIntOp::MAdd => a.wrapping_add(d.wrapping_mul(b)),
+        VIntOp::NMSub => a.wrapping_sub(d.wrapping_mul(b)),
+        VIntOp::Adc => a.wrapping_add(b).wrapping_add(c as u64),
+        VIntOp::Sbc => a.wrapping_sub(b).wrapping_sub(c as u64),
+        VIntOp::Merge => b,
+        VIntOp::SAddU => clipu(ua + ub, eew, sat),
+        VIntOp::SAdd => clip(sa + sb, eew, sat),
+        VIntOp::SSubU => clipu(ua - ub, eew, sat),
+        VIntOp::SSub => clip(sa - sb, eew, sat),
+        VIntOp::AAddU => roundoff(ua + ub, 1, vxrm) as u64,
+        VIntOp::AAdd => roundoff(sa + sb, 1, vxrm) as u64,
+        VIntOp::ASubU => roundoff(ua - ub, 1, vxrm) as u64,
+        VIntOp::ASub => roundoff(sa - sb, 1, vxrm) as u64,
+        VIntOp::SMul => clip(roundoff(sa * sb, bits - 1, vxrm), eew, sat),
+        VIntOp::SSrl => roundoff(ua, shamt, vxrm) as u64,
+        VIntOp::SSra => roundoff(sa, shamt, vxrm) as u64,
+    };
+    trunc(res, eew)
+}
+
+/// `a` from vs2, `b` from vs1 / scalar, `d` from vd, in SEW of `eew` bytes
+fn wide_op(op: VWideOp, a: u64, b: u64, d: u64, eew: usize, vxrm: u8, sat: &mut bool) -> u64 {
+    let weew = eew * 2;
+    let (sa, sb) = (sext(a, eew) as i128, sext(b, eew) as i128);
+    let (ua, ub) = (a as i128, b as i128);
+    // vs2 already in 2 * SEW
+    let (wsa, wua) = (sext(a, weew) as i128, a as i128);
+    let shamt = b as u32 & (weew as u32 * 8 - 1);
+    let res = match op {
+        VWideOp::WAddU => ua + ub,
+        VWideOp::WAdd => sa + sb,
+        VWideOp::WSubU => ua - ub,
+        VWideOp::WSub => sa - sb,
+        VWideOp::WAddUW => wua + ub,
+        VWideOp::WAddW => wsa + sb,
+        VWideOp::WSubUW => wua - ub,
+        VWideOp::WSubW => wsa - sb,
+        VWideOp::WMulU => ua * ub,
+        VWideOp::WMulSU => sa * ub,
+        VWideOp::WMul => sa * sb,
+        VWideOp::WMaccU => d as i128 + ua * ub,
+        VWideOp::WMacc => d as i128 + sa * sb,
+        VWideOp::WMaccSU => d as i128 + ua * sb,
+        VWideOp::WMaccUS => d as i128 + sa * ub,
+        VWideOp::NSrl => return trunc((wua >> shamt) as u64, eew),
+        VWideOp::NSra => return trunc((wsa >> shamt) as u64, eew),
+        VWideOp::NClipU => return clipu(roundoff(wua, shamt, vxrm), eew, sat),
+        VWideOp::NClip => return clip(roundoff(wsa, shamt, vxrm), eew, sat),
+    };
+    trunc(res as u64, weew)
+}
+
+fn cmp_op(op: VCmpOp, a: u64, b: u64, c: bool, eew: usize) -> bool {
+    let (sa, sb) = (sext(a, eew), sext(b, eew));
+    match op {
+        VCmpOp::Eq => a == b,
+        VCmpOp::Ne => a != b,
+        VCmpOp::LtU => a < b,
+        VCmpOp::Lt => sa < sb,
+        VCmpOp::LeU => a <= b,
+        VCmpOp::Le => sa <= sb,
+        VCmpOp::GtU => a > b,
+        VCmpOp::Gt => sa > sb,
+        VCmpOp::Madc => (a as u128 + b as u128 + c as u128) >> (eew * 8) != 0,
+        VCmpOp::Msbc => (a as i128) - (b as i128) - (c as i128) < 0,
+    }
+}
+
+/// accumulate element `e` into `acc`, both in `eew` bytes
+fn red_op(op: VRedOp, acc: u64, e: u64, eew: usize) -> u64 {
+    let (sacc, se) = (sext(acc, eew), sext(e, eew));
+    let res = match op {
+        VRedOp::Sum | VRedOp::WSumU | VRedOp::WSum => acc.wrapping_add(e),
+        VRedOp::And => acc & e,
+        VRedOp::Or => acc | e,
+        VRedOp::Xor => acc ^ e,
+        VRedOp::MinU => acc.min(e),
+        VRedOp::Min => sacc.min(se) as u64,
+        VRedOp::MaxU => acc.max(e),
+        VRedOp::Max => sacc.max(se) as u64,
+    };
+    trunc(res, eew)
+}
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    pub fn v_op(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VIntOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        let vs1 = src_vreg(src);
+        self.v_check(&[(vd, lmul), (vs2, lmul), (vs1, lmul)])?;
+        // masked destination can't be v0
+        self.v_require(vm || vd != 0)?;
+        let opnd = self.v_opnd(src);
+        // v0 is data rather than mask
+        let carry = matches!(op, VIntOp::Adc | VIntOp::Sbc | VIntOp::Merge);
+        let mut sat = false;
+        let v = &self.vpu;
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                let c = !vm && v.rd_mask(0, i);
+                (carry || v.active(vm, i)).then(|| {
+                    let a = v.rd_elem(vs2, i, sew);
+                    let b = opnd.get(v, i, sew);
+                    let d = v.rd_elem(vd, i, sew);
+                    match op {
+                        VIntOp::Merge if !vm && !c => a,
+                        _ => int_op(op, a, b, d, c, sew, v.vxrm, &mut sat),
+                    }
+                })
+            })
+            .collect();
+        self.vpu.vxsat |= sat;
+        let end = self.vpu.group_len(sew, lmul);
+        self.v_commit(vd, sew, self.vpu.vstart, res, end);
+        Ok(())
+    }
+
+    pub fn v_op_wide(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VWideOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        let narrow = matches!(
+            op,
+            VWideOp::NSrl | VWideOp::NSra | VWideOp::NClipU | VWideOp::NClip
+        );
+        let wide_vs2 = narrow
+            || matches!(
+                op,
+                VWideOp::WAddUW | VWideOp::WAddW | VWideOp::WSubUW | VWideOp::WSubW
+            );
+        let vs1 = src_vreg(src);
+        let (deew, seew) = match narrow {
+            true => (sew, sew * 2),
+            false => (sew * 2, if wide_vs2 { sew * 2 } else { sew }),
+        };
+        self.v_require(sew * 2 <= self.vpu.elen)?;
+        let (demul, semul) = (self.vpu.vtype.emul(deew), self.vpu.vtype.emul(seew));
+        self.v_check(&[(vd, demul), (vs2, semul), (vs1, lmul)])?;
+        self.v_require(vm || vd != 0)?;
+        let opnd = self.v_opnd(src);
+        let mut sat = false;
+        let v = &self.vpu;
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                v.active(vm, i).then(|| {
+                    let a = v.rd_elem(vs2, i, seew);
+                    let b = opnd.get(v, i, sew);
+                    let d = v.rd_elem(vd, i, deew);
+                    wide_op(op, a, b, d, sew, v.vxrm, &mut sat)
+                })
+            })
+            .collect();
+        self.vpu.vxsat |= sat;
+        let end = self.vpu.group_len(deew, demul);
+        self.v_commit(vd, deew, self.vpu.vstart, res, end);
+        Ok(())
+    }
+
+    pub fn v_cmp(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VCmpOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        let vs1 = src_vreg(src);
+        self.v_check(&[(vs2, lmul), (vs1, lmul)])?;
+        let opnd = self.v_opnd(src);
+        // v0 is carry-in rather than mask
+        let carry = matches!(op, VCmpOp::Madc | VCmpOp::Msbc);
+        let v = &self.vpu;
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                let c = !vm && v.rd_mask(0, i);
+                (carry || v.active(vm, i)).then(|| {
+                    let a = v.rd_elem(vs2, i, sew);
+                    let b = opnd.get(v, i, sew);
+                    cmp_op(op, a, b, c, sew)
+                })
+            })
+            .collect();
+        self.v_commit_mask(vd, self.vpu.vstart, res);
+        Ok(())
+    }
+
+    pub fn v_red(&mut self, vd: u8, vs2: u8, vs1: u8, vm: bool, op: VRedOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        let wide = matches!(op, VRedOp::WSumU | VRedOp::WSum);
+        let deew = if wide { sew * 2 } else { sew };
+        self.v_require(self.vpu.vstart == 0 && deew <= self.vpu.elen)?;
+        self.v_check(&[(vs2, lmul)])?;
+        let v = &self.vpu;
+        if v.vl == 0 {
+            return Ok(());
+        }
+        let mut acc = v.rd_elem(vs1, 0, deew);
+        for i in (0..v.vl).filter(|&i| v.active(vm, i)) {
+            let e = v.rd_elem(vs2, i, sew);
+            let e = match op {
+                VRedOp::WSum => sext(e, sew) as u64,
+                _ => e,
+            };
+            acc = red_op(op, acc, e, deew);
+        }
+        let end = v.vlenb / deew;
+        self.v_commit(vd, deew, 0, vec![Some(acc)], end);
+        Ok(())
+    }
+
+    pub fn v_mask(&mut self, vd: u8, vs2: u8, vs1: u8, op: VMaskLogic) -> Maybe<()> {
+        self.v_check(&[])?;
+        let v = &self.vpu;
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                let (a, b) = (v.rd_mask(vs2, i), v.rd_mask(vs1, i));
+                Some(match op {
+                    VMaskLogic::AndN => a & !b,
+                    VMaskLogic::And => a & b,
+                    VMaskLogic::Or => a | b,
+                    VMaskLogic::Xor => a ^ b,
+                    VMaskLogic::OrN => a | !b,
+                    VMaskLogic::Nand => !(a & b),
+                    VMaskLogic::Nor => !(a | b),
+                    VMaskLogic::Xnor => !(a ^ b),
+                })
+            })
+            .collect();
+        self.v_commit_mask(vd, self.vpu.vstart, res);
+        Ok(())
+    }
+
+    pub fn v_unary(&mut self, vd: u8, vs2: u8, vm: bool, op: VUnaryOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        match op {
+            VUnaryOp::Cpop | VUnaryOp::First => {
+                self.v_check(&[])?;
+                self.v_require(self.vpu.vstart == 0)?;
+                let v = &self.vpu;
+                let mut set = (0..v.vl).filter(|&i| v.active(vm, i) && v.rd_mask(vs2, i));
+                let res = match op {
+                    VUnaryOp::Cpop => Xlen::from(set.count() as u64),
+                    _ => set.next().map_or(!Xlen::from(0), |i| Xlen::from(i as u64)),
+                };
+                self.wr_gpr(vd, res);
+            }
+            VUnaryOp::Msbf | VUnaryOp::Msif | VUnaryOp::Msof => {
+                self.v_check(&[])?;
+                self.v_require(self.vpu.vstart == 0 && vd != vs2 && (vm || vd != 0))?;
+                let v = &self.vpu;
+                let mut found = false;
+                let res = (0..v.vl)
+                    .map(|i| {
+                        v.active(vm, i).then(|| {
+                            let first = !found && v.rd_mask(vs2, i);
+                            found |= first;
+                            match op {
+                                VUnaryOp::Msbf => !found,
+                                VUnaryOp::Msif => !found || first,
+                                _ => first,
+                            }
+                        })
+                    })
+                    .collect();
+                self.v_commit_mask(vd, 0, res);
+            }
+            VUnaryOp::Iota | VUnaryOp::Id => {
+                self.v_check(&[(vd, lmul)])?;
+                let iota = op == VUnaryOp::Iota;
+                let legal = !iota || (self.vpu.vstart == 0 && !overlap((vd, lmul), (vs2, 0)));
+                self.v_require(legal && (vm || vd != 0))?;
+                let v = &self.vpu;
+                let mut count = 0;
+                let res = (v.vstart..v.vl)
+                    .map(|i| {
+                        v.active(vm, i).then(|| {
+                            let val = if iota { count } else { i as u64 };
+                            count += v.rd_mask(vs2, i) as u64;
+                            trunc(val, sew)
+                        })
+                    })
+                    .collect();
+                let end = self.vpu.group_len(sew, lmul);
+                self.v_commit(vd, sew, self.vpu.vstart, res, end);
+            }
+            VUnaryOp::Zext(factor) | VUnaryOp::Sext(factor) => {
+                let seew = sew / factor as usize;
+                self.v_require(seew > 0)?;
+                let semul = self.vpu.vtype.emul(seew);
+                self.v_check(&[(vd, lmul), (vs2, semul)])?;
+                self.v_require(vm || vd != 0)?;
+                let signed = matches!(op, VUnaryOp::Sext(_));
+                let v = &self.vpu;
+                let res = (v.vstart..v.vl)
+                    .map(|i| {
+                        v.active(vm, i).then(|| {
+                            let e = v.rd_elem(vs2, i, seew);
+                            match signed {
+                                true => trunc(sext(e, seew) as u64, sew),
+                                false => e,
+                            }
+                        })
+                    })
+                    .collect();
+                let end = self.vpu.group_len(sew, lmul);
+                self.v_commit(vd, sew, self.vpu.vstart, res, end);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn v_perm(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VPermOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        // index operand of vrgatherei16
+        let (ieew, iemul) = match op {
+            VPermOp::RGatherEi16 => (2, self.vpu.vtype.emul(2)),
+            _ => (sew, lmul),
+        };
+        let vs1 = src_vreg(src);
+        self.v_check(&[(vd, lmul), (vs2, lmul), (vs1, iemul)])?;
+        self.v_require(vm || vd != 0)?;
+        // destination can't overlap source except for slide down
+        let legal = match (op, src) {
+            (VPermOp::SlideDown | VPermOp::Slide1Down, _) => true,
+            (VPermOp::Compress, _) => {
+                self.vpu.vstart == 0 && !overlap((vd, lmul), (vs2, lmul)) && vd != vs1
+            }
+            (_, VSrc::V(vs1)) => {
+                !overlap((vd, lmul), (vs2, lmul)) && !overlap((vd, lmul), (vs1, iemul))
+            }
+            _ => !overlap((vd, lmul), (vs2, lmul)),
+        };
+        self.v_require(legal)?;
+        // offset or index as unsigned xlen, scalar operand as SEW
+        let (offset, scalar) = match src {
+            VSrc::X(rs1) => (self.v_ugpr(rs1), self.v_gpr(rs1)),
+            VSrc::I(uimm) => (uimm as u64, uimm as u64),
+            VSrc::F(rs1) => (0, self.v_fscalar(rs1, sew)?),
+            VSrc::V(_) => (0, 0),
+        };
+        let v = &self.vpu;
+        let vlmax = v.vlmax() as u64;
+        let gather = |idx: u64| match idx < vlmax {
+            true => v.rd_elem(vs2, idx as usize, sew),
+            false => 0,
+        };
+        let (start, res) = match op {
+            VPermOp::SlideUp => {
+                let start = v.vstart.max(offset.min(v.vl as u64) as usize);
+                let res = (start..v.vl)
+                    .map(|i| {
+                        v.active(vm, i)
+                            .then(|| v.rd_elem(vs2, i - offset as usize, sew))
+                    })
+                    .collect();
+                (start, res)
+            }
+            VPermOp::Compress => {
+                let res = (0..v.vl)
+                    .filter(|&i| v.rd_mask(vs1, i))
+                    .map(|i| Some(v.rd_elem(vs2, i, sew)))
+                    .collect();
+                (0, res)
+            }
+            _ => {
+                let res = (v.vstart..v.vl)
+                    .map(|i| {
+                        v.active(vm, i).then(|| match op {
+                            VPermOp::SlideDown => gather((i as u64).saturating_add(offset)),
+                            VPermOp::Slide1Up if i == 0 => trunc(scalar, sew),
+                            VPermOp::Slide1Up => v.rd_elem(vs2, i - 1, sew),
+                            VPermOp::Slide1Down if i + 1 == v.vl => trunc(scalar, sew),
+                            VPermOp::Slide1Down => v.rd_elem(vs2, i + 1, sew),
+                            _ => match src {
+                                VSrc::V(vs1) => gather(v.rd_elem(vs1, i, ieew)),
+                                _ => gather(offset),
+                            },
+                        })
+                    })
+                    .collect();
+                (v.vstart, res)
+            }
+        };
+        let end = self.vpu.group_len(sew, lmul);
+        self.v_commit(vd, sew, start, res, end);
+        Ok(())
+    }
+
+    pub fn v_mv(&mut self, dst: u8, src: u8, op: VMvOp) -> Maybe<()> {
+        let sew = self.vpu.vtype.sew;
+        match op {
+            VMvOp::XS => {
+                self.v_check(&[])?;
+                let val = sext(self.vpu.rd_elem(src, 0, sew), sew);
+                self.wr_gpr(dst, Xlen::from(val));
+                self.vpu.vstart = 0;
+            }
+            VMvOp::SX => {
+                self.v_check(&[])?;
+                let val = self.v_gpr(src);
+                self.v_mv_s(dst, val);
+            }
+            VMvOp::FS | VMvOp::SF => self.v_fmv(dst, src, op)?,
+            VMvOp::Whole(nr) => {
+                let emul = nr.trailing_zeros() as i8;
+                self.v_require(aligned(dst, emul) && aligned(src, emul))?;
+                let v = &mut self.vpu;
+                let (dst, src) = (dst as usize * v.vlenb, src as usize * v.vlenb);
+                let len = nr as usize * v.vlenb;
+                v.vregs.copy_within(src..src + len, dst);
+                v.vstart = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// write `val` to element 0 of `vd` unless vstart >= vl
+    pub fn v_mv_s(&mut self, vd: u8, val: u64) {
+        let v = &self.vpu;
+        let sew = v.vtype.sew;
+        if v.vstart < v.vl {
+            let end = v.vlenb / sew;
+            self.v_commit(vd, sew, 0, vec![Some(trunc(val, sew))], end);
+        } else {
+            self.vpu.vstart = 0;
+        }
+    }
+}
@@ -0,0 +1,656 @@
+use super::*;
+use crate::fpu::{
+    soft::{self, Fmt, BINARY32, BINARY64},
+    FpExcept, FpOp, Fpu, FPE, FPE_DZ, FPE_NV, FPE_NX, FPE_OF,
+};
+use std::num::FpCategory;
+
+/// vfrec7 estimates, indexed by 7 msbs of normalized significand
+const REC7: [u8; 128] = [
+    127, 125, 123, 121, 119, 117, 116, 114, 112, 110, 109, 107, 105, 104, 102, 100, 99, 97, 96, 94,
+    93, 91, 90, 88, 87, 85, 84, 83, 81, 80, 79, 77, 76, 75, 74, 72, 71, 70, 69, 68, 66, 65, 64, 63,
+    62, 61, 60, 59, 58, 57, 56, 55, 54, 53, 52, 51, 50, 49, 48, 47, 46, 45, 44, 43, 42, 41, 40, 40,
+    39, 38, 37, 36, 35, 35, 34, 33, 32, 31, 31, 30, 29, 28, 28, 27, 26, 25, 25, 24, 23, 23, 22, 21,
+    21, 20, 19, 19, 18, 17, 17, 16, 15, 15, 14, 14, 13, 12, 12, 11, 11, 10, 9, 9, 8, 8, 7, 7, 6, 5,
+    5, 4, 4, 3, 3, 2, 2, 1, 1, 0,
+];
+
+/// vfrsqrt7 estimates, indexed by exponent lsb and 6 msbs of normalized significand
+const RSQRT7: [u8; 128] = [
+    52, 51, 50, 48, 47, 46, 44, 43, 42, 41, 40, 39, 38, 36, 35, 34, 33, 32, 31, 30, 30, 29, 28, 27,
+    26, 25, 24, 23, 23, 22, 21, 20, 19, 19, 18, 17, 16, 16, 15, 14, 14, 13, 12, 12, 11, 10, 10, 9,
+    9, 8, 7, 7, 6, 6, 5, 4, 4, 3, 3, 2, 2, 1, 1, 0, 127, 125, 123, 121, 119, 118, 116, 114, 113,
+    111, 109, 108, 106, 105, 103, 102, 100, 99, 97, 96, 95, 93, 92, 91, 90, 88, 87, 86, 85, 84, 83,
+    82, 80, 79, 78, 77, 76, 75, 74, 73, 72, 71, 70, 70, 69, 68, 67, 66, 65, 64, 63, 63, 62, 61, 60,
+    59, 59, 58, 57, 56, 56, 55, 54, 53,
+];
+
+/// floating-point element of vector registers
+trait VFloat: FpOp {
+    const BITS: u32;
+    /// fraction bits
+    const MANT: u32;
+    const BIAS: i64;
+
+    fn from_raw(val: u64) -> Self;
+    fn raw(self) -> u64;
+    fn from_f64(val: f64) -> Self;
+
+    fn is_snan(self) -> bool {
+        self.is_nan() && self.raw() & (1 << (Self::MANT - 1)) == 0
+    }
+    fn sign_mask() -> u64 {
+        1 << (Self::BITS - 1)
+    }
+    /// positive infinity
+    fn inf_bits() -> u64 {
+        ((1 << (Self::BITS - 1 - Self::MANT)) - 1) << Self::MANT
+    }
+}
+
+impl VFloat for f32 {
+    const BITS: u32 = 32;
+    const MANT: u32 = 23;
+    const BIAS: i64 = 127;
+
+    fn from_raw(val: u64) -> Self {
+        f32::from_bits(val as u32)
+    }
+    fn raw(self) -> u64 {
+        self.to_bits() as u64
+    }
+    fn from_f64(val: f64) -> Self {
+        val as f32
+    }
+}
+
+impl VFloat for f64 {
+    const BITS: u32 = 64;
+    const MANT: u32 = 52;
+    const BIAS: i64 = 1023;
+
+    fn from_raw(val: u64) -> Self {
+        f64::from_bits(val)
+    }
+    fn raw(self) -> u64 {
+        self.to_bits()
+    }
+    fn from_f64(val: f64) -> Self {
+        val
+    }
+}
+
+/// dispatch on floating-point SEW
+/// (sew, func, args, ...)
+macro_rules! sew_switch {
+    ($sew:expr, $func:ident, $($e:expr), *) => {
+        match $sew {
+            4 => $func::<f32>($($e), *),
+            _ => $func::<f64>($($e), *),
+        }
+    };
+}
+
+/// software format of an element of `eew` bytes
+fn fmt_of(eew: usize) -> Fmt {
+    match eew {
+        4 => BINARY32,
+        _ => BINARY64,
+    }
+}
+
+/// IEEE 754-2019 minimumNumber / maximumNumber
+fn min_max<T: VFloat>(fpu: &mut Fpu, a: T, b: T, max: bool) -> T {
+    if a.is_snan() || b.is_snan() {
+        fpu.set_fpe(FPE_NV);
+    }
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => T::canonical_nan(),
+        (true, false) => b,
+        (false, true) => a,
+        // -0.0 orders before +0.0
+        _ if a == b && a.is_neg() == max => b,
+        _ if a == b => a,
+        _ if (a < b) != max => a,
+        _ => b,
+    }
+}
+
+/// `a` from vs2, `b` from vs1 / scalar, `d` from vd
+fn fp_op<T: VFloat>(fpu: &mut Fpu, rm: RoundMode, op: VFpOp, a: u64, b: u64, d: u64) -> u64 {
+    let sign = T::sign_mask();
+    let bitwise = matches!(op, VFpOp::SgnJ | VFpOp::SgnJN | VFpOp::SgnJX | VFpOp::Merge);
+    if !bitwise && fpu.soft() {
+        let mut fpe = FPE;
+        let res = soft_op(T::FMT, rm, op, a, b, d, &mut fpe);
+        fpu.set_fpe(fpe);
+        return res as u64;
+    }
+    let (fa, fb, fd) = (T::from_raw(a), T::from_raw(b), T::from_raw(d));
+    fpu.pre_tiny();
+    let res = match op {
+        VFpOp::Add => fa + fb,
+        VFpOp::Sub => fa - fb,
+        VFpOp::RSub => fb - fa,
+        VFpOp::Mul => fa * fb,
+        VFpOp::Div => fa / fb,
+        VFpOp::RDiv => fb / fa,
+        VFpOp::Min => min_max(fpu, fa, fb, false),
+        VFpOp::Max => min_max(fpu, fa, fb, true),
+        // sign injection and moves keep nan payload
+        VFpOp::SgnJ => return (a & !sign) | (b & sign),
+        VFpOp::SgnJN => return (a & !sign) | (!b & sign),
+        VFpOp::SgnJX => return a ^ (b & sign),
+        VFpOp::Merge => return b,
+        VFpOp::Macc => fb.mul_add(fa, fd),
+        VFpOp::NMacc => (-fb).mul_add(fa, -fd),
+        VFpOp::MSac => fb.mul_add(fa, -fd),
+        VFpOp::NMSac => (-fb).mul_add(fa, fd),
+        VFpOp::MAdd => fb.mul_add(fd, fa),
+        VFpOp::NMAdd => (-fb).mul_add(fd, -fa),
+        VFpOp::MSub => fb.mul_add(fd, -fa),
+        VFpOp::NMSub => (-fb).mul_add(fd, fa),
+    };
+    fpu.post_tiny(res, |fpe| soft_op(T::FMT, rm, op, a, b, d, fpe))
+        .no_nan_box()
+        .raw()
+}
+
+/// `fp_op` on the software backend
+fn soft_op(fmt: Fmt, rm: RoundMode, op: VFpOp, a: u64, b: u64, d: u64, fpe: &mut FpExcept) -> u128 {
+    let neg = fmt.sign();
+    let (a, b, d) = (a as u128, b as u128, d as u128);
+    match op {
+        VFpOp::Add => soft::add(fmt, a, b, rm, fpe),
+        VFpOp::Sub => soft::sub(fmt, a, b, rm, fpe),
+        VFpOp::RSub => soft::sub(fmt, b, a, rm, fpe),
+        VFpOp::Mul => soft::mul(fmt, a, b, rm, fpe),
+        VFpOp::Div => soft::div(fmt, a, b, rm, fpe),
+        VFpOp::RDiv => soft::div(fmt, b, a, rm, fpe),
+        VFpOp::Min => soft::min_max(fmt, a, b, false, fpe),
+        VFpOp::Max => soft::min_max(fmt, a, b, true, fpe),
+        VFpOp::Macc => soft::fma(fmt, b, a, d, rm, fpe),
+        VFpOp::NMacc => soft::fma(fmt, b ^ neg, a, d ^ neg, rm, fpe),
+        VFpOp::MSac => soft::fma(fmt, b, a, d ^ neg, rm, fpe),
+        VFpOp::NMSac => soft::fma(fmt, b ^ neg, a, d, rm, fpe),
+        VFpOp::MAdd => soft::fma(fmt, b, d, a, rm, fpe),
+        VFpOp::NMAdd => soft::fma(fmt, b ^ neg, d, a ^ neg, rm, fpe),
+        VFpOp::MSub => soft::fma(fmt, b, d, a ^ neg, rm, fpe),
+        VFpOp::NMSub => soft::fma(fmt, b ^ neg, d, a, rm, fpe),
+        VFpOp::SgnJ | VFpOp::SgnJN | VFpOp::SgnJX | VFpOp::Merge => unreachable!(),
+    }
+}
+
+/// single-precision `a` from vs2, `b` from vs1 / scalar, double-precision `d` from vd
+fn fp_wide_op(fpu: &mut Fpu, rm: RoundMode, op: VFpWideOp, a: u64, b: u64, d: u64) -> u64 {
+    if fpu.soft() {
+        return soft_wide_op(fpu, rm, op, a, b, d);
+    }
+    let wa = match op {
+        VFpWideOp::WAddW | VFpWideOp::WSubW => f64::from_bits(a),
+        _ => f32::from_bits(a as u32) as f64,
+    };
+    let wb = f32::from_bits(b as u32) as f64;
+    let wd = f64::from_bits(d);
+    let res = match op {
+        VFpWideOp::WAdd | VFpWideOp::WAddW => wa + wb,
+        VFpWideOp::WSub | VFpWideOp::WSubW => wa - wb,
+        VFpWideOp::WMul => wa * wb,
+        VFpWideOp::WMacc => wb.mul_add(wa, wd),
+        VFpWideOp::WNMacc => (-wb).mul_add(wa, -wd),
+        VFpWideOp::WMSac => wb.mul_add(wa, -wd),
+        VFpWideOp::WNMSac => (-wb).mul_add(wa, wd),
+    };
+    res.no_nan_box().to_bits()
+}
+
+/// `fp_wide_op` on the software backend
+fn soft_wide_op(fpu: &mut Fpu, rm: RoundMode, op: VFpWideOp, a: u64, b: u64, d: u64) -> u64 {
+    let mut fpe = FPE;
+    let mut widen = |x: u64| soft::convert(BINARY32, BINARY64, x as u32 as u128, rm, &mut fpe);
+    let wa = match op {
+        VFpWideOp::WAddW | VFpWideOp::WSubW => a as u128,
+        _ => widen(a),
+    };
+    let wb = widen(b);
+    let (wd, neg) = (d as u128, BINARY64.sign());
+    let res = match op {
+        VFpWideOp::WAdd | VFpWideOp::WAddW => soft::add(BINARY64, wa, wb, rm, &mut fpe),
+        VFpWideOp::WSub | VFpWideOp::WSubW => soft::sub(BINARY64, wa, wb, rm, &mut fpe),
+        VFpWideOp::WMul => soft::mul(BINARY64, wa, wb, rm, &mut fpe),
+        VFpWideOp::WMacc => soft::fma(BINARY64, wb, wa, wd, rm, &mut fpe),
+        VFpWideOp::WNMacc => soft::fma(BINARY64, wb ^ neg, wa, wd ^ neg, rm, &mut fpe),
+        VFpWideOp::WMSac => soft::fma(BINARY64, wb, wa, wd ^ neg, rm, &mut fpe),
+        VFpWideOp::WNMSac => soft::fma(BINARY64, wb ^ neg, wa, wd, rm, &mut fpe),
+    };
+    fpu.set_fpe(fpe);
+    res as u64
+}
+
+fn fp_cmp<T: VFloat>(fpu: &mut Fpu, op: VFpCmpOp, a: u64, b: u64) -> bool {
+    if fpu.soft() {
+        return soft_cmp(fpu, T::FMT, op, a, b);
+    }
+    let (fa, fb) = (T::from_raw(a), T::from_raw(b));
+    // equality is quiet, ordering signals on any nan
+    let invalid = match op {
+        VFpCmpOp::Eq | VFpCmpOp::Ne => fa.is_snan() || fb.is_snan(),
+        _ => fa.is_nan() || fb.is_nan(),
+    };
+    if invalid {
+        fpu.set_fpe(FPE_NV);
+    }
+    match op {
+        VFpCmpOp::Eq => fa == fb,
+        VFpCmpOp::Ne => fa != fb,
+        VFpCmpOp::Le => fa <= fb,
+        VFpCmpOp::Lt => fa < fb,
+        VFpCmpOp::Gt => fa > fb,
+        VFpCmpOp::Ge => fa >= fb,
+    }
+}
+
+/// `fp_cmp` on the software backend, greater is less with operands swapped
+fn soft_cmp(fpu: &mut Fpu, fmt: Fmt, op: VFpCmpOp, a: u64, b: u64) -> bool {
+    let (a, b) = (a as u128, b as u128);
+    let mut fpe = FPE;
+    let res = match op {
+        VFpCmpOp::Eq => soft::cmp(fmt, a, b, FpCmpCond::Eq, &mut fpe),
+        VFpCmpOp::Ne => !soft::cmp(fmt, a, b, FpCmpCond::Eq, &mut fpe),
+        VFpCmpOp::Le => soft::cmp(fmt, a, b, FpCmpCond::Le, &mut fpe),
+        VFpCmpOp::Lt => soft::cmp(fmt, a, b, FpCmpCond::Lt, &mut fpe),
+        VFpCmpOp::Gt => soft::cmp(fmt, b, a, FpCmpCond::Lt, &mut fpe),
+        VFpCmpOp::Ge => soft::cmp(fmt, b, a, FpCmpCond::Le, &mut fpe),
+    };
+    fpu.set_fpe(fpe);
+    res
+}
+
+/// reduce active elements of `vs2` into `vs1[0]`, accumulating in `W`
+fn fp_red<W: VFloat>(
+    v: &Vpu,
+    fpu: &mut Fpu,
+    rm: RoundMode,
+    vs2: u8,
+    vs1: u8,
+    vm: bool,
+    op: VFpRedOp,
+) -> u64 {
+    let sew = v.vtype.sew;
+    let mut acc = W::from_raw(v.rd_elem(vs1, 0, W::BITS as usize / 8));
+    for i in (0..v.vl).filter(|&i| v.active(vm, i)) {
+        let e = v.rd_elem(vs2, i, sew);
+        let mut fpe = FPE;
+        let e = match sew {
+            4 if W::BITS == 64 && fpu.soft() => {
+                W::from_raw(soft::convert(BINARY32, BINARY64, e as u128, rm, &mut fpe) as u64)
+            }
+            4 => W::from_f64(f32::from_bits(e as u32) as f64),
+            _ => W::from_raw(e),
+        };
+        let (a, b) = (acc.raw() as u128, e.raw() as u128);
+        acc = match op {
+            VFpRedOp::Min | VFpRedOp::Max if fpu.soft() => {
+                let max = op == VFpRedOp::Max;
+                W::from_raw(soft::min_max(W::FMT, a, b, max, &mut fpe) as u64)
+            }
+            VFpRedOp::Min => min_max(fpu, acc, e, false),
+            VFpRedOp::Max => min_max(fpu, acc, e, true),
+            // ordered sum is a valid unordered sum
+            _ if fpu.soft() => W::from_raw(soft::add(W::FMT, a, b, rm, &mut fpe) as u64),
+            _ => acc + e,
+        };
+        fpu.set_fpe(fpe);
+    }
+    acc.no_nan_box().raw()
+}
+
+fn fp_sqrt<T: VFloat>(fpu: &mut Fpu, rm: RoundMode, a: u64) -> u64 {
+    if fpu.soft() {
+        let mut fpe = FPE;
+        let res = soft::sqrt(T::FMT, a as u128, rm, &mut fpe);
+        fpu.set_fpe(fpe);
+        return res as u64;
+    }
+    T::from_raw(a).sqrt().no_nan_box().raw()
+}
+
+fn fp_class<T: VFloat>(a: u64) -> u64 {
+    let x = T::from_raw(a);
+    let bit = match (x.is_neg(), x.classify()) {
+        (_, FpCategory::Nan) if x.is_snan() => 8,
+        (_, FpCategory::Nan) => 9,
+        (true, FpCategory::Infinite) => 0,
+        (true, FpCategory::Normal) => 1,
+        (true, FpCategory::Subnormal) => 2,
+        (true, FpCategory::Zero) => 3,
+        (false, FpCategory::Zero) => 4,
+        (false, FpCategory::Subnormal) => 5,
+        (false, FpCategory::Normal) => 6,
+        (false, FpCategory::Infinite) => 7,
+    };
+    1 << bit
+}
+
+/// (exponent, fraction) of finite nonzero `x`,
+/// subnormals are normalized with exponent <= 0
+fn normalize<T: VFloat>(x: T) -> (i64, u64) {
+    let frac_mask = (1 << T::MANT) - 1;
+    let exp = ((x.raw() & !T::sign_mask()) >> T::MANT) as i64;
+    let frac = x.raw() & frac_mask;
+    if exp != 0 {
+        return (exp, frac);
+    }
+    let lz = (frac << (64 - T::MANT)).leading_zeros();
+    (-(lz as i64), (frac << (lz + 1)) & frac_mask)
+}
+
+/// 7-bit reciprocal estimate
+fn rec7<T: VFloat>(fpu: &mut Fpu, rm: RoundMode, a: u64) -> u64 {
+    let x = T::from_raw(a);
+    let sign = a & T::sign_mask();
+    let inf = T::inf_bits();
+    match x.classify() {
+        FpCategory::Infinite => return sign,
+        FpCategory::Zero => {
+            fpu.set_fpe(FPE_DZ);
+            return sign | inf;
+        }
+        FpCategory::Nan => {
+            if x.is_snan() {
+                fpu.set_fpe(FPE_NV);
+            }
+            return T::canonical_nan().raw();
+        }
+        _ => (),
+    }
+    let (exp, frac) = normalize(x);
+    let mut out_exp = 2 * T::BIAS - 1 - exp;
+    // tiny subnormals overflow
+    if out_exp >= (inf >> T::MANT) as i64 {
+        fpu.set_fpe(FPE_OF | FPE_NX);
+        let to_inf = match rm {
+            RoundMode::Rtz => false,
+            RoundMode::Rdn => sign != 0,
+            RoundMode::Rup => sign == 0,
+            _ => true,
+        };
+        return sign | if to_inf { inf } else { inf - 1 };
+    }
+    let mut out_frac = (REC7[(frac >> (T::MANT - 7)) as usize] as u64) << (T::MANT - 7);
+    if out_exp <= 0 {
+        // subnormal result
+        out_frac = ((1 << T::MANT) | out_frac) >> (1 - out_exp);
+        out_exp = 0;
+    }
+    sign | (out_exp as u64) << T::MANT | out_frac
+}
+
+/// 7-bit reciprocal square root estimate
+fn rsqrt7<T: VFloat>(fpu: &mut Fpu, a: u64) -> u64 {
+    let x = T::from_raw(a);
+    let sign = a & T::sign_mask();
+    let inf = T::inf_bits();
+    match x.classify() {
+        FpCategory::Zero => {
+            fpu.set_fpe(FPE_DZ);
+            return sign | inf;
+        }
+        FpCategory::Nan if !x.is_snan() => return T::canonical_nan().raw(),
+        FpCategory::Infinite if sign == 0 => return 0,
+        _ if x.is_snan() || sign != 0 => {
+            fpu.set_fpe(FPE_NV);
+            return T::canonical_nan().raw();
+        }
+        _ => (),
+    }
+    let (exp, frac) = normalize(x);
+    let idx = ((exp & 1) << 6) as u64 | frac >> (T::MANT - 6);
+    let out_exp = (3 * T::BIAS - 1 - exp) / 2;
+    let out_frac = (RSQRT7[idx as usize] as u64) << (T::MANT - 7);
+    (out_exp as u64) << T::MANT | out_frac
+}
+
+/// narrow to single-precision rounding towards odd
+fn rod_f32(fpu: &mut Fpu, x: f64) -> u64 {
+    if x.is_nan() {
+        if x.is_snan() {
+            fpu.set_fpe(FPE_NV);
+        }
+        return f32::canonical_nan().raw();
+    }
+    let mut r = x as f32;
+    if x.is_finite() && r as f64 != x {
+        // step back towards zero when rounded away
+        if r.is_infinite() || (r as f64).abs() > x.abs() {
+            r = f32::from_bits(r.to_bits() - 1);
+        }
+        r = f32::from_bits(r.to_bits() | 1);
+        fpu.set_fpe(FPE_NX);
+    }
+    r.raw()
+}
+
+/// `a` of `seew` bytes converted to `deew` bytes,
+/// integer conversions are always done in software
+fn fp_cvt(fpu: &mut Fpu, cvt: VFpCvt, a: u64, seew: usize, deew: usize, rm: RoundMode) -> u64 {
+    // exact for both precisions
+    let f = || match seew {
+        4 => f32::from_bits(a as u32) as f64,
+        _ => f64::from_bits(a),
+    };
+    let (from, to) = (fmt_of(seew), fmt_of(deew));
+    let bits = deew as u32 * 8;
+    let mut fpe = FPE;
+    let res = match cvt {
+        VFpCvt::XuF => soft::to_int(from, a as u128, rm, bits, false, &mut fpe) as u64,
+        VFpCvt::XF => soft::to_int(from, a as u128, rm, bits, true, &mut fpe) as u64,
+        VFpCvt::RtzXuF => {
+            soft::to_int(from, a as u128, RoundMode::Rtz, bits, false, &mut fpe) as u64
+        }
+        VFpCvt::RtzXF => soft::to_int(from, a as u128, RoundMode::Rtz, bits, true, &mut fpe) as u64,
+        VFpCvt::FXu => soft::from_int(to, false, a as u128, rm, &mut fpe) as u64,
+        VFpCvt::FX => {
+            let x = sext(a, seew);
+            soft::from_int(to, x < 0, x.unsigned_abs() as u128, rm, &mut fpe) as u64
+        }
+        VFpCvt::FF | VFpCvt::RodFF if fpu.soft() => {
+            let rm = if cvt == VFpCvt::RodFF {
+                RoundMode::Rtz
+            } else {
+                rm
+            };
+            let res = soft::convert(from, to, a as u128, rm, &mut fpe) as u64;
+            // jam the sticky bit into the lsb
+            let nx = cvt == VFpCvt::RodFF && fpe & FPE_NX == FPE_NX && !to.is_nan(res as u128);
+            res | nx as u64
+        }
+        VFpCvt::FF if deew == 8 => f().no_nan_box().raw(),
+        VFpCvt::FF => (f() as f32).no_nan_box().raw(),
+        VFpCvt::RodFF => rod_f32(fpu, f()),
+    };
+    fpu.set_fpe(fpe);
+    trunc(res, deew)
+}
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    /// f\[rs1\] as element of `sew` bytes
+    pub fn v_fscalar(&mut self, rs1: u8, sew: usize) -> Maybe<u64> {
+        self.v_require(sew == 4 || sew == 8)?;
+        Ok(match sew {
+            4 => f32::rd_fpr(&self.fpu, rs1).raw(),
+            _ => f64::rd_fpr(&self.fpu, rs1).raw(),
+        })
+    }
+
+    fn v_fopnd(&mut self, src: VSrc, sew: usize) -> Maybe<Opnd> {
+        Ok(match src {
+            VSrc::F(rs1) => Opnd::Scalar(self.v_fscalar(rs1, sew)?),
+            _ => self.v_opnd(src),
+        })
+    }
+
+    /// fpu is on, `sew` is a supported precision, sets up dynamic rounding mode
+    fn v_fsetup(&mut self, sew: usize) -> Maybe<RoundMode> {
+        self.fp_enable(true)?;
+        self.v_require(sew == 4 || sew == 8)?;
+        self.set_rt_rm(RoundMode::Dyn)?;
+        Ok(self.fpu.get_dyn_rm())
+    }
+
+    pub fn v_fop(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VFpOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        self.v_check(&[(vd, lmul), (vs2, lmul), (src_vreg(src), lmul)])?;
+        self.v_require(vm || vd != 0)?;
+        let rm = self.v_fsetup(sew)?;
+        let opnd = self.v_fopnd(src, sew)?;
+        // v0 is data rather than mask
+        let merge = op == VFpOp::Merge;
+        let (v, fpu) = (&self.vpu, &mut self.fpu);
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                (merge || v.active(vm, i)).then(|| {
+                    let a = v.rd_elem(vs2, i, sew);
+                    let b = opnd.get(v, i, sew);
+                    let d = v.rd_elem(vd, i, sew);
+                    match op {
+                        VFpOp::Merge if !v.active(vm, i) => a,
+                        _ => sew_switch!(sew, fp_op, fpu, rm, op, a, b, d),
+                    }
+                })
+            })
+            .collect();
+        let end = self.vpu.group_len(sew, lmul);
+        self.v_commit(vd, sew, self.vpu.vstart, res, end);
+        Ok(())
+    }
+
+    pub fn v_fop_wide(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VFpWideOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        let wide_vs2 = matches!(op, VFpWideOp::WAddW | VFpWideOp::WSubW);
+        let semul = if wide_vs2 { lmul + 1 } else { lmul };
+        self.v_check(&[(vd, lmul + 1), (vs2, semul), (src_vreg(src), lmul)])?;
+        // single to double only, no half-precision
+        self.v_require(sew == 4 && self.vpu.elen == 8 && (vm || vd != 0))?;
+        let rm = self.v_fsetup(sew)?;
+        let opnd = self.v_fopnd(src, sew)?;
+        let seew = if wide_vs2 { 8 } else { 4 };
+        let (v, fpu) = (&self.vpu, &mut self.fpu);
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                v.active(vm, i).then(|| {
+                    let a = v.rd_elem(vs2, i, seew);
+                    let b = opnd.get(v, i, sew);
+                    let d = v.rd_elem(vd, i, 8);
+                    fp_wide_op(fpu, rm, op, a, b, d)
+                })
+            })
+            .collect();
+        let end = self.vpu.group_len(8, lmul + 1);
+        self.v_commit(vd, 8, self.vpu.vstart, res, end);
+        Ok(())
+    }
+
+    pub fn v_fcmp(&mut self, vd: u8, vs2: u8, src: VSrc, vm: bool, op: VFpCmpOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        self.v_check(&[(vs2, lmul), (src_vreg(src), lmul)])?;
+        self.v_fsetup(sew)?;
+        let opnd = self.v_fopnd(src, sew)?;
+        let (v, fpu) = (&self.vpu, &mut self.fpu);
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                v.active(vm, i).then(|| {
+                    let a = v.rd_elem(vs2, i, sew);
+                    let b = opnd.get(v, i, sew);
+                    sew_switch!(sew, fp_cmp, fpu, op, a, b)
+                })
+            })
+            .collect();
+        self.v_commit_mask(vd, self.vpu.vstart, res);
+        Ok(())
+    }
+
+    pub fn v_fred(&mut self, vd: u8, vs2: u8, vs1: u8, vm: bool, op: VFpRedOp) -> Maybe<()> {
+        let VType { lmul, sew, .. } = self.vpu.vtype;
+        let wide = matches!(op, VFpRedOp::WUSum | VFpRedOp::WOSum);
+        self.v_check(&[(vs2, lmul)])?;
+        let legal = self.vpu.vstart == 0 && (!wide || (sew == 4 && self.vpu.elen == 8));
+        self.v_require(legal)?;
+        let rm = self.v_fsetup(sew)?;
+        let (v, fpu) = (&self.vpu, &mut self.fpu);
+        if v.vl == 0 {
+            return Ok(());
+        }
+        let deew = if wide { 8 } else { sew };
+        let acc = sew_switch!(deew, fp_red, v, fpu, rm, vs2, vs1, vm, op);
+        let end = v.vlenb / deew;
+        self.v_commit(vd, deew, 0, vec![Some(acc)], end);
+        Ok(())
+    }
+
+    pub fn v_funary(&mut self, vd: u8, vs2: u8, vm: bool, op: VFpUnaryOp) -> Maybe<()> {
+        let vtype = self.vpu.vtype;
+        let sew = vtype.sew;
+        let (cvt, width) = match op {
+            VFpUnaryOp::Cvt(cvt, width) => (Some(cvt), width),
+            _ => (None, VWidth::Single),
+        };
+        let (deew, seew) = match width {
+            VWidth::Single => (sew, sew),
+            VWidth::Widen => (sew * 2, sew),
+            VWidth::Narrow => (sew, sew * 2),
+        };
+        // floating-point side of conversions
+        let fp = |eew| eew == 4 || eew == 8;
+        let legal = match cvt {
+            Some(VFpCvt::FXu | VFpCvt::FX) => fp(deew),
+            Some(VFpCvt::FF | VFpCvt::RodFF) => fp(deew) && fp(seew),
+            _ => fp(seew),
+        };
+        self.v_require(legal && deew.max(seew) <= self.vpu.elen)?;
+        let (demul, semul) = (vtype.emul(deew), vtype.emul(seew));
+        self.v_check(&[(vd, demul), (vs2, semul)])?;
+        self.v_require(vm || vd != 0)?;
+        self.fp_enable(true)?;
+        self.set_rt_rm(RoundMode::Dyn)?;
+        let rm = self.fpu.get_dyn_rm();
+        let (v, fpu) = (&self.vpu, &mut self.fpu);
+        let res = (v.vstart..v.vl)
+            .map(|i| {
+                v.active(vm, i).then(|| {
+                    let a = v.rd_elem(vs2, i, seew);
+                    match op {
+                        VFpUnaryOp::Sqrt => sew_switch!(sew, fp_sqrt, fpu, rm, a),
+                        VFpUnaryOp::Rsqrt7 => sew_switch!(sew, rsqrt7, fpu, a),
+                        VFpUnaryOp::Rec7 => sew_switch!(sew, rec7, fpu, rm, a),
+                        VFpUnaryOp::Class => sew_switch!(sew, fp_class, a),
+                        VFpUnaryOp::Cvt(cvt, _) => fp_cvt(fpu, cvt, a, seew, deew, rm),
+                    }
+                })
+            })
+            .collect();
+        let end = self.vpu.group_len(deew, demul);
+        self.v_commit(vd, deew, self.vpu.vstart, res, end);
+        Ok(())
+    }
+
+    /// vfmv.f.s / vfmv.s.f
+    pub fn v_fmv(&mut self, dst: u8, src: u8, op: VMvOp) -> Maybe<()> {
+        let sew = self.vpu.vtype.sew;
+        self.fp_enable(true)?;
+        self.v_check(&[])?;
+        if op == VMvOp::FS {
+            self.v_require(sew == 4 || sew == 8)?;
+            let val = self.vpu.rd_elem(src, 0, sew);
+            match sew {
+                4 => self.fpu.u32_mv_f32(dst, val as u32),
+                _ => self.fpu.u64_mv_f64(dst, val),
+            }
+            self.vpu.vstart = 0;
+        } else {
+            let val = self.v_fscalar(src, sew)?;
+            self.v_mv_s(dst, val);
+        }
+        Ok(())
+    }
+}
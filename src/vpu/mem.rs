@@ -0,0 +1,143 @@
+use super::*;
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    pub fn v_load(&mut self, op: VMemOp) -> Maybe<()> {
+        self.v_mem(op, false)
+    }
+
+    pub fn v_store(&mut self, op: VMemOp) -> Maybe<()> {
+        self.v_mem(op, true)
+    }
+
+    fn v_rd_mem(&mut self, addr: Xlen, eew: usize) -> Maybe<u64> {
+        Ok(match eew {
+            1 => self.rd_mem8(addr)? as u64,
+            2 => self.rd_mem16(addr)? as u64,
+            4 => self.rd_mem32(addr)? as u64,
+            _ => self.rd_mem64(addr)?,
+        })
+    }
+
+    fn v_wr_mem(&mut self, addr: Xlen, eew: usize, val: u64) -> Maybe<()> {
+        match eew {
+            1 => self.wr_mem8(addr, val as u8),
+            2 => self.wr_mem16(addr, val as u16),
+            4 => self.wr_mem32(addr, val as u32),
+            _ => self.wr_mem64(addr, val),
+        }
+    }
+
+    /// element accesses start from vstart,
+    /// vstart is left at the faulting element on exceptions
+    fn v_mem(&mut self, op: VMemOp, store: bool) -> Maybe<()> {
+        let VMemOp {
+            vd,
+            rs1,
+            mode,
+            eew,
+            nf,
+            vm,
+        } = op;
+        let eew = eew as usize;
+        let nf = nf as usize;
+        let base = self.rd_gpr(rs1);
+        if mode == VAddrMode::Whole {
+            return self.v_mem_whole(vd, base, eew, nf, store);
+        }
+        self.v_check(&[])?;
+        let vtype = self.vpu.vtype;
+        // indexed accesses take data width from SEW and offset width from encoding
+        let (deew, demul) = match mode {
+            VAddrMode::Indexed(vs2, _) => {
+                self.v_check(&[(vs2, vtype.emul(eew))])?;
+                (vtype.sew, vtype.lmul)
+            }
+            VAddrMode::Mask => (1, 0),
+            _ => (eew, vtype.emul(eew)),
+        };
+        let legal = aligned(vd, demul)
+            && regs(demul) * nf <= 8
+            && vd as usize + regs(demul) * nf <= 32
+            && (store || vm || vd != 0);
+        self.v_require(legal)?;
+        let (evl, end) = match mode {
+            VAddrMode::Mask => (self.vpu.vl.div_ceil(8), self.vpu.vlenb),
+            _ => (self.vpu.vl, self.vpu.group_len(deew, demul)),
+        };
+        let stride = match mode {
+            VAddrMode::Strided(rs2) => self.v_gpr(rs2),
+            _ => (nf * deew) as u64,
+        };
+        let ones = !store && self.vpu.vtype.ma;
+        'elems: for i in self.vpu.vstart..evl {
+            if !self.vpu.active(vm, i) {
+                for field in (0..nf).filter(|_| ones) {
+                    let reg = vd + (field * regs(demul)) as u8;
+                    self.vpu.wr_elem(reg, i, deew, u64::MAX);
+                }
+                continue;
+            }
+            let offset = match mode {
+                VAddrMode::Indexed(vs2, _) => self.vpu.rd_elem(vs2, i, eew),
+                _ => (i as u64).wrapping_mul(stride),
+            };
+            for field in 0..nf {
+                let reg = vd + (field * regs(demul)) as u8;
+                let addr = base.add(offset.wrapping_add((field * deew) as u64));
+                self.vpu.vstart = i;
+                if store {
+                    let val = self.vpu.rd_elem(reg, i, deew);
+                    self.v_wr_mem(addr, deew, val)?;
+                    continue;
+                }
+                // only the first element traps for fault-only-first,
+                // later faults trim vl instead
+                let val = match mode {
+                    VAddrMode::UnitFF if i > 0 => match self.try_rd_mem(addr, deew as u8) {
+                        Some(val) => val,
+                        None => {
+                            self.vpu.vl = i;
+                            break 'elems;
+                        }
+                    },
+                    _ => self.v_rd_mem(addr, deew)?,
+                };
+                self.vpu.wr_elem(reg, i, deew, val);
+            }
+        }
+        let v = &mut self.vpu;
+        let tail = match mode {
+            VAddrMode::Mask => v.vl.div_ceil(8),
+            _ => v.vl,
+        };
+        if !store && v.vtype.ta {
+            for field in 0..nf {
+                let reg = vd + (field * regs(demul)) as u8;
+                for idx in tail..end {
+                    v.wr_elem(reg, idx, deew, u64::MAX);
+                }
+            }
+        }
+        v.vstart = 0;
+        Ok(())
+    }
+
+    /// whole register load / store of `nf` registers, ignores vtype and vl
+    fn v_mem_whole(&mut self, vd: u8, base: Xlen, eew: usize, nf: usize, store: bool) -> Maybe<()> {
+        self.v_require(aligned(vd, nf.trailing_zeros() as i8))?;
+        let evl = nf * self.vpu.vlenb / eew;
+        for i in self.vpu.vstart..evl {
+            let addr = base.add((i * eew) as u64);
+            self.vpu.vstart = i;
+            if store {
+                let val = self.vpu.rd_elem(vd, i, eew);
+                self.v_wr_mem(addr, eew, val)?;
+            } else {
+                let val = self.v_rd_mem(addr, eew)?;
+                self.vpu.wr_elem(vd, i, eew, val);
+            }
+        }
+        self.vpu.vstart = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+#[cfg(feature = "H")]
+use crate::privilege::MSTATUS_VS;
+use crate::{
+    fpu::FpState,
+    hart::Hart,
+    privilege::{VCSR, VL, VSTART, VTYPE, VXRM, VXSAT},
+    uop::*,
+    utils::Maybe,
+    xlen::{Cast, XlenT},
+};
+
+mod alu;
+mod fp;
+mod mem;
+#[cfg(all(test, feature = "RV64"))]
+mod tests;
+
+/// decoded vtype csr
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VType {
+    /// log2 of LMUL, -3 ..= 3
+    pub lmul: i8,
+    /// selected element width in bytes
+    pub sew: usize,
+    /// tail agnostic
+    pub ta: bool,
+    /// mask agnostic
+    pub ma: bool,
+    /// unsupported setting
+    pub vill: bool,
+}
+
+impl Default for VType {
+    fn default() -> Self {
+        Self {
+            lmul: 0,
+            sew: 1,
+            ta: false,
+            ma: false,
+            vill: true,
+        }
+    }
+}
+
+impl VType {
+    /// legalize value requested by vset{i}vl{i}, `elen` in bytes
+    fn new<Xlen: XlenT>(raw: Xlen, elen: usize) -> Self {
+        let reserved = raw >> 8 != Xlen::from(0);
+        let raw: u32 = raw.into();
+        let vlmul = raw & 7;
+        let vsew = (raw >> 3) & 7;
+        let lmul = ((vlmul << 5) as u8 as i8) >> 5;
+        let sew = 1usize << vsew;
+        // LMUL >= SEW / ELEN
+        let narrow = lmul < 0 && sew << -lmul > elen;
+        if reserved || vlmul == 4 || vsew > 3 || sew > elen || narrow {
+            return Self::default();
+        }
+        Self {
+            lmul,
+            sew,
+            ta: raw & (1 << 6) != 0,
+            ma: raw & (1 << 7) != 0,
+            vill: false,
+        }
+    }
+    /// csr value
+    fn bits<Xlen: XlenT>(self) -> Xlen {
+        if self.vill {
+            return Xlen::from(1) << (Xlen::XLEN - 1);
+        }
+        let vlmul = self.lmul as u32 & 7;
+        let vsew = self.sew.trailing_zeros();
+        Xlen::from(vlmul | vsew << 3 | (self.ta as u32) << 6 | (self.ma as u32) << 7)
+    }
+    /// log2 of EMUL for element width `eew` bytes
+    fn emul(self, eew: usize) -> i8 {
+        self.lmul + eew.trailing_zeros() as i8 - self.sew.trailing_zeros() as i8
+    }
+}
+
+/// holds state of vector unit
+#[derive(Debug, Clone)]
+pub struct Vpu {
+    /// vector register file as little endian bytes,
+    /// registers of a group are contiguous
+    vregs: Box<[u8]>,
+    /// VLEN / 8
+    vlenb: usize,
+    /// ELEN / 8
+    elen: usize,
+    pub vtype: VType,
+    pub vl: usize,
+    pub vstart: usize,
+    /// fixed-point rounding mode
+    pub vxrm: u8,
+    /// fixed-point saturation flag
+    pub vxsat: bool,
+    /// vector context status in mstatus.VS
+    pub vs: FpState,
+}
+
+impl Default for Vpu {
+    fn default() -> Self {
+        Self::new(128, 64)
+    }
+}
+
+impl Vpu {
+    /// `vlen` and `elen` in bits, both powers of two
+    /// with 8 <= `elen` <= 64 and `elen` <= `vlen` <= 65536
+    pub fn new(vlen: usize, elen: usize) -> Self {
+        assert!(vlen.is_power_of_two() && elen.is_power_of_two());
+        assert!((8..=64).contains(&elen) && elen <= vlen && vlen <= 1 << 16);
+        Self {
+            vregs: vec![0; vlen / 8 * 32].into_boxed_slice(),
+            vlenb: vlen / 8,
+            elen: elen / 8,
+            vtype: Default::default(),
+            vl: 0,
+            vstart: 0,
+            vxrm: 0,
+            vxsat: false,
+            vs: FpState::Initial,
+        }
+    }
+
+    pub fn vlenb(&self) -> usize {
+        self.vlenb
+    }
+
+    /// elements in a register group of `eew` bytes with EMUL = 2 ^ `emul`
+    fn group_len(&self, eew: usize, emul: i8) -> usize {
+        regs(emul) * self.vlenb / eew
+    }
+
+    /// VLMAX of current vtype
+    fn vlmax(&self) -> usize {
+        let len = self.vlenb / self.vtype.sew;
+        match self.vtype.lmul {
+            lmul if lmul < 0 => len >> -lmul,
+            lmul => len << lmul,
+        }
+    }
+
+    /// element `idx` of register group `reg`
+    pub fn rd_elem(&self, reg: u8, idx: usize, eew: usize) -> u64 {
+        let pos = reg as usize * self.vlenb + idx * eew;
+        let mut buf = [0u8; 8];
+        buf[..eew].copy_from_slice(&self.vregs[pos..pos + eew]);
+        u64::from_le_bytes(buf)
+    }
+
+    pub fn wr_elem(&mut self, reg: u8, idx: usize, eew: usize, val: u64) {
+        let pos = reg as usize * self.vlenb + idx * eew;
+        self.vregs[pos..pos + eew].copy_from_slice(&val.to_le_bytes()[..eew]);
+    }
+
+    /// mask bit `idx` of register `reg`
+    pub fn rd_mask(&self, reg: u8, idx: usize) -> bool {
+        let byte = self.vregs[reg as usize * self.vlenb + idx / 8];
+        byte & (1 << (idx % 8)) != 0
+    }
+
+    pub fn wr_mask(&mut self, reg: u8, idx: usize, val: bool) {
+        let byte = &mut self.vregs[reg as usize * self.vlenb + idx / 8];
+        *byte = (*byte & !(1 << (idx % 8))) | (val as u8) << (idx % 8);
+    }
+
+    /// raw bytes of register `reg`
+    pub fn vreg(&self, reg: u8) -> &[u8] {
+        let pos = reg as usize * self.vlenb;
+        &self.vregs[pos..pos + self.vlenb]
+    }
+
+    /// element `idx` is active, `vm` for unmasked
+    fn active(&self, vm: bool, idx: usize) -> bool {
+        vm || self.rd_mask(0, idx)
+    }
+}
+
+/// registers in a group with EMUL = 2 ^ `emul`
+fn regs(emul: i8) -> usize {
+    1 << emul.max(0)
+}
+
+/// register group with EMUL = 2 ^ `emul` is legal
+fn aligned(reg: u8, emul: i8) -> bool {
+    (-3..=3).contains(&emul) && (reg as usize).is_multiple_of(regs(emul))
+}
+
+/// register groups `(reg, emul)` share any register
+fn overlap(a: (u8, i8), b: (u8, i8)) -> bool {
+    let (a_lo, b_lo) = (a.0 as usize, b.0 as usize);
+    a_lo < b_lo + regs(b.1) && b_lo < a_lo + regs(a.1)
+}
+
+/// sign-extend element of `eew` bytes
+fn sext(val: u64, eew: usize) -> i64 {
+    let shamt = 64 - eew as u32 * 8;
+    ((val << shamt) as i64) >> shamt
+}
+
+/// truncate to element of `eew` bytes
+fn trunc(val: u64, eew: usize) -> u64 {
+    if eew == 8 {
+        val
+    } else {
+        val & ((1 << (eew * 8)) - 1)
+    }
+}
+
+/// vector register of operand, v0 for scalars
+fn src_vreg(src: VSrc) -> u8 {
+    match src {
+        VSrc::V(vs1) => vs1,
+        _ => 0,
+    }
+}
+
+/// vector or splatted scalar operand
+#[derive(Debug, Clone, Copy)]
+enum Opnd {
+    Vec(u8),
+    Scalar(u64),
+}
+
+impl Opnd {
+    fn get(self, vpu: &Vpu, idx: usize, eew: usize) -> u64 {
+        match self {
+            Opnd::Vec(reg) => vpu.rd_elem(reg, idx, eew),
+            Opnd::Scalar(val) => trunc(val, eew),
+        }
+    }
+}
+
+impl<Xlen: XlenT> Hart<Xlen> {
+    pub fn vsetvl(&mut self, rd: u8, cfg: VCfg) -> Maybe<()> {
+        let (avl, vtype) = match cfg {
+            VCfg::Vli(rs1, zimm) => (self.v_avl(rd, rs1), Xlen::from(zimm)),
+            VCfg::Ivli(uimm, zimm) => (uimm as u64, Xlen::from(zimm)),
+            VCfg::Vl(rs1, rs2) => (self.v_avl(rd, rs1), self.rd_gpr(rs2)),
+        };
+        let v = &mut self.vpu;
+        v.vtype = VType::new(vtype, v.elen);
+        v.vl = if v.vtype.vill {
+            0
+        } else {
+            avl.min(v.vlmax() as u64) as usize
+        };
+        v.vstart = 0;
+        let vl = v.vl as u64;
+        self.wr_gpr(rd, Xlen::from(vl));
+        Ok(())
+    }
+
+    /// application vector length,
+    /// x0 as rs1 requests VLMAX, or keeps vl when rd is also x0
+    fn v_avl(&self, rd: u8, rs1: u8) -> u64 {
+        match (rd, rs1) {
+            (0, 0) => self.vpu.vl as u64,
+            (_, 0) => u64::MAX,
+            _ => self.v_ugpr(rs1),
+        }
+    }
+
+    pub fn vcsr_rd(&self, addr: u16) -> Xlen {
+        let v = &self.vpu;
+        match addr {
+            VSTART => Xlen::from(v.vstart as u64),
+            VXSAT => Xlen::from(v.vxsat as u8),
+            VXRM => Xlen::from(v.vxrm),
+            VCSR => Xlen::from(v.vxrm << 1 | v.vxsat as u8),
+            VL => Xlen::from(v.vl as u64),
+            VTYPE => v.vtype.bits(),
+            _ => Xlen::from(v.vlenb as u64),
+        }
+    }
+
+    pub fn vcsr_write(&mut self, addr: u16, val: Xlen) {
+        let val: u64 = val.into();
+        let v = &mut self.vpu;
+        match addr {
+            VSTART => v.vstart = val as usize & (v.vlenb * 8 - 1),
+            VXSAT => v.vxsat = val & 1 != 0,
+            VXRM => v.vxrm = val as u8 & 3,
+            _ => {
+                v.vxsat = val & 1 != 0;
+                v.vxrm = (val >> 1) as u8 & 3;
+            }
+        }
+        self.v_dirty();
+    }
+
+    /// vector instructions are illegal when the vpu is off,
+    /// `write` marks vector state dirty
+    pub fn v_enable(&mut self, write: bool) -> Maybe<()> {
+        if self.v_off() {
+            return self.raise(Exception::IllegalInstr);
+        }
+        if write {
+            self.v_dirty();
+        }
+        Ok(())
+    }
+
+    /// VS of mstatus, and of vsstatus in VS / VU-mode
+    pub fn v_off(&self) -> bool {
+        #[cfg(feature = "H")]
+        if self.priv_ctrl.is_virt()
+            && self.priv_ctrl.vsstatus & Xlen::from(MSTATUS_VS) == Xlen::from(0)
+        {
+            return true;
+        }
+        self.vpu.vs == FpState::Off
+    }
+
+    /// vector writes in VS / VU-mode also dirty vsstatus
+    fn v_dirty(&mut self) {
+        self.vpu.vs = FpState::Dirty;
+        #[cfg(feature = "H")]
+        if self.priv_ctrl.is_virt() {
+            let p = &mut self.priv_ctrl;
+            p.vsstatus = p.vsstatus | Xlen::from(MSTATUS_VS);
+        }
+    }
+
+    /// raise illegal instruction unless `cond`
+    fn v_require(&mut self, cond: bool) -> Maybe<()> {
+        if cond {
+            Ok(())
+        } else {
+            self.raise(Exception::IllegalInstr)
+        }
+    }
+
+    /// vtype is legal and register groups `(reg, emul)` are aligned
+    fn v_check(&mut self, groups: &[(u8, i8)]) -> Maybe<()> {
+        let legal = !self.vpu.vtype.vill && groups.iter().all(|&(reg, emul)| aligned(reg, emul));
+        self.v_require(legal)
+    }
+
+    /// gpr sign-extended to 64 bits
+    fn v_gpr(&self, reg: u8) -> u64 {
+        let val = self.rd_gpr(reg);
+        if Xlen::XLEN == 32 {
+            Cast::<i32>::into(val) as u64
+        } else {
+            val.into()
+        }
+    }
+
+    /// gpr as unsigned index or length, saturates on rv128
+    fn v_ugpr(&self, reg: u8) -> u64 {
+        let val: u128 = self.rd_gpr(reg).into();
+        val.min(u64::MAX as u128) as u64
+    }
+
+    fn v_opnd(&self, src: VSrc) -> Opnd {
+        match src {
+            VSrc::V(vs1) => Opnd::Vec(vs1),
+            VSrc::X(rs1) => Opnd::Scalar(self.v_gpr(rs1)),
+            VSrc::I(imm) => Opnd::Scalar(imm as i64 as u64),
+            VSrc::F(_) => panic!("bad uop"),
+        }
+    }
+
+    /// write results of elements from `start` on, `None` for inactive elements,
+    /// followed by tail up to element `end`\
+    /// agnostic elements are overwritten with all ones
+    fn v_commit(&mut self, vd: u8, eew: usize, start: usize, res: Vec<Option<u64>>, end: usize) {
+        let v = &mut self.vpu;
+        let tail = start + res.len();
+        for (idx, val) in (start..).zip(res) {
+            match val {
+                Some(val) => v.wr_elem(vd, idx, eew, val),
+                None if v.vtype.ma => v.wr_elem(vd, idx, eew, u64::MAX),
+                None => (),
+            }
+        }
+        if v.vtype.ta {
+            for idx in tail..end {
+                v.wr_elem(vd, idx, eew, u64::MAX);
+            }
+        }
+        v.vstart = 0;
+    }
+
+    /// `v_commit` for mask destination, tail runs to VLEN
+    fn v_commit_mask(&mut self, vd: u8, start: usize, res: Vec<Option<bool>>) {
+        let v = &mut self.vpu;
+        let tail = start + res.len();
+        for (idx, val) in (start..).zip(res) {
+            match val {
+                Some(val) => v.wr_mask(vd, idx, val),
+                None if v.vtype.ma => v.wr_mask(vd, idx, true),
+                None => (),
+            }
+        }
+        if v.vtype.ta {
+            for idx in tail..v.vlenb * 8 {
+                v.wr_mask(vd, idx, true);
+            }
+        }
+        v.vstart = 0;
+    }
+}
@@ -0,0 +1,587 @@
+use super::*;
+use crate::{
+    fpu::{FpState, FPE, FPE_DZ, FPE_NV, FPE_NX, FPE_OF},
+    machine::{Machine, RAM_BASE},
+    privilege::VLENB,
+};
+
+const ADDR: u64 = RAM_BASE + 0x100;
+
+/// vtypei of (SEW bytes, log2 LMUL)
+fn vtypei(sew: usize, lmul: i8, ta: bool, ma: bool) -> u16 {
+    (lmul as u16 & 7) | (sew.trailing_zeros() as u16) << 3 | (ta as u16) << 6 | (ma as u16) << 7
+}
+
+/// vsetvli with avl from a0, returns vl
+fn vset(hart: &mut Hart<u64>, avl: u64, sew: usize, lmul: i8) -> usize {
+    hart.wr_gpr(10, avl);
+    hart.vsetvl(5, VCfg::Vli(10, vtypei(sew, lmul, false, false)))
+        .unwrap();
+    hart.vpu.vl
+}
+
+fn fill(hart: &mut Hart<u64>, reg: u8, eew: usize, vals: &[u64]) {
+    for (i, &val) in vals.iter().enumerate() {
+        hart.vpu.wr_elem(reg, i, eew, val);
+    }
+}
+
+fn elems(hart: &Hart<u64>, reg: u8, eew: usize, n: usize) -> Vec<u64> {
+    (0..n).map(|i| hart.vpu.rd_elem(reg, i, eew)).collect()
+}
+
+fn set_mask(hart: &mut Hart<u64>, reg: u8, bits: u64) {
+    for i in 0..64 {
+        hart.vpu.wr_mask(reg, i, bits >> i & 1 != 0);
+    }
+}
+
+#[test]
+fn vsetvl() {
+    let mut hart = Hart::<u64>::default();
+    assert_eq!(vset(&mut hart, 10, 4, 0), 4);
+    assert_eq!(hart.rd_gpr(5), 4);
+    assert_eq!(vset(&mut hart, 10, 1, 1), 10);
+    assert_eq!(vset(&mut hart, 200, 1, 3), 128);
+    assert_eq!(vset(&mut hart, 100, 4, -1), 2);
+    // x0 as avl requests VLMAX
+    hart.vsetvl(5, VCfg::Vli(0, vtypei(2, 1, true, false)))
+        .unwrap();
+    assert_eq!(hart.vpu.vl, 16);
+    assert_eq!(hart.vcsr_rd(VTYPE), 0x49);
+    // rd and rs1 of x0 keep vl
+    hart.vsetvl(0, VCfg::Vli(0, vtypei(1, 0, false, false)))
+        .unwrap();
+    assert_eq!(hart.vpu.vl, 16);
+    hart.vsetvl(6, VCfg::Ivli(3, vtypei(2, 0, false, false)))
+        .unwrap();
+    assert_eq!(hart.rd_gpr(6), 3);
+    // SEW / LMUL > ELEN
+    assert_eq!(vset(&mut hart, 10, 8, -2), 0);
+    assert!(hart.vpu.vtype.vill);
+    assert_eq!(hart.vcsr_rd(VTYPE), 1 << 63);
+    assert!(hart.v_op(1, 2, VSrc::V(3), true, VIntOp::Add).is_err());
+    // reserved bits
+    hart.wr_gpr(11, 1 << 8);
+    hart.vsetvl(5, VCfg::Vl(10, 11)).unwrap();
+    assert!(hart.vpu.vtype.vill);
+}
+
+#[test]
+fn vcsr() {
+    let mut hart = Hart::<u64>::default();
+    hart.vcsr_write(VCSR, 0b101);
+    assert_eq!(hart.vcsr_rd(VXRM), 0b10);
+    assert_eq!(hart.vcsr_rd(VXSAT), 1);
+    hart.vcsr_write(VXRM, 0b111);
+    assert_eq!(hart.vcsr_rd(VCSR), 0b111);
+    assert_eq!(hart.vcsr_rd(VLENB), 16);
+}
+
+#[test]
+fn int_op() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 6, 2, 0);
+    fill(&mut hart, 2, 2, &[1, 2, 3, 4, 5, 6, 7, 8]);
+    fill(&mut hart, 3, 2, &[10, 20, 30, 40, 50, 60, 70, 80]);
+    fill(&mut hart, 1, 2, &[0xaaaa; 8]);
+    hart.v_op(1, 2, VSrc::V(3), true, VIntOp::Add).unwrap();
+    assert_eq!(
+        elems(&hart, 1, 2, 8),
+        [11, 22, 33, 44, 55, 66, 0xaaaa, 0xaaaa]
+    );
+    // masked off elements undisturbed
+    set_mask(&mut hart, 0, 0b0101);
+    hart.v_op(1, 2, VSrc::I(-1), false, VIntOp::Add).unwrap();
+    assert_eq!(
+        elems(&hart, 1, 2, 8),
+        [0, 22, 2, 44, 55, 66, 0xaaaa, 0xaaaa]
+    );
+    // agnostic elements filled with ones
+    hart.vsetvl(5, VCfg::Vli(10, vtypei(2, 0, true, true)))
+        .unwrap();
+    hart.v_op(1, 2, VSrc::X(10), false, VIntOp::RSub).unwrap();
+    assert_eq!(
+        elems(&hart, 1, 2, 8),
+        [5, 0xffff, 3, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff]
+    );
+    // masked destination can't be v0
+    assert!(hart.v_op(0, 2, VSrc::V(3), false, VIntOp::Add).is_err());
+    // misaligned register group
+    vset(&mut hart, 6, 2, 1);
+    assert!(hart.v_op(1, 2, VSrc::V(4), true, VIntOp::Add).is_err());
+}
+
+#[test]
+fn int_op_mul_div() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 1, 0);
+    fill(&mut hart, 2, 1, &[0x80, 7, 0xff, 100]);
+    fill(&mut hart, 3, 1, &[0xff, 0, 2, 3]);
+    hart.v_op(4, 2, VSrc::V(3), true, VIntOp::Div).unwrap();
+    assert_eq!(elems(&hart, 4, 1, 4), [0x80, 0xff, 0, 33]);
+    hart.v_op(4, 2, VSrc::V(3), true, VIntOp::RemU).unwrap();
+    assert_eq!(elems(&hart, 4, 1, 4), [0x80, 7, 1, 1]);
+    hart.v_op(4, 2, VSrc::V(3), true, VIntOp::MulH).unwrap();
+    assert_eq!(elems(&hart, 4, 1, 4), [0, 0, 0xff, 1]);
+    fill(&mut hart, 4, 1, &[1, 1, 1, 1]);
+    hart.v_op(4, 2, VSrc::V(3), true, VIntOp::Macc).unwrap();
+    assert_eq!(elems(&hart, 4, 1, 4), [0x81, 1, 0xff, 45]);
+}
+
+#[test]
+fn fixed_point() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 1, 0);
+    fill(&mut hart, 2, 1, &[0xf0, 0x70, 0x80, 3]);
+    hart.v_op(1, 2, VSrc::I(0x10), true, VIntOp::SAddU).unwrap();
+    assert_eq!(elems(&hart, 1, 1, 4), [0xff, 0x80, 0x90, 0x13]);
+    assert!(hart.vpu.vxsat);
+    hart.vpu.vxsat = false;
+    hart.v_op(1, 2, VSrc::I(0x10), true, VIntOp::SAdd).unwrap();
+    assert_eq!(elems(&hart, 1, 1, 4), [0, 0x7f, 0x90, 0x13]);
+    assert!(hart.vpu.vxsat);
+    // averaging add of 3 + 0 under each rounding mode
+    fill(&mut hart, 3, 1, &[0; 4]);
+    for (vxrm, exp) in [(0, 2), (1, 2), (2, 1), (3, 1)] {
+        hart.vpu.vxrm = vxrm;
+        hart.v_op(1, 2, VSrc::V(3), true, VIntOp::AAddU).unwrap();
+        assert_eq!(hart.vpu.rd_elem(1, 3, 1), exp);
+    }
+    // narrowing clip of 16-bit to 8-bit
+    hart.vpu.vxrm = 0;
+    fill(&mut hart, 4, 2, &[0x1234, 0xff00, 0x00ff, 0x0010]);
+    hart.v_op_wide(1, 4, VSrc::I(4), true, VWideOp::NClip)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 1, 4), [0x7f, 0xf0, 0x10, 1]);
+}
+
+#[test]
+fn widen() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 1, 0);
+    fill(&mut hart, 2, 1, &[0xff, 1, 0x80, 0x7f]);
+    fill(&mut hart, 3, 1, &[0xff, 0xff, 2, 2]);
+    hart.v_op_wide(4, 2, VSrc::V(3), true, VWideOp::WAddU)
+        .unwrap();
+    assert_eq!(elems(&hart, 4, 2, 4), [0x1fe, 0x100, 0x82, 0x81]);
+    hart.v_op_wide(4, 2, VSrc::V(3), true, VWideOp::WMul)
+        .unwrap();
+    assert_eq!(elems(&hart, 4, 2, 4), [1, 0xffff, 0xff00, 0xfe]);
+    // destination overlaps lowest part of source
+    assert!(hart
+        .v_op_wide(3, 2, VSrc::V(3), true, VWideOp::WAdd)
+        .is_err());
+}
+
+#[test]
+fn mask() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 8, 4, 1);
+    fill(&mut hart, 2, 4, &[5, 1, 7, 0, 9, 5, 2, 5]);
+    hart.v_cmp(1, 2, VSrc::I(5), true, VCmpOp::Eq).unwrap();
+    assert_eq!(hart.vpu.vreg(1)[0], 0b1010_0001);
+    hart.v_cmp(4, 2, VSrc::I(5), true, VCmpOp::Gt).unwrap();
+    assert_eq!(hart.vpu.vreg(4)[0], 0b0001_0100);
+    hart.v_mask(5, 1, 4, VMaskLogic::Or).unwrap();
+    assert_eq!(hart.vpu.vreg(5)[0], 0b1011_0101);
+    hart.v_mask(5, 5, 4, VMaskLogic::AndN).unwrap();
+    assert_eq!(hart.vpu.vreg(5)[0], 0b1010_0001);
+    hart.v_unary(10, 5, true, VUnaryOp::Cpop).unwrap();
+    assert_eq!(hart.rd_gpr(10), 3);
+    hart.v_unary(10, 4, true, VUnaryOp::First).unwrap();
+    assert_eq!(hart.rd_gpr(10), 2);
+    hart.v_unary(6, 4, true, VUnaryOp::Msbf).unwrap();
+    assert_eq!(hart.vpu.vreg(6)[0], 0b0000_0011);
+    hart.v_unary(6, 4, true, VUnaryOp::Msif).unwrap();
+    assert_eq!(hart.vpu.vreg(6)[0], 0b0000_0111);
+    hart.v_unary(6, 4, true, VUnaryOp::Msof).unwrap();
+    assert_eq!(hart.vpu.vreg(6)[0], 0b0000_0100);
+    hart.v_unary(8, 5, true, VUnaryOp::Iota).unwrap();
+    assert_eq!(elems(&hart, 8, 4, 8), [0, 1, 1, 1, 1, 1, 2, 2]);
+    hart.v_unary(8, 0, true, VUnaryOp::Id).unwrap();
+    assert_eq!(elems(&hart, 8, 4, 8), [0, 1, 2, 3, 4, 5, 6, 7]);
+    // add with carry out
+    vset(&mut hart, 2, 8, 0);
+    fill(&mut hart, 2, 8, &[u64::MAX, 1]);
+    set_mask(&mut hart, 0, 0b10);
+    hart.v_cmp(1, 2, VSrc::I(0), false, VCmpOp::Madc).unwrap();
+    assert_eq!(hart.vpu.vreg(1)[0] & 3, 0);
+    hart.v_cmp(1, 2, VSrc::I(1), true, VCmpOp::Madc).unwrap();
+    assert_eq!(hart.vpu.vreg(1)[0] & 3, 1);
+}
+
+#[test]
+fn reduction() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 6, 1, 0);
+    fill(&mut hart, 2, 1, &[1, 2, 3, 0xff, 5, 6, 7]);
+    fill(&mut hart, 3, 2, &[100]);
+    hart.v_red(1, 2, 3, true, VRedOp::Sum).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 1), 116);
+    hart.v_red(1, 2, 3, true, VRedOp::Min).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 1), 0xff);
+    hart.v_red(1, 2, 3, true, VRedOp::MaxU).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 1), 0xff);
+    hart.v_red(1, 2, 3, true, VRedOp::WSum).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 2), 116);
+    hart.v_red(1, 2, 3, true, VRedOp::WSumU).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 2), 372);
+    set_mask(&mut hart, 0, 0b11);
+    hart.v_red(1, 2, 3, false, VRedOp::Sum).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 1), 103);
+}
+
+#[test]
+fn permute() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 6, 2, 0);
+    fill(&mut hart, 2, 2, &[1, 2, 3, 4, 5, 6, 7, 8]);
+    fill(&mut hart, 1, 2, &[0; 8]);
+    hart.v_perm(1, 2, VSrc::I(2), true, VPermOp::SlideUp)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 2, 6), [0, 0, 1, 2, 3, 4]);
+    // elements past VLMAX read as zero
+    hart.v_perm(1, 2, VSrc::I(3), true, VPermOp::SlideDown)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 2, 6), [4, 5, 6, 7, 8, 0]);
+    hart.wr_gpr(11, -1i64 as u64);
+    hart.v_perm(1, 2, VSrc::X(11), true, VPermOp::Slide1Up)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 2, 6), [0xffff, 1, 2, 3, 4, 5]);
+    hart.v_perm(1, 2, VSrc::X(11), true, VPermOp::Slide1Down)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 2, 6), [2, 3, 4, 5, 6, 0xffff]);
+    fill(&mut hart, 3, 2, &[5, 0, 100, 1, 1, 7]);
+    hart.v_perm(1, 2, VSrc::V(3), true, VPermOp::RGather)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 2, 6), [6, 1, 0, 2, 2, 8]);
+    set_mask(&mut hart, 4, 0b10_1010);
+    hart.v_perm(1, 2, VSrc::V(4), true, VPermOp::Compress)
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 2, 3), [2, 4, 6]);
+    // destination overlaps source
+    assert!(hart
+        .v_perm(2, 2, VSrc::I(1), true, VPermOp::SlideUp)
+        .is_err());
+    // extension
+    fill(&mut hart, 3, 1, &[0x80, 0x7f, 0xff, 1, 2, 3]);
+    hart.v_unary(1, 3, true, VUnaryOp::Sext(2)).unwrap();
+    assert_eq!(elems(&hart, 1, 2, 4), [0xff80, 0x7f, 0xffff, 1]);
+    hart.v_unary(1, 3, true, VUnaryOp::Zext(2)).unwrap();
+    assert_eq!(elems(&hart, 1, 2, 4), [0x80, 0x7f, 0xff, 1]);
+}
+
+#[test]
+fn mv() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 4, 0);
+    fill(&mut hart, 2, 4, &[0x8000_0000, 1, 2, 3]);
+    hart.v_mv(10, 2, VMvOp::XS).unwrap();
+    assert_eq!(hart.rd_gpr(10), 0xffff_ffff_8000_0000);
+    hart.wr_gpr(11, 0x1234_5678_9abc);
+    hart.v_mv(2, 11, VMvOp::SX).unwrap();
+    assert_eq!(elems(&hart, 2, 4, 4), [0x5678_9abc, 1, 2, 3]);
+    hart.v_mv(4, 2, VMvOp::Whole(2)).unwrap();
+    assert_eq!(hart.vpu.vreg(4), hart.vpu.vreg(2));
+    assert!(hart.v_mv(3, 2, VMvOp::Whole(2)).is_err());
+}
+
+#[test]
+fn fp_op() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 4, 0);
+    let (one, two, neg_zero) = (1f32.to_bits() as u64, 2f32.to_bits() as u64, 0x8000_0000);
+    fill(&mut hart, 2, 4, &[one, two, neg_zero, 0x7f80_0001]);
+    hart.fpu.u32_mv_f32(10, 0.5f32.to_bits());
+    hart.v_fop(1, 2, VSrc::F(10), true, VFpOp::Add).unwrap();
+    let res: Vec<f32> = elems(&hart, 1, 4, 3)
+        .iter()
+        .map(|&e| f32::from_bits(e as u32))
+        .collect();
+    assert_eq!(res, [1.5, 2.5, 0.5]);
+    // canonical nan
+    assert_eq!(hart.vpu.rd_elem(1, 3, 4), 0x7fc0_0000);
+    fill(&mut hart, 3, 4, &[0; 4]);
+    hart.fpu.clr_all_fpe();
+    hart.v_fop(1, 2, VSrc::V(3), true, VFpOp::Min).unwrap();
+    assert_eq!(elems(&hart, 1, 4, 4), [0, 0, neg_zero, 0]);
+    assert_eq!(hart.fpu.get_fpe(), FPE_NV);
+    hart.v_fop(1, 2, VSrc::V(3), true, VFpOp::SgnJN).unwrap();
+    assert_eq!(elems(&hart, 1, 4, 2), [one | neg_zero, two | neg_zero]);
+    // vd = vs1 * vs2 + vd
+    fill(&mut hart, 1, 4, &[one, one]);
+    hart.v_fop(1, 2, VSrc::V(2), true, VFpOp::Macc).unwrap();
+    assert_eq!(elems(&hart, 1, 4, 2), [two, 5f32.to_bits() as u64]);
+    // quiet compare on qnan, signaling compare on any nan
+    fill(&mut hart, 3, 4, &[one, one, 0x7fc0_0000, 0x7fc0_0000]);
+    hart.fpu.clr_all_fpe();
+    hart.v_fcmp(4, 2, VSrc::V(3), true, VFpCmpOp::Eq).unwrap();
+    assert_eq!(hart.vpu.vreg(4)[0] & 0xf, 0b0001);
+    assert_eq!(hart.fpu.get_fpe(), FPE_NV);
+    fill(&mut hart, 2, 4, &[one, one, one, one]);
+    hart.fpu.clr_all_fpe();
+    hart.v_fcmp(4, 2, VSrc::V(3), true, VFpCmpOp::Ne).unwrap();
+    assert_eq!(hart.vpu.vreg(4)[0] & 0xf, 0b1100);
+    assert_eq!(hart.fpu.get_fpe(), FPE);
+    hart.v_fcmp(4, 2, VSrc::V(3), true, VFpCmpOp::Le).unwrap();
+    assert_eq!(hart.fpu.get_fpe(), FPE_NV);
+    // half precision is unsupported
+    vset(&mut hart, 4, 2, 0);
+    assert!(hart.v_fop(1, 2, VSrc::V(3), true, VFpOp::Add).is_err());
+}
+
+#[test]
+fn fp_state() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 4, 0);
+    hart.fpu.set_fs(FpState::Off);
+    assert!(hart.v_fop(1, 2, VSrc::V(3), true, VFpOp::Add).is_err());
+    assert!(hart.v_fcmp(1, 2, VSrc::V(3), true, VFpCmpOp::Eq).is_err());
+    assert!(hart.v_funary(1, 2, true, VFpUnaryOp::Class).is_err());
+    assert!(hart.v_mv(1, 10, VMvOp::SF).is_err());
+    assert!(hart.v_mv(10, 1, VMvOp::FS).is_err());
+    // any vector fp instruction dirties the fpu
+    hart.fpu.set_fs(FpState::Initial);
+    hart.v_funary(1, 2, true, VFpUnaryOp::Class).unwrap();
+    assert_eq!(hart.fpu.get_fs(), FpState::Dirty);
+}
+
+#[test]
+fn fp_rmm() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 2, 4, 0);
+    // 1.0 + 2 ^ -24 is a tie, away from even
+    let tie = 2f32.powi(-24).to_bits() as u64;
+    fill(
+        &mut hart,
+        2,
+        4,
+        &[1f32.to_bits() as u64, (-1f32).to_bits() as u64],
+    );
+    fill(&mut hart, 3, 4, &[tie, tie | 0x8000_0000]);
+    hart.fpu.set_frm(4);
+    hart.fpu.clr_all_fpe();
+    hart.v_fop(1, 2, VSrc::V(3), true, VFpOp::Add).unwrap();
+    assert_eq!(elems(&hart, 1, 4, 2), [0x3f80_0001, 0xbf80_0001]);
+    assert_eq!(hart.fpu.get_fpe(), FPE_NX);
+    fill(&mut hart, 4, 8, &[(1.0 + 2f64.powi(-24)).to_bits()]);
+    hart.v_funary(1, 4, true, VFpUnaryOp::Cvt(VFpCvt::FF, VWidth::Narrow))
+        .unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 4), 0x3f80_0001);
+}
+
+#[test]
+fn fp_wide_red() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 4, 0);
+    let vals = [1.5f32, -2.0, 1e30, 3.0];
+    let bits: Vec<u64> = vals.iter().map(|v| v.to_bits() as u64).collect();
+    fill(&mut hart, 2, 4, &bits);
+    hart.v_fop_wide(4, 2, VSrc::V(2), true, VFpWideOp::WMul)
+        .unwrap();
+    let res: Vec<f64> = elems(&hart, 4, 8, 4)
+        .into_iter()
+        .map(f64::from_bits)
+        .collect();
+    assert_eq!(res, [2.25, 4.0, 1e30f32 as f64 * 1e30f32 as f64, 9.0]);
+    fill(&mut hart, 3, 8, &[0.5f64.to_bits()]);
+    hart.v_fred(1, 2, 3, true, VFpRedOp::WOSum).unwrap();
+    assert_eq!(
+        f64::from_bits(hart.vpu.rd_elem(1, 0, 8)),
+        0.5 + 1.5 - 2.0 + 1e30f32 as f64 + 3.0
+    );
+    fill(&mut hart, 3, 4, &[0x7fc0_0000]);
+    hart.v_fred(1, 2, 3, true, VFpRedOp::Max).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 0, 4), 1e30f32.to_bits() as u64);
+    hart.fpu.u32_mv_f32(10, 0);
+    hart.v_mv(10, 2, VMvOp::FS).unwrap();
+    assert_eq!(hart.fpu.f32_mv_u32(10), 1.5f32.to_bits());
+}
+
+#[test]
+fn fp_cvt() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 4, 0);
+    let vals = [2.5f32, -1.5, 3e9, f32::NAN];
+    let bits: Vec<u64> = vals.iter().map(|v| v.to_bits() as u64).collect();
+    fill(&mut hart, 2, 4, &bits);
+    let cvt = |cvt, width| VFpUnaryOp::Cvt(cvt, width);
+    hart.fpu.clr_all_fpe();
+    hart.v_funary(1, 2, true, cvt(VFpCvt::XF, VWidth::Single))
+        .unwrap();
+    assert_eq!(
+        elems(&hart, 1, 4, 4),
+        [2, 0xffff_fffe, 0x7fff_ffff, 0x7fff_ffff]
+    );
+    assert_eq!(hart.fpu.get_fpe(), FPE_NV | FPE_NX);
+    hart.v_funary(1, 2, true, cvt(VFpCvt::RtzXuF, VWidth::Single))
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 4, 3), [2, 0, 3_000_000_000]);
+    hart.v_funary(4, 2, true, cvt(VFpCvt::XF, VWidth::Widen))
+        .unwrap();
+    assert_eq!(elems(&hart, 4, 8, 3), [2, -2i64 as u64, 3_000_000_000]);
+    hart.v_funary(4, 2, true, cvt(VFpCvt::FF, VWidth::Widen))
+        .unwrap();
+    assert_eq!(f64::from_bits(hart.vpu.rd_elem(4, 1, 8)), -1.5);
+    fill(
+        &mut hart,
+        6,
+        8,
+        &[1.0f64.to_bits(), (1.0 + f64::EPSILON).to_bits()],
+    );
+    hart.v_funary(1, 6, true, cvt(VFpCvt::RodFF, VWidth::Narrow))
+        .unwrap();
+    assert_eq!(elems(&hart, 1, 4, 2), [0x3f80_0000, 0x3f80_0001]);
+    fill(&mut hart, 2, 4, &[-3i64 as u64, 7]);
+    hart.v_funary(1, 2, true, cvt(VFpCvt::FX, VWidth::Single))
+        .unwrap();
+    assert_eq!(
+        elems(&hart, 1, 4, 2),
+        [(-3f32).to_bits() as u64, 7f32.to_bits() as u64]
+    );
+    // vfclass
+    fill(
+        &mut hart,
+        2,
+        4,
+        &[0xff80_0000, 0x8000_0000, 0x7f80_0001, 0x7fc0_0000],
+    );
+    hart.v_funary(1, 2, true, VFpUnaryOp::Class).unwrap();
+    assert_eq!(elems(&hart, 1, 4, 4), [1, 1 << 3, 1 << 8, 1 << 9]);
+}
+
+#[test]
+fn fp_estimate() {
+    let mut hart = Hart::<u64>::default();
+    vset(&mut hart, 4, 4, 0);
+    let vals = [1f32, 4.0, -2f32.powi(127), f32::from_bits(1 << 19)];
+    let bits: Vec<u64> = vals.iter().map(|v| v.to_bits() as u64).collect();
+    fill(&mut hart, 2, 4, &bits);
+    hart.fpu.clr_all_fpe();
+    hart.v_funary(1, 2, true, VFpUnaryOp::Rec7).unwrap();
+    assert_eq!(
+        elems(&hart, 1, 4, 4),
+        [0x3f7f_0000, 0x3e7f_0000, 0x803f_c000, 0x7f80_0000]
+    );
+    assert_eq!(hart.fpu.get_fpe(), FPE_OF | FPE_NX);
+    // overflow to max finite when rounding towards zero
+    hart.fpu.set_dyn_rm(RoundMode::Rtz);
+    hart.v_funary(1, 2, true, VFpUnaryOp::Rec7).unwrap();
+    assert_eq!(hart.vpu.rd_elem(1, 3, 4), 0x7f7f_ffff);
+    hart.fpu.set_dyn_rm(RoundMode::Rne);
+    hart.fpu.clr_all_fpe();
+    hart.v_funary(1, 2, true, VFpUnaryOp::Rsqrt7).unwrap();
+    assert_eq!(
+        elems(&hart, 1, 4, 3),
+        [0x3f7f_0000, 0x3eff_0000, 0x7fc0_0000]
+    );
+    assert_eq!(hart.fpu.get_fpe(), FPE_NV);
+    fill(
+        &mut hart,
+        2,
+        4,
+        &[0x8000_0000, 0x7f80_0000, 0x7fc0_0000, 0x3f80_0000],
+    );
+    hart.fpu.clr_all_fpe();
+    hart.v_funary(1, 2, true, VFpUnaryOp::Rsqrt7).unwrap();
+    assert_eq!(elems(&hart, 1, 4, 2), [0xff80_0000, 0]);
+    assert_eq!(hart.fpu.get_fpe(), FPE_DZ);
+    // double precision
+    vset(&mut hart, 2, 8, 0);
+    fill(&mut hart, 2, 8, &[2f64.to_bits(), 0.25f64.to_bits()]);
+    hart.v_funary(1, 2, true, VFpUnaryOp::Rec7).unwrap();
+    assert_eq!(
+        elems(&hart, 1, 8, 2),
+        [0x3fdf_e000_0000_0000, 0x400f_e000_0000_0000]
+    );
+}
+
+#[test]
+fn load_store() {
+    let mut machine = Machine::<u64>::new(1, 0x1000);
+    let bus = machine.bus.clone();
+    let hart = &mut machine.harts[0];
+    for i in 0..32 {
+        bus.write(0, ADDR + i, 1, i).unwrap();
+    }
+    hart.wr_gpr(11, ADDR);
+    let op = |mode, eew, nf| VMemOp {
+        vd: 4,
+        rs1: 11,
+        mode,
+        eew,
+        nf,
+        vm: true,
+    };
+    vset(hart, 4, 2, 0);
+    hart.v_load(op(VAddrMode::Unit, 2, 1)).unwrap();
+    assert_eq!(elems(hart, 4, 2, 4), [0x0100, 0x0302, 0x0504, 0x0706]);
+    hart.wr_gpr(12, 5);
+    hart.v_load(op(VAddrMode::Strided(12), 1, 1)).unwrap();
+    assert_eq!(elems(hart, 4, 1, 4), [0, 5, 10, 15]);
+    // offsets of 8 bits, data of SEW
+    fill(hart, 8, 1, &[3, 1, 20, 0]);
+    hart.v_load(op(VAddrMode::Indexed(8, true), 1, 1)).unwrap();
+    assert_eq!(elems(hart, 4, 2, 4), [0x0403, 0x0201, 0x1514, 0x0100]);
+    // segments of three fields
+    hart.v_load(op(VAddrMode::Unit, 1, 3)).unwrap();
+    assert_eq!(elems(hart, 4, 1, 4), [0, 3, 6, 9]);
+    assert_eq!(elems(hart, 5, 1, 4), [1, 4, 7, 10]);
+    assert_eq!(elems(hart, 6, 1, 4), [2, 5, 8, 11]);
+    // masked store
+    set_mask(hart, 0, 0b1001);
+    fill(hart, 4, 2, &[0xaaaa, 0xbbbb, 0xcccc, 0xdddd]);
+    hart.v_store(VMemOp {
+        vm: false,
+        ..op(VAddrMode::Unit, 2, 1)
+    })
+    .unwrap();
+    assert_eq!(bus.read(ADDR, 8), Ok(0xdddd_0504_0302_aaaa));
+    // vl of 10 covers 2 mask bytes
+    vset(hart, 10, 1, 0);
+    fill(hart, 4, 1, &[0xff; 16]);
+    hart.v_load(op(VAddrMode::Mask, 1, 1)).unwrap();
+    assert_eq!(elems(hart, 4, 1, 3), [0xaa, 0xaa, 0xff]);
+    // whole registers ignore vl
+    hart.v_load(op(VAddrMode::Whole, 8, 2)).unwrap();
+    assert_eq!(hart.vpu.rd_elem(5, 1, 8), 0x1f1e_1d1c_1b1a_1918);
+    hart.wr_gpr(11, ADDR + 0x100);
+    hart.v_store(op(VAddrMode::Whole, 1, 2)).unwrap();
+    assert_eq!(bus.read(ADDR + 0x118, 8), Ok(0x1f1e_1d1c_1b1a_1918));
+    // misaligned segment group
+    vset(hart, 4, 2, 1);
+    assert!(hart.v_load(op(VAddrMode::Unit, 2, 4)).is_ok());
+    assert!(hart.v_load(op(VAddrMode::Unit, 2, 5)).is_err());
+}
+
+#[test]
+fn load_fault() {
+    let mut machine = Machine::<u64>::new(1, 0x1000);
+    let hart = &mut machine.harts[0];
+    let end = RAM_BASE + 0x1000;
+    let op = |mode| VMemOp {
+        vd: 4,
+        rs1: 11,
+        mode,
+        eew: 4,
+        nf: 1,
+        vm: true,
+    };
+    vset(hart, 4, 4, 0);
+    fill(hart, 4, 4, &[0; 4]);
+    // fault-only-first trims vl
+    hart.wr_gpr(11, end - 8);
+    hart.v_load(op(VAddrMode::UnitFF)).unwrap();
+    assert_eq!(hart.vpu.vl, 2);
+    assert_eq!(hart.vpu.vstart, 0);
+    // fault on the first element traps
+    vset(hart, 4, 4, 0);
+    hart.wr_gpr(11, end);
+    assert!(hart.v_load(op(VAddrMode::UnitFF)).is_err());
+    assert_eq!(hart.vpu.vl, 4);
+    // regular loads trap with vstart at the faulting element
+    hart.wr_gpr(11, end - 12);
+    assert!(hart.v_load(op(VAddrMode::Unit)).is_err());
+    assert_eq!(hart.vcsr_rd(VSTART), 3);
+}
@@ -1,6 +1,6 @@
 use std::{
     fmt::{Debug, Display},
-    ops::{BitAnd, BitOr, BitXor, Shl, Shr},
+    ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr},
 };
 
 /// same as using `as` keyword
@@ -156,6 +156,8 @@ impl XlenOp for u128 {
 /// implements all ALU operations required by riscv spec
 pub trait XlenT:
     Sized
+    + Send
+    + Sync
     + Copy
     + Default
     + Ord
@@ -164,6 +166,7 @@ pub trait XlenT:
     + BitAnd<Output = Self>
     + BitOr<Output = Self>
     + BitXor<Output = Self>
+    + Not<Output = Self>
     + Shl<u32, Output = Self>
     + Shr<u32, Output = Self>
     + CastPrimitive
@@ -209,6 +212,39 @@ pub trait XlenT:
     fn divu(self, rhs: Self) -> Self;
     #[cfg(feature = "M")]
     fn remu(self, rhs: Self) -> Self;
+    /// count leading zeros
+    #[cfg(feature = "Zbb")]
+    fn clz(self) -> u32;
+    /// count trailing zeros
+    #[cfg(feature = "Zbb")]
+    fn ctz(self) -> u32;
+    /// count set bits
+    #[cfg(feature = "Zbb")]
+    fn cpop(self) -> u32;
+    /// rotate left
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    fn rol(self, shamt: u32) -> Self;
+    /// rotate right
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    fn ror(self, shamt: u32) -> Self;
+    /// reverse byte order
+    #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+    fn rev8(self) -> Self;
+    /// each byte to 0xff if non-zero
+    #[cfg(feature = "Zbb")]
+    fn orc_b(self) -> Self;
+    /// reverse bits in each byte
+    #[cfg(feature = "Zbkb")]
+    fn brev8(self) -> Self;
+    /// low half of carry-less product
+    #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+    fn clmul(self, rhs: Self) -> Self;
+    /// high half of carry-less product
+    #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+    fn clmulh(self, rhs: Self) -> Self;
+    /// carry-less product bits \[2 * xlen - 2 : xlen - 1\]
+    #[cfg(feature = "Zbc")]
+    fn clmulr(self, rhs: Self) -> Self;
 }
 
 /// implements XlenT trait for xlen\
@@ -304,6 +340,59 @@ macro_rules! impl_xlen_t {
                     self.wrapping_rem(rhs)
                 }
             }
+            #[cfg(feature = "Zbb")]
+            fn clz(self) -> u32 {
+                self.leading_zeros()
+            }
+            #[cfg(feature = "Zbb")]
+            fn ctz(self) -> u32 {
+                self.trailing_zeros()
+            }
+            #[cfg(feature = "Zbb")]
+            fn cpop(self) -> u32 {
+                self.count_ones()
+            }
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            fn rol(self, shamt: u32) -> Self {
+                self.rotate_left(shamt)
+            }
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            fn ror(self, shamt: u32) -> Self {
+                self.rotate_right(shamt)
+            }
+            #[cfg(any(feature = "Zbb", feature = "Zbkb"))]
+            fn rev8(self) -> Self {
+                self.swap_bytes()
+            }
+            #[cfg(feature = "Zbb")]
+            fn orc_b(self) -> Self {
+                (0..<$t>::BITS)
+                    .step_by(8)
+                    .filter(|pos| (self >> pos) & 0xff != 0)
+                    .fold(0, |res, pos| res | (0xff << pos))
+            }
+            #[cfg(feature = "Zbkb")]
+            fn brev8(self) -> Self {
+                self.swap_bytes().reverse_bits()
+            }
+            #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+            fn clmul(self, rhs: Self) -> Self {
+                (0..<$t>::BITS)
+                    .filter(|pos| (rhs >> pos) & 1 != 0)
+                    .fold(0, |res, pos| res ^ (self << pos))
+            }
+            #[cfg(any(feature = "Zbc", feature = "Zbkc"))]
+            fn clmulh(self, rhs: Self) -> Self {
+                (1..<$t>::BITS)
+                    .filter(|pos| (rhs >> pos) & 1 != 0)
+                    .fold(0, |res, pos| res ^ (self >> (<$t>::BITS - pos)))
+            }
+            #[cfg(feature = "Zbc")]
+            fn clmulr(self, rhs: Self) -> Self {
+                (0..<$t>::BITS)
+                    .filter(|pos| (rhs >> pos) & 1 != 0)
+                    .fold(0, |res, pos| res ^ (self >> (<$t>::BITS - pos - 1)))
+            }
         }
     };
 }
@@ -345,6 +434,28 @@ mod tests {
             assert_eq!(val4.mulhsu(val4), 0xffff0001);
         }
 
+        #[cfg(feature = "Zbb")]
+        {
+            let val = 0x0012_f000u32;
+            assert_eq!((val.clz(), val.ctz(), val.cpop()), (11, 12, 6));
+            assert_eq!((0u32.clz(), 0u32.ctz()), (32, 32));
+            assert_eq!(val.rol(12), 0x2f00_0001);
+            assert_eq!(val.ror(16), 0xf000_0012);
+            assert_eq!(val.rev8(), 0x00f0_1200);
+            assert_eq!(val.orc_b(), 0x00ff_ff00);
+        }
+
+        #[cfg(feature = "Zbc")]
+        {
+            let val1 = 0x8000_0003u32;
+            let val2 = 0x0000_0005u32;
+            assert_eq!(val1.clmul(val2), 0x8000_000f);
+            assert_eq!(val1.clmulh(val2), 0x2);
+            assert_eq!(val1.clmulr(val2), 0x5);
+            assert_eq!(val1.clmul(val1), 0x0000_0005);
+            assert_eq!(val1.clmulh(val1), 0x4000_0000);
+        }
+
         // u64 has same logic as u32
 
         #[cfg(all(feature = "M", feature = "RV128"))]
@@ -364,6 +475,25 @@ mod tests {
             assert_eq!(val1.mulhsu(val2), 0x80000000000000000000000000000000);
             assert_eq!(val3.mulhsu(val2), 0x7ffffffffffffffffffffffffffffffe);
             assert_eq!(val4.mulhsu(val4), 0xffffffffffffffff0000000000000001);
+
+            let val5 = 0x0123456789abcdeffedcba9876543210u128;
+            let val6 = 0xdeadbeefcafebabe0badf00dfeedfaceu128;
+            assert_eq!(val5.mul(val6), 0x8424c28d2668cf181e69aaaba973e8e0);
+            assert_eq!(val5.mulhu(val6), 0x00fd5bdeeeb2a01e5c7436e84170a7e9);
+            assert_eq!(val5.mulh(val6), 0xffda16776506d22e5d977c4fcb1c75d9);
+            assert_eq!(val6.mulhsu(val5), 0xffda16776506d22e5d977c4fcb1c75d9);
+            assert_eq!(val6.mulhsu(val6), 0xe3040e23b7943f5abb3949bbbd02b46c);
+            assert_eq!(val6.divu(val5), 0xc3);
+            assert_eq!(val6.remu(val5), 0xcfe111ed20dceee98bcdebdccbd89e);
+            assert_eq!(val6.div(val5), 0xffffffffffffffffffffffffffffffe3);
+            assert_eq!(val6.rem(val5), 0xffac9baa63750eedeaaf13536677a69e);
+            // overflow and division by zero
+            assert_eq!(val1.div(val2), val1);
+            assert_eq!(val1.rem(val2), 0);
+            assert_eq!(val5.div(0), val2);
+            assert_eq!(val5.divu(0), val2);
+            assert_eq!(val6.rem(0), val6);
+            assert_eq!(val6.remu(0), val6);
         }
     }
 }